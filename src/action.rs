@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use strum::Display;
 
-#[derive(Debug, Clone, PartialEq, Eq, Display, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Display, Serialize, Deserialize)]
 pub enum Action {
     Tick,
     Render,
@@ -11,5 +11,68 @@ pub enum Action {
     Quit,
     ClearScreen,
     Error(String),
+    Toast(String),
     Help,
+    FocusNext,
+    FocusPrevious,
+    TogglePin,
+    ZoomIn,
+    ZoomOut,
+    PanLeft,
+    PanRight,
+    PanReset,
+    ToggleDebug,
+    ToggleInfo,
+    /// Show what the reader of the current chart's source last saw.
+    ToggleInspector,
+    /// Close the popups over the charts.
+    CloseOverlay,
+    ToggleExpand,
+    ToggleCrosshair,
+    ToggleYFrom,
+    MarkRangeStart,
+    MarkRangeEnd,
+    ClearRange,
+    EditTitle,
+    EditUnit,
+    /// Edit the reference of the focused chart.
+    EditReference,
+    OpenInput(String, String),
+    SubmitInput(String),
+    CancelInput,
+    WriteProfile,
+    TogglePause,
+    TogglePauseAll,
+    ClearChart,
+    ClearAll,
+    MoveChartEarlier,
+    MoveChartLater,
+    /// Leave the focused chart out of the view.
+    HideChart,
+    /// Bring back the hidden charts.
+    ShowAll,
+    /// Draw the charts grouped or apart.
+    ToggleGroup,
+    /// Switch to the next `--layout`.
+    CycleLayout,
+    AddNote,
+    /// Write the history of the focused chart to a file.
+    ExportChart,
+    /// Read the config file again, on the reload key or SIGHUP.
+    ReloadConfig,
+    /// Pause or resume chart `n`, or every chart, from `--control-fifo`.
+    SetPaused(Option<usize>, bool),
+    /// Drop the samples of chart `n`, from `--control-fifo`.
+    ResetChart(usize),
+    /// Change the reference of chart `n`, from `--control-fifo`.
+    SetReference(usize, f64),
+    /// Add a note with this text, from `--control-fifo`.
+    Mark(String),
+    /// Write the summary of every chart with its samples to the export directory.
+    Snapshot,
+    DataUpdated(usize),
+    /// The cells the last frame changed and the cells on the screen, for the debug overlay.
+    FrameDrawn(usize, usize),
+    /// A chart went above its reference, for the `--notify` channels.
+    Notify(String),
 }
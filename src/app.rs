@@ -1,17 +1,48 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+    path::Path,
+    time::Duration,
+};
+
+use color_eyre::eyre::eyre;
 use color_eyre::Result;
 use crossterm::event::KeyEvent;
-use ratatui::prelude::Rect;
+use ratatui::{
+    buffer::Buffer,
+    prelude::{Rect, Size},
+};
 use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc;
-use tracing::{debug, info};
+use tokio::{sync::mpsc, time::Instant};
+use tracing::{debug, error, info, warn};
 
 use crate::{
     action::Action,
-    components::{dash::Dash, Component},
-    config::Config,
-    tui::{Event, Tui},
+    cli::{NotifyChannel, SimulateArgs},
+    components::{
+        banner::{summary_lines, Banner},
+        dash::DashView,
+        fps::FpsCounter,
+        input::TextInput,
+        toast::Toast,
+        Component,
+    },
+    config::{get_config_file, get_data_dir, load_error, Config},
+    doctor::{self, ConfigStatus, Probes},
+    errors::{is_broken_pipe, report_closed_stdout},
+    notify::notify,
+    profile::DEFAULT_PROFILE,
+    rates,
+    tui::{changed_cells, Event, Tui, TuiBackend},
 };
 
+/// Resize events closer together than this are taken as one, like the dozens a second of a
+/// terminal corner being dragged: the dashboard is laid out once at the size it ends up with.
+const RESIZE_DEBOUNCE: Duration = Duration::from_millis(50);
+/// The longest a resize waits for the terminal to stop changing size, so a slow drag still
+/// shows in between.
+const MAX_RESIZE_DELAY: Duration = Duration::from_millis(500);
+
 pub struct App {
     config: Config,
     tick_rate: f64,
@@ -19,51 +50,250 @@ pub struct App {
     components: Vec<Box<dyn Component>>,
     should_quit: bool,
     should_suspend: bool,
+    error: Option<String>,
     mode: Mode,
     last_tick_key_events: Vec<KeyEvent>,
     action_tx: mpsc::UnboundedSender<Action>,
     action_rx: mpsc::UnboundedReceiver<Action>,
+    /// Set when a signal ended the session.
+    exit_code: Option<i32>,
+    notify: Vec<NotifyChannel>,
+    /// Shown once the dashboard is up, e.g. why the config file was ignored.
+    startup_toasts: Vec<String>,
+    /// The last frame drawn, to count the cells the next one changes.
+    last_frame: Option<Buffer>,
+    /// Set once the output the dashboard is drawn on was closed, nothing is drawn after.
+    output_closed: bool,
+    /// The newest size of a terminal being resized, until [`RESIZE_DEBOUNCE`] passes without
+    /// another resize.
+    pending_resize: Option<PendingResize>,
+    /// Where `--control-fifo` creates the FIFO.
+    #[cfg(unix)]
+    control_fifo: Option<std::path::PathBuf>,
+}
+
+/// The size a burst of resize events ended at so far.
+#[derive(Debug, Clone, Copy)]
+struct PendingResize {
+    width: u16,
+    height: u16,
+    /// The first resize of the burst.
+    since: Instant,
+    /// The newest resize.
+    last: Instant,
 }
 
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Mode {
     #[default]
     Home,
+    /// A text input is open, keys go to it instead of the keybindings.
+    Input,
+}
+
+/// Load the config and fill in the flags that weren't given from the selected profile. A config
+/// file that can't be loaded is warned about and the built-in settings are used instead, the
+/// warning is returned too.
+fn load_config(args: &mut crate::cli::Cli) -> (Config, Option<String>) {
+    let (config, warning) = match Config::new() {
+        Ok(config) => (config, None),
+        Err(err) => {
+            let warning = format!("{}, using the built-in settings", load_error(err));
+            warn!("{warning}");
+            eprintln!("Warning: {warning}");
+            (Config::builtin(), Some(warning))
+        }
+    };
+    let profile = args.profile.as_deref().unwrap_or(DEFAULT_PROFILE);
+    if let Some(profile) = config.profiles.get(profile) {
+        profile.apply(args);
+    }
+    (config, warning)
+}
+
+/// Set the tick and frame rates the dashboard runs at, see [`rates::reconcile`].
+fn apply_rates(args: &mut crate::cli::Cli) -> Result<()> {
+    if args.low_bandwidth {
+        args.frame_rate = args.frame_rate.min(rates::LOW_BANDWIDTH_FRAME_RATE);
+    }
+    let (rates, notes) = rates::reconcile(
+        args.update_frequency,
+        args.tick_rate,
+        args.frame_rate,
+        args.strict,
+    )?;
+    for note in notes {
+        info!("{note}");
+    }
+    (args.tick_rate, args.frame_rate) = (rates.tick_rate, rates.frame_rate);
+    Ok(())
+}
+
+/// The startup banner for the flags after the profile was applied, also logged.
+fn banner(args: &crate::cli::Cli, config: &Config) -> Banner {
+    let config_file = get_config_file();
+    let profile = args.profile.as_deref().unwrap_or(DEFAULT_PROFILE);
+    let lines = summary_lines(
+        args,
+        config_file.exists().then_some(config_file.as_path()),
+        profile,
+        config.profiles.contains_key(profile),
+    );
+    for line in &lines {
+        info!("{line}");
+    }
+    if args.no_banner {
+        Banner::default()
+    } else {
+        Banner::new(lines)
+    }
+}
+
+/// The lines read at once by the commands that don't follow their input: the `--input` file, or
+/// stdin.
+fn read_input(args: &crate::cli::Cli) -> Result<Box<dyn BufRead>> {
+    match &args.input {
+        Some(path) if path != Path::new("-") => {
+            let file =
+                File::open(path).map_err(|err| eyre!("Can't read {}: {err}", path.display()))?;
+            Ok(Box::new(BufReader::new(file)))
+        }
+        _ => Ok(Box::new(std::io::stdin().lock())),
+    }
+}
+
+/// Run `ttydash test`: print what the flags extract from the first `lines` lines of the input.
+/// Returns whether any value was found.
+pub fn self_test(mut args: crate::cli::Cli, lines: usize) -> Result<bool> {
+    let (config, _) = load_config(&mut args);
+    let input = read_input(&args)?;
+    let mut dash = DashView::new(args)?;
+    dash.register_config_handler(config)?;
+    dash.self_test(input, lines, &mut std::io::stdout().lock())
+}
+
+/// Run `ttydash doctor`: print the build, the terminal and the settings the dashboard would run
+/// with.
+pub fn doctor(mut args: crate::cli::Cli) -> Result<()> {
+    let (config, problem) = load_config(&mut args);
+    apply_rates(&mut args)?;
+    let profile = args.profile.as_deref().unwrap_or(DEFAULT_PROFILE);
+    let status = ConfigStatus::new(problem, config.profiles.contains_key(profile));
+    let report = doctor::report(&args, &Probes::detect(), &status);
+    write!(std::io::stdout(), "{report}")?;
+    Ok(())
+}
+
+/// Run `ttydash simulate`: chart the input without a terminal, write the last frame to
+/// `--render-to` and the summary to `--summary`.
+pub fn simulate(mut args: crate::cli::Cli, simulate: SimulateArgs) -> Result<()> {
+    let (config, _) = load_config(&mut args);
+    let input = read_input(&args)?;
+    let mut dash = DashView::new(args)?;
+    dash.register_config_handler(config)?;
+    let frame = dash.simulate(
+        input,
+        simulate.frames,
+        Size::new(simulate.width, simulate.height),
+    )?;
+    match simulate.render_to {
+        Some(path) if path != Path::new("-") => std::fs::write(path, frame)?,
+        _ => print_stdout(&frame, "frame")?,
+    }
+    dash.exit()
+}
+
+/// Run `--once`: chart all of the input and print a single frame with the stats under it.
+pub fn once(mut args: crate::cli::Cli) -> Result<()> {
+    let (config, _) = load_config(&mut args);
+    let size = Size::new(args.width, args.height);
+    let input = read_input(&args)?;
+    let mut dash = DashView::new(args)?;
+    dash.register_config_handler(config)?;
+    let text = dash.once(input, size)?;
+    print_stdout(&text, "frame")?;
+    dash.exit()
+}
+
+/// Write `text`, the `what` of a command, to stdout. Stdout closed early, e.g. by `| head`, is
+/// warned about rather than an error.
+fn print_stdout(text: &str, what: &str) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    match write!(stdout, "{text}").and_then(|_| stdout.flush()) {
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => report_closed_stdout(what),
+        result => result?,
+    }
+    Ok(())
 }
 
 impl App {
-    pub fn new(args: crate::cli::Cli) -> Result<Self> {
+    pub fn new(mut args: crate::cli::Cli) -> Result<Self> {
         let (action_tx, action_rx) = mpsc::unbounded_channel();
+        let (config, config_warning) = load_config(&mut args);
+        let mut startup_toasts = Vec::from_iter(config_warning);
+        if !args.low_bandwidth && std::env::var_os("SSH_CONNECTION").is_some() {
+            info!("Running over SSH without --low-bandwidth");
+            startup_toasts.push(
+                "Running over SSH: --low-bandwidth sends less per frame if typing lags".to_string(),
+            );
+        }
+        apply_rates(&mut args)?;
+        let banner = banner(&args, &config);
+        let notify = args.notify.clone().unwrap_or_default();
+        #[cfg(unix)]
+        let control_fifo = args
+            .control_fifo
+            .clone()
+            .map(|path| path.unwrap_or_else(|| get_data_dir().join("control")));
         Ok(Self {
             tick_rate: args.tick_rate,
             frame_rate: args.frame_rate,
-            components: vec![Box::new(Dash::new(args))],
+            components: vec![
+                Box::new(DashView::new(args)?),
+                Box::new(FpsCounter::default()),
+                Box::new(banner),
+                Box::new(Toast::default()),
+                Box::new(TextInput::default()),
+            ],
             should_quit: false,
             should_suspend: false,
-            config: Config::new()?,
+            error: None,
+            config,
             mode: Mode::Home,
             last_tick_key_events: Vec::new(),
             action_tx,
             action_rx,
+            exit_code: None,
+            notify,
+            startup_toasts,
+            last_frame: None,
+            output_closed: false,
+            pending_resize: None,
+            #[cfg(unix)]
+            control_fifo,
         })
     }
 
     pub async fn run(&mut self) -> Result<()> {
+        // before the terminal is taken over, so a FIFO that can't be created is a plain error
+        #[cfg(unix)]
+        let control = match self.control_fifo.take() {
+            Some(path) => Some(crate::control::ControlFifo::spawn(
+                path,
+                self.action_tx.clone(),
+            )?),
+            None => None,
+        };
+        // pasted text arrives in one piece instead of as key presses matching keybindings
         let mut tui = Tui::new()?
             .tick_rate(self.tick_rate)
-            .frame_rate(self.frame_rate);
+            .frame_rate(self.frame_rate)
+            .paste(true);
         tui.enter()?;
+        self.init_components(&tui)?;
 
-        for component in self.components.iter_mut() {
-            component.register_action_handler(self.action_tx.clone())?;
-        }
-        for component in self.components.iter_mut() {
-            component.register_config_handler(self.config.clone())?;
-        }
-        for component in self.components.iter_mut() {
-            component.init(tui.size()?)?;
-        }
-
+        #[cfg(unix)]
+        let signals = crate::signals::SignalHandler::spawn(self.action_tx.clone())?;
         let action_tx = self.action_tx.clone();
 
         loop {
@@ -79,11 +309,52 @@ impl App {
                 break;
             }
         }
-        tui.exit()?;
+        // the summary is written even when the terminal couldn't be given back
+        let restored = tui.exit();
+        for component in self.components.iter_mut() {
+            component.exit()?;
+        }
+        #[cfg(unix)]
+        {
+            signals.close();
+            self.exit_code = signals.exit_code();
+            if let Some(control) = control {
+                control.close();
+            }
+        }
+        if let Some(error) = self.error.take() {
+            return Err(eyre!(error));
+        }
+        match restored {
+            // a closed output has no terminal left to give back
+            Err(err) if self.output_closed && is_broken_pipe(&err) => Ok(()),
+            result => result,
+        }
+    }
+
+    /// Hand the components the action sender, the config and the size of `tui`.
+    fn init_components<B: TuiBackend>(&mut self, tui: &Tui<B>) -> Result<()> {
+        for component in self.components.iter_mut() {
+            component.register_action_handler(self.action_tx.clone())?;
+        }
+        for component in self.components.iter_mut() {
+            component.register_config_handler(self.config.clone())?;
+        }
+        for component in self.components.iter_mut() {
+            component.init(tui.size()?)?;
+        }
+        for message in self.startup_toasts.drain(..) {
+            self.action_tx.send(Action::Toast(message))?;
+        }
         Ok(())
     }
 
-    async fn handle_events(&mut self, tui: &mut Tui) -> Result<()> {
+    /// The exit code for a session ended by a signal.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    async fn handle_events<B: TuiBackend>(&mut self, tui: &mut Tui<B>) -> Result<()> {
         let Some(event) = tui.next_event().await else {
             return Ok(());
         };
@@ -91,11 +362,23 @@ impl App {
         match event {
             Event::Quit => action_tx.send(Action::Quit)?,
             Event::Tick => action_tx.send(Action::Tick)?,
+            // a frame laid out for a size that is still changing would be thrown away
+            Event::Render if self.pending_resize.is_some() => {}
             Event::Render => action_tx.send(Action::Render)?,
-            Event::Resize(x, y) => action_tx.send(Action::Resize(x, y))?,
+            Event::Resize(width, height) => {
+                let now = Instant::now();
+                let since = self.pending_resize.map_or(now, |pending| pending.since);
+                self.pending_resize = Some(PendingResize {
+                    width,
+                    height,
+                    since,
+                    last: now,
+                });
+            }
             Event::Key(key) => self.handle_key_event(key)?,
             _ => {}
         }
+        self.settle_resize()?;
         for component in self.components.iter_mut() {
             if let Some(action) = component.handle_events(Some(event.clone()))? {
                 action_tx.send(action)?;
@@ -104,6 +387,21 @@ impl App {
         Ok(())
     }
 
+    /// Resize to the size a burst of resize events ended at once it is over, or once it went on
+    /// for [`MAX_RESIZE_DELAY`].
+    fn settle_resize(&mut self) -> Result<()> {
+        let Some(pending) = self.pending_resize else {
+            return Ok(());
+        };
+        if pending.last.elapsed() >= RESIZE_DEBOUNCE || pending.since.elapsed() >= MAX_RESIZE_DELAY
+        {
+            self.pending_resize = None;
+            self.action_tx
+                .send(Action::Resize(pending.width, pending.height))?;
+        }
+        Ok(())
+    }
+
     fn handle_key_event(&mut self, key: KeyEvent) -> Result<()> {
         let action_tx = self.action_tx.clone();
         let Some(keymap) = self.config.keybindings.get(&self.mode) else {
@@ -129,9 +427,12 @@ impl App {
         Ok(())
     }
 
-    fn handle_actions(&mut self, tui: &mut Tui) -> Result<()> {
+    fn handle_actions<B: TuiBackend>(&mut self, tui: &mut Tui<B>) -> Result<()> {
         while let Ok(action) = self.action_rx.try_recv() {
-            if action != Action::Tick && action != Action::Render {
+            if !matches!(
+                action,
+                Action::Tick | Action::Render | Action::DataUpdated(_) | Action::FrameDrawn(..)
+            ) {
                 debug!("{action:?}");
             }
             match action {
@@ -139,11 +440,32 @@ impl App {
                     self.last_tick_key_events.drain(..);
                 }
                 Action::Quit => self.should_quit = true,
+                // there is no SIGTSTP to stop the process with on Windows
+                Action::Suspend if cfg!(windows) => self.action_tx.send(Action::Toast(
+                    "Suspending is not supported on Windows".to_string(),
+                ))?,
                 Action::Suspend => self.should_suspend = true,
                 Action::Resume => self.should_suspend = false,
-                Action::ClearScreen => tui.terminal.clear()?,
+                Action::ClearScreen if !self.output_closed => {
+                    let cleared = tui.terminal.clear();
+                    self.drawn(cleared)?;
+                    self.last_frame = None;
+                }
+                Action::Notify(ref message) if !self.output_closed => {
+                    if let Some(mut output) = tui.terminal.backend_mut().output() {
+                        notify(&mut output, &self.notify, message)?
+                    }
+                }
                 Action::Resize(w, h) => self.handle_resize(tui, w, h)?,
                 Action::Render => self.render(tui)?,
+                Action::ReloadConfig => self.reload_config()?,
+                Action::OpenInput(..) => self.mode = Mode::Input,
+                Action::SubmitInput(_) | Action::CancelInput => self.mode = Mode::Home,
+                Action::Error(ref message) => {
+                    error!("{message}");
+                    self.error = Some(message.clone());
+                    self.should_quit = true;
+                }
                 _ => {}
             }
             for component in self.components.iter_mut() {
@@ -155,14 +477,55 @@ impl App {
         Ok(())
     }
 
-    fn handle_resize(&mut self, tui: &mut Tui, w: u16, h: u16) -> Result<()> {
-        tui.resize(Rect::new(0, 0, w, h))?;
+    /// Read the config file again, e.g. after `ttydash add` or an edit changed it, and hand it
+    /// to the components. A file that can't be loaded keeps the current config.
+    fn reload_config(&mut self) -> Result<()> {
+        let message = match Config::new() {
+            Ok(config) => {
+                for component in self.components.iter_mut() {
+                    component.register_config_handler(config.clone())?;
+                }
+                self.config = config;
+                "Reloaded the config".to_string()
+            }
+            Err(err) => format!("{}, keeping the current settings", load_error(err)),
+        };
+        info!("{message}");
+        self.action_tx.send(Action::Toast(message))?;
+        Ok(())
+    }
+
+    fn handle_resize<B: TuiBackend>(&mut self, tui: &mut Tui<B>, w: u16, h: u16) -> Result<()> {
+        if self.output_closed {
+            return Ok(());
+        }
+        let resized = tui.resize(Rect::new(0, 0, w, h));
+        self.drawn(resized)?;
         self.render(tui)?;
         Ok(())
     }
 
-    fn render(&mut self, tui: &mut Tui) -> Result<()> {
-        tui.draw(|frame| {
+    /// The result of a write to the output the dashboard is drawn on. Rust ignores SIGPIPE, so
+    /// an output that was closed, like stdout after `| head` quit, is a `BrokenPipe` error here.
+    /// That isn't fatal: nothing is drawn anymore, the charts keep reading until the dashboard
+    /// quits and the `--summary` has all of it.
+    fn drawn<T>(&mut self, result: io::Result<T>) -> Result<Option<T>> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => {
+                warn!("The output was closed, the dashboard keeps running without drawing");
+                self.output_closed = true;
+                Ok(None)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn render<B: TuiBackend>(&mut self, tui: &mut Tui<B>) -> Result<()> {
+        if self.output_closed {
+            return Ok(());
+        }
+        let drawn = tui.draw(|frame| {
             for component in self.components.iter_mut() {
                 if let Err(err) = component.draw(frame, frame.area()) {
                     let _ = self
@@ -170,7 +533,72 @@ impl App {
                         .send(Action::Error(format!("Failed to draw: {:?}", err)));
                 }
             }
-        })?;
+        });
+        let Some(frame) = self.drawn(drawn)? else {
+            return Ok(());
+        };
+        let changed = changed_cells(self.last_frame.as_ref(), frame.buffer);
+        let cells = frame.buffer.content.len();
+        self.last_frame = Some(frame.buffer.clone());
+        self.action_tx.send(Action::FrameDrawn(changed, cells))?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use pretty_assertions::assert_eq;
+    use ratatui::backend::TestBackend;
+
+    use super::*;
+    use crate::cli::Cli;
+
+    #[tokio::test]
+    async fn test_resize_storm() {
+        let input = std::env::temp_dir().join(format!("ttydash-resize-{}", std::process::id()));
+        std::fs::write(&input, "1\n2\n3\n").unwrap();
+        let args = Cli::parse_from([
+            "ttydash",
+            "--no-banner",
+            "--input",
+            input.to_str().unwrap(),
+            "--history",
+            "auto",
+        ]);
+        let mut app = App::new(args).unwrap();
+        let mut tui = Tui::with_backend(TestBackend::new(80, 24)).unwrap();
+        app.init_components(&tui).unwrap();
+
+        // a corner dragged down to nothing and out again, with frames due all along
+        let sizes = (0..100)
+            .map(|n: i32| {
+                let width = (80 - 2 * n).unsigned_abs() as u16;
+                let height = (24 - n / 2).unsigned_abs() as u16;
+                (width, height)
+            })
+            .collect::<Vec<_>>();
+        for &(width, height) in &sizes {
+            tui.backend_mut().resize(width, height);
+            tui.event_tx.send(Event::Resize(width, height)).unwrap();
+            tui.event_tx.send(Event::Render).unwrap();
+            app.handle_events(&mut tui).await.unwrap();
+            app.handle_events(&mut tui).await.unwrap();
+            app.handle_actions(&mut tui).unwrap();
+        }
+        // nothing was laid out for the sizes passed through
+        assert!(app.last_frame.is_none());
+
+        tokio::time::sleep(RESIZE_DEBOUNCE).await;
+        tui.event_tx.send(Event::Render).unwrap();
+        app.handle_events(&mut tui).await.unwrap();
+        app.handle_actions(&mut tui).unwrap();
+        let (width, height) = sizes[sizes.len() - 1];
+        let area = Rect::new(0, 0, width, height);
+        assert_eq!(app.last_frame.as_ref().map(|frame| frame.area), Some(area));
+        assert_eq!(tui.backend().buffer().area, area);
+        assert!(app.pending_resize.is_none());
+        assert!(!app.should_quit);
+        std::fs::remove_file(input).unwrap();
+    }
+}
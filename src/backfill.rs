@@ -0,0 +1,153 @@
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    time::Duration,
+};
+
+use crate::source::parse_duration;
+
+/// Size of the blocks read backwards from the end of the input.
+const BLOCK_SIZE: u64 = 64 * 1024;
+
+/// How much of the existing input `--backfill` charts before following it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backfill {
+    Lines(usize),
+    /// Lines with a timestamp within this long of the newest one.
+    Duration(Duration),
+}
+
+/// Parse `--backfill`, a number of lines like `500` or a duration like `30s` or `1h`.
+pub fn parse_backfill(value: &str) -> Result<Backfill, String> {
+    match value.parse::<usize>() {
+        Ok(lines) => Ok(Backfill::Lines(lines)),
+        Err(_) => parse_duration(value).map(Backfill::Duration),
+    }
+}
+
+/// A seekable handle to stdin, when it is redirected from a regular file.
+pub fn stdin_file() -> io::Result<File> {
+    #[cfg(unix)]
+    let handle = std::os::fd::AsFd::as_fd(&io::stdin()).try_clone_to_owned()?;
+    #[cfg(windows)]
+    let handle = std::os::windows::io::AsHandle::as_handle(&io::stdin()).try_clone_to_owned()?;
+    let file = File::from(handle);
+    if !file.metadata()?.is_file() {
+        return Err(io::Error::other("stdin is not redirected from a file"));
+    }
+    Ok(file)
+}
+
+/// Read lines backwards from the end of `input` in blocks for as long as `keep` accepts them,
+/// without reading the rest of the input. Empty lines are skipped. Returns the kept lines oldest
+/// first and leaves `input` positioned at its end.
+pub fn read_tail<R: Read + Seek>(
+    input: &mut R,
+    mut keep: impl FnMut(&str) -> bool,
+) -> io::Result<Vec<String>> {
+    let end = input.seek(SeekFrom::End(0))?;
+    let mut position = end;
+    let mut lines = vec![];
+    // the start of the line that continues past the blocks read so far
+    let mut partial = vec![];
+    let mut done = false;
+    let mut take = |bytes: &[u8], lines: &mut Vec<String>| {
+        let line = String::from_utf8_lossy(bytes);
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            return true;
+        }
+        if !keep(line) {
+            return false;
+        }
+        lines.push(line.to_string());
+        true
+    };
+    while position > 0 && !done {
+        let size = BLOCK_SIZE.min(position);
+        position -= size;
+        input.seek(SeekFrom::Start(position))?;
+        let mut block = vec![0; size as usize];
+        input.read_exact(&mut block)?;
+        block.extend_from_slice(&partial);
+        let mut rest = block.as_slice();
+        while let Some(newline) = rest.iter().rposition(|&byte| byte == b'\n') {
+            if !take(&rest[newline + 1..], &mut lines) {
+                done = true;
+                break;
+            }
+            rest = &rest[..newline];
+        }
+        partial = rest.to_vec();
+    }
+    if !done {
+        take(&partial, &mut lines);
+    }
+    input.seek(SeekFrom::Start(end))?;
+    lines.reverse();
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn numbered(lines: usize) -> String {
+        (1..=lines).map(|i| format!("{i}\n")).collect()
+    }
+
+    #[test]
+    fn test_parse_backfill() {
+        assert_eq!(parse_backfill("500"), Ok(Backfill::Lines(500)));
+        assert_eq!(
+            parse_backfill("10m"),
+            Ok(Backfill::Duration(Duration::from_secs(600)))
+        );
+        assert!(parse_backfill("soon").is_err());
+    }
+
+    #[test]
+    fn test_last_lines() -> io::Result<()> {
+        let mut input = Cursor::new(numbered(10));
+        let mut count = 0;
+        let lines = read_tail(&mut input, |_| {
+            count += 1;
+            count <= 3
+        })?;
+        assert_eq!(lines, ["8", "9", "10"]);
+        assert_eq!(input.position(), input.get_ref().len() as u64);
+        Ok(())
+    }
+
+    #[test]
+    fn test_lines_across_blocks() -> io::Result<()> {
+        // long enough to span several blocks, with lines cut at block boundaries
+        let text = numbered(50_000);
+        assert!(text.len() as u64 > 3 * BLOCK_SIZE);
+        let lines = read_tail(&mut Cursor::new(&text), |_| true)?;
+        assert_eq!(lines.len(), 50_000);
+        assert_eq!(lines[0], "1");
+        assert_eq!(lines[12_345], "12346");
+
+        let mut count = 0;
+        let lines = read_tail(&mut Cursor::new(&text), |_| {
+            count += 1;
+            count <= 20_000
+        })?;
+        assert_eq!(lines.first().map(String::as_str), Some("30001"));
+        assert_eq!(lines.last().map(String::as_str), Some("50000"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_crlf_and_missing_final_newline() -> io::Result<()> {
+        let lines = read_tail(&mut Cursor::new("a\r\n\r\nb\r\nc"), |_| true)?;
+        assert_eq!(lines, ["a", "b", "c"]);
+        assert!(read_tail(&mut Cursor::new(""), |_| true)?.is_empty());
+        Ok(())
+    }
+}
@@ -1,13 +1,22 @@
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
+
 use clap::ArgAction;
 use clap::Args;
 use clap::Parser;
 use clap::Subcommand;
 use clap::ValueEnum;
+use ratatui::style::Color;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 
+use crate::backfill::Backfill;
 use crate::config::get_config_dir;
 use crate::config::get_data_dir;
+use crate::highlight::HighlightRange;
+use crate::source::{Source, Warmup};
 
-#[derive(Debug, ValueEnum, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Layout {
     Horizontal,
     Vertical,
@@ -15,10 +24,189 @@ pub enum Layout {
     Auto,
 }
 
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumberLocale {
+    #[default]
+    Plain,
+    En,
+    De,
+    Fr,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LabelFormat {
+    #[default]
+    Auto,
+    Si,
+    Binary,
+    Plain,
+}
+
+/// `--history`, a fixed number of samples or as many as the widest chart shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum History {
+    Samples(usize),
+    Auto,
+}
+
+impl History {
+    /// Samples kept for every chart at startup, `auto` starts out with the default.
+    pub fn samples(self) -> usize {
+        match self {
+            History::Samples(samples) => samples,
+            History::Auto => 200,
+        }
+    }
+}
+
+fn parse_regex(value: &str) -> Result<Regex, String> {
+    Regex::new(value).map_err(|err| err.to_string())
+}
+
+fn parse_history(value: &str) -> Result<History, String> {
+    if value == "auto" {
+        return Ok(History::Auto);
+    }
+    value
+        .parse()
+        .map(History::Samples)
+        .map_err(|_| format!("invalid history `{value}`, expected a number of samples or `auto`"))
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyChannel {
+    Bell,
+    Osc9,
+    Title,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnitPrefix {
+    Lock,
+    #[default]
+    Auto,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StatsPanel {
+    Right,
+    Bottom,
+    #[default]
+    Off,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StatsScope {
+    #[default]
+    Window,
+    Session,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    Unit,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sort {
+    Name,
+    Value,
+    Alert,
+    #[default]
+    None,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayMode {
+    #[default]
+    Chart,
+    Ticker,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Grid {
+    #[default]
+    None,
+    Dots,
+    Baseline,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq, Default)]
+pub enum YFrom {
+    #[default]
+    Zero,
+    Min,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnParseError {
+    Zero,
+    #[default]
+    Skip,
+    Last,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Number,
+    Bool,
+    Graphite,
+    Statsd,
+    /// Lines counted per update interval by the value `--count-by` captures, set by that flag.
+    #[value(skip)]
+    Count,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GroupScale {
+    #[default]
+    Shared,
+    Normalized,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Header {
+    Auto,
+    FirstLine,
+    #[default]
+    None,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeAxis {
+    None,
+    #[default]
+    Relative,
+    Clock,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeDirection {
+    Ltr,
+    #[default]
+    Rtl,
+}
+
 #[derive(Parser, Debug)]
-#[command(author, version = version(), about)]
+#[command(
+    author,
+    version = version(),
+    about,
+    after_help = "The dashboard is drawn on /dev/tty (CONOUT$ on Windows) and only falls back to \
+                  stdout when there is no terminal, so stdout can be redirected, e.g. for \
+                  `--summary -`."
+)]
 pub struct Cli {
-    /// Tick rate, i.e. number of ticks per second
+    /// Tick rate, i.e. number of ticks per second, raised to at least one per --update-frequency
     #[arg(long, value_name = "FLOAT", default_value_t = 4.0)]
     pub tick_rate: f64,
 
@@ -26,38 +214,365 @@ pub struct Cli {
     #[arg(short, long, value_name = "FLOAT", default_value_t = 60.0)]
     pub frame_rate: f64,
 
+    /// Send less to the terminal, e.g. over a slow SSH link: at most 10 frames per second and ASCII bars
+    #[arg(long)]
+    pub low_bandwidth: bool,
+
     /// Chart title, will be shown at the top of the chart
     #[arg(short, long, value_name = "STRING")]
     pub titles: Option<Vec<String>>,
 
+    /// What the chart shows, where it comes from or what a bad value is, shown with F1 or d
+    #[arg(long, value_name = "STRING")]
+    pub descriptions: Option<Vec<String>>,
+
     /// Unit to be used in the chart (e.g. "ms", "MB")
     #[arg(short, long)]
     pub units: Option<Vec<String>>,
 
+    /// Color of the chart bars (e.g. "cyan", "#ff8800")
+    #[arg(short, long, value_name = "COLOR")]
+    pub colors: Option<Vec<Color>>,
+
+    /// Target value of the chart, drawn as a dashed line with the last value's distance to it
+    #[arg(long = "reference", value_name = "FLOAT")]
+    pub references: Option<Vec<f64>>,
+
+    /// Round every value of the chart to the nearest multiple of the step before it is stored
+    #[arg(long = "quantize", value_name = "STEP", value_parser = crate::number::parse_step)]
+    pub quantize: Option<Vec<f64>>,
+
+    /// How to get attention when a chart goes above its --reference: a `bell`, an `osc9` desktop notification and/or the terminal `title`
+    #[arg(long, value_name = "STRING", value_delimiter = ',')]
+    pub notify: Option<Vec<NotifyChannel>>,
+
     /// Index vector to be used in the chart
     #[arg(short, long, value_name = "INT")]
     pub indices: Option<Vec<usize>>,
 
+    /// Column header of the input whose names become the chart titles, `auto` takes the last line without numbers before the first one with numbers
+    #[arg(long, value_name = "STRING", default_value("none"))]
+    pub header: Header,
+
+    /// Header names of the columns to chart, e.g. `us,sy`, instead of --indices
+    #[arg(long, value_name = "STRING", value_delimiter = ',')]
+    pub fields: Option<Vec<String>>,
+
     /// Group together to show multiple charts in the same window
     #[clap(
         short,
         long,
         default_missing_value("true"),
-        default_value("false"),
         num_args(0..=1),
         require_equals(true),
         action = ArgAction::Set,
     )]
     pub group: Option<bool>,
 
+    /// Group the charts into one panel per unit, charts without a unit share a panel
+    #[arg(long, value_name = "STRING")]
+    pub group_by: Option<GroupBy>,
+
+    /// Y scale of a group chart, `normalized` rescales every chart between its own min and max in view so their shapes can be compared
+    #[arg(long, value_name = "STRING", default_value("shared"))]
+    pub group_scale: GroupScale,
+
+    /// Where the bars of a chart start, `min` zooms onto the range in view so small changes on a large offset show
+    #[arg(long, value_name = "STRING", default_value("zero"))]
+    pub y_from: YFrom,
+
     /// Update frequency, i.e. number of milliseconds between updates
     #[arg(long, value_name = "INT", default_value_t = 1000)]
     pub update_frequency: u64,
 
-    /// Layout of the chart
-    #[clap(short, long, value_name = "STRING", default_value("auto"))]
+    /// Per-chart input settings, e.g. `every=5s` to advance the history at most every 5 seconds,
+    /// `cadence=arrival` to take a sample for every value as it arrives or `ssh:host!command` to
+    /// chart a remote command instead of stdin, `desc=` describes it
+    #[arg(long = "source", value_name = "SPEC", value_parser = crate::source::parse_source)]
+    pub sources: Option<Vec<Source>>,
+
+    /// Follow a file like `tail -F` instead of stdin, each in its own chart titled by the file
+    /// name. Repeatable, `*` and `?` in the file name match files that also appear later
+    #[arg(
+        long = "file",
+        visible_alias = "follow",
+        value_name = "PATH",
+        conflicts_with = "once"
+    )]
+    pub files: Vec<String>,
+
+    /// Read the lines from a file instead of stdin, `-` for stdin. Its end shows the last values
+    /// until it grows
+    #[arg(long, value_name = "PATH", conflicts_with = "files")]
+    pub input: Option<PathBuf>,
+
+    /// Run a command through the shell every --interval and chart what it prints, like `watch`,
+    /// instead of reading stdin. A run still going when the next is due skips that one
+    #[arg(long, value_name = "COMMAND", conflicts_with_all = ["files", "input", "once", "backfill"])]
+    pub exec: Option<String>,
+
+    /// Milliseconds between two runs of the `--exec` command [default: --update-frequency]
+    #[arg(long, value_name = "INT", requires = "exec", value_parser = clap::value_parser!(u64).range(1..))]
+    pub interval: Option<u64>,
+
+    /// Accept connections on a TCP address, like `0.0.0.0:9999`, and chart the lines every
+    /// client sends instead of stdin. A bare port listens on 127.0.0.1
+    #[arg(
+        long,
+        value_name = "ADDR:PORT",
+        value_parser = crate::http::parse_addr,
+        conflicts_with_all = ["files", "input", "exec", "once", "backfill"]
+    )]
+    pub listen: Option<SocketAddr>,
+
+    /// Give every `--listen` connection a chart of its own, titled by the client's address,
+    /// instead of charting them all together
+    #[arg(long, requires = "listen")]
+    pub per_connection: bool,
+
+    /// Receive datagrams on a UDP address, like `0.0.0.0:8125`, and chart the numbers in them
+    /// instead of stdin. A bare port listens on 127.0.0.1
+    #[arg(
+        long,
+        value_name = "ADDR:PORT",
+        value_parser = crate::http::parse_addr,
+        conflicts_with_all = ["files", "input", "exec", "once", "backfill", "listen"]
+    )]
+    pub listen_udp: Option<SocketAddr>,
+
+    /// Create a Unix socket at PATH and chart the lines every program that connects to it
+    /// writes, like `nc -U PATH`, instead of stdin. The socket is removed on exit
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["files", "input", "exec", "once", "backfill", "listen", "listen_udp"]
+    )]
+    pub socket: Option<PathBuf>,
+
+    /// Start the `--input` file over from its first line once it ends, to replay demo data
+    #[arg(long = "loop", requires = "input", conflicts_with = "once")]
+    pub loop_input: bool,
+
+    /// Count the failed runs of an `ssh:` source's command in its title and as notes, and stop
+    /// restarting it after N of them [default: never]
+    #[arg(long, value_name = "N", num_args = 0..=1, require_equals = true)]
+    pub restart_on_failure: Option<Option<u32>>,
+
+    /// Chart the last lines of the --file files, or of stdin redirected from a file, before
+    /// following them, a number of lines like `500` or, with `--format graphite`, a duration like
+    /// `1h`
+    #[arg(long, value_name = "N|DURATION", value_parser = crate::backfill::parse_backfill)]
+    pub backfill: Option<Backfill>,
+
+    /// Leave the first samples of every chart out of the stats and the summary, a number of
+    /// samples like `20` or a duration like `30s` from the chart's first sample
+    #[arg(long, value_name = "N|DURATION", value_parser = crate::source::parse_warmup)]
+    pub warmup: Option<Warmup>,
+
+    /// Mark a chart that got no sample for this long as stale, e.g. `2m`, and leave a stale
+    /// series of a group chart out of its scale
+    #[arg(long, value_name = "DURATION", value_parser = crate::source::parse_duration)]
+    pub stale_after: Option<Duration>,
+
+    /// Number of samples kept for each chart, `auto` grows it to fill the width of the widest chart
+    #[arg(long, value_name = "INT|auto", default_value = "200", value_parser = parse_history)]
+    pub history: History,
+
+    /// Layout of the chart [default: auto]
+    #[clap(short, long, value_name = "STRING")]
     pub layout: Option<Layout>,
 
+    /// Time axis labels, `relative` counts seconds back from the newest sample and `clock` prints
+    /// the time of day
+    #[arg(long, value_name = "STRING", default_value("relative"))]
+    pub time_axis: TimeAxis,
+
+    /// Where the newest sample is drawn, `rtl` keeps it on the right and `ltr` on the left
+    #[arg(long, value_name = "STRING", default_value("rtl"))]
+    pub time_direction: TimeDirection,
+
+    /// Highlight a range of samples (e.g. "100..200", counted from the first sample) or of
+    /// times of day (e.g. "14:02..14:05")
+    #[arg(long, value_name = "RANGE", value_parser = crate::highlight::parse_highlight_range)]
+    pub highlight_range: Option<HighlightRange>,
+
+    /// Display order of the charts, `value` sorts by the latest sample in descending order and
+    /// `alert` puts the charts above their --reference first
+    #[arg(long, value_name = "STRING")]
+    pub sort: Option<Sort>,
+
+    /// Display order of the charts by number without --sort, e.g. `3,1,2`, unlisted charts follow
+    #[arg(long, value_name = "INT", value_delimiter = ',')]
+    pub order: Option<Vec<usize>>,
+
+    /// Format of the values, `bool` maps tokens like "ok/fail" or "up/down" to on/off
+    #[arg(long, value_name = "STRING", default_value("number"))]
+    pub format: Format,
+
+    /// Count the lines matching the regex per update interval, with one chart for every value
+    /// of its first capture group, e.g. `" ([1-5])\d\d "` for a chart per HTTP status class
+    #[arg(long, value_name = "REGEX", value_parser = parse_regex, conflicts_with = "format")]
+    pub count_by: Option<Regex>,
+
+    /// Charts `--count-by` creates at most, lines with further values are counted in `other`
+    #[arg(long, value_name = "INT", default_value_t = 10, requires = "count_by", value_parser = clap::value_parser!(u16).range(1..))]
+    pub max_groups: u16,
+
+    /// Show the stats of all charts in one table instead of a line in every chart
+    #[arg(long, value_name = "STRING", default_value("off"))]
+    pub stats_panel: StatsPanel,
+
+    /// Samples the average, min, max and p95 shown are about, `session` takes every sample since
+    /// the chart started instead of the retained ones, the p95 within 1% of the exact one
+    #[arg(long, value_name = "STRING", default_value("window"))]
+    pub stats_scope: StatsScope,
+
+    /// `ticker` lists every chart on one line with its last value, trend and a sparkline, next
+    /// to the full chart of the focused one
+    #[arg(long, value_name = "STRING", default_value("chart"))]
+    pub mode: DisplayMode,
+
+    /// Draw a group of two charts with half-block cells, doubling the history shown
+    #[arg(long)]
+    pub dense: bool,
+
+    /// Draw tiny values in exact proportion, even when that leaves them empty, instead of at
+    /// least one eighth of a cell high
+    #[arg(long)]
+    pub no_min_bar: bool,
+
+    /// Mark the empty cells of the charts so their extent shows: a dot in every cell, or only on
+    /// the bottom row
+    #[arg(long, value_enum, value_name = "STYLE", default_value_t = Grid::None)]
+    pub grid: Grid,
+
+    /// Leave out the glyphs in front of the chart titles that tell the kind of source, like `⎙`
+    /// for a file, and its state, like `▶` live or `✖` failed
+    #[arg(long)]
+    pub no_icons: bool,
+
+    /// Leave the keys of the chart actions, like `r reset · space pause`, out of the bottom
+    /// border of the focused chart, which then shows its time markers like the others
+    #[arg(long)]
+    pub no_hints: bool,
+
+    /// Add the samples a chart got and how many arrive per second to its title, e.g. `n=1432 @1.0/s`
+    #[arg(long)]
+    pub show_counts: bool,
+
+    /// Prefixes for large values in the y label and stats, `auto` uses binary ones for `B` and `iB`
+    #[arg(long, value_name = "STRING", default_value("auto"))]
+    pub label_format: LabelFormat,
+
+    /// How the prefix of the y label follows the max, `lock` keeps the first one and `auto` switches 20% past a boundary
+    #[arg(long, value_name = "STRING", default_value("auto"))]
+    pub unit_prefix: UnitPrefix,
+
+    /// Append the notes added with `n` to this file as they are entered
+    #[arg(long, value_name = "PATH")]
+    pub notes_file: Option<PathBuf>,
+
+    /// Decimal and thousands separators of the displayed numbers, e.g. `de` shows 1.234,56
+    #[arg(long, value_name = "STRING", default_value("plain"))]
+    pub number_locale: NumberLocale,
+
+    /// Chart the per-second rate of change of the values, e.g. for monotonic counters
+    #[arg(long)]
+    pub rate: bool,
+
+    /// Compute the rate over a sliding window this long instead of adjacent samples, e.g. `10s`
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = crate::source::parse_duration,
+        requires = "rate"
+    )]
+    pub rate_window: Option<Duration>,
+
+    /// Skip a value repeating the chart's previous one, and its timestamp if it has one, as a heartbeat
+    #[arg(long)]
+    pub dedupe: bool,
+
+    /// Add a note whenever an on/off chart turns on or off, marked on the charts like the ones added with `n`
+    #[arg(long)]
+    pub mark_transitions: bool,
+
+    /// What a unit match that isn't a number becomes: a `zero`, `skip`ped or the chart's `last` value. Every one is counted as a parse error
+    #[arg(long, value_name = "STRING", default_value("skip"))]
+    pub on_parse_error: OnParseError,
+
+    /// Write a JSON summary of every chart on exit, `-` for stdout
+    #[arg(long, value_name = "PATH")]
+    pub summary: Option<PathBuf>,
+
+    /// Put the retained samples of every chart and their times in the `--summary`
+    #[arg(long, requires = "summary")]
+    pub summary_include_samples: bool,
+
+    /// Average the samples of a chart in buckets down to this many in the `--summary`
+    #[arg(long, value_name = "INT", requires = "summary_include_samples", value_parser = clap::value_parser!(u64).range(1..))]
+    pub summary_max_samples: Option<u64>,
+
+    /// Directory `E` exports the focused chart to [default: the current directory]
+    #[arg(long, value_name = "DIR")]
+    pub export_dir: Option<PathBuf>,
+
+    /// Format of the chart exported with `E`, `csv` has `time,value` rows after `#` comments
+    #[arg(long, value_name = "STRING", default_value("csv"))]
+    pub export_format: ExportFormat,
+
+    /// Read commands like `pause`, `reset 2` or `mark deploy` from a FIFO created at PATH [default: `control` in the data directory]
+    #[arg(long, value_name = "PATH", num_args = 0..=1, require_equals = true)]
+    pub control_fifo: Option<Option<PathBuf>>,
+
+    /// Serve the live stats as JSON on `GET /stats`, a bare port listens on 127.0.0.1
+    #[arg(long, value_name = "ADDR", value_parser = crate::http::parse_addr)]
+    pub http: Option<SocketAddr>,
+
+    /// Drop the oldest samples of every chart when the history would need more memory (e.g. "256M")
+    #[arg(long, value_name = "SIZE", value_parser = crate::memory::parse_size)]
+    pub max_memory: Option<usize>,
+
+    /// Parse only every k-th Graphite or StatsD line while more lines than this arrive per second
+    #[arg(long, value_name = "INT", default_value_t = 50_000)]
+    pub max_input_rate: usize,
+
+    /// Profile from the config file to load, and to write back with `W` [default: default]
+    #[arg(long, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Draw the time labels against a fixed clock so frames are reproducible, for screenshots
+    #[arg(long)]
+    pub deterministic: bool,
+
+    /// Read all of the input, print a single frame and the stats of every chart, then exit
+    #[arg(long)]
+    pub once: bool,
+
+    /// Width of the `--once` frame
+    #[arg(long, value_name = "INT", default_value_t = 40, requires = "once", value_parser = clap::value_parser!(u16).range(2..))]
+    pub width: u16,
+
+    /// Height of the `--once` frame
+    #[arg(long, value_name = "INT", default_value_t = 8, requires = "once", value_parser = clap::value_parser!(u16).range(2..))]
+    pub height: u16,
+
+    /// Fail instead of warning when per-chart options don't match the number of charts
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Only log, instead of also notifying, when a unit or column matched none of the first lines
+    #[arg(long)]
+    pub quiet_diagnostics: bool,
+
+    /// Don't mark the part of the history in view on the bottom border of the focused chart
+    #[arg(long)]
+    pub no_scrollbar: bool,
+
+    /// Don't show the summary of the effective configuration at startup
+    #[arg(long)]
+    pub no_banner: bool,
+
     #[command(subcommand)]
     pub cmd: Option<Commands>,
 }
@@ -70,6 +585,21 @@ pub enum Commands {
     Remove(RemoveArgs),
     /// List all regexes
     List,
+    /// Import regexes from a JSON list of {name, regex, description} or TOML [[regexes]]
+    Import(ImportArgs),
+    /// Export the added regexes as JSON, or as TOML to a `.toml` file
+    Export(ExportArgs),
+    /// Check the config file
+    #[command(subcommand)]
+    Config(ConfigCommands),
+    /// Print what the flags extract from the first lines of the input, without starting the TUI
+    Test(TestArgs),
+    /// Print the build, the terminal as ttydash sees it and the settings in effect, for bug
+    /// reports
+    Doctor,
+    /// Chart the input without a terminal and write the last frame, for tests
+    #[command(hide = true)]
+    Simulate(SimulateArgs),
 }
 #[derive(Args, Debug)]
 pub struct AddArgs {
@@ -79,16 +609,66 @@ pub struct AddArgs {
     /// The regex to add
     #[arg(short, long)]
     pub regex: String,
+    /// Start a new config file when the current one can't be parsed, keeping it as `.bak`
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommands {
+    /// Check the syntax, regexes and key bindings of the config file
+    Validate,
+}
+
+#[derive(Args, Debug)]
+pub struct TestArgs {
+    /// Number of input lines to read
+    #[arg(short, long, value_name = "INT", default_value_t = 20)]
+    pub lines: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct SimulateArgs {
+    /// Number of input lines to chart, one per update interval [default: all]
+    #[arg(long, value_name = "INT")]
+    pub frames: Option<usize>,
+    /// Width of the frame
+    #[arg(long, value_name = "INT", default_value_t = 100, value_parser = clap::value_parser!(u16).range(2..))]
+    pub width: u16,
+    /// Height of the frame
+    #[arg(long, value_name = "INT", default_value_t = 30, value_parser = clap::value_parser!(u16).range(2..))]
+    pub height: u16,
+    /// File to write the last frame to, stdout when missing or `-`
+    #[arg(long, value_name = "PATH")]
+    pub render_to: Option<PathBuf>,
 }
 
 #[derive(Args, Debug)]
 pub struct RemoveArgs {
     /// The name of the regex to remove
     #[arg(short, long)]
-    name: String,
+    pub name: String,
+}
+
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+    /// File to import, `-` for stdin
+    pub file: PathBuf,
+    /// Replace regexes that already exist under the same name
+    #[arg(long)]
+    pub overwrite: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    /// File to write, stdout when missing or `-`
+    pub file: Option<PathBuf>,
+    /// Include the regexes that come with ttydash
+    #[arg(long)]
+    pub include_builtin: bool,
 }
 
-const VERSION_MESSAGE: &str = concat!(
+pub const VERSION_MESSAGE: &str = concat!(
     env!("CARGO_PKG_VERSION"),
     "-",
     env!("VERGEN_GIT_DESCRIBE"),
@@ -0,0 +1,265 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+    task::{Poll, Waker},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use futures::future::{self, BoxFuture};
+
+/// Where the readers and the drawing get the time from and wait on, so `ttydash simulate` and
+/// the tests can run them on a virtual clock instead of the real one.
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// The time samples are stored at.
+    fn now(&self) -> Instant;
+    /// The wall clock time, for the clock labels of the time axis.
+    fn wall(&self) -> SystemTime;
+    /// Wait until `duration` passed on this clock.
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+    /// Tick every `period` on this clock, the first tick right away.
+    fn interval(&self, period: Duration) -> Interval;
+}
+
+/// The system clock and tokio's timers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RealClock;
+
+impl Clock for RealClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn wall(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+
+    fn interval(&self, period: Duration) -> Interval {
+        Interval::new(Arc::new(*self), period)
+    }
+}
+
+/// A clock that stands still until it is moved with [`MockClock::set`] or
+/// [`MockClock::advance`], waking the sleeps that are due. Clones share the time.
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    time: Arc<Mutex<MockTime>>,
+}
+
+#[derive(Debug)]
+struct MockTime {
+    now: Instant,
+    wall: SystemTime,
+    sleepers: Vec<Waker>,
+}
+
+impl MockClock {
+    /// A clock at `start`, with the wall clock at the time it was created.
+    pub fn new(start: Instant) -> Self {
+        let time = MockTime {
+            now: start,
+            wall: SystemTime::now(),
+            sleepers: vec![],
+        };
+        Self {
+            time: Arc::new(Mutex::new(time)),
+        }
+    }
+
+    /// Move the clock to `now`, forward or back, and the wall clock by as much.
+    pub fn set(&self, now: Instant) {
+        let mut time = self.time.lock().unwrap();
+        time.wall = if now >= time.now {
+            time.wall + (now - time.now)
+        } else {
+            time.wall - (time.now - now)
+        };
+        time.now = now;
+        // the sleeps check whether they are due when polled and register again if not
+        for waker in time.sleepers.drain(..) {
+            waker.wake();
+        }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        let now = self.now() + by;
+        self.set(now);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.time.lock().unwrap().now
+    }
+
+    fn wall(&self) -> SystemTime {
+        self.time.lock().unwrap().wall
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        let deadline = self.now() + duration;
+        let time = self.time.clone();
+        Box::pin(future::poll_fn(move |cx| {
+            let mut time = time.lock().unwrap();
+            if time.now >= deadline {
+                return Poll::Ready(());
+            }
+            time.sleepers.push(cx.waker().clone());
+            Poll::Pending
+        }))
+    }
+
+    fn interval(&self, period: Duration) -> Interval {
+        Interval::new(Arc::new(self.clone()), period)
+    }
+}
+
+/// Ticks every `period` of a [`Clock`]. Ticks missed while the caller was busy come right after
+/// another, like the default of `tokio::time::interval`.
+pub struct Interval {
+    clock: Arc<dyn Clock>,
+    period: Duration,
+    next: Instant,
+}
+
+impl Interval {
+    fn new(clock: Arc<dyn Clock>, period: Duration) -> Self {
+        let next = clock.now();
+        Self {
+            clock,
+            period,
+            next,
+        }
+    }
+
+    /// Wait for the next tick and return when it was due. Cancel safe: a tick waited for in a
+    /// `select!` branch that lost is still the next one.
+    pub async fn tick(&mut self) -> Instant {
+        let due = self.next;
+        let wait = due.saturating_duration_since(self.clock.now());
+        if !wait.is_zero() {
+            self.clock.sleep(wait).await;
+        }
+        self.next = due + self.period;
+        due
+    }
+}
+
+/// Seconds since local midnight.
+pub fn local_seconds_of_day(time: SystemTime) -> u32 {
+    let (hours, minutes, seconds) = local_time(time);
+    hours * 3600 + minutes * 60 + seconds
+}
+
+/// Local wall clock time as `HH:MM:SS`.
+pub fn format_clock(time: SystemTime) -> String {
+    let (hours, minutes, seconds) = local_time(time);
+    format!("{hours:02}:{minutes:02}:{seconds:02}")
+}
+
+pub fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Hours, minutes and seconds of the local time of day.
+#[cfg(unix)]
+fn local_time(time: SystemTime) -> (u32, u32, u32) {
+    let secs = unix_seconds(time) as libc::time_t;
+    // SAFETY: localtime_r only writes to the tm we own
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    unsafe { libc::localtime_r(&secs, &mut tm) };
+    (tm.tm_hour as u32, tm.tm_min as u32, tm.tm_sec as u32)
+}
+
+/// Hours, minutes and seconds of the time of day. Without libc there is no time zone database to
+/// ask, so the clock is shown in UTC.
+#[cfg(not(unix))]
+fn local_time(time: SystemTime) -> (u32, u32, u32) {
+    let secs = (unix_seconds(time) % (24 * 3600)) as u32;
+    (secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// Poll `future` once, without a runtime driving it.
+    fn ready<F: std::future::Future + ?Sized>(future: std::pin::Pin<&mut F>) -> bool {
+        let mut cx = std::task::Context::from_waker(futures::task::noop_waker_ref());
+        future.poll(&mut cx).is_ready()
+    }
+
+    #[test]
+    fn test_mock_sleep() {
+        let start = Instant::now();
+        let clock = MockClock::new(start);
+        let wall = clock.wall();
+        let mut sleep = clock.sleep(Duration::from_secs(5));
+        assert!(!ready(sleep.as_mut()));
+        clock.advance(Duration::from_secs(4));
+        assert!(!ready(sleep.as_mut()));
+        clock.advance(Duration::from_secs(1));
+        assert!(ready(sleep.as_mut()));
+        assert_eq!(clock.now(), start + Duration::from_secs(5));
+        assert_eq!(clock.wall(), wall + Duration::from_secs(5));
+        clock.set(start);
+        assert_eq!(clock.wall(), wall);
+    }
+
+    #[test]
+    fn test_interval() {
+        let start = Instant::now();
+        let clock = MockClock::new(start);
+        let mut interval = clock.interval(Duration::from_secs(2));
+        let mut ticks = vec![];
+        for _ in 0..3 {
+            let mut tick = std::pin::pin!(interval.tick());
+            while !ready(tick.as_mut()) {
+                clock.advance(Duration::from_millis(500));
+            }
+            ticks.push(clock.now() - start);
+        }
+        let seconds = |s: &[u64]| {
+            s.iter()
+                .map(|&s| Duration::from_secs(s))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ticks, seconds(&[0, 2, 4]));
+        // a tick given up on is still the next one, missed ones come at once
+        {
+            let mut tick = std::pin::pin!(interval.tick());
+            assert!(!ready(tick.as_mut()));
+        }
+        clock.advance(Duration::from_secs(6));
+        for _ in 0..3 {
+            assert!(ready(std::pin::pin!(interval.tick()).as_mut()));
+        }
+        assert!(!ready(std::pin::pin!(interval.tick()).as_mut()));
+    }
+
+    #[test]
+    fn test_format_clock() {
+        let label = format_clock(SystemTime::now());
+        assert_eq!(label.len(), 8);
+        assert_eq!(label.matches(':').count(), 2);
+    }
+
+    #[test]
+    fn test_seconds_of_day() {
+        let now = SystemTime::now();
+        let later = now + Duration::from_secs(61);
+        let (a, b) = (local_seconds_of_day(now), local_seconds_of_day(later));
+        // unless midnight passed in between
+        if b > a {
+            assert_eq!(b - a, 61);
+        }
+        assert!(a < 24 * 3600);
+    }
+}
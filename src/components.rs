@@ -8,8 +8,12 @@ use tokio::sync::mpsc::UnboundedSender;
 
 use crate::{action::Action, config::Config, tui::Event};
 
+pub mod banner;
+mod barchart;
 pub mod dash;
 pub mod fps;
+pub mod input;
+pub mod toast;
 
 /// `Component` is a trait that represents a visual and interactive element of the user interface.
 ///
@@ -68,6 +72,7 @@ pub trait Component {
         let action = match event {
             Some(Event::Key(key_event)) => self.handle_key_event(key_event)?,
             Some(Event::Mouse(mouse_event)) => self.handle_mouse_event(mouse_event)?,
+            Some(Event::Paste(text)) => self.handle_paste_event(text)?,
             _ => None,
         };
         Ok(action)
@@ -98,6 +103,20 @@ pub trait Component {
         let _ = mouse; // to appease clippy
         Ok(None)
     }
+    /// Handle text pasted into the terminal, which arrives in one piece rather than as key
+    /// presses, and produce actions if necessary.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The pasted text, as is.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<Action>>` - An action to be processed or none.
+    fn handle_paste_event(&mut self, text: String) -> Result<Option<Action>> {
+        let _ = text; // to appease clippy
+        Ok(None)
+    }
     /// Update the state of the component based on a received action. (REQUIRED)
     ///
     /// # Arguments
@@ -122,4 +141,12 @@ pub trait Component {
     ///
     /// * `Result<()>` - An Ok result or an error.
     fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()>;
+    /// Clean up once the application has quit and the terminal has been restored.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - An Ok result or an error.
+    fn exit(&mut self) -> Result<()> {
+        Ok(())
+    }
 }
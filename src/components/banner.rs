@@ -0,0 +1,253 @@
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use clap::ValueEnum;
+use color_eyre::Result;
+use crossterm::event::KeyEvent;
+use ratatui::{prelude::*, widgets::*};
+use unicode_width::UnicodeWidthStr;
+
+use super::Component;
+use crate::{
+    action::Action,
+    cli::{Cli, History},
+    profile::DEFAULT_PROFILE,
+};
+
+const BANNER_DURATION: Duration = Duration::from_secs(5);
+
+/// The effective configuration, shown at the top for a few seconds after startup so a stale
+/// flag from a profile or the config file doesn't go unnoticed. Any key hides it.
+#[derive(Debug, Default)]
+pub struct Banner {
+    lines: Vec<String>,
+    shown: Option<Instant>,
+}
+
+impl Banner {
+    pub fn new(lines: Vec<String>) -> Self {
+        Self { lines, shown: None }
+    }
+
+    fn hide(&mut self) {
+        self.lines.clear();
+    }
+}
+
+/// Describe the flags after the profile was applied, in three lines: where the values come
+/// from, the charts and how they are sampled, and the config that was loaded.
+pub fn summary_lines(
+    args: &Cli,
+    config_file: Option<&Path>,
+    profile: &str,
+    profile_found: bool,
+) -> Vec<String> {
+    let ssh = args
+        .sources
+        .iter()
+        .flatten()
+        .filter(|source| source.ssh.is_some())
+        .count();
+    let input_file = args.input.as_deref().filter(|path| *path != Path::new("-"));
+    let input = match (ssh, input_file) {
+        _ if !args.files.is_empty() => format!("files {}", args.files.join(" ")),
+        _ if args.exec.is_some() => format!("command `{}`", args.exec.as_ref().unwrap()),
+        _ if args.per_connection => format!("tcp {}, a chart per connection", args.listen.unwrap()),
+        _ if args.listen.is_some() => format!("tcp {}", args.listen.unwrap()),
+        _ if args.listen_udp.is_some() => format!("udp {}", args.listen_udp.unwrap()),
+        _ if args.socket.is_some() => format!("socket {}", args.socket.as_ref().unwrap().display()),
+        (_, Some(path)) if args.loop_input => format!("file {}, looped", path.display()),
+        (_, Some(path)) => format!("file {}", path.display()),
+        (0, None) => "stdin".to_string(),
+        (1, None) => "1 ssh source".to_string(),
+        (ssh, None) => format!("{ssh} ssh sources"),
+    };
+    let mut extract = vec![];
+    if let Some(count_by) = &args.count_by {
+        extract.push(format!("count by {count_by}"));
+    }
+    if let Some(units) = &args.units {
+        extract.push(format!("units {}", units.join(" ")));
+    }
+    if let Some(fields) = &args.fields {
+        extract.push(format!("fields {}", fields.join(",")));
+    } else if let Some(indices) = &args.indices {
+        let indices = indices.iter().map(usize::to_string).collect::<Vec<_>>();
+        extract.push(format!("columns {}", indices.join(",")));
+    }
+    if args.header != crate::cli::Header::None {
+        extract.push(format!("header {}", value_name(args.header)));
+    }
+    if extract.is_empty() {
+        extract.push("every number".to_string());
+    }
+    let charts = [
+        args.units.as_ref().map(Vec::len),
+        args.fields.as_ref().map(Vec::len),
+        args.indices.as_ref().map(Vec::len),
+        (ssh > 0).then_some(ssh),
+    ]
+    .into_iter()
+    .flatten()
+    .next();
+
+    let mut sampling = vec![
+        match charts {
+            Some(charts) => format!("Charts: {charts}"),
+            None => "Charts: from the input".to_string(),
+        },
+        format!(
+            "every {} ms",
+            args.interval.unwrap_or(args.update_frequency)
+        ),
+        format!("{} ticks/s, {} fps", args.tick_rate, args.frame_rate),
+        match args.history {
+            History::Samples(samples) => format!("history {samples}"),
+            History::Auto => "history auto".to_string(),
+        },
+    ];
+    if let Some(references) = &args.references {
+        let references = references.iter().map(f64::to_string).collect::<Vec<_>>();
+        sampling.push(format!("references {}", references.join(", ")));
+    }
+    if args.warmup.is_some() {
+        sampling.push("warmup".to_string());
+    }
+    if args.rate {
+        sampling.push("rate".to_string());
+    }
+
+    let config = match config_file {
+        Some(path) => path.display().to_string(),
+        None => "none".to_string(),
+    };
+    let profile = if profile_found {
+        format!("profile {profile}")
+    } else if profile == DEFAULT_PROFILE {
+        "no profile".to_string()
+    } else {
+        format!("profile {profile} (not found)")
+    };
+    vec![
+        format!(
+            "Input: {input}, format {}, {}",
+            value_name(args.format),
+            extract.join(", ")
+        ),
+        sampling.join(", "),
+        format!("Config: {config}, {profile}"),
+    ]
+}
+
+/// The name a value of a flag is given as on the command line.
+fn value_name(value: impl ValueEnum) -> String {
+    value
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+impl Component for Banner {
+    fn handle_key_event(&mut self, _key: KeyEvent) -> Result<Option<Action>> {
+        // the key still does what it is bound to
+        self.hide();
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        if action == Action::Tick {
+            let shown = *self.shown.get_or_insert_with(Instant::now);
+            if shown.elapsed() >= BANNER_DURATION {
+                self.hide();
+            }
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        if self.lines.is_empty() {
+            return Ok(());
+        }
+        let longest = self
+            .lines
+            .iter()
+            .map(|line| line.width() as u16)
+            .max()
+            .unwrap_or_default();
+        let width = (longest + 4).min(area.width);
+        let height = (self.lines.len() as u16 + 2).min(area.height);
+        let banner_area = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + 1.min(area.height - height),
+            width,
+            height,
+        );
+        let text = self
+            .lines
+            .iter()
+            .map(|line| Line::from(line.as_str()))
+            .collect::<Vec<_>>();
+        let paragraph = Paragraph::new(text).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(Color::DarkGray))
+                .padding(Padding::horizontal(1)),
+        );
+        frame.render_widget(Clear, banner_area);
+        frame.render_widget(paragraph, banner_area);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use clap::Parser;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_summary_lines() {
+        let args = Cli::parse_from(["ttydash", "-u", "ms", "-u", "%", "--reference", "250"]);
+        let config = PathBuf::from("/home/me/.config/ttydash/config.json5");
+        assert_eq!(
+            summary_lines(&args, Some(&config), "default", true),
+            [
+                "Input: stdin, format number, units ms %",
+                "Charts: 2, every 1000 ms, 4 ticks/s, 60 fps, history 200, references 250",
+                "Config: /home/me/.config/ttydash/config.json5, profile default",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_summary_lines_defaults() {
+        let args = Cli::parse_from(["ttydash", "--history", "50", "--header", "auto"]);
+        assert_eq!(
+            summary_lines(&args, None, "work", false),
+            [
+                "Input: stdin, format number, header auto",
+                "Charts: from the input, every 1000 ms, 4 ticks/s, 60 fps, history 50",
+                "Config: none, profile work (not found)",
+            ]
+        );
+        assert_eq!(
+            summary_lines(&args, None, DEFAULT_PROFILE, false)[2],
+            "Config: none, no profile"
+        );
+    }
+
+    #[test]
+    fn test_any_key_hides() {
+        let mut banner = Banner::new(vec!["Input: stdin".to_string()]);
+        banner
+            .handle_key_event(KeyEvent::from(crossterm::event::KeyCode::Char('x')))
+            .unwrap();
+        assert!(banner.lines.is_empty());
+    }
+}
@@ -1,225 +1,1329 @@
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc, RwLock,
+//! The dashboard, in three parts:
+//!
+//! - [`SourceReader`] (`reader`) runs in its own task, reads the input and stores every value
+//!   into the model, then sends `Action::DataUpdated` for the charts that changed.
+//! - [`ChartModel`] (`model`) holds the charts and the counters about the input behind locks,
+//!   shared by the reader, the view and the `--http` server.
+//! - [`DashView`] is the `Component`: it handles the keys and actions and draws the model.
+//!
+//! Data flows one way, from the reader through the model to the view. The view only writes to
+//! the model for what the user asks for, like pausing or clearing a chart, and to fit
+//! `--history auto` to the terminal.
+
+use std::{
+    collections::HashMap,
+    net::TcpListener,
+    path::PathBuf,
+    sync::{atomic::Ordering, Arc},
+    time::{Duration, Instant, SystemTime},
 };
 
-use super::Component;
+use super::{
+    barchart::{Bar, BarChart, Grid},
+    Component,
+};
 use crate::{
     action::Action,
+    app::Mode,
     cli::{self, Cli},
+    clock::{Clock, RealClock},
+    config::{get_config_file, Config, Theme},
+    errors::{is_broken_pipe, report_closed_stdout},
+    highlight::TimeRange,
+    http,
+    layout::{compute_grid, LayoutOpts},
+    memory::format_size,
+    notify::Alerts,
+    number::{format_number, scale_to, scale_unit, PrefixState},
+    positional::Positional,
+    profile::{self, Profile, DEFAULT_PROFILE},
+    summary::{bucket_means, ChartSummary, SessionSummary, Summary},
 };
-use color_eyre::Result;
+use color_eyre::{eyre::eyre, Result};
 
 use ratatui::{prelude::*, widgets::*};
 
 use symbols::bar;
-use tokio::{io::AsyncBufReadExt, sync::mpsc::UnboundedSender, task};
+use tokio::{sync::mpsc::UnboundedSender, task};
+use tracing::warn;
+use unicode_width::UnicodeWidthStr;
 
-#[derive(Debug, Clone)]
-struct DashState {
-    data: Vec<f64>,
-    unit: String,
-    length: usize,
-    min_value: f64,
-    max_value: f64,
-    average: f64,
-}
+use arrivals::format_counts;
+use debug::draw_overlay;
+use dense::draw_dense;
+pub use dense::supports_color;
+use diagnostics::truncate;
+use extract::extractor;
+use hints::{chart_hints, Hint};
+use model::{ChartModel, DashState};
+use normalize::{normalize, window_range, zoom_range, zoomed};
+use reader::SourceReader;
+use stale::stale_label;
+use time_axis::{generate_time_markers, marker_line, time_label};
+use view_state::ViewState;
 
-impl DashState {
-    fn new(size: usize) -> Self {
-        Self {
-            data: vec![0.0; size],
-            unit: String::new(),
-            length: 0,
-            min_value: f64::INFINITY,
-            max_value: f64::NEG_INFINITY,
-            average: 0.0,
-        }
-    }
-
-    fn calculate_stats(&mut self) {
-        let data_slice = &self.data[self.data.len() - self.length..];
-        let sum: f64 = data_slice.iter().sum();
-        let len = data_slice.len() as f64;
-        self.average = sum / len;
-        self.min_value = data_slice.iter().copied().fold(f64::INFINITY, f64::min);
-        self.max_value = data_slice.iter().copied().fold(f64::NEG_INFINITY, f64::max);
-    }
+mod arrivals;
+mod count_by;
+mod debug;
+mod dense;
+mod diagnostics;
+mod exec;
+mod export;
+mod extract;
+mod files;
+mod guard;
+mod header;
+mod hints;
+mod history;
+mod icons;
+mod info;
+mod inspect;
+mod listen;
+mod model;
+mod normalize;
+mod notes;
+mod once;
+mod protocol;
+mod rate;
+mod reader;
+mod rolling;
+mod scrollbar;
+mod self_test;
+mod simulate;
+mod sketch;
+#[cfg(test)]
+mod snapshots;
+mod sparkline;
+mod ssh;
+mod stale;
+mod stats_panel;
+#[cfg(test)]
+mod test_util;
+mod ticker;
+mod time_axis;
+mod udp;
+#[cfg(unix)]
+mod unix_socket;
+mod view_state;
+mod warmup;
 
-    fn update(&mut self, value: f64) {
-        self.data.rotate_left(1);
-        self.data[0] = value;
-        self.calculate_stats();
-        self.length = std::cmp::min(self.length + 1, self.data.len());
-    }
-}
+/// How often the display order is recomputed when sorting by value, to avoid flicker.
+const SORT_REFRESH: Duration = Duration::from_secs(1);
+/// Zoom levels go up to one column per 2^MAX_ZOOM samples.
+const MAX_ZOOM: u32 = 10;
+/// Number of columns moved by a single pan.
+const PAN_STEP: usize = 10;
+/// Number of identical newest samples after which a chart hints that the extraction may be wrong.
+const FLAT_HINT_SAMPLES: usize = 30;
+/// The wall clock time of every frame with `--deterministic`.
+const DETERMINISTIC_NOW: Duration = Duration::from_secs(1_700_000_000);
+/// How long `W` waits for the second press that confirms writing the profile.
+const WRITE_CONFIRM_TIMEOUT: Duration = Duration::from_secs(5);
+/// Narrowest room left of the stats line a title is wrapped into, below that it moves entirely
+/// to the second line.
+const MIN_TITLE_WIDTH: usize = 12;
+/// The bars drawn with `--low-bandwidth`, rising from `_` through `-` and `=` to a full `#`.
+const ASCII_BARS: bar::Set = bar::Set {
+    full: "#",
+    seven_eighths: "=",
+    three_quarters: "=",
+    five_eighths: "-",
+    half: "-",
+    three_eighths: "-",
+    one_quarter: "_",
+    one_eighth: "_",
+    empty: " ",
+};
 
-impl Default for DashState {
-    fn default() -> Self {
-        Self::new(200)
-    }
+/// Chart labels that can be edited at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EditField {
+    Title,
+    Unit,
+    Reference,
+    Note,
 }
 
-#[derive(Debug, Default, Clone)]
-pub struct Dash {
+/// Draws the charts of the [`ChartModel`] and handles what the user does with them.
+#[derive(Debug, Clone)]
+pub struct DashView {
+    /// The charts, shared with the reader that fills them.
+    model: ChartModel,
+    /// Cloned into the task reading the input when the component starts.
+    reader: SourceReader,
     bar_set: bar::Set,
-    update_frequency: u64,
+    /// The effective `--tick-rate` and `--frame-rate`, for the debug overlay.
+    tick_rate: f64,
+    frame_rate: f64,
+    /// The cells the last frame changed and all cells, for the debug overlay.
+    frame_cells: Option<(usize, usize)>,
+    history: usize,
+    /// `--history auto`, the history grows with the widest chart.
+    history_auto: bool,
     group: bool,
+    group_by: Option<cli::GroupBy>,
+    group_scale: cli::GroupScale,
+    y_from: cli::YFrom,
     layout: cli::Layout,
+    time_axis: cli::TimeAxis,
+    time_direction: cli::TimeDirection,
+    theme: Theme,
+    sort: cli::Sort,
+    number_locale: cli::NumberLocale,
+    label_format: cli::LabelFormat,
+    unit_prefix: cli::UnitPrefix,
+    /// The prefix of the y label of every chart or group of charts.
+    prefixes: HashMap<Vec<usize>, PrefixState>,
+    stats_panel: cli::StatsPanel,
+    /// Whether the stats shown are about the retained samples or the whole session.
+    stats_scope: cli::StatsScope,
+    mode: cli::DisplayMode,
+    /// Sparkline of the recent samples of every chart, for the stats panel.
+    trends: Vec<String>,
+    trends_updated: Option<Instant>,
+    dense: bool,
+    /// Values above zero are drawn at least a tick high, unless `--no-min-bar`.
+    min_bar: bool,
+    grid: cli::Grid,
+    /// Source glyphs in front of the titles, unless `--no-icons`.
+    icons: bool,
+    /// The keys of the chart actions in the focused chart's border, unless `--no-hints`.
+    hints_shown: bool,
+    /// The hints of the bound chart actions, the most important first.
+    hints: Vec<Hint>,
+    show_counts: bool,
+    scrollbar: bool,
+    bool_tokens: HashMap<String, bool>,
+
+    titles: Positional<String>,
+    colors: Positional<Color>,
+    references: Positional<f64>,
+    /// Which charts are above their reference, with `--notify`.
+    alerts: Option<Alerts>,
+    summary: Option<PathBuf>,
+    /// The most samples of a chart the summary lists, when it lists them at all.
+    summary_samples: Option<usize>,
+    /// `--export-dir`, the current directory when missing.
+    export_dir: Option<PathBuf>,
+    export_format: cli::ExportFormat,
+    /// `--stale-after`.
+    stale_after: Option<Duration>,
+    notes_file: Option<PathBuf>,
+    http_listener: Option<Arc<TcpListener>>,
+    /// Use [`DETERMINISTIC_NOW`] instead of the wall clock.
+    deterministic: bool,
+    debug: bool,
+    /// The `F1` popup about the current chart is open.
+    info: bool,
+    /// The `I` popup about the source of the current chart is open.
+    inspecting: bool,
+    highlight: Option<TimeRange>,
+    /// The chart and field the open text input edits.
+    editing: Option<(usize, EditField)>,
+    /// Where `[` was pressed, waiting for `]`.
+    highlight_start: Option<SystemTime>,
+    /// Width of the widest chart drawn last, to keep the crosshair on screen.
+    view_width: usize,
+    /// The area of the last frame, what `view_width` was measured in.
+    drawn_area: Rect,
+    /// Profile `W` writes the live settings to.
+    profile: String,
+    /// When `W` was first pressed, it has to be pressed again to write the profile.
+    write_armed: Option<Instant>,
 
-    state: Arc<RwLock<Vec<DashState>>>,
-    titles: Option<Vec<String>>,
-    units: Vec<String>,
-    indices: Option<Vec<usize>>,
+    /// Display position to state index, so sorting never touches the state vector.
+    order: Vec<usize>,
+    order_updated: Option<Instant>,
+    /// Whether a chart got a sample since the order was computed.
+    data_changed: bool,
+    /// Focus, pan, zoom and the other things done with the charts.
+    view: ViewState,
 
     command_tx: Option<UnboundedSender<Action>>,
-    stop_signal: Arc<AtomicBool>,
 }
 
-impl Dash {
-    pub fn new(args: Cli) -> Self {
-        let bar_set = bar::Set {
-            full: "⣿",
-            seven_eighths: "⣾",
-            three_quarters: "⣶",
-            five_eighths: "⣴",
-            half: "⣤",
-            three_eighths: "⣠",
-            one_quarter: "⣀",
-            one_eighth: "⢀",
-            empty: " ",
-        };
-        let stop_signal = Arc::new(AtomicBool::new(false));
-        let units = args.units.unwrap_or_default();
-        let instance = Self {
-            titles: args.titles,
-            state: Arc::new(RwLock::new(vec![DashState::default()])),
-            units,
-            group: args.group.unwrap_or(false),
-            indices: args.indices,
+impl DashView {
+    pub fn new(args: Cli) -> Result<Self> {
+        let bar_set = if args.low_bandwidth {
+            // a byte per cell instead of three
+            ASCII_BARS
+        } else {
+            bar::Set {
+                full: "⣿",
+                seven_eighths: "⣾",
+                three_quarters: "⣶",
+                five_eighths: "⣴",
+                half: "⣤",
+                three_eighths: "⣠",
+                one_quarter: "⣀",
+                one_eighth: "⢀",
+                empty: " ",
+            }
+        };
+        // bind before the terminal is taken over so failures are visible
+        let http_listener = match args.http {
+            Some(addr) => {
+                let listener = TcpListener::bind(addr)
+                    .map_err(|err| eyre!("Failed to listen on {addr}: {err}"))?;
+                listener.set_nonblocking(true)?;
+                Some(Arc::new(listener))
+            }
+            None => None,
+        };
+        let model = ChartModel::default();
+        let reader = SourceReader::new(&args, model.clone())?;
+        Ok(Self {
+            model,
+            reader,
+            titles: Positional::new("titles", args.titles),
+            colors: Positional::new("colors", args.colors),
+            references: Positional::new("references", args.references),
+            alerts: args.notify.is_some().then(Alerts::default),
+            // `g` toggles between the panels of `--group-by` and the charts apart
+            group: args.group.unwrap_or(false) || args.group_by.is_some(),
+            group_by: args.group_by,
+            group_scale: args.group_scale,
+            y_from: args.y_from,
+            summary: args.summary,
+            summary_samples: args.summary_include_samples.then(|| {
+                args.summary_max_samples
+                    .map_or(usize::MAX, |max| max as usize)
+            }),
+            export_dir: args.export_dir,
+            export_format: args.export_format,
+            stale_after: args.stale_after,
+            notes_file: args.notes_file,
+            http_listener,
+            deterministic: args.deterministic,
+            debug: false,
+            info: false,
+            inspecting: false,
+            highlight: args.highlight_range.map(|range| {
+                let now = wall_time(args.deterministic, &RealClock);
+                range.resolve(now, Duration::from_millis(args.update_frequency), now)
+            }),
+            highlight_start: None,
+            editing: None,
+            view_width: 0,
+            drawn_area: Rect::default(),
+            profile: args.profile.unwrap_or_else(|| DEFAULT_PROFILE.to_string()),
+            write_armed: None,
+            order: vec![],
+            order_updated: None,
+            data_changed: false,
+            view: ViewState {
+                // charts are numbered from 1 on the command line
+                arranged: args
+                    .order
+                    .iter()
+                    .flatten()
+                    .filter_map(|number| number.checked_sub(1))
+                    .collect(),
+                ..Default::default()
+            },
             command_tx: None,
-            update_frequency: args.update_frequency,
+            tick_rate: args.tick_rate,
+            frame_rate: args.frame_rate,
+            frame_cells: None,
+            history: args.history.samples(),
+            history_auto: args.history == cli::History::Auto,
             bar_set,
             layout: args.layout.unwrap_or_default(),
-            stop_signal: stop_signal.clone(),
-        };
-        let cloned_instance = instance.clone();
-        task::spawn(cloned_instance.update_chart(stop_signal));
-        instance
-    }
-
-    async fn update_chart(self, stop_signal: Arc<AtomicBool>) {
-        let stdin = tokio::io::stdin();
-        let mut lines = tokio::io::BufReader::new(stdin).lines();
-        while !stop_signal.load(Ordering::Relaxed) {
-            tokio::time::sleep(tokio::time::Duration::from_millis(self.update_frequency)).await;
-            let line = lines.next_line().await.unwrap().unwrap();
-            let mut state = self.state.write().unwrap();
-            if !self.units.is_empty() {
-                for (i, unit) in self.units.iter().enumerate() {
-                    let unit_str = unit.to_string();
-                    // parse the value with the unit
-                    let re = regex::Regex::new(&format!(r"(?i)\b(\d+(\.\d+)?)\s*{}\b", unit_str))
-                        .unwrap();
-                    if let Some(captures) = re.captures(&line) {
-                        let value = captures
-                            .get(1)
-                            .and_then(|v| v.as_str().parse::<f64>().ok())
-                            .unwrap_or(0.0);
-                        // state.update(value);
-                        state[i].update(value);
-                        state[i].unit = unit_str.to_string();
-                    }
-                }
-            } else if line.split_whitespace().next().is_some() {
-                let values: Vec<f64> = line
-                    .split_whitespace()
-                    .filter_map(|value_str| value_str.parse::<f64>().ok())
-                    .collect();
-                if let Some(indices) = &self.indices {
-                    // Update only the specified indices
-                    if state.len() < values.len() {
-                        state.resize(indices.len(), DashState::default());
-                    }
-                    indices
+            time_axis: args.time_axis,
+            time_direction: args.time_direction,
+            theme: Theme::default(),
+            sort: args.sort.unwrap_or_default(),
+            number_locale: args.number_locale,
+            label_format: args.label_format,
+            unit_prefix: args.unit_prefix,
+            prefixes: HashMap::new(),
+            stats_panel: args.stats_panel,
+            stats_scope: args.stats_scope,
+            mode: args.mode,
+            trends: vec![],
+            trends_updated: None,
+            dense: args.dense,
+            min_bar: !args.no_min_bar,
+            grid: args.grid,
+            icons: !args.no_icons,
+            hints_shown: !args.no_hints,
+            hints: vec![],
+            show_counts: args.show_counts,
+            scrollbar: !args.no_scrollbar,
+            bool_tokens: HashMap::new(),
+        })
+    }
+
+    /// A dashboard showing `state` without a reader, for rendering tests.
+    #[cfg(test)]
+    fn with_states(state: Vec<DashState>, args: Cli) -> Result<Self> {
+        let instance = Self::new(args)?;
+        *instance.model.state.write().unwrap() = state;
+        Ok(instance)
+    }
+
+    fn summary(&self) -> Summary {
+        let state = self.model.state.read().unwrap();
+        summarize(
+            &state,
+            0..state.len(),
+            &self.titles,
+            &self.references,
+            &self.model,
+            self.summary_samples,
+        )
+    }
+
+    fn start_http_server(&self) -> Result<()> {
+        let Some(listener) = &self.http_listener else {
+            return Ok(());
+        };
+        let listener = tokio::net::TcpListener::from_std(listener.try_clone()?)?;
+        let model = self.model.clone();
+        let titles = self.titles.clone();
+        let references = self.references.clone();
+        let snapshot = move || {
+            let state = model.state.read().unwrap();
+            summarize(&state, 0..state.len(), &titles, &references, &model, None)
+        };
+        task::spawn(http::serve(
+            listener,
+            snapshot,
+            self.reader.cancellation_token.clone(),
+        ));
+        Ok(())
+    }
+
+    /// Recompute the display order if the number of charts changed, or when it is due.
+    fn refresh_order(&mut self, state: &[DashState]) {
+        let due = match self.sort {
+            cli::Sort::Value | cli::Sort::Alert => match self.order_updated {
+                Some(updated) => self.data_changed && updated.elapsed() >= SORT_REFRESH,
+                None => true,
+            },
+            _ => true,
+        };
+        let shown = (0..state.len()).filter(|i| !self.view.hidden.contains(i));
+        if self.order.len() == shown.clone().count() && !due {
+            return;
+        }
+        let mut order = shown
+            .filter(|i| !self.view.pinned.contains(i))
+            .collect::<Vec<_>>();
+        match self.sort {
+            cli::Sort::Name => order.sort_by_key(|&i| chart_title(&self.titles, state, i)),
+            cli::Sort::Value => order.sort_by(|&a, &b| {
+                let a = state[a].last().unwrap_or(f64::NEG_INFINITY);
+                let b = state[b].last().unwrap_or(f64::NEG_INFINITY);
+                b.total_cmp(&a)
+            }),
+            // a stable sort keeps the rest in creation order
+            cli::Sort::Alert => order.sort_by_key(|&i| !self.above_reference(&state[i], i)),
+            cli::Sort::None => {
+                let position = |i| {
+                    self.view
+                        .arranged
                         .iter()
-                        .filter_map(|&index| values.get(index - 1).copied()) // Safe access to values
-                        .enumerate()
-                        .for_each(|(i, value)| state[i].update(value));
-                } else {
-                    if state.len() < values.len() {
-                        state.resize(values.len(), DashState::default());
-                    }
-                    state
-                        .iter_mut()
-                        .zip(values.iter())
-                        .for_each(|(state_item, &value)| {
-                            state_item.update(value);
-                        });
-                }
+                        .position(|&arranged| arranged == i)
+                };
+                // a stable sort keeps the charts that weren't moved in creation order
+                order.sort_by_key(|&i| position(i).unwrap_or(usize::MAX));
+            }
+        }
+        // pinned charts stay on top in the order they were pinned
+        self.order = self
+            .view
+            .pinned
+            .iter()
+            .copied()
+            .filter(|&i| i < state.len() && !self.view.hidden.contains(&i))
+            .chain(order)
+            .collect();
+        self.order_updated = Some(Instant::now());
+        self.data_changed = false;
+    }
+
+    fn move_focus(&mut self, forward: bool) {
+        let len = self.order.len();
+        if len == 0 {
+            return;
+        }
+        let position = match self
+            .view
+            .focus
+            .and_then(|focus| self.order.iter().position(|&i| i == focus))
+        {
+            Some(position) if forward => (position + 1) % len,
+            Some(position) => (position + len - 1) % len,
+            None if forward => 0,
+            None => len - 1,
+        };
+        self.view.focus = Some(self.order[position]);
+    }
+
+    fn toggle_pin(&mut self) {
+        let Some(focus) = self.view.focus else {
+            return;
+        };
+        if let Some(position) = self.view.pinned.iter().position(|&i| i == focus) {
+            self.view.pinned.remove(position);
+        } else {
+            self.view.pinned.push(focus);
+        }
+        self.order_updated = None;
+        self.order.clear();
+    }
+
+    /// Swap the focused chart with its neighbour in the display order. Pinned charts only move
+    /// among pinned charts, and moving a chart turns `--sort` off so it stays where it was put.
+    fn move_chart(&mut self, later: bool) {
+        let Some(focus) = self.view.focus else {
+            return;
+        };
+        let Some(position) = self.order.iter().position(|&i| i == focus) else {
+            return;
+        };
+        let neighbour = if later {
+            position + 1
+        } else {
+            position.wrapping_sub(1)
+        };
+        let Some(&other) = self.order.get(neighbour) else {
+            return;
+        };
+        if self.view.pinned.contains(&focus) != self.view.pinned.contains(&other) {
+            return;
+        }
+        self.order.swap(position, neighbour);
+        let (pinned, arranged) = self
+            .order
+            .iter()
+            .partition(|i| self.view.pinned.contains(i));
+        self.view.pinned = pinned;
+        self.view.arranged = arranged;
+        if self.sort != cli::Sort::None {
+            self.sort = cli::Sort::None;
+            if let Some(tx) = &self.command_tx {
+                let _ = tx.send(Action::Toast(
+                    "Sorting is off while charts are arranged by hand".to_string(),
+                ));
             }
         }
-        // release the IO
-        drop(lines);
+    }
+
+    /// Now, on the model's clock.
+    fn instant(&self) -> Instant {
+        self.model.instant()
+    }
+
+    /// The wall clock time frames are drawn at.
+    fn wall_now(&self) -> SystemTime {
+        wall_time(self.deterministic, self.model.clock.as_ref())
+    }
+
+    /// The cadence the reader stores samples at, unless a source asks for less.
+    fn base_interval(&self) -> Duration {
+        self.reader.base_interval()
+    }
+
+    /// Read and store time from `clock`, for tests and `ttydash simulate`.
+    fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.model.clock = clock;
+        self.reader.model.clock = self.model.clock.clone();
+    }
+
+    /// Format a number for display in the configured `--number-locale`.
+    fn number(&self, value: f64, decimals: usize) -> String {
+        format_number(value, decimals, self.number_locale)
+    }
+
+    /// Whether the last value of chart `i` is above its `--reference`.
+    fn above_reference(&self, chart: &DashState, i: usize) -> bool {
+        match (self.references.get(i), chart.last()) {
+            (Some(&reference), Some(last)) => last > reference,
+            _ => false,
+        }
+    }
+
+    /// A notification when chart `i` just went above its reference, see [`Alerts`].
+    fn check_alert(&mut self, i: usize) -> Option<Action> {
+        let alerts = self.alerts.as_mut()?;
+        let reference = *self.references.get(i)?;
+        let state = self.model.state.read().unwrap();
+        let last = state.get(i)?.last()?;
+        if !alerts.update(i, last > reference, self.model.clock.now()) {
+            return None;
+        }
+        let with_unit = |value| match self.scaled(value, 2, &state[i].unit) {
+            (value, unit) if unit.is_empty() => value,
+            (value, unit) => format!("{value} {unit}"),
+        };
+        Some(Action::Notify(format!(
+            "{} above {}: {}",
+            chart_title(&self.titles, &state, i),
+            with_unit(reference),
+            with_unit(last)
+        )))
+    }
+
+    /// The y label of `charts` for `max`, keeping its prefix steady as the max moves.
+    fn y_label(&mut self, charts: &[usize], max: f64, unit: &str) -> String {
+        let steps = self.prefixes.entry(charts.to_vec()).or_default().pick(
+            max,
+            unit,
+            self.label_format,
+            self.unit_prefix,
+        );
+        match scale_to(max, unit, self.label_format, steps) {
+            Some((max, unit)) => format!("{}{unit}", self.number(max, 1)),
+            None => format!("{}{unit}", self.number(max, 0)),
+        }
+    }
+
+    /// The bottom and top labels of a zoomed chart, in the prefix the top one gets and with
+    /// enough decimals to tell a narrow range apart.
+    fn zoomed_labels(
+        &mut self,
+        charts: &[usize],
+        min: f64,
+        max: f64,
+        unit: &str,
+    ) -> (String, String) {
+        let steps = self.prefixes.entry(charts.to_vec()).or_default().pick(
+            max,
+            unit,
+            self.label_format,
+            self.unit_prefix,
+        );
+        let label = |value: f64| match scale_to(value, unit, self.label_format, steps) {
+            Some((value, unit)) => format!("{}{unit}", self.number(value, 2)),
+            None => format!("{}{unit}", self.number(value, 2)),
+        };
+        (label(min), label(max))
+    }
+
+    /// What `--grid` draws in the empty cells of a chart, a byte with `--low-bandwidth`.
+    fn grid_cells(&self) -> Option<Grid> {
+        let ascii = self.bar_set == ASCII_BARS;
+        let (symbol, bottom) = match self.grid {
+            cli::Grid::None => return None,
+            cli::Grid::Dots if ascii => (".", false),
+            cli::Grid::Dots => (self.theme.grid_dot.as_str(), false),
+            cli::Grid::Baseline if ascii => ("_", true),
+            cli::Grid::Baseline => (self.theme.grid_baseline.as_str(), true),
+        };
+        Some(Grid {
+            symbol: symbol.to_string(),
+            style: self.theme.grid,
+            bottom,
+        })
+    }
+
+    /// A value and its unit for display, large values scaled to a prefixed unit with one decimal.
+    fn scaled(&self, value: f64, decimals: usize, unit: &str) -> (String, String) {
+        match scale_unit(value, unit, self.label_format) {
+            Some((value, unit)) => (self.number(value, 1), unit),
+            None => (self.number(value, decimals), unit.to_string()),
+        }
+    }
+
+    fn samples_per_column(&self) -> usize {
+        1 << self.view.zoom
+    }
+
+    fn seconds_per_column(&self, interval: Duration) -> f64 {
+        self.samples_per_column() as f64 * interval.as_secs_f64()
+    }
+
+    /// The pan offset in seconds, the same point in time for every chart.
+    fn offset_seconds(&self) -> f64 {
+        self.view.offset as f64 * self.base_interval().as_secs_f64()
+    }
+
+    /// The pan offset in samples of a chart advancing every `interval`.
+    fn chart_offset(&self, interval: Duration) -> usize {
+        (self.offset_seconds() / interval.as_secs_f64()).round() as usize
+    }
+
+    fn zoom(&mut self, zoom_in: bool) {
+        self.view.zoom = if zoom_in {
+            self.view.zoom.saturating_sub(1)
+        } else {
+            (self.view.zoom + 1).min(MAX_ZOOM)
+        };
+    }
+
+    fn pan(&mut self, back: bool) {
+        let step = PAN_STEP * self.samples_per_column();
+        if back {
+            // the offset counts base intervals, slower charts reach further back
+            let base = self.base_interval().as_secs_f64();
+            let longest = self
+                .model
+                .state
+                .read()
+                .unwrap()
+                .iter()
+                .map(|s| (s.length as f64 * s.interval.as_secs_f64() / base) as usize)
+                .max();
+            let limit = longest.unwrap_or_default().saturating_sub(1);
+            self.view.offset = (self.view.offset + step).min(limit);
+        } else {
+            self.view.offset = self.view.offset.saturating_sub(step);
+        }
+    }
+
+    /// Describes the zoom level and pan offset in the chart titles, empty for the default view.
+    fn view_label(&self, interval: Duration) -> String {
+        let mut label = String::new();
+        if self.view.zoom > 0 {
+            label += &format!(
+                " ×{} {}/col",
+                self.samples_per_column(),
+                format_seconds(self.seconds_per_column(interval))
+            );
+        }
+        if self.view.offset > 0 {
+            label += &format!(" -{}", format_seconds(self.offset_seconds()));
+        }
+        let sampling = self.model.sampling.load(Ordering::Relaxed);
+        if sampling > 1 {
+            label += &format!(" sampling 1/{sampling}");
+        }
+        label
     }
 }
 
-impl Drop for Dash {
+impl Drop for DashView {
     fn drop(&mut self) {
-        self.stop_signal.store(true, Ordering::Relaxed);
+        self.reader.stop();
     }
 }
 
-fn generate_time_markers(window_size: u16, state_len: usize) -> Vec<Span<'static>> {
-    let time_labels = (1..)
-        .map(|i| i * 30)
-        .take_while(|&t| t <= window_size - 5)
-        .collect::<Vec<_>>();
-    time_labels
-        .iter()
-        .scan(0, |last_label_len, &time| {
-            let pos = window_size - time - 1;
-            if pos < window_size {
-                let time_marker = format!("{}s", time);
-                let time_marker_len = time_marker.len() + 1;
-                let spacing = "─".repeat(30 * state_len - *last_label_len);
-                *last_label_len = time_marker_len;
-                Some(vec![
-                    Span::raw(spacing),
-                    Span::raw("├"),
-                    Span::styled(time_marker, Style::default().gray()),
-                ])
-            } else {
-                None
+/// The wall clock time of `clock`, or a fixed one with `--deterministic`.
+fn wall_time(deterministic: bool, clock: &dyn Clock) -> SystemTime {
+    if deterministic {
+        SystemTime::UNIX_EPOCH + DETERMINISTIC_NOW
+    } else {
+        clock.wall()
+    }
+}
+
+fn retained_bytes(state: &[DashState]) -> usize {
+    state.iter().map(DashState::retained_bytes).sum()
+}
+
+/// The title of chart `i`: the one edited at runtime, the `--titles` entry, the name of its
+/// field, metric, host or file, the one derived from its unit or source, or its position.
+fn chart_title(titles: &Positional<String>, state: &[DashState], i: usize) -> String {
+    let chart = state.get(i);
+    chart
+        .and_then(|chart| chart.title.clone())
+        .or_else(|| titles.get(i).cloned())
+        .or_else(|| chart.and_then(|chart| chart.name.clone()))
+        .or_else(|| chart.and_then(|chart| chart.auto_title.clone()))
+        .unwrap_or(format!("Chart {}", i + 1))
+}
+
+/// The stats of the `charts` in `state`, the charts of `model`, with at most `max_samples` of
+/// their samples when given.
+fn summarize(
+    state: &[DashState],
+    charts: impl IntoIterator<Item = usize>,
+    titles: &Positional<String>,
+    references: &Positional<f64>,
+    model: &ChartModel,
+    max_samples: Option<usize>,
+) -> Summary {
+    let (now, wall) = (model.clock.now(), model.clock.wall());
+    let charts = charts
+        .into_iter()
+        .map(|i| {
+            let chart = &state[i];
+            let has_samples = chart.has_stats();
+            let values = max_samples.map(|max| bucket_means(chart.samples(), max));
+            let times = max_samples
+                .zip(chart.newest_time(now, wall))
+                .map(|(max, newest)| {
+                    let times = (0..chart.length)
+                        .rev()
+                        .map(|age| chart.sample_time(newest, age))
+                        .map(|time| {
+                            let since_epoch = time.duration_since(SystemTime::UNIX_EPOCH);
+                            since_epoch.unwrap_or_default().as_secs_f64()
+                        })
+                        .collect::<Vec<_>>();
+                    bucket_means(&times, max)
+                });
+            ChartSummary {
+                title: chart_title(titles, state, i),
+                raw_name: chart.raw_name.clone(),
+                description: chart.description.clone(),
+                unit: chart.unit.clone(),
+                reference: references.get(i).copied(),
+                samples: chart.length,
+                warmup_samples: chart.warmup,
+                last: chart.last(),
+                average: has_samples.then_some(chart.average),
+                min: has_samples.then_some(chart.min_value),
+                max: has_samples.then_some(chart.max_value),
+                paused: chart.paused_since.is_some(),
+                paused_seconds: chart.paused_time(now).as_secs_f64(),
+                session: (chart.session.count() > 0).then(|| SessionSummary {
+                    samples: chart.session.count(),
+                    average: chart.session.average(),
+                    min: chart.session.min(),
+                    max: chart.session.max(),
+                    p50: chart.session.percentile(50.0),
+                    p90: chart.session.percentile(90.0),
+                    p95: chart.session.percentile(95.0),
+                    p99: chart.session.percentile(99.0),
+                }),
+                values,
+                times,
             }
         })
-        .flatten()
-        .collect::<Vec<_>>()
-        .into_iter()
-        .rev()
+        .collect();
+    Summary {
+        charts,
+        skipped_lines: model.skipped_lines.load(Ordering::Relaxed),
+        notes: model.notes.lock().unwrap().clone(),
+        ..Default::default()
+    }
+}
+
+fn format_seconds(seconds: f64) -> String {
+    if seconds.fract() == 0.0 {
+        format!("{:.0}s", seconds)
+    } else {
+        format!("{:.1}s", seconds)
+    }
+}
+
+/// Map the newest samples onto `width` columns, newest last. Each column holds the max of
+/// `bucket` samples, the view ends `offset` samples before the newest one, and columns without
+/// any sample are `None`.
+fn downsample(samples: &[f64], width: usize, bucket: usize, offset: usize) -> Vec<Option<f64>> {
+    let end = samples.len().saturating_sub(offset);
+    let start = end.saturating_sub(width * bucket);
+    let mut columns = samples[start..end]
+        .rchunks(bucket)
+        .map(|chunk| chunk.iter().copied().reduce(f64::max))
+        .collect::<Vec<_>>();
+    columns.resize(width, None);
+    columns.reverse();
+    columns
+}
+
+/// Map samples taken at `times` onto `width` columns of `seconds_per_column` each, newest last.
+/// The newest column ends at `end`, in the seconds of `times`, each column holds the max of the
+/// samples taken within it, and columns without any sample are `None`.
+fn downsample_by_time(
+    samples: &[f64],
+    times: &[f64],
+    end: f64,
+    width: usize,
+    seconds_per_column: f64,
+) -> Vec<Option<f64>> {
+    let mut columns = vec![None; width];
+    for (&value, &time) in samples.iter().zip(times).rev() {
+        let age = end - time;
+        if age < 0.0 {
+            // panned back past it
+            continue;
+        }
+        let column = (age / seconds_per_column) as usize;
+        if column >= width {
+            // the older samples are further back still
+            break;
+        }
+        let max = &mut columns[width - 1 - column];
+        *max = Some(max.map_or(value, |max: f64| max.max(value)));
+    }
+    columns
+}
+
+/// Wall clock time of the sample `samples` samples older than the newest one.
+fn sample_time(newest: SystemTime, interval: Duration, samples: usize) -> SystemTime {
+    newest - interval.mul_f64(samples as f64)
+}
+
+/// Repeat every sample `factor` times, to line a slower series up with a faster one.
+fn resample(samples: &[f64], factor: usize) -> Vec<f64> {
+    samples
+        .iter()
+        .flat_map(|&sample| std::iter::repeat_n(sample, factor))
         .collect()
 }
 
-impl Dash {
-    fn draw_grouped_chart(&mut self, frame: &mut Frame, area: &Rect) -> Result<()> {
-        let state = self.state.read().unwrap();
-        let window_size = (area.width - 1) / state.len() as u16;
+impl DashView {
+    /// The chart the crosshair and range marks refer to.
+    fn current_chart(&self) -> Option<usize> {
+        self.view.focus.or(self.order.first().copied())
+    }
+
+    /// Pause or resume the current chart, or every chart. Resuming all charts only happens
+    /// when every chart is paused, otherwise the running ones are paused too.
+    fn toggle_pause(&mut self, all: bool) {
+        let now = self.instant();
+        let mut state = self.model.state.write().unwrap();
+        if all {
+            let pause = state.iter().any(|chart| chart.paused_since.is_none());
+            for chart in state.iter_mut() {
+                if chart.paused_since.is_none() == pause {
+                    chart.toggle_pause(now);
+                }
+            }
+        } else if let Some(chart) = self.current_chart().and_then(|i| state.get_mut(i)) {
+            chart.toggle_pause(now);
+        }
+    }
 
-        let span_vec = generate_time_markers(window_size, state.len());
+    /// Drop the samples of the current chart, or of every chart.
+    fn clear(&mut self, all: bool) {
+        let mut state = self.model.state.write().unwrap();
+        if all {
+            state.iter_mut().for_each(DashState::clear);
+        } else if let Some(chart) = self.current_chart().and_then(|i| state.get_mut(i)) {
+            chart.clear();
+        }
+    }
+
+    /// Pause or resume chart `i`, or every chart, from `--control-fifo`.
+    fn set_paused(&mut self, i: Option<usize>, paused: bool) {
+        if i.is_some_and(|i| !self.has_chart(i)) {
+            return;
+        }
+        let now = self.instant();
+        let mut state = self.model.state.write().unwrap();
+        for (n, chart) in state.iter_mut().enumerate() {
+            if i.is_none_or(|i| i == n) && chart.paused_since.is_some() != paused {
+                chart.toggle_pause(now);
+            }
+        }
+    }
+
+    /// Drop the samples of chart `i`, from `--control-fifo`.
+    fn reset_chart(&mut self, i: usize) {
+        if self.has_chart(i) {
+            self.model.state.write().unwrap()[i].clear();
+        }
+    }
+
+    /// Change the reference of chart `i`, from `--control-fifo`.
+    fn set_reference(&mut self, i: usize, reference: f64) {
+        if self.has_chart(i) {
+            self.references.set(i, reference);
+        }
+    }
+
+    /// Whether there is a chart `i`, a toast says there isn't.
+    fn has_chart(&self, i: usize) -> bool {
+        let charts = self.model.state.read().unwrap().len();
+        if i < charts {
+            return true;
+        }
+        if let Some(tx) = &self.command_tx {
+            let _ = tx.send(Action::Toast(format!(
+                "There is no chart {}, only {charts}",
+                i + 1
+            )));
+        }
+        false
+    }
 
+    /// Open a text input for the title or unit of the current chart.
+    fn start_edit(&mut self, field: EditField) {
+        let Some(i) = self.current_chart() else {
+            return;
+        };
+        let state = self.model.state.read().unwrap();
+        let (prompt, value) = match field {
+            EditField::Title => ("Title", chart_title(&self.titles, &state, i)),
+            EditField::Unit => ("Unit", state[i].unit.clone()),
+            EditField::Reference => (
+                "Reference",
+                self.references
+                    .get(i)
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+            ),
+            EditField::Note => ("Note", String::new()),
+        };
+        drop(state);
+        self.editing = Some((i, field));
+        if let Some(tx) = &self.command_tx {
+            let _ = tx.send(Action::OpenInput(prompt.to_string(), value));
+        }
+    }
+
+    fn finish_edit(&mut self, value: String) {
+        let Some((i, field)) = self.editing.take() else {
+            return;
+        };
+        if field == EditField::Note {
+            self.add_note(value);
+            return;
+        }
+        if field == EditField::Reference {
+            match value.trim().parse::<f64>() {
+                Ok(reference) if reference.is_finite() => self.set_reference(i, reference),
+                _ => {
+                    if let Some(tx) = &self.command_tx {
+                        let _ = tx.send(Action::Toast(format!("Not a number: {value}")));
+                    }
+                }
+            }
+            return;
+        }
+        let mut state = self.model.state.write().unwrap();
+        let Some(chart) = state.get_mut(i) else {
+            return;
+        };
+        match field {
+            // an empty title falls back to the default one
+            EditField::Title => chart.title = Some(value).filter(|title| !title.is_empty()),
+            EditField::Unit => chart.unit = value,
+            EditField::Note | EditField::Reference => {}
+        }
+        // the new title may change the order
+        self.order_updated = None;
+    }
+
+    /// The settings to write back with `W`. Titles and units are only stored when they were
+    /// given or edited, so a profile without them keeps using the metric names.
+    fn live_profile(&self) -> Profile {
+        let state = self.model.state.read().unwrap();
+        let titles = (!self.titles.values().is_empty()
+            || state.iter().any(|chart| chart.title.is_some()))
+        .then(|| {
+            (0..state.len())
+                .map(|i| chart_title(&self.titles, &state, i))
+                .collect()
+        });
+        let units = (!self.reader.units.is_empty()
+            || state.iter().any(|chart| !chart.unit.is_empty()))
+        .then(|| state.iter().map(|chart| chart.unit.clone()).collect());
+        let descriptions = state
+            .iter()
+            .any(|chart| chart.description.is_some())
+            .then(|| {
+                state
+                    .iter()
+                    .map(|chart| chart.description.clone().unwrap_or_default())
+                    .collect()
+            });
+        let colors = Some(self.colors.values().to_vec()).filter(|colors| !colors.is_empty());
+        Profile {
+            titles,
+            descriptions,
+            units,
+            colors,
+            layout: Some(self.layout),
+            sort: Some(self.sort),
+            group: Some(self.group),
+            order: (!self.view.arranged.is_empty())
+                .then(|| self.order.iter().map(|i| i + 1).collect()),
+        }
+    }
+
+    /// Handle `W`. The first press asks for confirmation, a second one within
+    /// [`WRITE_CONFIRM_TIMEOUT`] writes the live settings to the profile.
+    fn write_profile(&mut self) {
+        let path = get_config_file();
+        let armed = self
+            .write_armed
+            .take()
+            .is_some_and(|armed| armed.elapsed() < WRITE_CONFIRM_TIMEOUT);
+        let message = if !armed {
+            self.write_armed = Some(Instant::now());
+            format!(
+                "Press W again to write the current settings to profile `{}` in {}",
+                self.profile,
+                path.display()
+            )
+        } else {
+            match profile::write_profile(&path, &self.profile, &self.live_profile()) {
+                Ok(()) => format!(
+                    "Wrote profile `{}` to {} (comments in the file are not preserved)",
+                    self.profile,
+                    path.display()
+                ),
+                Err(err) => {
+                    warn!("Failed to write profile: {err}");
+                    format!("Failed to write profile: {err}")
+                }
+            }
+        };
+        if let Some(tx) = &self.command_tx {
+            let _ = tx.send(Action::Toast(message));
+        }
+    }
+
+    fn toggle_crosshair(&mut self) {
+        self.view.crosshair = match self.view.crosshair {
+            Some(_) => None,
+            None => Some(0),
+        };
+    }
+
+    fn move_crosshair(&mut self, left: bool) {
+        let Some(column) = self.view.crosshair else {
+            return;
+        };
+        let older = left != self.newest_left();
+        self.view.crosshair = Some(if older {
+            (column + 1).min(self.view_width.saturating_sub(1))
+        } else {
+            column.saturating_sub(1)
+        });
+    }
+
+    /// The columns of a `cadence=arrival` chart `width` columns wide, spaced by the time its
+    /// samples arrived with the columns of a chart advancing every `interval`. `None` for the
+    /// charts spaced by sample.
+    fn arrival_columns(
+        &self,
+        chart: &DashState,
+        width: usize,
+        interval: Duration,
+    ) -> Option<Vec<Option<f64>>> {
+        let times = chart.times()?;
+        let elapsed = self
+            .instant()
+            .saturating_duration_since(chart.first_update?);
+        Some(downsample_by_time(
+            chart.samples(),
+            times,
+            elapsed.as_secs_f64() - self.offset_seconds(),
+            width,
+            self.seconds_per_column(interval),
+        ))
+    }
+
+    /// Wall clock time of the newest sample in column `column`, counted from the newest edge.
+    fn column_time(&self, chart: &DashState, column: usize) -> Option<SystemTime> {
+        if chart.times().is_some() {
+            // the columns of an arrival chart end now, whether a sample came lately or not
+            let seconds =
+                self.offset_seconds() + column as f64 * self.seconds_per_column(chart.interval);
+            return Some(self.wall_now() - Duration::from_secs_f64(seconds));
+        }
+        let samples = self.chart_offset(chart.interval) + column * self.samples_per_column();
+        Some(sample_time(
+            chart.newest_time(self.instant(), self.wall_now())?,
+            chart.interval,
+            samples,
+        ))
+    }
+
+    /// Handle `[` and `]`, marking the ends of the highlighted range at the crosshair.
+    fn mark_range(&mut self, start: bool) {
+        let time = self.view.crosshair.and_then(|column| {
+            let state = self.model.state.read().unwrap();
+            self.column_time(state.get(self.current_chart()?)?, column)
+        });
+        let Some(time) = time else {
+            if let Some(tx) = &self.command_tx {
+                let message = "Press c to place the crosshair before marking a range".to_string();
+                let _ = tx.send(Action::Toast(message));
+            }
+            return;
+        };
+        if start {
+            self.highlight_start = Some(time);
+            self.highlight = Some(TimeRange::between(time, time));
+        } else {
+            let start = self.highlight_start.take().unwrap_or(time);
+            self.highlight = Some(TimeRange::between(start, time));
+        }
+    }
+
+    fn newest_left(&self) -> bool {
+        self.time_direction == cli::TimeDirection::Ltr
+    }
+
+    /// Put the columns of `downsample` in display order.
+    fn orient<T>(&self, mut columns: Vec<T>) -> Vec<T> {
+        if self.newest_left() {
+            columns.reverse();
+        }
+        columns
+    }
+
+    /// The bottom border of a chart `width` columns wide, each `column_width` cells wide.
+    fn time_marker_line(
+        &self,
+        width: u16,
+        column_width: usize,
+        interval: Duration,
+    ) -> Line<'static> {
+        if self.time_axis == cli::TimeAxis::None {
+            return Line::default();
+        }
+        let now = self.wall_now();
+        let seconds_per_column = self.seconds_per_column(interval);
+        let offset_seconds = self.offset_seconds();
+        let markers =
+            generate_time_markers(width as usize, self.theme.time_marker_spacing, |column| {
+                time_label(
+                    self.time_axis,
+                    column,
+                    seconds_per_column,
+                    offset_seconds,
+                    now,
+                )
+            });
+        marker_line(&markers, column_width, &self.theme, self.newest_left())
+    }
+
+    /// Mark the column where the backfilled samples end on the bottom border.
+    fn draw_backfill_divider(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        chart: &DashState,
+        width: usize,
+    ) {
+        let Some(live) = chart.live_samples.filter(|&live| live < chart.length) else {
+            return;
+        };
+        let inner = area.inner(Margin::new(1, 1));
+        let Some(position) = self.divider_position(chart, width.min(inner.width as usize), live)
+        else {
+            return;
+        };
+        let cell = &mut frame.buffer_mut()[(inner.x + position as u16, area.bottom() - 1)];
+        // never cover a time label
+        if cell.symbol() == "─" {
+            cell.set_symbol("┴").set_style(self.theme.time_label);
+        }
+    }
+
+    /// The position of the column, out of `width`, holding the oldest of the `newest` samples
+    /// of `chart`, if it is in view.
+    fn divider_position(&self, chart: &DashState, width: usize, newest: usize) -> Option<usize> {
+        let samples = newest.checked_sub(self.chart_offset(chart.interval))?;
+        let column = samples / self.samples_per_column();
+        if column >= width {
+            return None;
+        }
+        Some(if self.newest_left() {
+            column
+        } else {
+            width - 1 - column
+        })
+    }
+
+    /// Patch the background of the highlighted range and the crosshair onto a drawn chart.
+    fn paint_columns(&self, frame: &mut Frame, area: Rect, chart: &DashState, width: usize) {
+        let inner = area.inner(Margin::new(1, 1));
+        let bucket = self.samples_per_column();
+        for position in 0..width.min(inner.width as usize) {
+            let column = if self.newest_left() {
+                position
+            } else {
+                width - 1 - position
+            };
+            let mut style = Style::default();
+            if let Some(range) = self.highlight {
+                let newest = self.column_time(chart, column);
+                let oldest = self.column_time(chart, column).map(|time| {
+                    time.checked_sub(chart.interval.mul_f64((bucket - 1) as f64))
+                        .unwrap_or(time)
+                });
+                if let (Some(newest), Some(oldest)) = (newest, oldest) {
+                    if range.overlaps(oldest, newest) {
+                        style = style.patch(self.theme.highlight);
+                    }
+                }
+            }
+            if self.view.crosshair == Some(column) {
+                style = style.patch(self.theme.crosshair);
+            }
+            if style != Style::default() {
+                let x = inner.x + position as u16;
+                frame
+                    .buffer_mut()
+                    .set_style(Rect::new(x, inner.y, 1, inner.height), style);
+            }
+        }
+    }
+
+    /// Draw the charts `charts` overlaid in one panel with a shared scale.
+    fn draw_grouped_chart(
+        &mut self,
+        frame: &mut Frame,
+        area: &Rect,
+        charts: &[usize],
+    ) -> Result<()> {
+        // two charts share every cell in dense mode, otherwise each takes a column per sample
+        let dense = self.dense && charts.len() == 2 && supports_color();
+        let columns_per_sample = if dense { 1 } else { charts.len() };
+        let window_size = if dense {
+            area.width.saturating_sub(2)
+        } else {
+            area.width.saturating_sub(1) / charts.len() as u16
+        };
+        self.fit_history(window_size as usize);
+        let shared = Arc::clone(&self.model.state);
+        let state = shared.read().unwrap();
+        // everything is shown at the cadence of the fastest chart
+        let finest = charts
+            .iter()
+            .map(|&i| state[i].interval)
+            .min()
+            .unwrap_or(self.base_interval());
+
+        let time_markers = self.time_marker_line(window_size, columns_per_sample, finest);
+        let first_unit = &state[charts[0]].unit;
+        let shared_unit = charts
+            .iter()
+            .all(|&i| state[i].unit == *first_unit)
+            .then_some(first_unit.as_str());
+        // with a title of its own, every series is named, otherwise the group is
+        let titled = charts
+            .iter()
+            .any(|&i| state[i].title.is_some() || self.titles.get(i).is_some());
+        let mut name = if titled {
+            charts
+                .iter()
+                .map(|&i| chart_title(&self.titles, &state, i))
+                .collect::<Vec<_>>()
+                .join(" / ")
+        } else {
+            "Group Chart".to_string()
+        };
+        let unit_suffix = match shared_unit {
+            Some(unit) if !unit.is_empty() => format!(" [{unit}]"),
+            _ => String::new(),
+        };
+        let mut prefix = String::new();
+        if charts.iter().any(|&i| state[i].paused_since.is_some()) {
+            prefix = "❚❚ ".to_string();
+        }
+        let view_label = self.view_label(finest);
+        let inner_width = area.width.saturating_sub(2) as usize;
+        let room =
+            inner_width.saturating_sub(prefix.width() + unit_suffix.width() + view_label.width());
+        if titled {
+            name = truncate(&name, room.max(1));
+        }
+
+        let title = format!("{prefix}{name}{unit_suffix}{view_label}");
+        let (title, overflow) = wrap_title(&title, inner_width, inner_width);
+        let block = Block::default()
+            .border_type(BorderType::Rounded)
+            .title(Line::from(title).right_aligned()) // Add chart title
+            .padding(Padding::top(overflow.is_some() as u16))
+            .title_bottom(time_markers) // Add time markers
+            .title_alignment(Alignment::Right)
+            .borders(Borders::ALL);
         let mut chart = BarChart::default()
             .bar_set(self.bar_set.clone())
-            .bar_gap(0)
-            .block(
-                Block::default()
-                    .border_type(BorderType::Rounded)
-                    .title(Line::from("Group Chart").right_aligned()) // Add chart title
-                    .title_bottom(Line::from(span_vec)) // Add time markers
-                    .title_alignment(Alignment::Right)
-                    .borders(Borders::ALL),
-            )
-            .bar_width(1)
-            .group_gap(0);
+            .min_bar(self.min_bar)
+            .grid(self.grid_cells())
+            .block(block.clone());
 
         // Define a color map to style the bars
         let color_map = [
@@ -230,42 +1334,181 @@ impl Dash {
             Color::Magenta,
             Color::Cyan,
             Color::White,
-        ];
-
-        let _bars = &(0..window_size)
-            .map(|i| {
-                BarGroup::default().bars(
-                    &(0..state.len())
-                        .map(|n| {
-                            let state_n = &state[n];
-                            let value =
-                                state_n.data[state_n.data.len().saturating_sub((i + 1).into())];
-                            Bar::default()
-                                .value(value as u64)
-                                .text_value("".to_owned())
-                                .style(Style::default().fg(color_map[n % color_map.len()]))
-                        })
-                        .collect::<Vec<_>>(),
-                )
-            })
-            .rev()
-            .for_each(|bar_group| {
-                chart = chart.clone().data(bar_group.clone());
-            });
-
-        frame.render_widget(chart, *area);
+        ];
 
-        let max_value = state.iter().map(|s| s.max_value).fold(0.0, f64::max);
+        let mut columns = charts
+            .iter()
+            .map(|&n| {
+                let state_n = &state[n];
+                if let Some(columns) = self.arrival_columns(state_n, window_size as usize, finest) {
+                    return self.orient(columns);
+                }
+                let factor = (state_n.interval.as_secs_f64() / finest.as_secs_f64()).round();
+                self.orient(downsample(
+                    &resample(state_n.samples(), (factor as usize).max(1)),
+                    window_size as usize,
+                    self.samples_per_column(),
+                    self.chart_offset(finest),
+                ))
+            })
+            .collect::<Vec<_>>();
+        let normalized = self.group_scale == cli::GroupScale::Normalized;
+        let stale = charts
+            .iter()
+            .map(|&i| self.stale_age(&state[i]))
+            .collect::<Vec<_>>();
+        // a dead series shouldn't pin the scale of the live ones
+        let live = |column: &usize| stale[*column].is_none();
+        let any_live = (0..charts.len()).any(|column| live(&column));
+        if !normalized && any_live && stale.iter().any(Option::is_some) {
+            let live_max = (0..charts.len())
+                .filter(live)
+                .map(|column| bars_max(&columns[column]))
+                .max()
+                .unwrap_or(0);
+            chart = chart.max(live_max.max(1));
+        }
+        // the real range of every chart in view, for the legend
+        let ranges = columns
+            .iter()
+            .map(|column| window_range(column))
+            .collect::<Vec<_>>();
+        if normalized {
+            for column in &mut columns {
+                *column = normalize(column)
+                    .into_iter()
+                    .map(|value| value.map(|value| value * normalize::STEPS))
+                    .collect();
+            }
+            chart = chart.max(normalize::STEPS as u64);
+        }
+        let color_of = |n: usize| {
+            self.colors
+                .get(n)
+                .copied()
+                .unwrap_or(color_map[n % color_map.len()])
+        };
+        if dense {
+            let inner = block.inner(*area);
+            frame.render_widget(block, *area);
+            let colors = [color_of(charts[0]), color_of(charts[1])];
+            draw_dense(
+                frame.buffer_mut(),
+                inner,
+                [&columns[0], &columns[1]],
+                colors,
+                self.min_bar,
+            );
+        } else {
+            // one column per series at every position, side by side
+            let bars = (0..window_size as usize)
+                .flat_map(|i| {
+                    charts
+                        .iter()
+                        .enumerate()
+                        .map(move |(column, &n)| (i, column, n))
+                })
+                .map(|(i, column, n)| {
+                    let value = columns[column][i].unwrap_or_default();
+                    let mut style = Style::default().fg(color_of(n));
+                    if stale[column].is_some() {
+                        style = style.add_modifier(Modifier::DIM);
+                    }
+                    Bar::default().value(value as u64).style(style)
+                })
+                .collect();
+            chart = chart.bars(bars);
+            frame.render_widget(chart, *area);
+        }
+        let label_row = 1 + overflow.is_some() as u16;
+        if let Some(overflow) = overflow {
+            draw_title_overflow(frame, *area, overflow);
+        }
+        if let Some(&fastest) = charts.iter().find(|&&i| state[i].interval == finest) {
+            self.draw_note_markers(
+                frame,
+                *area,
+                &state[fastest],
+                window_size as usize,
+                columns_per_sample,
+            );
+        }
 
-        let [top, _] = Layout::vertical([Constraint::Length(2), Constraint::Min(0)]).areas(*area);
-        let y_message = format!("{:.0}{}", max_value, state[0].unit);
-        let y_span = Span::styled(y_message, Style::new().dim().fg(Color::DarkGray));
-        let y_paragraph = Paragraph::new(y_span)
+        let [top, _] =
+            Layout::vertical([Constraint::Length(label_row + 1), Constraint::Min(0)]).areas(*area);
+        let y_line = if normalized {
+            // the bars have no common scale, show the range each one spans instead
+            let mut spans = vec![];
+            for (column, &n) in charts.iter().enumerate() {
+                let Some((min, max)) = ranges[column] else {
+                    continue;
+                };
+                let (min, min_unit) = self.scaled(min, 2, &state[n].unit);
+                let (max, max_unit) = self.scaled(max, 2, &state[n].unit);
+                let mut style = Style::new().fg(color_of(n));
+                let mut span = match titled {
+                    true => format!("■ {} ", chart_title(&self.titles, &state, n)),
+                    false => "■ ".to_string(),
+                };
+                span = format!("{span}{min}{min_unit}..{max}{max_unit} ");
+                if let Some(age) = stale[column] {
+                    style = style.add_modifier(Modifier::DIM);
+                    span = format!("{span}{} ", stale_label(age));
+                }
+                spans.push(Span::styled(span, style));
+            }
+            Line::from(spans)
+        } else {
+            // the label belongs to the series holding the max, the first one on a tie
+            let owner = charts
+                .iter()
+                .enumerate()
+                .filter(|(column, _)| !any_live || live(column))
+                .map(|(_, &i)| i)
+                .min_by(|&a, &b| state[b].max_value.total_cmp(&state[a].max_value))
+                .unwrap_or(charts[0]);
+            let max_value = state[owner].max_value.max(0.0);
+            // named or of different units, every series gets a legend entry, otherwise only the
+            // stale ones do
+            let legend = charts
+                .iter()
+                .enumerate()
+                .filter(|(column, _)| titled || shared_unit.is_none() || stale[*column].is_some())
+                .map(|(column, &n)| {
+                    let mut entry = format!(" ■ {}", chart_title(&self.titles, &state, n));
+                    if shared_unit.is_none() && !state[n].unit.is_empty() {
+                        entry = format!("{entry} [{}]", state[n].unit);
+                    }
+                    let mut style = Style::new().fg(color_of(n));
+                    if let Some(age) = stale[column] {
+                        entry = format!("{entry} {}", stale_label(age));
+                        style = style.add_modifier(Modifier::DIM);
+                    }
+                    Span::styled(entry, style)
+                })
+                .collect::<Vec<_>>();
+            let owner_color = color_of(owner);
+            let (y_message, y_style) = match shared_unit {
+                Some(unit) => (
+                    self.y_label(charts, max_value, unit),
+                    Style::new().dim().fg(Color::DarkGray),
+                ),
+                // the prefix of one unit means nothing to another, each series keeps its own
+                None => (
+                    self.y_label(&[owner], max_value, &state[owner].unit),
+                    Style::new().dim().fg(owner_color),
+                ),
+            };
+            let mut spans = vec![Span::styled(y_message, y_style)];
+            spans.extend(legend);
+            Line::from(spans)
+        };
+        let y_paragraph = Paragraph::new(y_line)
             .left_aligned()
             .block(Block::default().padding(Padding {
                 left: 2,
-                right: 0,
-                top: 1,
+                right: 1,
+                top: label_row,
                 bottom: 0,
             }));
         frame.render_widget(y_paragraph, top);
@@ -274,61 +1517,305 @@ impl Dash {
     }
 
     fn draw_chart(&mut self, frame: &mut Frame, area: &Rect, i: usize) -> Result<()> {
-        let title = self
-            .titles
-            .as_ref()
-            .and_then(|titles| titles.get(i))
-            .unwrap_or(&format!("Chart {}", i + 1))
-            .to_string();
-        let state = self.state.read().unwrap();
+        // a terminal shrunk to nothing while it is resized still draws
+        let columns = area.width.saturating_sub(1) as usize;
+        self.view_width = self.view_width.max(columns);
+        self.fit_history(columns);
+        // not borrowed from `self`, the y label keeps its prefix in `self`
+        let shared = Arc::clone(&self.model.state);
+        let state = shared.read().unwrap();
+        let interval = state[i].interval;
+        let mut title = chart_title(&self.titles, &state, i);
+        if let Some(&restarts) = self.model.restarts.lock().unwrap().get(&i) {
+            title = format!("{title} (restarts: {restarts})");
+        }
+        if let Some(age) = self.stale_age(&state[i]) {
+            title = format!("{title} {}", stale_label(age));
+        }
+        title += &self.view_label(interval);
+        // the icons tell about pauses and failures themselves
+        if state[i].paused_since.is_some() && !self.icons {
+            title = format!("❚❚ {title}");
+        }
+        if self.view.pinned.contains(&i) {
+            title = format!("📌 {title}");
+        }
+        let source_error = self.model.source_error(i);
+        if source_error.is_some() && !self.icons {
+            title = format!("⚠ {title}");
+        }
+        title = self.chart_icons(i, &state[i]) + &title;
+        let border_style = if self.view.focus == Some(i) {
+            Style::default().fg(Color::Cyan)
+        } else if source_error.is_some() {
+            Style::default().fg(Color::Red)
+        } else {
+            Style::default()
+        };
+        let color = self.colors.get(i).copied().unwrap_or(Color::Green);
         let state = &state[i];
-        let chart_state = &state.data;
-        let width = area.width - 1;
-        let start = chart_state.len().saturating_sub(width as usize);
-        let bars = chart_state[start..]
+        let width = area.width.saturating_sub(1);
+        let is_bool = self.reader.is_on_off(i);
+        let flat = state.flat_value().filter(|_| !is_bool);
+        // zero high bars draw nothing, show a flat zero as a thin line at the bottom instead
+        let zero_line = flat == Some(0.0);
+        let columns = self.orient(
+            self.arrival_columns(state, width as usize, interval)
+                .unwrap_or_else(|| {
+                    downsample(
+                        state.samples(),
+                        width as usize,
+                        self.samples_per_column(),
+                        self.chart_offset(interval),
+                    )
+                }),
+        );
+        let mut message = if let Some(error) = source_error {
+            error
+        } else if self.stats_panel != cli::StatsPanel::Off {
+            // the panel shows them, keep the line for the range and crosshair readouts
+            String::new()
+        } else if is_bool {
+            let (average, _, _) = state.scoped_stats(self.stats_scope);
+            format!("Uptime: {}%", self.number(average * 100.0, 2))
+        } else {
+            let (average, min, max) = state.scoped_stats(self.stats_scope);
+            let (average, average_unit) = self.scaled(average, 2, &state.unit);
+            let (min, min_unit) = self.scaled(min, 2, &state.unit);
+            let (max, max_unit) = self.scaled(max, 2, &state.unit);
+            format!("Avg: {average} {average_unit} Min: {min} {min_unit} Max: {max} {max_unit}")
+        };
+        if let Some((average, min, max)) = self
+            .highlight
+            .and_then(|range| state.range_stats(&range, self.instant(), self.wall_now()))
+        {
+            if !message.is_empty() {
+                message += " │ ";
+            }
+            message += &if is_bool {
+                format!("Range uptime: {}%", self.number(average * 100.0, 2))
+            } else {
+                format!(
+                    "Range Avg: {} Min: {} Max: {}",
+                    self.number(average, 2),
+                    self.number(min, 2),
+                    self.number(max, 2)
+                )
+            };
+        }
+        let reference = self.references.get(i).copied().filter(|_| !is_bool);
+        let reference_readout = reference.zip(state.last()).map(|(reference, last)| {
+            let (delta, above) = reference_delta(last, reference, |value, decimals| {
+                self.number(value, decimals)
+            });
+            let last = self.number(last, 2);
+            let color = if above { Color::Red } else { Color::Green };
+            (format!("Last: {last} {} ({delta})", state.unit), color)
+        });
+        if let Some(&dropped) = self.model.throttled.lock().unwrap().get(&i) {
+            if !message.is_empty() {
+                message += " │ ";
+            }
+            message += &format!("⏬ {dropped} throttled");
+        }
+        if let Some(&errors) = self.model.parse_errors.lock().unwrap().get(&i) {
+            if !message.is_empty() {
+                message += " │ ";
+            }
+            message += &format!("⚠ {errors} parse errors");
+        }
+        if let Some(dropped) = self.dropped_datagrams() {
+            if !message.is_empty() {
+                message += " │ ";
+            }
+            message += &dropped;
+        }
+        if let Some(column) = self.view.crosshair {
+            let position = if self.newest_left() {
+                column
+            } else {
+                (width as usize).saturating_sub(column + 1)
+            };
+            if let Some(Some(value)) = columns.get(position) {
+                if !message.is_empty() {
+                    message += " │ ";
+                }
+                message += &format!("⌖ {} {}", self.number(*value, 2), state.unit);
+            }
+            for note in self.notes_at(state, column) {
+                message += &format!(" │ ✎ {note}");
+            }
+        }
+        // last, so it can be told apart in the line below
+        if let Some((readout, _)) = &reference_readout {
+            if !message.is_empty() {
+                message += " │ ";
+            }
+            message += readout;
+        }
+        // the title shares the top border with the stats line, keep them apart when possible
+        let inner_width = area.width.saturating_sub(2) as usize;
+        let beside_message = inner_width.saturating_sub(message.width() + 3);
+        let title_width = if message.is_empty() {
+            inner_width
+        } else {
+            beside_message
+        };
+        // the counts stay whole, the title is cut or wrapped first
+        let counts = self
+            .show_counts
+            .then(|| {
+                let per_second = state.arrivals.per_second(self.instant());
+                format!(" {}", format_counts(state.stored, per_second))
+            })
+            .filter(|counts| counts.width() < title_width);
+        let counts_width = counts.as_ref().map_or(0, |counts| counts.width());
+        // the room up to the stats line, keeping a column between them
+        let free = inner_width.saturating_sub(message.width() + 2);
+        let (title, overflow) = if message.is_empty() || title_width >= MIN_TITLE_WIDTH {
+            wrap_title(&title, title_width - counts_width, inner_width)
+        } else if title.width() + counts_width <= free {
+            (title, None)
+        } else {
+            // too little room beside the stats, the whole title goes to the second line
+            (String::new(), Some(truncate(&title, inner_width)))
+        };
+        let mut title = Line::from(title);
+        if let Some(counts) = counts {
+            title.push_span(Span::styled(counts, Style::new().dim()));
+        }
+        let zoom = match self.y_from {
+            cli::YFrom::Min if !is_bool && flat.is_none() => zoom_range(&columns, reference),
+            _ => None,
+        };
+        let bars = columns
             .iter()
-            .map(|&value| Bar::default().value(value as u64).text_value("".to_owned()))
+            .map(|column| {
+                match *column {
+                    None => Bar::default().value(0),
+                    Some(_) if zero_line => Bar::default().value(1),
+                    Some(value) if !is_bool => match zoom {
+                        Some(range) => Bar::default().value(zoomed(value, range) as u64),
+                        None => Bar::default().value(value as u64),
+                    },
+                    Some(value) => {
+                        // on/off samples fill the whole column, the color tells the state apart
+                        let color = if value > 0.0 {
+                            Color::Green
+                        } else {
+                            Color::Red
+                        };
+                        Bar::default().value(1).style(Style::default().fg(color))
+                    }
+                }
+            })
             .collect::<Vec<_>>();
 
-        let span_vec = generate_time_markers(width, 1);
-        let chart = BarChart::default()
-            .data(BarGroup::default().bars(&bars))
+        let time_markers = self
+            .hint_line(i, area.width.saturating_sub(2) as usize)
+            .unwrap_or_else(|| self.time_marker_line(width, 1, interval));
+        let mut chart = BarChart::default()
+            .bars(bars)
             .bar_set(self.bar_set.clone())
-            .bar_gap(0)
-            .bar_style(Style::default().fg(Color::Green))
+            .min_bar(self.min_bar)
+            .grid(self.grid_cells())
+            .bar_style(Style::default().fg(color))
             .block(
                 Block::default()
                     .border_type(BorderType::Rounded)
-                    .title(Line::from(title).right_aligned())
-                    .title_bottom(Line::from(span_vec))
+                    .title(title.right_aligned())
+                    .title_bottom(time_markers)
                     .title_alignment(Alignment::Right)
-                    .borders(Borders::ALL),
-            )
-            .bar_width(1);
+                    .borders(Borders::ALL)
+                    .border_style(border_style)
+                    .padding(Padding::top(overflow.is_some() as u16)),
+            );
+        if is_bool {
+            chart = chart.max(1);
+        }
+        if zero_line {
+            // a quarter of a row high
+            chart = chart.max(4 * area.height.saturating_sub(2) as u64);
+        }
+        // keep the reference in view, the bars scale to the highest one otherwise
+        let scale_max = match zoom {
+            Some(_) => Some(normalize::STEPS as u64),
+            None => {
+                reference.map(|reference| bars_max(&columns).max(reference.ceil() as u64).max(1))
+            }
+        };
+        if let Some(scale_max) = scale_max {
+            chart = chart.max(scale_max);
+        }
         frame.render_widget(chart, *area);
+        let reference_height = match zoom {
+            Some(range) => reference.map(|reference| zoomed(reference, range)),
+            None => reference,
+        };
+        if let (Some(reference), Some(scale_max)) = (reference_height, scale_max) {
+            let mut inner = area.inner(Margin::new(1, 1));
+            inner.y += overflow.is_some() as u16;
+            inner.height = inner.height.saturating_sub(overflow.is_some() as u16);
+            draw_reference_line(frame, inner, reference, scale_max, self.theme.reference);
+        }
+        self.paint_columns(frame, *area, state, width as usize);
+        self.draw_backfill_divider(frame, *area, state, width as usize);
+        self.draw_warmup(frame, *area, state, width as usize);
+        self.draw_note_markers(frame, *area, state, width as usize, 1);
+        if self.scrollbar && self.view.focus == Some(i) {
+            self.draw_scrollbar(frame, *area, state, width as usize);
+        }
+        if let Some(value) = flat {
+            draw_flat_hint(frame, *area, value);
+        } else {
+            self.draw_history_hint(frame, *area, state, width as usize);
+        }
+        let label_row = 1 + overflow.is_some() as u16;
+        if let Some(overflow) = overflow {
+            draw_title_overflow(frame, *area, overflow);
+        }
 
         let [top, _] = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(*area);
 
-        let message = format!(
-            "Avg: {:.2} {} Min: {:.2} {} Max: {:.2} {}",
-            state.average, state.unit, state.min_value, state.unit, state.max_value, state.unit
-        );
-        let span = Span::styled(message, Style::new().dim());
-        let paragraph = Paragraph::new(span)
+        let line = match reference_readout {
+            // only the distance to the reference stands out
+            Some((readout, color)) => {
+                let start = message.len() - readout.len();
+                Line::from(vec![
+                    Span::styled(message[..start].to_string(), Style::new().dim()),
+                    Span::styled(readout, Style::new().fg(color)),
+                ])
+            }
+            None => Line::from(Span::styled(message, Style::new().dim())),
+        };
+        let paragraph = Paragraph::new(line)
             .left_aligned()
             .block(Block::default().padding(Padding::horizontal(2)));
         frame.render_widget(paragraph, top);
+        if is_bool {
+            return Ok(());
+        }
 
-        let [top, _] = Layout::vertical([Constraint::Length(2), Constraint::Min(0)]).areas(*area);
-        let max_value = state.max_value;
-        let y_message = format!("{:.0}{}", max_value, state.unit);
+        let [top, _] =
+            Layout::vertical([Constraint::Length(label_row + 1), Constraint::Min(0)]).areas(*area);
+        let y_message = match zoom {
+            Some((min, max)) => {
+                let (bottom, top) = self.zoomed_labels(&[i], min, max, &state.unit);
+                draw_zoomed_label(frame, *area, &bottom);
+                top
+            }
+            None => {
+                let max_value = state.max_value.max(reference.unwrap_or(f64::NEG_INFINITY));
+                self.y_label(&[i], max_value, &state.unit)
+            }
+        };
         let y_span = Span::styled(y_message, Style::new().dim().fg(Color::DarkGray));
         let y_paragraph = Paragraph::new(y_span)
             .left_aligned()
             .block(Block::default().padding(Padding {
                 left: 2,
                 right: 0,
-                top: 1,
+                top: label_row,
                 bottom: 0,
             }));
         frame.render_widget(y_paragraph, top);
@@ -337,24 +1824,294 @@ impl Dash {
     }
 }
 
-fn is_prime(n: usize) -> bool {
-    if n < 2 {
-        return false;
+impl DashView {
+    fn draw_debug(&self, frame: &mut Frame, area: Rect) {
+        let state = self.model.state.read().unwrap();
+        let used = format_size(retained_bytes(&state));
+        let history = state
+            .first()
+            .map_or(self.history, |chart| chart.data.size());
+        let match_counts = self.model.match_counts.lock().unwrap();
+        let mut rows = vec![
+            ("charts", state.len().to_string()),
+            ("history", format!("{history} samples per chart")),
+            (
+                "rates",
+                format!(
+                    "a sample every {} ms, {} ticks/s, {} fps",
+                    self.reader.update_frequency, self.tick_rate, self.frame_rate
+                ),
+            ),
+            (
+                "last frame",
+                match self.frame_cells {
+                    Some((changed, cells)) => format!("{changed} of {cells} cells changed"),
+                    None => "not drawn yet".to_string(),
+                },
+            ),
+            (
+                "memory",
+                match self.reader.max_memory {
+                    Some(limit) => format!("{used} of {}", format_size(limit)),
+                    None => used,
+                },
+            ),
+            (
+                "skipped lines",
+                self.model.skipped_lines.load(Ordering::Relaxed).to_string(),
+            ),
+        ];
+        if let Some(dropped) = self.dropped_datagrams() {
+            rows.push(("datagrams", dropped));
+        }
+        if !state.is_empty() {
+            let now = self.instant();
+            let counts = state
+                .iter()
+                .enumerate()
+                .map(|(i, chart)| {
+                    let counts = format_counts(chart.stored, chart.arrivals.per_second(now));
+                    format!("chart {}: {counts}", i + 1)
+                })
+                .collect::<Vec<_>>();
+            rows.push(("samples", counts.join(", ")));
+        }
+        let throttled = self.model.throttled.lock().unwrap();
+        if !throttled.is_empty() {
+            rows.push(("throttled", per_chart_counts(&throttled)));
+        }
+        let deduped = self.model.deduped.lock().unwrap();
+        if !deduped.is_empty() {
+            rows.push(("deduped", per_chart_counts(&deduped)));
+        }
+        let non_finite = self.model.non_finite.lock().unwrap();
+        if !non_finite.is_empty() {
+            rows.push(("NaN/inf", per_chart_counts(&non_finite)));
+        }
+        let parse_errors = self.model.parse_errors.lock().unwrap();
+        if !parse_errors.is_empty() {
+            rows.push(("parse errors", per_chart_counts(&parse_errors)));
+        }
+        rows.extend(match_counts.extractors.iter().map(|extractor| {
+            (
+                extractor.name.as_str(),
+                format!("{} of {} lines matched", extractor.matched, extractor.seen),
+            )
+        }));
+        draw_overlay(frame, area, "Debug", &rows);
+    }
+}
+
+/// `chart 1: 5, chart 3: 2` for the debug overlay.
+fn per_chart_counts(counts: &HashMap<usize, usize>) -> String {
+    let mut charts = counts.iter().collect::<Vec<_>>();
+    charts.sort();
+    charts
+        .into_iter()
+        .map(|(i, count)| format!("chart {}: {count}", i + 1))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Split a chart title wider than `width` into the part shown in the top border and a second
+/// line of up to `rest_width`, breaking between words where possible. Only a second line that
+/// doesn't fit either is cut with `…`.
+fn wrap_title(title: &str, width: usize, rest_width: usize) -> (String, Option<String>) {
+    if title.width() <= width || width == 0 {
+        return (title.to_string(), None);
+    }
+    let mut first = 0;
+    let mut word_break = None;
+    for (index, c) in title.char_indices() {
+        if title[..index + c.len_utf8()].width() > width {
+            break;
+        }
+        first = index + c.len_utf8();
+        if c == ' ' && title[..index].trim_end().width() > 0 {
+            word_break = Some(index);
+        }
+    }
+    // the next character starting a word means the whole line fits before it
+    let at_word_end = title[first..].starts_with(' ');
+    let split = if at_word_end {
+        first
+    } else {
+        word_break.unwrap_or(first)
+    };
+    let rest = title[split..].trim_start();
+    let rest = if rest.width() > rest_width {
+        truncate(rest, rest_width)
+    } else {
+        rest.to_string()
+    };
+    (title[..split].trim_end().to_string(), Some(rest))
+}
+
+/// Draw the second line of a wrapped title just inside the top border, under the first.
+fn draw_title_overflow(frame: &mut Frame, area: Rect, line: String) {
+    let inner = area.inner(Margin::new(1, 1));
+    if inner.height == 0 {
+        return;
+    }
+    let row = Rect::new(inner.x, inner.y, inner.width, 1);
+    let paragraph = Paragraph::new(Span::styled(line, Style::new().dim())).right_aligned();
+    frame.render_widget(Clear, row);
+    frame.render_widget(paragraph, row);
+}
+
+/// The highest bar of the columns, as the bar chart scales them.
+fn bars_max(columns: &[Option<f64>]) -> u64 {
+    columns
+        .iter()
+        .flatten()
+        .map(|&value| value as u64)
+        .max()
+        .unwrap_or(0)
+}
+
+/// The signed distance of `last` to `reference` and its percentage, e.g. `−38.00, −15.2%`, and
+/// whether `last` is above the reference.
+fn reference_delta(
+    last: f64,
+    reference: f64,
+    number: impl Fn(f64, usize) -> String,
+) -> (String, bool) {
+    let delta = last - reference;
+    let sign = if delta < 0.0 { "−" } else { "+" };
+    let mut readout = format!("{sign}{}", number(delta.abs(), 2));
+    if reference != 0.0 {
+        let percent = delta / reference.abs() * 100.0;
+        readout += &format!(", {sign}{}%", number(percent.abs(), 1));
+    }
+    (readout, delta > 0.0)
+}
+
+/// Draw a dashed line across the empty cells of `inner` at the height of `reference`, on a chart
+/// scaled to `scale_max`.
+fn draw_reference_line(
+    frame: &mut Frame,
+    inner: Rect,
+    reference: f64,
+    scale_max: u64,
+    style: Style,
+) {
+    if inner.height == 0 || reference < 0.0 {
+        return;
+    }
+    let rows = reference / scale_max as f64 * inner.height as f64;
+    let row = (rows.floor() as u16).min(inner.height - 1);
+    let y = inner.bottom() - 1 - row;
+    for x in inner.left()..inner.right() {
+        let cell = &mut frame.buffer_mut()[(x, y)];
+        if cell.symbol() == " " {
+            cell.set_symbol("╌").set_style(style);
+        }
+    }
+}
+
+/// Tell the user that a chart only got one value, usually a capture that matches the wrong thing.
+/// The true bottom of a `--y-from min` chart in its lowest row, with a mark that the bars don't
+/// start at zero.
+fn draw_zoomed_label(frame: &mut Frame, area: Rect, bottom: &str) {
+    let inner = area.inner(Margin::new(2, 1));
+    if inner.height < 2 {
+        return;
+    }
+    let row = Rect {
+        y: inner.bottom() - 1,
+        height: 1,
+        ..inner
+    };
+    let label = Span::styled(
+        format!("{bottom} ↕ zoomed"),
+        Style::new().dim().fg(Color::DarkGray),
+    );
+    frame.render_widget(Paragraph::new(label), row);
+}
+
+fn draw_flat_hint(frame: &mut Frame, area: Rect, value: f64) {
+    let inner = area.inner(Margin::new(2, 1));
+    if inner.height == 0 {
+        return;
     }
-    for i in 2..=((n as f64).sqrt() as usize) {
-        if n % i == 0 {
-            return false;
+    let hint = format!(
+        "The last {FLAT_HINT_SAMPLES} samples are all {value}, check your extraction with `ttydash test`"
+    );
+    let [_, row, _] = Layout::vertical([
+        Constraint::Fill(1),
+        Constraint::Length(1),
+        Constraint::Fill(1),
+    ])
+    .areas(inner);
+    let paragraph = Paragraph::new(Span::styled(hint, Style::new().dim().italic())).centered();
+    frame.render_widget(paragraph, row);
+}
+
+/// Partition the charts in display order into one group per unit, in order of first appearance.
+fn group_by_unit(state: &[DashState], order: &[usize]) -> Vec<Vec<usize>> {
+    let mut panels: Vec<Vec<usize>> = vec![];
+    for &i in order {
+        match panels
+            .iter_mut()
+            .find(|panel| state[panel[0]].unit == state[i].unit)
+        {
+            Some(panel) => panel.push(i),
+            None => panels.push(vec![i]),
         }
     }
-    true
+    panels
 }
 
-impl Component for Dash {
+impl Component for DashView {
     fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        self.reader.command_tx = Some(tx.clone());
         self.command_tx = Some(tx);
         Ok(())
     }
 
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.theme = config.theme.clone();
+        if let Some(keymap) = config.keybindings.get(&Mode::Home) {
+            self.hints = chart_hints(keymap);
+        }
+        if let Some(tokens) = config.bool_tokens.get(&Mode::Home) {
+            self.bool_tokens = tokens.clone();
+        }
+        if self.reader.has_on_off() {
+            let tokens = Some(self.bool_tokens.clone());
+            self.reader.set_extractor(
+                extractor(&self.reader.units, self.reader.indices.clone(), tokens)?.into(),
+            );
+        }
+        Ok(())
+    }
+
+    fn init(&mut self, _area: Size) -> Result<()> {
+        // the reader is started here so it sees the registered config
+        let charts = self.reader.expected_charts().unwrap_or(1);
+        self.reader
+            .enforce_memory_limit(&mut self.model.state.write().unwrap(), charts);
+        task::spawn(self.reader.clone().run());
+        self.start_http_server()?;
+        Ok(())
+    }
+
+    fn exit(&mut self) -> Result<()> {
+        self.reader.cancellation_token.cancel();
+        // the process may exit before the reader tasks holding the socket are dropped
+        #[cfg(unix)]
+        if let Some(socket) = &self.reader.socket {
+            socket.remove();
+        }
+        if let Some(path) = &self.summary {
+            match self.summary().write(path) {
+                Err(err) if is_broken_pipe(&err) => report_closed_stdout("summary"),
+                result => result?,
+            }
+        }
+        Ok(())
+    }
+
     fn update(&mut self, action: Action) -> Result<Option<Action>> {
         match action {
             Action::Tick => {
@@ -363,107 +2120,511 @@ impl Component for Dash {
             Action::Render => {
                 // add any logic here that should run on every render
             }
+            Action::DataUpdated(i) => {
+                self.data_changed = true;
+                return Ok(self.check_alert(i));
+            }
+            Action::FocusNext => self.move_focus(true),
+            Action::FocusPrevious => self.move_focus(false),
+            Action::TogglePin => self.toggle_pin(),
+            Action::MoveChartEarlier => self.move_chart(false),
+            Action::MoveChartLater => self.move_chart(true),
+            Action::HideChart => self.hide_chart(),
+            Action::ShowAll => self.show_all(),
+            Action::ToggleGroup => self.toggle_group(),
+            Action::CycleLayout => self.cycle_layout(),
+            Action::ZoomIn => self.zoom(true),
+            Action::ZoomOut => self.zoom(false),
+            // pan towards the side of the arrow, where the older samples depend on the direction
+            Action::PanLeft if self.view.crosshair.is_some() => self.move_crosshair(true),
+            Action::PanRight if self.view.crosshair.is_some() => self.move_crosshair(false),
+            Action::PanLeft => self.pan(!self.newest_left()),
+            Action::PanRight => self.pan(self.newest_left()),
+            Action::ToggleCrosshair => self.toggle_crosshair(),
+            // only the drawing changes, the samples stay as they are
+            Action::ToggleYFrom => {
+                self.y_from = match self.y_from {
+                    cli::YFrom::Zero => cli::YFrom::Min,
+                    cli::YFrom::Min => cli::YFrom::Zero,
+                }
+            }
+            Action::EditTitle => self.start_edit(EditField::Title),
+            Action::EditUnit => self.start_edit(EditField::Unit),
+            Action::EditReference => self.start_edit(EditField::Reference),
+            Action::AddNote => self.start_edit(EditField::Note),
+            Action::SubmitInput(value) => self.finish_edit(value),
+            Action::CancelInput => self.editing = None,
+            Action::MarkRangeStart => self.mark_range(true),
+            Action::MarkRangeEnd => self.mark_range(false),
+            Action::ClearRange => {
+                self.highlight = None;
+                self.highlight_start = None;
+            }
+            Action::PanReset => self.view.offset = 0,
+            Action::ToggleDebug => self.debug = !self.debug,
+            Action::FrameDrawn(changed, cells) => self.frame_cells = Some((changed, cells)),
+            Action::ToggleInfo => {
+                self.info = !self.info;
+                self.inspecting &= !self.info;
+            }
+            Action::ToggleInspector => self.toggle_inspector(),
+            Action::CloseOverlay => (self.info, self.inspecting) = (false, false),
+            Action::ToggleExpand if self.mode == cli::DisplayMode::Ticker => {
+                self.view.expanded = !self.view.expanded
+            }
+            Action::WriteProfile => self.write_profile(),
+            Action::ExportChart => self.export_chart(),
+            Action::TogglePause => self.toggle_pause(false),
+            Action::TogglePauseAll => self.toggle_pause(true),
+            Action::ClearChart => self.clear(false),
+            Action::ClearAll => self.clear(true),
+            Action::SetPaused(i, paused) => self.set_paused(i, paused),
+            Action::ResetChart(i) => self.reset_chart(i),
+            Action::SetReference(i, reference) => self.set_reference(i, reference),
+            Action::Mark(text) => self.add_note(text),
+            Action::Snapshot => self.snapshot(),
             _ => {}
         }
         Ok(None)
     }
 
     fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
-        if !self.group {
-            let state = self.state.read().unwrap();
-            let num_chart_states = state.len();
-            // split the area
-            let chunks = match self.layout {
-                cli::Layout::Vertical => {
-                    Layout::vertical(vec![Constraint::Percentage(100); num_chart_states])
-                        .split(area)
-                        .iter()
-                        .copied()
-                        .collect::<Vec<_>>()
-                }
-                cli::Layout::Horizontal => {
-                    Layout::horizontal(vec![Constraint::Percentage(100); num_chart_states])
-                        .split(area)
-                        .iter()
-                        .copied()
-                        .collect::<Vec<_>>()
-                }
-                cli::Layout::Auto => {
-                    if is_prime(num_chart_states) {
-                        // grid + 1
-                        let (rows, cols) = match num_chart_states - 1 {
-                            1 => (1, 1),
-                            2 => (1, 2),
-                            _ => {
-                                let rows = (2..=num_chart_states - 1)
-                                    .rev()
-                                    .find(|&i| num_chart_states % i == 0)
-                                    .unwrap_or(1);
-                                let cols = num_chart_states / rows;
-                                (rows, cols)
-                            }
-                        };
-                        let row_constraints =
-                            vec![Constraint::Percentage((100 / rows + 1) as u16); rows + 1];
-                        let row_chunks = Layout::default()
-                            .direction(Direction::Vertical)
-                            .constraints(row_constraints)
-                            .split(area);
-                        let mut chunks = vec![];
-                        for row_chunk in row_chunks[1..].iter() {
-                            let col_constraints =
-                                vec![Constraint::Percentage((100 / cols) as u16); cols];
-                            let col_chunks = Layout::default()
-                                .direction(Direction::Horizontal)
-                                .constraints(col_constraints)
-                                .split(*row_chunk);
-                            let col_chunks_vec = col_chunks.iter().copied().collect::<Vec<_>>();
-                            chunks.extend(col_chunks_vec);
-                        }
-                        chunks.insert(0, row_chunks[0]);
-                        chunks
-                    } else {
-                        let (rows, cols) = match num_chart_states {
-                            1 => (1, 1),
-                            2 => (1, 2),
-                            _ => {
-                                let rows = (2..=num_chart_states - 1)
-                                    .rev()
-                                    .find(|&i| num_chart_states % i == 0)
-                                    .unwrap_or(1);
-                                let cols = num_chart_states / rows;
-                                (rows, cols)
-                            }
-                        };
-                        let row_constraints =
-                            vec![Constraint::Percentage((100 / rows) as u16); rows];
-                        let row_chunks = Layout::default()
-                            .direction(Direction::Vertical)
-                            .constraints(row_constraints)
-                            .split(area);
-                        let mut chunks = vec![];
-                        for row_chunk in row_chunks.iter() {
-                            let col_constraints =
-                                vec![Constraint::Percentage((100 / cols) as u16); cols];
-                            let col_chunks = Layout::default()
-                                .direction(Direction::Horizontal)
-                                .constraints(col_constraints)
-                                .split(*row_chunk);
-                            let col_chunks_vec = col_chunks.iter().copied().collect::<Vec<_>>();
-                            chunks.extend(col_chunks_vec);
-                        }
-                        chunks
-                    }
-                }
-            };
+        // the widest chart is measured again in a new size, to keep the crosshair in view
+        if area != self.drawn_area {
+            self.drawn_area = area;
+            self.view_width = 0;
+        }
+        self.refresh_trends();
+        let (area, stats_panel) = self.split_stats_panel(area);
+        if self.mode == cli::DisplayMode::Ticker {
+            self.draw_ticker(frame, area)?;
+        } else if !self.group {
+            let state_lock = self.model.state.clone();
+            let state = state_lock.read().unwrap();
+            self.refresh_order(&state);
+            let chunks = compute_grid(self.order.len(), area, self.layout, &LayoutOpts::default());
             // release the lock
             drop(state);
-            for (i, chunk) in chunks.iter().enumerate() {
+            for (chunk, i) in chunks.iter().zip(self.order.clone()) {
                 self.draw_chart(frame, chunk, i)?;
             }
+        } else if let Some(cli::GroupBy::Unit) = self.group_by {
+            let state_lock = self.model.state.clone();
+            let state = state_lock.read().unwrap();
+            self.refresh_order(&state);
+            // computed on every frame so new charts and edited units land in the right panel
+            let panels = group_by_unit(&state, &self.order);
+            drop(state);
+            let chunks = compute_grid(panels.len(), area, self.layout, &LayoutOpts::default());
+            for (chunk, charts) in chunks.iter().zip(panels) {
+                self.draw_grouped_chart(frame, chunk, &charts)?;
+            }
         } else {
-            self.draw_grouped_chart(frame, &area)?;
+            let state_lock = self.model.state.clone();
+            let state = state_lock.read().unwrap();
+            self.refresh_order(&state);
+            // in creation order, so every series keeps its column
+            let charts = (0..state.len())
+                .filter(|i| !self.view.hidden.contains(i))
+                .collect::<Vec<_>>();
+            drop(state);
+            if !charts.is_empty() {
+                self.draw_grouped_chart(frame, &area, &charts)?;
+            }
+        }
+        if let Some((panel, rows)) = stats_panel {
+            self.draw_stats_panel(frame, panel, rows);
+        }
+        if self.debug {
+            self.draw_debug(frame, area);
+        }
+        if self.info {
+            self.draw_info(frame, area);
+        }
+        if self.inspecting {
+            self.draw_inspector(frame, area);
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::memory;
+    use reader::ReaderState;
+
+    #[test]
+    fn test_retained_bytes_estimate() {
+        for (charts, history) in [(1, 200), (50, 10_000), (8, 1_000_000)] {
+            let state = (0..charts)
+                .map(|_| DashState::new(history, Duration::from_secs(1)))
+                .collect::<Vec<_>>();
+            let actual = retained_bytes(&state) as f64;
+            let estimate = memory::estimate_bytes(charts, history) as f64;
+            assert!(
+                (estimate - actual).abs() <= actual * 0.1,
+                "{charts} charts of {history} samples: estimated {estimate}, retained {actual}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_downsample_by_time() {
+        let samples = [5.0, 1.0, 7.0, 2.0, 3.0];
+        let times = [0.5, 3.2, 3.9, 4.1, 9.5];
+        // a burst shares a column, a quiet stretch leaves columns empty
+        assert_eq!(
+            downsample_by_time(&samples, &times, 10.0, 8, 1.0),
+            [
+                None,
+                Some(7.0),
+                Some(2.0),
+                None,
+                None,
+                None,
+                None,
+                Some(3.0)
+            ]
+        );
+        assert_eq!(
+            downsample_by_time(&samples, &times, 10.0, 3, 4.0),
+            [Some(5.0), Some(7.0), Some(3.0)]
+        );
+        // panned back past the newest sample
+        assert_eq!(
+            downsample_by_time(&samples, &times, 4.0, 2, 1.0),
+            [None, Some(7.0)]
+        );
+    }
+
+    #[test]
+    fn test_arrival_and_interval_charts() {
+        use clap::Parser;
+
+        let args = Cli::parse_from(["ttydash", "--source", "", "--source", "cadence=arrival"]);
+        let mut dash = DashView::new(args).unwrap();
+        let start = Instant::now();
+        dash.set_clock(Arc::new(crate::clock::MockClock::new(
+            start + Duration::from_secs(4),
+        )));
+        let mut state = vec![];
+        let mut reader = ReaderState::default();
+        for (seconds, value) in [(0, 1.0), (1, 2.0), (3, 3.0)] {
+            let at = start + Duration::from_secs(seconds);
+            for i in 0..2 {
+                dash.reader.store(&mut state, &mut reader, i, value, at);
+            }
+        }
+        let interval = dash.base_interval();
+        assert_eq!(dash.arrival_columns(&state[0], 4, interval), None);
+        assert_eq!(
+            dash.arrival_columns(&state[1], 5, interval),
+            Some(vec![Some(1.0), Some(2.0), None, Some(3.0), None])
+        );
+        // both are drawn against the same time markers
+        assert_eq!(
+            dash.column_time(&state[1], 1),
+            Some(dash.wall_now() - interval)
+        );
+    }
+
+    #[test]
+    fn test_group_by_unit() {
+        let state = ["ms", "%", "", "ms", "%"]
+            .iter()
+            .map(|unit| DashState {
+                unit: unit.to_string(),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            group_by_unit(&state, &[4, 0, 1, 2, 3]),
+            vec![vec![4, 1], vec![0, 3], vec![2]]
+        );
+        assert_eq!(group_by_unit(&state, &[]), Vec::<Vec<usize>>::new());
+    }
+
+    #[test]
+    fn test_wrap_title() {
+        let title = "p99 request latency for checkout-service (us-east-1)";
+        assert_eq!(wrap_title(title, 60, 60), (title.to_string(), None));
+        assert_eq!(
+            wrap_title(title, 30, 30),
+            (
+                "p99 request latency for".to_string(),
+                Some("checkout-service (us-east-1)".to_string())
+            )
+        );
+        // the second line is only cut when it doesn't fit either
+        assert_eq!(
+            wrap_title(title, 20, 20),
+            (
+                "p99 request latency".to_string(),
+                Some("for checkout-servic…".to_string())
+            )
+        );
+        // the second line may be wider, next to the stats line only the first is narrow
+        assert_eq!(
+            wrap_title(title, 20, 40),
+            (
+                "p99 request latency".to_string(),
+                Some("for checkout-service (us-east-1)".to_string())
+            )
+        );
+        // no space to break at
+        assert_eq!(
+            wrap_title("checkout-service", 10, 10),
+            ("checkout-s".to_string(), Some("ervice".to_string()))
+        );
+        // a wide character never straddles the edge
+        assert_eq!(
+            wrap_title("延迟 延迟延迟", 5, 5),
+            ("延迟".to_string(), Some("延迟…".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_move_chart() {
+        use clap::Parser;
+
+        let charts = || {
+            (0..4)
+                .map(|_| DashState::new(10, Duration::from_secs(1)))
+                .collect()
+        };
+        let mut dash = DashView::with_states(charts(), Cli::parse_from(["ttydash"])).unwrap();
+        let state = dash.model.state.clone();
+        dash.refresh_order(&state.read().unwrap());
+        dash.view.focus = Some(2);
+        dash.move_chart(false);
+        dash.move_chart(false);
+        // already first
+        dash.move_chart(false);
+        dash.refresh_order(&state.read().unwrap());
+        assert_eq!(dash.order, [2, 0, 1, 3]);
+        assert_eq!(dash.view.focus, Some(2));
+        assert_eq!(dash.live_profile().order, Some(vec![3, 1, 2, 4]));
+
+        // pinned charts stay above the others
+        dash.view.focus = Some(3);
+        dash.toggle_pin();
+        dash.refresh_order(&state.read().unwrap());
+        dash.move_chart(true);
+        dash.refresh_order(&state.read().unwrap());
+        assert_eq!(dash.order, [3, 2, 0, 1]);
+
+        let args = Cli::parse_from(["ttydash", "--order", "4,2", "--sort", "none"]);
+        let mut dash = DashView::with_states(charts(), args).unwrap();
+        dash.refresh_order(&state.read().unwrap());
+        assert_eq!(dash.order, [3, 1, 0, 2]);
+    }
+
+    #[test]
+    fn test_reference_delta() {
+        let number = |value: f64, decimals: usize| format!("{value:.decimals$}");
+        assert_eq!(
+            reference_delta(212.0, 250.0, number),
+            ("−38.00, −15.2%".to_string(), false)
+        );
+        assert_eq!(
+            reference_delta(300.0, 250.0, number),
+            ("+50.00, +20.0%".to_string(), true)
+        );
+        assert_eq!(
+            reference_delta(1.5, 0.0, number),
+            ("+1.50".to_string(), true)
+        );
+    }
+
+    #[test]
+    fn test_warmup_samples() {
+        use clap::Parser;
+
+        let dash = DashView::new(Cli::parse_from(["ttydash", "--warmup", "2"])).unwrap();
+        let mut state = vec![];
+        let mut reader = ReaderState::default();
+        let now = Instant::now();
+        for value in [100.0, 90.0, 1.0, 2.0, 3.0] {
+            dash.reader.store(&mut state, &mut reader, 0, value, now);
+        }
+        // a chart created later warms up on its own
+        for value in [50.0, 60.0, 5.0] {
+            dash.reader.store(&mut state, &mut reader, 1, value, now);
+        }
+        assert_eq!(state[0].warmup, 2);
+        assert_eq!(state[0].samples().len(), 5);
+        assert_eq!(
+            (state[0].average, state[0].min_value, state[0].max_value),
+            (2.0, 1.0, 3.0)
+        );
+        assert_eq!(state[0].stats_samples(), &[1.0, 2.0, 3.0]);
+        assert_eq!(state[1].warmup, 2);
+        assert_eq!(state[1].stats_samples(), &[5.0]);
+
+        let (titles, references) = (Positional::default(), Positional::default());
+        let summary = summarize(&state, 0..2, &titles, &references, &dash.model, None);
+        assert_eq!(summary.charts[0].warmup_samples, 2);
+        assert_eq!(summary.charts[0].max, Some(3.0));
+        let session = summary.charts[0].session.as_ref().unwrap();
+        assert_eq!((session.samples, session.min, session.max), (3, 1.0, 3.0));
+    }
+
+    #[test]
+    fn test_session_stats_scope() {
+        use clap::Parser;
+
+        let args = ["ttydash", "--history", "4", "--stats-scope", "session"];
+        let dash = DashView::new(Cli::parse_from(args)).unwrap();
+        let mut state = vec![];
+        let mut reader = ReaderState::default();
+        let now = Instant::now();
+        for value in 1..=100 {
+            dash.reader
+                .store(&mut state, &mut reader, 0, value as f64, now);
+        }
+        assert_eq!(state[0].samples(), &[97.0, 98.0, 99.0, 100.0]);
+        assert_eq!(state[0].scoped_stats(dash.stats_scope), (50.5, 1.0, 100.0));
+        let (titles, references) = (Positional::default(), Positional::default());
+        let summary = summarize(&state, 0..1, &titles, &references, &dash.model, None);
+        assert_eq!(summary.charts[0].min, Some(97.0));
+        let session = summary.charts[0].session.as_ref().unwrap();
+        assert_eq!(session.samples, 100);
+        let p99 = session.p99.unwrap();
+        assert!((p99 - 99.0).abs() <= 0.99, "{p99}");
+    }
+
+    #[test]
+    fn test_warmup_duration() {
+        use clap::Parser;
+
+        let dash = DashView::new(Cli::parse_from(["ttydash", "--warmup", "10s"])).unwrap();
+        let mut state = vec![];
+        let mut reader = ReaderState::default();
+        let start = Instant::now();
+        for seconds in [0, 5, 10, 15] {
+            let at = start + Duration::from_secs(seconds);
+            dash.reader
+                .store(&mut state, &mut reader, 0, seconds as f64, at);
+        }
+        assert_eq!(state[0].warmup, 2);
+        assert_eq!(state[0].average, 12.5);
+
+        // warmup samples leave the history like any other
+        let mut chart = DashState::new(3, Duration::from_secs(1));
+        for value in [1.0, 2.0] {
+            chart.update(value);
+            chart.mark_warmup();
+        }
+        chart.update(3.0);
+        chart.update(4.0);
+        assert_eq!(chart.warmup, 1);
+        assert_eq!(chart.stats_samples(), &[3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_add_note() {
+        use clap::Parser;
+
+        let path = std::env::temp_dir().join(format!("ttydash-notes-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let path_arg = path.to_str().unwrap();
+        let dash = DashView::new(Cli::parse_from(["ttydash", "--notes-file", path_arg])).unwrap();
+        dash.add_note("  ".to_string());
+        dash.add_note(" deploy v2 ".to_string());
+        let notes = dash.model.notes.lock().unwrap().clone();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].text, "deploy v2");
+        let written = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(written.lines().count(), 1);
+        assert!(written.ends_with("\tdeploy v2\n"));
+    }
+
+    #[test]
+    fn test_control_actions() {
+        use clap::Parser;
+
+        let args = Cli::parse_from(["ttydash", "--reference", "80"]);
+        let mut dash = DashView::new(args).unwrap();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        dash.register_action_handler(tx).unwrap();
+        let mut reader = ReaderState::default();
+        dash.reader.ingest_line("1 2", &mut reader);
+        while rx.try_recv().is_ok() {}
+
+        dash.update(Action::SetPaused(Some(1), true)).unwrap();
+        dash.update(Action::SetPaused(Some(1), true)).unwrap();
+        let paused = |dash: &DashView| {
+            let state = dash.model.state.read().unwrap();
+            state
+                .iter()
+                .map(|chart| chart.paused_since.is_some())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(paused(&dash), [false, true]);
+        dash.update(Action::SetPaused(None, true)).unwrap();
+        assert_eq!(paused(&dash), [true, true]);
+        dash.update(Action::SetPaused(None, false)).unwrap();
+        assert_eq!(paused(&dash), [false, false]);
+
+        dash.update(Action::ResetChart(0)).unwrap();
+        assert_eq!(dash.model.state.read().unwrap()[0].last(), None);
+        assert_eq!(dash.model.state.read().unwrap()[1].last(), Some(2.0));
+
+        dash.update(Action::SetReference(1, 1.5)).unwrap();
+        assert_eq!(dash.references.get(0), Some(&80.0));
+        assert_eq!(dash.references.get(1), Some(&1.5));
+
+        dash.update(Action::Mark("deploy".to_string())).unwrap();
+        assert_eq!(dash.model.notes.lock().unwrap()[0].text, "deploy");
+
+        dash.update(Action::ResetChart(2)).unwrap();
+        assert_eq!(
+            rx.try_recv().unwrap(),
+            Action::Toast("There is no chart 3, only 2".to_string())
+        );
+    }
+
+    #[test]
+    fn test_value_sort_waits_for_data() {
+        use clap::Parser;
+
+        let mut dash = DashView::new(Cli::parse_from(["ttydash", "--sort", "value"])).unwrap();
+        let mut state = vec![];
+        let mut reader = ReaderState::default();
+        let now = Instant::now();
+        dash.reader.store(&mut state, &mut reader, 1, 1.0, now);
+        dash.refresh_order(&state);
+        assert_eq!(dash.order, [1, 0]);
+
+        dash.reader.store(&mut state, &mut reader, 0, 2.0, now);
+        dash.order_updated = Some(Instant::now() - SORT_REFRESH);
+        dash.refresh_order(&state);
+        assert_eq!(dash.order, [1, 0]);
+
+        dash.update(Action::DataUpdated(0)).unwrap();
+        dash.refresh_order(&state);
+        assert_eq!(dash.order, [0, 1]);
+    }
+
+    #[test]
+    fn test_reloaded_bool_tokens_reach_the_reader() {
+        use clap::Parser;
+
+        let mut dash = DashView::new(Cli::parse_from(["ttydash", "--format", "bool"])).unwrap();
+        let mut config = Config::default();
+        let tokens = |word: &str| HashMap::from([(word.to_string(), true)]);
+        config.bool_tokens.insert(Mode::Home, tokens("up"));
+        dash.register_config_handler(config.clone()).unwrap();
+        // the clone the reader task runs with
+        let running = dash.reader.clone();
+        assert_eq!(running.extractor().extract("up"), [(0, 1.0)]);
+        config.bool_tokens.insert(Mode::Home, tokens("ready"));
+        dash.register_config_handler(config).unwrap();
+        assert_eq!(running.extractor().extract("ready"), [(0, 1.0)]);
+        assert_eq!(running.extractor().extract("up"), []);
+    }
+}
@@ -0,0 +1,134 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// How far back the arrival rate looks.
+pub const ARRIVAL_WINDOW: Duration = Duration::from_secs(10);
+
+/// How fast samples arrive at a chart, over the last [`ARRIVAL_WINDOW`]. Arrivals are counted
+/// per second rather than kept one by one, so a chart fed thousands of lines a second costs as
+/// little as a slow one.
+#[derive(Debug, Clone, Default)]
+pub struct ArrivalRate {
+    /// When the first sample arrived.
+    start: Option<Instant>,
+    /// Samples per second since `start`, the newest last.
+    buckets: VecDeque<(u64, usize)>,
+}
+
+impl ArrivalRate {
+    /// Count a sample arriving at `now`.
+    pub fn record(&mut self, now: Instant) {
+        let start = *self.start.get_or_insert(now);
+        let second = now.saturating_duration_since(start).as_secs();
+        match self.buckets.back_mut() {
+            Some((last, count)) if *last == second => *count += 1,
+            _ => self.buckets.push_back((second, 1)),
+        }
+        let window = ARRIVAL_WINDOW.as_secs();
+        while self
+            .buckets
+            .front()
+            .is_some_and(|&(oldest, _)| oldest + window <= second)
+        {
+            self.buckets.pop_front();
+        }
+    }
+
+    /// Samples per second over the window up to `now`, or the time since the first sample when
+    /// that is shorter, counted as at least a second. Zero once nothing arrived for a window.
+    pub fn per_second(&self, now: Instant) -> f64 {
+        let Some(start) = self.start else {
+            return 0.0;
+        };
+        let elapsed = now.saturating_duration_since(start);
+        let window = ARRIVAL_WINDOW.as_secs();
+        // the window ends in the current second, which is only partly over
+        let first = elapsed.as_secs().saturating_sub(window - 1);
+        let arrived: usize = self
+            .buckets
+            .iter()
+            .filter(|&&(second, _)| second >= first)
+            .map(|&(_, count)| count)
+            .sum();
+        let covered = elapsed
+            .min(Duration::from_secs(window - 1) + subsec(elapsed))
+            .max(Duration::from_secs(1));
+        arrived as f64 / covered.as_secs_f64()
+    }
+}
+
+/// The part of `duration` past its whole seconds.
+fn subsec(duration: Duration) -> Duration {
+    Duration::from_nanos(duration.subsec_nanos() as u64)
+}
+
+/// `n=1432 @1.0/s`, the samples a chart got and how fast they arrive, for its title.
+pub fn format_counts(total: usize, per_second: f64) -> String {
+    format!(
+        "n={} @{}/s",
+        compact(total as f64, 0),
+        compact(per_second, 1)
+    )
+}
+
+/// A short form of `value`: `k` and `M` from ten thousand on, `decimals` only below ten.
+fn compact(value: f64, decimals: usize) -> String {
+    match value {
+        value if value >= 1e6 => format!("{:.1}M", value / 1e6),
+        value if value >= 1e4 => format!("{:.1}k", value / 1e3),
+        value if value >= 10.0 => format!("{value:.0}"),
+        value => format!("{value:.decimals$}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_format_counts() {
+        assert_eq!(format_counts(1432, 1.0), "n=1432 @1.0/s");
+        assert_eq!(format_counts(14_321, 12.6), "n=14.3k @13/s");
+        assert_eq!(format_counts(0, 0.0), "n=0 @0.0/s");
+        assert_eq!(format_counts(999, 0.3), "n=999 @0.3/s");
+        assert_eq!(format_counts(2_500_000, 37.4), "n=2.5M @37/s");
+        assert_eq!(format_counts(12, 42_000.0), "n=12 @42.0k/s");
+    }
+
+    #[test]
+    fn test_steady_rate() {
+        let start = Instant::now();
+        let mut rate = ArrivalRate::default();
+        assert_eq!(rate.per_second(start), 0.0);
+        // one sample every half second for a minute
+        for tick in 0..120 {
+            rate.record(start + Duration::from_millis(500 * tick));
+        }
+        let now = start + Duration::from_millis(59_600);
+        assert!((rate.per_second(now) - 2.0).abs() < 0.1);
+        // only the last window is kept
+        assert!(rate.buckets.len() <= ARRIVAL_WINDOW.as_secs() as usize);
+    }
+
+    #[test]
+    fn test_young_and_stalled() {
+        let start = Instant::now();
+        let mut rate = ArrivalRate::default();
+        for tick in 0..4 {
+            rate.record(start + Duration::from_secs(tick));
+        }
+        // four samples in the three seconds and a half so far
+        let now = start + Duration::from_millis(3500);
+        assert!((rate.per_second(now) - 4.0 / 3.5).abs() < 1e-9);
+        // the first sample doesn't make for a burst
+        let mut first = ArrivalRate::default();
+        first.record(start);
+        assert_eq!(first.per_second(start), 1.0);
+        // nothing for a whole window
+        assert_eq!(rate.per_second(start + Duration::from_secs(20)), 0.0);
+    }
+}
@@ -0,0 +1,106 @@
+use super::{
+    protocol::{Metric, MetricKind},
+    reader::{ReaderState, SourceReader},
+};
+
+/// The chart counting the lines of the values `--max-groups` leaves out.
+pub const OTHER_GROUP: &str = "other";
+
+impl SourceReader {
+    /// A count of one for the chart of the value `--count-by` captures on `line`, or `None`
+    /// when it doesn't match. Once `--max-groups` values have a chart, new ones are counted in
+    /// [`OTHER_GROUP`].
+    pub(super) fn count_metric(&self, line: &str, reader: &ReaderState) -> Option<Metric> {
+        let captures = self.count_by.as_ref()?.captures(line)?;
+        // without a capture group the whole match is the value
+        let value = match captures.len() {
+            1 => captures.get(0),
+            _ => captures.get(1),
+        }?;
+        let groups = reader
+            .names
+            .keys()
+            .filter(|&name| name != OTHER_GROUP)
+            .count();
+        let name = if reader.names.contains_key(value.as_str()) || groups < self.max_groups {
+            value.as_str()
+        } else {
+            OTHER_GROUP
+        };
+        Some(Metric {
+            name: name.to_string(),
+            value: 1.0,
+            kind: MetricKind::Counter,
+            timestamp: None,
+            relative: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use pretty_assertions::assert_eq;
+
+    use super::super::model::ChartModel;
+    use super::*;
+    use crate::cli::Cli;
+
+    fn names(args: &[&str], lines: &[&str]) -> Vec<Option<String>> {
+        let args = Cli::parse_from([&["ttydash"], args].concat());
+        let source = SourceReader::new(&args, ChartModel::default()).unwrap();
+        let mut reader = source.new_reader();
+        for line in lines {
+            source.ingest_metrics(line, &mut reader, 1);
+        }
+        let state = source.model.state.read().unwrap();
+        state.iter().map(|chart| chart.name.clone()).collect()
+    }
+
+    #[test]
+    fn test_groups() {
+        let lines = ["GET / 200", "GET /a 404", "boot", "GET /b 200"];
+        let names = names(&["--count-by", r" (\d)\d\d$"], &lines);
+        assert_eq!(names, [Some("2".to_string()), Some("4".to_string())]);
+    }
+
+    #[test]
+    fn test_counts_per_interval() {
+        let args = Cli::parse_from(["ttydash", "--count-by", r" (\d)\d\d$"]);
+        let source = SourceReader::new(&args, ChartModel::default()).unwrap();
+        let mut reader = source.new_reader();
+        for line in ["GET / 200", "GET /a 500", "GET /b 204", "GET /c 200"] {
+            source.ingest_metrics(line, &mut reader, 1);
+        }
+        source.flush_pending(&mut reader);
+        source.ingest_metrics("GET /d 503", &mut reader, 1);
+        source.flush_pending(&mut reader);
+        let state = source.model.state.read().unwrap();
+        // an interval without a line of its group is a zero
+        assert_eq!(state[0].samples(), [3.0, 0.0]);
+        assert_eq!(state[1].samples(), [1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_whole_match() {
+        let names = names(
+            &["--count-by", "ERROR|WARN"],
+            &["WARN a", "ERROR b", "INFO c"],
+        );
+        assert_eq!(names, [Some("WARN".to_string()), Some("ERROR".to_string())]);
+    }
+
+    #[test]
+    fn test_max_groups() {
+        let lines = ["a 1", "b 2", "c 3", "a 4", "d 5"];
+        let names = names(&["--count-by", "^(\\w)", "--max-groups", "2"], &lines);
+        assert_eq!(
+            names,
+            [
+                Some("a".to_string()),
+                Some("b".to_string()),
+                Some(OTHER_GROUP.to_string())
+            ]
+        );
+    }
+}
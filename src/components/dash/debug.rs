@@ -0,0 +1,35 @@
+use ratatui::{prelude::*, widgets::*};
+use unicode_width::UnicodeWidthStr;
+
+/// Draw `name: value` rows in a box in the top left corner, on top of the charts, like the
+/// internal counters of the debug overlay.
+pub fn draw_overlay(frame: &mut Frame, area: Rect, title: &str, rows: &[(&str, String)]) {
+    let name_width = rows.iter().map(|(name, _)| name.width()).max().unwrap_or(0);
+    let lines = rows
+        .iter()
+        .map(|(name, value)| {
+            Line::from(vec![
+                Span::styled(format!("{name:<name_width$} "), Style::new().dim()),
+                Span::raw(value.as_str()),
+            ])
+        })
+        .collect::<Vec<_>>();
+    let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4;
+    let height = lines.len() as u16 + 2;
+    let debug_area = Rect::new(
+        area.x + 1,
+        area.y + 1,
+        width.min(area.width.saturating_sub(2)),
+        height.min(area.height.saturating_sub(2)),
+    );
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::DarkGray))
+            .padding(Padding::horizontal(1)),
+    );
+    frame.render_widget(Clear, debug_area);
+    frame.render_widget(paragraph, debug_area);
+}
@@ -0,0 +1,139 @@
+use ratatui::{buffer::Buffer, layout::Rect, style::Color};
+
+/// Whether the terminal can be expected to show two colors in one cell.
+pub fn supports_color() -> bool {
+    std::env::var_os("NO_COLOR").is_none()
+        && std::env::var("TERM").map_or(true, |term| term != "dumb")
+}
+
+/// Draw two series into `area` with one sample of each per cell, the first in the left half
+/// and the second in the right half of the cell, so the history is twice as long as with one
+/// bar per series. Bars are scaled to the largest value shown and are whole cells high, with
+/// `min_bar` a value above zero is at least one.
+pub fn draw_dense(
+    buf: &mut Buffer,
+    area: Rect,
+    columns: [&[Option<f64>]; 2],
+    colors: [Color; 2],
+    min_bar: bool,
+) {
+    let max = columns
+        .iter()
+        .flat_map(|column| column.iter().flatten())
+        .fold(0.0, |max: f64, &value| max.max(value));
+    if max <= 0.0 || area.height == 0 {
+        return;
+    }
+    let rows = |value: Option<f64>| {
+        let value = value.unwrap_or_default().max(0.0);
+        let rows = (value / max * area.height as f64).round() as u16;
+        match min_bar && value > 0.0 {
+            true => rows.max(1),
+            false => rows,
+        }
+    };
+    for x in 0..area
+        .width
+        .min(columns[0].len().max(columns[1].len()) as u16)
+    {
+        let left = rows(columns[0].get(x as usize).copied().flatten());
+        let right = rows(columns[1].get(x as usize).copied().flatten());
+        for row in 0..area.height {
+            // rows count up from the bottom
+            let y = area.bottom() - 1 - row;
+            let cell = &mut buf[(area.x + x, y)];
+            match (row < left, row < right) {
+                (true, true) if colors[0] == colors[1] => {
+                    cell.set_symbol("█").set_fg(colors[0]);
+                }
+                (true, true) => {
+                    cell.set_symbol("▌").set_fg(colors[0]).set_bg(colors[1]);
+                }
+                (true, false) => {
+                    cell.set_symbol("▌").set_fg(colors[0]);
+                }
+                (false, true) => {
+                    cell.set_symbol("▐").set_fg(colors[1]);
+                }
+                (false, false) => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// The symbols of every row, top to bottom.
+    fn rows(buf: &Buffer) -> Vec<String> {
+        (0..buf.area.height)
+            .map(|y| (0..buf.area.width).map(|x| buf[(x, y)].symbol()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_two_series_per_cell() {
+        let area = Rect::new(0, 0, 4, 4);
+        let mut buf = Buffer::empty(area);
+        let left = [Some(4.0), Some(2.0), None, Some(0.0)];
+        let right = [Some(2.0), Some(4.0), Some(1.0), None];
+        draw_dense(
+            &mut buf,
+            area,
+            [&left, &right],
+            [Color::Green, Color::Red],
+            false,
+        );
+        assert_eq!(rows(&buf), ["▌▐  ", "▌▐  ", "▌▌  ", "▌▌▐ "]);
+        // both halves filled take the second color as background
+        assert_eq!(buf[(0, 3)].fg, Color::Green);
+        assert_eq!(buf[(0, 3)].bg, Color::Red);
+        // only the left half
+        assert_eq!(buf[(0, 0)].bg, Color::Reset);
+        assert_eq!(buf[(1, 0)].fg, Color::Red);
+    }
+
+    #[test]
+    fn test_same_colors_fill_the_cell() {
+        let area = Rect::new(0, 0, 1, 2);
+        let mut buf = Buffer::empty(area);
+        draw_dense(
+            &mut buf,
+            area,
+            [&[Some(1.0)], &[Some(1.0)]],
+            [Color::Cyan; 2],
+            false,
+        );
+        assert_eq!(rows(&buf), ["█", "█"]);
+    }
+
+    #[test]
+    fn test_nothing_to_draw() {
+        let area = Rect::new(0, 0, 2, 2);
+        let mut buf = Buffer::empty(area);
+        draw_dense(
+            &mut buf,
+            area,
+            [&[Some(0.0)], &[None]],
+            [Color::Green, Color::Red],
+            true,
+        );
+        assert_eq!(rows(&buf), ["  ", "  "]);
+    }
+
+    #[test]
+    fn test_min_bar() {
+        let area = Rect::new(0, 0, 2, 4);
+        let columns: [&[Option<f64>]; 2] = [&[Some(1000.0), Some(1.0)], &[Some(0.0), None]];
+        let colors = [Color::Green, Color::Red];
+        let mut buf = Buffer::empty(area);
+        draw_dense(&mut buf, area, columns, colors, false);
+        assert_eq!(rows(&buf), ["▌ ", "▌ ", "▌ ", "▌ "]);
+        let mut buf = Buffer::empty(area);
+        draw_dense(&mut buf, area, columns, colors, true);
+        assert_eq!(rows(&buf), ["▌ ", "▌ ", "▌ ", "▌▌"]);
+    }
+}
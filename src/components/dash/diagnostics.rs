@@ -0,0 +1,121 @@
+use unicode_width::UnicodeWidthStr;
+
+/// Lines an extractor gets to match something before it is reported.
+pub const NEVER_MATCHED_LINES: usize = 50;
+/// Width of the sample line quoted when an extractor never matches.
+const SAMPLE_WIDTH: usize = 60;
+
+/// How many lines every extractor saw and matched, shown in the debug overlay.
+#[derive(Debug, Clone, Default)]
+pub struct MatchCounts {
+    pub extractors: Vec<ExtractorCount>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExtractorCount {
+    pub name: String,
+    pub seen: usize,
+    pub matched: usize,
+}
+
+impl MatchCounts {
+    pub fn new(names: Vec<String>) -> Self {
+        let extractors = names
+            .into_iter()
+            .map(|name| ExtractorCount {
+                name,
+                ..Default::default()
+            })
+            .collect();
+        Self { extractors }
+    }
+
+    /// Count a line for every extractor, `matched` tells which of them matched it. Returns the
+    /// extractors that just saw their [`NEVER_MATCHED_LINES`]th line without matching any, so
+    /// every extractor is reported once.
+    pub fn record(&mut self, matched: impl Fn(usize) -> bool) -> Vec<String> {
+        let mut never_matched = vec![];
+        for (i, extractor) in self.extractors.iter_mut().enumerate() {
+            extractor.seen += 1;
+            if matched(i) {
+                extractor.matched += 1;
+            } else if extractor.matched == 0 && extractor.seen == NEVER_MATCHED_LINES {
+                never_matched.push(extractor.name.clone());
+            }
+        }
+        never_matched
+    }
+}
+
+/// Explain that `extractor` never matched, quoting `line` as an example of the input.
+pub fn never_matched_message(extractor: &str, line: &str) -> String {
+    let line = strip_ansi_escapes::strip_str(line);
+    format!(
+        "{extractor} matched none of the first {NEVER_MATCHED_LINES} lines, e.g. \"{}\"",
+        truncate(line.trim(), SAMPLE_WIDTH)
+    )
+}
+
+/// Cut `text` to `width` columns, marking the cut with `…`.
+pub fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let mut truncated = String::new();
+    for c in text.chars() {
+        if truncated.width() + 1 >= width {
+            break;
+        }
+        truncated.push(c);
+    }
+    truncated + "…"
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_reports_once_after_threshold() {
+        let mut counts = MatchCounts::new(vec!["unit ms".to_string(), "unit %".to_string()]);
+        let mut reported = vec![];
+        for _ in 0..NEVER_MATCHED_LINES * 2 {
+            // only the first extractor ever matches
+            reported.extend(counts.record(|i| i == 0));
+        }
+        assert_eq!(reported, ["unit %"]);
+        assert_eq!(counts.extractors[0].matched, NEVER_MATCHED_LINES * 2);
+        assert_eq!(counts.extractors[1].seen, NEVER_MATCHED_LINES * 2);
+    }
+
+    #[test]
+    fn test_late_match_is_not_reported() {
+        let mut counts = MatchCounts::new(vec!["column 3".to_string()]);
+        assert!(counts.record(|_| true).is_empty());
+        for _ in 0..NEVER_MATCHED_LINES {
+            assert!(counts.record(|_| false).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_message_strips_escapes() {
+        let message = never_matched_message("unit ms", "\x1b[32mok\x1b[0m took 12 s  ");
+        assert_eq!(
+            message,
+            "unit ms matched none of the first 50 lines, e.g. \"ok took 12 s\""
+        );
+        let long = "x".repeat(100);
+        assert!(never_matched_message("numbers", &long).ends_with("x…\""));
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("a long line of input", 10), "a long li…");
+    }
+}
@@ -0,0 +1,169 @@
+//! `--exec`: a command run through the shell once per `--interval`, like `watch`, its output
+//! charted the way lines of stdin are. A run still going when the next one is due makes that
+//! one skip instead of queueing up behind it.
+
+use std::{process::Stdio, sync::atomic::Ordering};
+
+use tokio::{process::Command, task, task::JoinHandle};
+use tracing::{debug, warn};
+
+use super::reader::{ReaderState, SourceReader};
+use crate::{action::Action, cli, text::sanitize};
+
+impl SourceReader {
+    /// Run the `--exec` command every interval until the dashboard stops, killing the run that
+    /// is still going then.
+    pub(super) async fn run_exec(&self, command: &str, reader: ReaderState) {
+        let mut interval = self.model.clock.interval(self.base_interval());
+        // handed to every run and back, runs never overlap
+        let mut reader = Some(reader);
+        let mut running: Option<JoinHandle<ReaderState>> = None;
+        while !self.stop_signal.load(Ordering::Relaxed) {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = self.cancellation_token.cancelled() => break,
+            }
+            if let Some(run) = running.take() {
+                if !run.is_finished() {
+                    debug!("`{command}` is still running, skipping a run");
+                    running = Some(run);
+                    continue;
+                }
+                reader = run.await.ok();
+            }
+            let state = reader.take().unwrap_or_else(|| self.new_reader());
+            running = Some(task::spawn(
+                self.clone().exec_once(command.to_string(), state),
+            ));
+        }
+        if let Some(run) = running {
+            // the child is killed as its run is dropped
+            run.abort();
+        }
+    }
+
+    /// Run the command once and chart the lines it printed, whatever its exit status. A
+    /// command the shell can't be started for and a failing one are both a toast.
+    async fn exec_once(self, command: String, mut reader: ReaderState) -> ReaderState {
+        let output = shell(&command)
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .output()
+            .await;
+        let output = match output {
+            Ok(output) => output,
+            Err(err) => {
+                self.report_failure(format!("Failed to run `{command}`: {err}"));
+                return reader;
+            }
+        };
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let complaint = stderr
+                .lines()
+                .map(sanitize)
+                .rfind(|line| !line.trim().is_empty());
+            let message = match complaint {
+                Some(complaint) => format!("`{command}` failed ({}): {complaint}", output.status),
+                None => format!("`{command}` failed ({})", output.status),
+            };
+            warn!("{message}");
+            if let Some(tx) = &self.command_tx {
+                let _ = tx.send(Action::Toast(message));
+            }
+        }
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            self.ingest(line, &mut reader);
+        }
+        if self.format != cli::Format::Number && self.format != cli::Format::Bool {
+            // a run is an update interval of its own
+            self.flush_pending(&mut reader);
+            self.announce_updates(&mut reader);
+        }
+        reader
+    }
+}
+
+/// `command` as a line for the shell, so it can have pipes like `df / | tail -1`.
+fn shell(command: &str) -> Command {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell.arg(command);
+    shell
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::super::{
+        model::ChartModel,
+        test_util::{new_source, parse, wait_for},
+    };
+    use super::*;
+
+    fn exec_source(command: &str, interval: &str) -> SourceReader {
+        new_source(&["--exec", command, "--interval", interval])
+    }
+
+    #[tokio::test]
+    async fn test_exec() {
+        let mut source = exec_source("echo 42ms; echo 'oops' >&2; exit 3", "10");
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        source.command_tx = Some(tx);
+        let task = tokio::spawn(source.clone().run());
+        wait_for(&source, |state| {
+            state.first().is_some_and(|chart| chart.stored >= 2)
+        })
+        .await;
+        source.stop();
+        task.await.unwrap();
+        let state = source.model.state.read().unwrap();
+        assert_eq!(state[0].samples()[..2], [42.0, 42.0]);
+        assert_eq!(state[0].unit, "ms");
+        assert_eq!(state[0].interval, Duration::from_millis(10));
+        let toast = std::iter::from_fn(|| rx.try_recv().ok())
+            .find(|action| matches!(action, Action::Toast(_)));
+        assert_eq!(
+            toast,
+            Some(Action::Toast(
+                "`echo 42ms; echo 'oops' >&2; exit 3` failed (exit status: 3): oops".to_string()
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_slow_runs_are_skipped() {
+        let path = std::env::temp_dir().join(format!("ttydash-exec-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let command = format!("echo run >> {}; sleep 0.2; echo 1", path.display());
+        let source = exec_source(&command, "10");
+        let task = tokio::spawn(source.clone().run());
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        source.cancellation_token.cancel();
+        task.await.unwrap();
+        // 50 intervals passed, the runs didn't pile up behind each other
+        let runs = std::fs::read_to_string(&path).unwrap().lines().count();
+        assert!((2..=4).contains(&runs), "{runs} runs");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_exec_flags() {
+        assert!(parse(&["--interval", "100"]).is_err());
+        assert!(parse(&["--exec", "date +%S", "--file", "a.log"]).is_err());
+        let args = parse(&["--exec", "date +%S"]).unwrap();
+        let source = SourceReader::new(&args, ChartModel::default()).unwrap();
+        assert_eq!(source.base_interval(), Duration::from_secs(1));
+        assert_eq!(source.input_name(), "date +%S");
+    }
+}
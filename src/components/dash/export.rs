@@ -0,0 +1,236 @@
+use std::{
+    fs::{File, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use color_eyre::Result;
+use tracing::warn;
+
+use super::{chart_title, cli, summarize, DashView};
+use crate::{action::Action, clock::unix_seconds, summary::Export};
+
+impl DashView {
+    /// Write the history of the current chart to a file named after its title, `E`.
+    pub(super) fn export_chart(&self) {
+        let Some(i) = self.current_chart() else {
+            return;
+        };
+        let message = match self.write_export(i) {
+            Ok(path) => format!("Exported the chart to {}", path.display()),
+            Err(err) => {
+                warn!("Failed to export chart {}: {err}", i + 1);
+                format!("Failed to export the chart: {err}")
+            }
+        };
+        if let Some(tx) = &self.command_tx {
+            let _ = tx.send(Action::Toast(message));
+        }
+    }
+
+    /// Write the summary of every chart with all its samples to `--export-dir`, on `snapshot`
+    /// from `--control-fifo`.
+    pub(super) fn snapshot(&self) {
+        let message = match self.write_snapshot() {
+            Ok(path) => format!("Wrote a snapshot to {}", path.display()),
+            Err(err) => {
+                warn!("Failed to write a snapshot: {err}");
+                format!("Failed to write a snapshot: {err}")
+            }
+        };
+        if let Some(tx) = &self.command_tx {
+            let _ = tx.send(Action::Toast(message));
+        }
+    }
+
+    fn write_snapshot(&self) -> Result<PathBuf> {
+        let state = self.model.state.read().unwrap();
+        let export = Export {
+            exported_at: unix_time(self.wall_now()),
+            ttydash: env!("CARGO_PKG_VERSION").to_string(),
+            summary: summarize(
+                &state,
+                0..state.len(),
+                &self.titles,
+                &self.references,
+                &self.model,
+                Some(usize::MAX),
+            ),
+        };
+        drop(state);
+        let dir = self.export_dir.as_deref().unwrap_or(Path::new("."));
+        let (path, file) = create_export_file(dir, "snapshot", "json")?;
+        export.write_json(file)?;
+        Ok(path)
+    }
+
+    /// Write chart `i` in `--export-format` to `--export-dir`, returning the path written.
+    fn write_export(&self, i: usize) -> Result<PathBuf> {
+        let state = self.model.state.read().unwrap();
+        let title = chart_title(&self.titles, &state, i);
+        let export = Export {
+            exported_at: unix_time(self.wall_now()),
+            ttydash: env!("CARGO_PKG_VERSION").to_string(),
+            summary: summarize(
+                &state,
+                [i],
+                &self.titles,
+                &self.references,
+                &self.model,
+                Some(usize::MAX),
+            ),
+        };
+        drop(state);
+        let extension = match self.export_format {
+            cli::ExportFormat::Csv => "csv",
+            cli::ExportFormat::Json => "json",
+        };
+        let dir = self.export_dir.as_deref().unwrap_or(Path::new("."));
+        let (path, file) = create_export_file(dir, &file_stem(&title), extension)?;
+        match self.export_format {
+            cli::ExportFormat::Csv => export.write_csv(file)?,
+            cli::ExportFormat::Json => export.write_json(file)?,
+        }
+        Ok(path)
+    }
+}
+
+/// `time` in seconds since the Unix epoch.
+fn unix_time(time: SystemTime) -> f64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+/// A file name for a chart titled `title`: its letters and digits, with a `_` for every run of
+/// anything else.
+fn file_stem(title: &str) -> String {
+    let mut stem = String::new();
+    for c in title.chars() {
+        if c.is_alphanumeric() || c == '-' {
+            stem.push(c);
+        } else if !stem.is_empty() && !stem.ends_with('_') {
+            stem.push('_');
+        }
+    }
+    let stem = stem.trim_end_matches('_');
+    if stem.is_empty() {
+        "chart".to_string()
+    } else {
+        stem.to_string()
+    }
+}
+
+/// Create `<stem>.<extension>` in `dir`, never overwriting a file: when the name is taken the
+/// current Unix time is added to it.
+fn create_export_file(dir: &Path, stem: &str, extension: &str) -> io::Result<(PathBuf, File)> {
+    let create = |name: String| -> io::Result<(PathBuf, File)> {
+        let path = dir.join(name);
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        Ok((path, file))
+    };
+    match create(format!("{stem}.{extension}")) {
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+            let now = unix_seconds(SystemTime::now());
+            create(format!("{stem}-{now}.{extension}"))
+        }
+        created => created,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use clap::Parser;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::cli::Cli;
+
+    #[test]
+    fn test_file_stem() {
+        assert_eq!(file_stem("p99 latency (ms)"), "p99_latency_ms");
+        assert_eq!(file_stem("web-1: /api/users"), "web-1_api_users");
+        assert_eq!(file_stem("../../etc/passwd"), "etc_passwd");
+        assert_eq!(file_stem("µs"), "µs");
+        assert_eq!(file_stem("%"), "chart");
+    }
+
+    #[test]
+    fn test_export_focused_chart() {
+        let dir = std::env::temp_dir().join(format!("ttydash-export-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let args = Cli::parse_from([
+            "ttydash",
+            "-t",
+            "cpu",
+            "-t",
+            "disk io",
+            "-u",
+            "%",
+            "-u",
+            "MB",
+            "--reference",
+            "80",
+            "--reference",
+            "200",
+            "--export-dir",
+            dir.to_str().unwrap(),
+        ]);
+        let mut dash = DashView::new(args).unwrap();
+        let mut reader = dash.reader.new_reader();
+        dash.reader.ingest_line("12% 150MB", &mut reader);
+        dash.reader.ingest_line("15% 180MB", &mut reader);
+        dash.view.focus = Some(1);
+
+        let path = dash.write_export(1).unwrap();
+        assert_eq!(path, dir.join("disk_io.csv"));
+        let csv = fs::read_to_string(&path).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[1..5],
+            [
+                "# title: disk io",
+                "# unit: MB",
+                "# reference: 200",
+                "# average: 165, min: 150, max: 180"
+            ]
+        );
+        assert_eq!(lines[5], "time,value");
+        assert!(lines[6].ends_with(",150") && lines[7].ends_with(",180"));
+        assert_eq!(lines.len(), 8);
+
+        // a second export doesn't overwrite the first
+        dash.export_format = cli::ExportFormat::Json;
+        assert_eq!(dash.write_export(1).unwrap(), dir.join("disk_io.json"));
+        let again = dash.write_export(1).unwrap();
+        assert!(again
+            .file_name()
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .starts_with("disk_io-"));
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&again).unwrap()).unwrap();
+        assert_eq!(json["charts"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            json["charts"][0]["values"],
+            serde_json::json!([150.0, 180.0])
+        );
+
+        // a snapshot has every chart
+        let path = dash.write_snapshot().unwrap();
+        assert_eq!(path, dir.join("snapshot.json"));
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["charts"].as_array().unwrap().len(), 2);
+        assert_eq!(json["charts"][0]["values"], serde_json::json!([12.0, 15.0]));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,395 @@
+//! Extractors take the values of a line apart for the `number` and `bool` formats. The keyed
+//! Graphite and StatsD formats are parsed in [`super::protocol`] instead, since their metrics
+//! carry a name, a kind and a timestamp besides the value.
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug},
+};
+
+use color_eyre::{eyre::eyre, Result};
+use regex::Regex;
+
+/// Finds the values of a line and the chart each of them goes to.
+pub trait ValueExtractor: Debug + Send + Sync {
+    /// The values in `line` as `(chart index, value)` pairs.
+    fn extract(&self, line: &str) -> Vec<(usize, f64)>;
+
+    /// The values in `line` like [`Self::extract`], and the charts whose part matched but
+    /// didn't parse as a finite number, for `--on-parse-error`.
+    fn extract_checked(&self, line: &str) -> (Vec<(usize, f64)>, Vec<usize>) {
+        (self.extract(line), vec![])
+    }
+
+    /// What the diagnostics call each part of the extractor, see [`super::diagnostics`].
+    fn names(&self) -> Vec<String>;
+
+    /// Whether part `part` of the extractor matched, given the values extracted from a line.
+    fn matched(&self, values: &[(usize, f64)], part: usize) -> bool {
+        values.iter().any(|&(i, _)| i == part)
+    }
+
+    /// How the value for chart `i` was taken, for `ttydash test`.
+    fn describe(&self, i: usize) -> String;
+
+    /// The unit glued to the only value of `line`, like `ms` in `42ms`, for a chart that has
+    /// no unit yet.
+    fn suffix(&self, _line: &str) -> Option<String> {
+        None
+    }
+}
+
+/// Build the extractor for the flags: units when given, otherwise whitespace separated columns.
+/// `bool_tokens` maps words to on/off for the on/off charts, which the reader turns the numbers
+/// of into on/off too.
+pub fn extractor(
+    units: &[String],
+    indices: Option<Vec<usize>>,
+    bool_tokens: Option<HashMap<String, bool>>,
+) -> Result<Box<dyn ValueExtractor>> {
+    if units.is_empty() {
+        Ok(Box::new(ColumnExtractor {
+            indices,
+            bool_tokens,
+        }))
+    } else {
+        Ok(Box::new(UnitExtractor::new(units)?))
+    }
+}
+
+/// Takes the number in front of every `--units` entry. A unit given more than once takes its
+/// successive matches on the line, in order.
+pub struct UnitExtractor {
+    units: Vec<(String, Regex)>,
+}
+
+impl UnitExtractor {
+    pub fn new(units: &[String]) -> Result<Self> {
+        let units = units
+            .iter()
+            .map(|unit| {
+                // a unit like `%` can't end on a word boundary
+                let boundary = if unit.ends_with(|c: char| c.is_alphanumeric() || c == '_') {
+                    r"\b"
+                } else {
+                    ""
+                };
+                let pattern =
+                    format!(r"(?i)\b(\d+(?:\.\d+)?(?:/\d+(?:\.\d+)?)*)\s*{unit}{boundary}");
+                Regex::new(&pattern)
+                    .map(|regex| (unit.clone(), regex))
+                    .map_err(|err| eyre!("Invalid unit {unit:?}: {err}"))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { units })
+    }
+}
+
+impl Debug for UnitExtractor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let units = self.units.iter().map(|(unit, _)| unit).collect::<Vec<_>>();
+        f.debug_struct("UnitExtractor")
+            .field("units", &units)
+            .finish()
+    }
+}
+
+impl ValueExtractor for UnitExtractor {
+    fn extract(&self, line: &str) -> Vec<(usize, f64)> {
+        self.extract_checked(line).0
+    }
+
+    fn extract_checked(&self, line: &str) -> (Vec<(usize, f64)>, Vec<usize>) {
+        let mut matches: HashMap<&str, (Vec<Option<f64>>, usize)> = HashMap::new();
+        let mut values = vec![];
+        let mut failed = vec![];
+        for (i, (unit, regex)) in self.units.iter().enumerate() {
+            let (unit_values, cursor) = matches
+                .entry(unit.as_str())
+                .or_insert_with(|| (unit_values(regex, line), 0));
+            match unit_values.get(*cursor) {
+                Some(Some(value)) => values.push((i, *value)),
+                Some(None) => failed.push(i),
+                None => {}
+            }
+            *cursor += 1;
+        }
+        (values, failed)
+    }
+
+    /// A unit given more than once is numbered.
+    fn names(&self) -> Vec<String> {
+        let units = self.units.iter().map(|(unit, _)| unit).collect::<Vec<_>>();
+        units
+            .iter()
+            .enumerate()
+            .map(|(i, unit)| {
+                if units.iter().filter(|&other| other == unit).count() > 1 {
+                    let occurrence = units[..=i].iter().filter(|&other| other == unit).count();
+                    format!("unit {unit} #{occurrence}")
+                } else {
+                    format!("unit {unit}")
+                }
+            })
+            .collect()
+    }
+
+    fn describe(&self, i: usize) -> String {
+        format!("unit {}", self.units[i].0)
+    }
+}
+
+/// Every value `regex` finds on the line, in order, `None` for a match that isn't a finite
+/// number. Values sharing the unit like `min/avg/max = 1.2/3.4/5.6 ms` count as one match each.
+fn unit_values(regex: &Regex, line: &str) -> Vec<Option<f64>> {
+    regex
+        .captures_iter(line)
+        .flat_map(|captures| {
+            captures[1]
+                .split('/')
+                .map(|value| value.parse::<f64>().ok().filter(|value| value.is_finite()))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Takes the whitespace separated tokens that are values, all of them or the `--indices` ones.
+#[derive(Debug)]
+pub struct ColumnExtractor {
+    indices: Option<Vec<usize>>,
+    /// Words meaning on or off, for `--format bool` and `format=bool`.
+    bool_tokens: Option<HashMap<String, bool>>,
+}
+
+impl ColumnExtractor {
+    /// The value and unit of a line that is nothing but a number with its unit glued to it,
+    /// which the whitespace split can't tell apart. Not for `--format bool`.
+    fn glued_value<'a>(&self, line: &'a str) -> Option<(f64, &'a str)> {
+        let mut tokens = line.split_whitespace();
+        match (tokens.next(), tokens.next(), &self.bool_tokens) {
+            (Some(token), None, None) => glued(token),
+            _ => None,
+        }
+    }
+
+    fn parse_value(&self, token: &str) -> Option<f64> {
+        let value = token.parse::<f64>();
+        match &self.bool_tokens {
+            None => value.ok(),
+            Some(tokens) => value
+                .ok()
+                .or_else(|| tokens.get(&token.to_lowercase()).map(|&on| on as u8 as f64)),
+        }
+    }
+}
+
+impl ValueExtractor for ColumnExtractor {
+    fn extract(&self, line: &str) -> Vec<(usize, f64)> {
+        let mut values: Vec<f64> = line
+            .split_whitespace()
+            .filter_map(|token| self.parse_value(token))
+            .collect();
+        if values.is_empty() {
+            values.extend(self.glued_value(line).map(|(value, _)| value));
+        }
+        match &self.indices {
+            // update only the specified indices
+            Some(indices) => indices
+                .iter()
+                .enumerate()
+                .filter_map(|(i, &index)| {
+                    let value = values.get(index.checked_sub(1)?)?;
+                    Some((i, *value))
+                })
+                .collect(),
+            None => values.into_iter().enumerate().collect(),
+        }
+    }
+
+    fn names(&self) -> Vec<String> {
+        match &self.indices {
+            Some(indices) => indices
+                .iter()
+                .map(|index| format!("column {index}"))
+                .collect(),
+            None => vec!["numbers".to_string()],
+        }
+    }
+
+    /// Without `--indices` every value counts, however many charts it makes.
+    fn matched(&self, values: &[(usize, f64)], part: usize) -> bool {
+        match self.indices {
+            Some(_) => values.iter().any(|&(i, _)| i == part),
+            None => !values.is_empty(),
+        }
+    }
+
+    fn describe(&self, i: usize) -> String {
+        match &self.indices {
+            Some(indices) => format!("column {}", indices[i]),
+            None => format!("column {}", i + 1),
+        }
+    }
+
+    fn suffix(&self, line: &str) -> Option<String> {
+        self.glued_value(line).map(|(_, unit)| unit.to_string())
+    }
+}
+
+/// A token like `42ms`, `-3.5C` or `99%`: a number with its unit glued to it, taken apart.
+fn glued(token: &str) -> Option<(f64, &str)> {
+    let split = token.find(|c: char| c.is_alphabetic() || c == '%' || c == '°')?;
+    let (number, unit) = token.split_at(split);
+    // `1e3ms` or `0x1f` are no number with a unit
+    if unit.contains(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let value = number
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())?;
+    Some((value, unit))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn units(units: &[&str]) -> UnitExtractor {
+        UnitExtractor::new(
+            &units
+                .iter()
+                .map(|unit| unit.to_string())
+                .collect::<Vec<_>>(),
+        )
+        .unwrap()
+    }
+
+    fn columns(indices: Option<Vec<usize>>) -> ColumnExtractor {
+        ColumnExtractor {
+            indices,
+            bool_tokens: None,
+        }
+    }
+
+    #[test]
+    fn test_ping_reply() {
+        let ms = units(&["ms"]);
+        assert_eq!(
+            ms.extract("64 bytes from 1.1.1.1: icmp_seq=1 ttl=57 time=12.3 ms"),
+            [(0, 12.3)]
+        );
+        assert_eq!(ms.extract("Request timeout for icmp_seq 4"), []);
+    }
+
+    #[test]
+    fn test_repeated_units_take_successive_matches() {
+        let ms = units(&["ms", "ms"]);
+        assert_eq!(
+            ms.extract("connect 12ms first byte 30 ms"),
+            [(0, 12.0), (1, 30.0)]
+        );
+        // a missing later match leaves that chart alone
+        assert_eq!(ms.extract("connect 12ms"), [(0, 12.0)]);
+
+        let ping = units(&["ms", "ms", "ms", "ms"]);
+        assert_eq!(
+            ping.extract("rtt min/avg/max/mdev = 0.045/0.060/0.077/0.012 ms"),
+            [(0, 0.045), (1, 0.06), (2, 0.077), (3, 0.012)]
+        );
+
+        let mixed = units(&["ms", "%", "ms"]);
+        assert_eq!(
+            mixed.extract("1 ms 50% 2 ms"),
+            [(0, 1.0), (1, 50.0), (2, 2.0)]
+        );
+        assert_eq!(mixed.names(), ["unit ms #1", "unit %", "unit ms #2"]);
+    }
+
+    #[test]
+    fn test_unparsable_matches() {
+        // a match too long for an f64 overflows to infinity
+        let huge = format!("{} ms", "9".repeat(400));
+        let ms = units(&["ms", "ms"]);
+        assert_eq!(
+            ms.extract_checked(&format!("{huge} 12 ms")),
+            (vec![(1, 12.0)], vec![0])
+        );
+        assert_eq!(ms.extract(&huge), []);
+        // the units' own patterns never capture nothing, a regex that can does
+        let empty = Regex::new(r"(\d*)\s*ms").unwrap();
+        assert_eq!(unit_values(&empty, "took ms, 3 ms"), [None, Some(3.0)]);
+    }
+
+    #[test]
+    fn test_unit_boundaries() {
+        // `ms` must not match inside `msg`, `%` has no word boundary after it
+        let extractor = units(&["ms", "%"]);
+        assert_eq!(extractor.extract("5 msgs at 3% cpu"), [(1, 3.0)]);
+        assert_eq!(extractor.extract("CPU 12.5%, 40MS"), [(0, 40.0), (1, 12.5)]);
+    }
+
+    #[test]
+    fn test_invalid_unit() {
+        assert!(UnitExtractor::new(&["(".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_vmstat_columns() {
+        let line =
+            " 1  0      0 6353364 181292 4218128    0    0     7    21  105  190  3  1 96  0  0";
+        let picked = columns(Some(vec![13, 15]));
+        assert_eq!(picked.extract(line), [(0, 3.0), (1, 96.0)]);
+        assert_eq!(picked.names(), ["column 13", "column 15"]);
+        // a header line has no values
+        assert_eq!(picked.extract("r  b   swpd   free   buff  cache"), []);
+        assert!(!picked.matched(&[(0, 3.0)], 1));
+    }
+
+    #[test]
+    fn test_all_columns() {
+        let all = columns(None);
+        assert_eq!(
+            all.extract("load 0.52 0.58 0.59"),
+            [(0, 0.52), (1, 0.58), (2, 0.59)]
+        );
+        assert_eq!(all.describe(2), "column 3");
+        assert!(all.matched(&[(2, 0.59)], 0));
+    }
+
+    #[test]
+    fn test_glued_units() {
+        let all = columns(None);
+        for (line, value, unit) in [
+            ("42ms", 42.0, Some("ms")),
+            ("-3.5C", -3.5, Some("C")),
+            ("99%", 99.0, Some("%")),
+            (" 1.2GiB\n", 1.2, Some("GiB")),
+            ("42", 42.0, None),
+        ] {
+            assert_eq!(all.extract(line), [(0, value)], "{line}");
+            assert_eq!(all.suffix(line).as_deref(), unit, "{line}");
+        }
+        // only a line of one such token, and only letters after the number
+        for line in ["took 42ms", "42ms 17ms", "1e3ms", "0x1f", "ms", "12.5.1GB"] {
+            assert_eq!(all.extract(line), [], "{line}");
+            assert_eq!(all.suffix(line), None, "{line}");
+        }
+        assert_eq!(columns(Some(vec![1])).extract("42ms"), [(0, 42.0)]);
+    }
+
+    #[test]
+    fn test_bool_tokens() {
+        let tokens = HashMap::from([("up".to_string(), true), ("down".to_string(), false)]);
+        let extractor = ColumnExtractor {
+            indices: None,
+            bool_tokens: Some(tokens),
+        };
+        assert_eq!(
+            extractor.extract("api UP db down cache 0 queue 7"),
+            [(0, 1.0), (1, 0.0), (2, 0.0), (3, 7.0)]
+        );
+    }
+}
@@ -0,0 +1,504 @@
+//! `--file`: every file, or every file a pattern like `logs/*.log` matches, is followed like
+//! `tail -F` in its own task and feeds its own chart, titled by the file name.
+
+use std::{
+    collections::HashMap,
+    fs::{self, File, Metadata},
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::atomic::Ordering,
+    time::Duration,
+};
+
+use tokio::task;
+use tracing::{debug, info};
+
+use super::{
+    inspect::Connection,
+    reader::{read_backfill, ReaderState, SourceReader},
+};
+use crate::backfill::Backfill;
+
+/// How often a followed file is checked for new lines at most, when `--update-frequency` is
+/// shorter.
+const MIN_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// How often the patterns are expanded again to find files that appeared since.
+const GLOB_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A file read as it grows, reopened when it is rotated and read from the start again when it
+/// is truncated.
+#[derive(Debug)]
+struct FileFollower {
+    path: PathBuf,
+    file: Option<File>,
+    /// Device and inode of the open file, to notice the path now names another one.
+    id: Option<(u64, u64)>,
+    position: u64,
+    /// Where to start reading when the file is opened next: its end at startup, like `tail -f`,
+    /// and the start of any file showing up under the path later.
+    start: u64,
+    /// The start of a line whose end wasn't written yet.
+    partial: Vec<u8>,
+}
+
+impl FileFollower {
+    /// Follow `path` from its current end, or from the start when it doesn't exist yet.
+    fn new(path: PathBuf, from_end: bool) -> Self {
+        let start = match fs::metadata(&path) {
+            Ok(metadata) if from_end => metadata.len(),
+            _ => 0,
+        };
+        Self {
+            path,
+            file: None,
+            id: None,
+            position: 0,
+            start,
+            partial: Vec::new(),
+        }
+    }
+
+    /// The lines written since the last poll. A deleted file is a `NotFound` error until a file
+    /// shows up under its path again, which is then read from the start.
+    fn poll(&mut self) -> io::Result<Vec<String>> {
+        let metadata = match fs::metadata(&self.path) {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                if let Some(mut file) = self.file.take() {
+                    // what was written before it went away still counts
+                    let lines = self.read_lines(&mut file)?;
+                    self.id = None;
+                    self.partial.clear();
+                    if !lines.is_empty() {
+                        return Ok(lines);
+                    }
+                }
+                self.start = 0;
+                return Err(err);
+            }
+        };
+        let mut lines = vec![];
+        if self.file.is_some() && file_id(&metadata) != self.id {
+            debug!("{} was rotated, reopening it", self.path.display());
+            if let Some(mut file) = self.file.take() {
+                lines = self.read_lines(&mut file)?;
+            }
+            self.partial.clear();
+        }
+        let mut file = match self.file.take() {
+            Some(file) => file,
+            None => {
+                let file = File::open(&self.path)?;
+                self.id = file_id(&metadata);
+                self.position = std::mem::take(&mut self.start);
+                file
+            }
+        };
+        if metadata.len() < self.position {
+            debug!(
+                "{} was truncated, reading it from the start",
+                self.path.display()
+            );
+            self.position = 0;
+            self.partial.clear();
+        }
+        lines.extend(self.read_lines(&mut file)?);
+        self.file = Some(file);
+        Ok(lines)
+    }
+
+    /// The complete lines of `file` past the position, the rest is kept for the next read.
+    fn read_lines(&mut self, file: &mut File) -> io::Result<Vec<String>> {
+        file.seek(SeekFrom::Start(self.position))?;
+        let mut buffer = vec![];
+        self.position += file.read_to_end(&mut buffer)? as u64;
+        self.partial.extend_from_slice(&buffer);
+        let Some(end) = self.partial.iter().rposition(|&byte| byte == b'\n') else {
+            return Ok(vec![]);
+        };
+        let rest = self.partial.split_off(end + 1);
+        let complete = std::mem::replace(&mut self.partial, rest);
+        Ok(String::from_utf8_lossy(&complete)
+            .lines()
+            .map(str::to_string)
+            .collect())
+    }
+}
+
+#[cfg(unix)]
+fn file_id(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some((metadata.dev(), metadata.ino()))
+}
+
+/// Without inodes a rotation only shows as the file getting shorter.
+#[cfg(not(unix))]
+fn file_id(_metadata: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// The files `pattern` names, sorted. `*` and `?` are only expanded in the file name, a path
+/// without them is taken as is, whether it exists yet or not.
+fn expand(pattern: &str) -> Vec<PathBuf> {
+    let path = Path::new(pattern);
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return vec![path.to_path_buf()];
+    };
+    if !name.contains(['*', '?']) {
+        return vec![path.to_path_buf()];
+    }
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut paths = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+        .filter(|entry| wildcard_match(name, &entry.file_name().to_string_lossy()))
+        .map(|entry| dir.join(entry.file_name()))
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+}
+
+/// Whether `name` matches `pattern`, where `*` stands for any run of characters and `?` for one.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+    let (mut p, mut n) = (0, 0);
+    // where the last `*` was and the name position it matched up to
+    let mut star = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// The title of the chart of `path`.
+fn file_title(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .into_owned()
+}
+
+impl SourceReader {
+    /// Follow the `--file` files, each in its own task, and look for new files matching the
+    /// patterns every [`GLOB_INTERVAL`] until the dashboard stops.
+    pub(super) async fn follow_files(&self) {
+        let mut charts = HashMap::new();
+        let mut tasks = vec![];
+        // the files there at startup are followed from their end, later ones from the start
+        let mut from_end = true;
+        while !self.stop_signal.load(Ordering::Relaxed) {
+            for path in self.files.iter().flat_map(|pattern| expand(pattern)) {
+                if charts.contains_key(&path) {
+                    continue;
+                }
+                let i = self.add_file_chart(&path);
+                info!("Following {} in chart {}", path.display(), i + 1);
+                charts.insert(path.clone(), i);
+                let follower = FileFollower::new(path, from_end);
+                let backfill = self.backfill.filter(|_| from_end);
+                tasks.push(task::spawn(self.clone().follow_file(i, follower, backfill)));
+            }
+            from_end = false;
+            tokio::select! {
+                _ = self.model.clock.sleep(GLOB_INTERVAL) => {}
+                _ = self.cancellation_token.cancelled() => break,
+            }
+        }
+        for task in tasks {
+            let _ = task.await;
+        }
+    }
+
+    /// How long a followed file is left alone between checks for new lines: an update interval,
+    /// the lines written in between are all charted at once.
+    fn poll_interval(&self) -> Duration {
+        self.base_interval().max(MIN_POLL_INTERVAL)
+    }
+
+    /// The chart for the file at `path`.
+    fn add_file_chart(&self, path: &Path) -> usize {
+        self.add_named_chart(file_title(path))
+    }
+
+    /// Chart the end of the file of chart `i` at once, and follow it from where those lines end.
+    fn backfill_file(
+        &self,
+        i: usize,
+        backfill: Backfill,
+        follower: &mut FileFollower,
+        reader: &mut ReaderState,
+    ) {
+        let lines = File::open(&follower.path).and_then(|mut file| {
+            let lines = read_backfill(backfill, &mut file)?;
+            follower.start = file.stream_position()?;
+            Ok(lines)
+        });
+        let lines = match lines {
+            Ok(lines) => lines,
+            Err(err) => {
+                debug!(
+                    "Nothing to backfill from {}: {err}",
+                    follower.path.display()
+                );
+                return;
+            }
+        };
+        let now = self.instant();
+        for (line, time) in self.spread_over_past(&lines, now) {
+            self.store_source_line(i, line, reader, time);
+        }
+        let mut state = self.model.state.write().unwrap();
+        if let Some(chart) = state.get_mut(i).filter(|chart| chart.length > 0) {
+            chart.live_samples = Some(0);
+        }
+        drop(state);
+        self.announce_updates(reader);
+        debug!(
+            "Backfilled {} lines of {}",
+            lines.len(),
+            follower.path.display()
+        );
+    }
+
+    /// Chart the lines appended to the file of chart `i`, after the end `backfill` asks for.
+    /// While the file is gone or unreadable the chart shows why and goes stale, it comes back
+    /// when the file does.
+    async fn follow_file(self, i: usize, mut follower: FileFollower, backfill: Option<Backfill>) {
+        let mut reader = ReaderState::default();
+        let title = file_title(&follower.path);
+        self.model.update_source(i, |status| {
+            status.spec = follower.path.display().to_string()
+        });
+        if let Some(backfill) = backfill {
+            self.backfill_file(i, backfill, &mut follower, &mut reader);
+        }
+        while !self.stop_signal.load(Ordering::Relaxed) {
+            match follower.poll() {
+                Ok(lines) => {
+                    let recovered = self.model.update_source(i, |status| {
+                        let failing = status.error.take().is_some();
+                        status.connection = Connection::Live;
+                        for line in &lines {
+                            status.received(line);
+                        }
+                        failing
+                    });
+                    if recovered {
+                        self.model.state.write().unwrap()[i].source_gone = false;
+                    }
+                    for line in lines {
+                        self.ingest_source_line(i, &line, &mut reader);
+                    }
+                }
+                Err(err) => {
+                    let (message, connection) = match err.kind() {
+                        io::ErrorKind::NotFound => {
+                            (format!("{title}: no such file"), Connection::Missing)
+                        }
+                        _ => (format!("{title}: {err}"), Connection::Unreadable),
+                    };
+                    let previous = self.model.update_source(i, |status| {
+                        status.connection = connection;
+                        status.error.replace(message.clone())
+                    });
+                    if previous.as_ref() != Some(&message) {
+                        debug!("{message}");
+                    }
+                    self.model.state.write().unwrap()[i].source_gone = true;
+                }
+            }
+            tokio::select! {
+                _ = self.model.clock.sleep(self.poll_interval()) => {}
+                _ = self.cancellation_token.cancelled() => return,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use pretty_assertions::assert_eq;
+
+    use super::super::{
+        model::ChartModel,
+        test_util::{new_source, wait_for},
+    };
+    use super::*;
+
+    /// An empty directory of its own for every test.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ttydash-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn append(path: &Path, text: &str) {
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn test_follow_from_end() {
+        let dir = temp_dir("follow");
+        let path = dir.join("a.log");
+        append(&path, "old 1\n");
+        let mut follower = FileFollower::new(path.clone(), true);
+        assert_eq!(follower.poll().unwrap(), Vec::<String>::new());
+        append(&path, "new 2\nnew ");
+        assert_eq!(follower.poll().unwrap(), ["new 2"]);
+        append(&path, "3\n");
+        assert_eq!(follower.poll().unwrap(), ["new 3"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rotation() {
+        let dir = temp_dir("rotation");
+        let path = dir.join("a.log");
+        append(&path, "");
+        let mut follower = FileFollower::new(path.clone(), true);
+        append(&path, "1\n");
+        assert_eq!(follower.poll().unwrap(), ["1"]);
+        // the logger still writes a last line to the rotated file before it moves on
+        fs::rename(&path, dir.join("a.log.1")).unwrap();
+        append(&dir.join("a.log.1"), "2\n");
+        append(&path, "3\n");
+        assert_eq!(follower.poll().unwrap(), ["2", "3"]);
+        append(&path, "4\n");
+        assert_eq!(follower.poll().unwrap(), ["4"]);
+
+        // copytruncate rotation empties the file in place
+        fs::write(&path, "").unwrap();
+        assert_eq!(follower.poll().unwrap(), Vec::<String>::new());
+        append(&path, "5\n");
+        assert_eq!(follower.poll().unwrap(), ["5"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_deleted_and_recreated() {
+        let dir = temp_dir("deleted");
+        let path = dir.join("a.log");
+        append(&path, "");
+        let mut follower = FileFollower::new(path.clone(), true);
+        append(&path, "1\n");
+        assert_eq!(follower.poll().unwrap(), ["1"]);
+        fs::remove_file(&path).unwrap();
+        let err = follower.poll().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        // a file that shows up again is new, all of it counts
+        append(&path, "2\n3\n");
+        assert_eq!(follower.poll().unwrap(), ["2", "3"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_late_file() {
+        let dir = temp_dir("late");
+        let path = dir.join("b.log");
+        let mut follower = FileFollower::new(path.clone(), true);
+        assert!(follower.poll().is_err());
+        append(&path, "1\n");
+        assert_eq!(follower.poll().unwrap(), ["1"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_expand() {
+        let dir = temp_dir("expand");
+        let pattern = dir.join("*.log").to_string_lossy().into_owned();
+        assert_eq!(expand(&pattern), Vec::<PathBuf>::new());
+        append(&dir.join("b.log"), "");
+        append(&dir.join("a.log"), "");
+        append(&dir.join("a.txt"), "");
+        assert_eq!(expand(&pattern), [dir.join("a.log"), dir.join("b.log")]);
+        // a plain path is followed even before it exists
+        let missing = dir.join("c.log").to_string_lossy().into_owned();
+        assert_eq!(expand(&missing), [dir.join("c.log")]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*.log", "a.log"));
+        assert!(wildcard_match("*.log", ".log"));
+        assert!(!wildcard_match("*.log", "a.log.1"));
+        assert!(wildcard_match("app-?.log", "app-1.log"));
+        assert!(!wildcard_match("app-?.log", "app-10.log"));
+        assert!(wildcard_match("a*b*c", "aXbYbZc"));
+        assert!(!wildcard_match("a*b*c", "aXbYbZ"));
+        assert!(wildcard_match("*", ""));
+    }
+
+    #[test]
+    fn test_file_charts() {
+        use clap::Parser;
+
+        let args = crate::cli::Cli::parse_from(["ttydash", "--follow", "a.log", "-t", "api"]);
+        assert_eq!(args.files, ["a.log"]);
+        let source = SourceReader::new(&args, ChartModel::default()).unwrap();
+        assert_eq!(source.poll_interval(), Duration::from_secs(1));
+        assert_eq!(source.add_file_chart(Path::new("logs/a.log")), 0);
+        assert_eq!(source.add_file_chart(Path::new("logs/b.log")), 1);
+        let state = source.model.state.read().unwrap();
+        assert_eq!(super::super::chart_title(&source.titles, &state, 0), "api");
+        assert_eq!(
+            super::super::chart_title(&source.titles, &state, 1),
+            "b.log"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_backfill() {
+        let dir = temp_dir("backfill");
+        let path = dir.join("a.log");
+        append(&path, "1\n2\n\n3\n4\n5\n");
+        let source = new_source(&["--file", path.to_str().unwrap(), "--backfill", "3"]);
+        let task = tokio::spawn(source.clone().run());
+        wait_for(&source, |state| {
+            state.first().is_some_and(|chart| chart.stored == 3)
+        })
+        .await;
+        // the file is followed from where the backfilled lines end
+        append(&path, "6\n");
+        wait_for(&source, |state| state[0].stored == 4).await;
+        source.stop();
+        task.await.unwrap();
+        let state = source.model.state.read().unwrap();
+        assert_eq!(state[0].samples()[..4], [3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(state[0].live_samples, Some(1));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,179 @@
+use std::time::{Duration, Instant};
+
+/// How often the input rate is measured and the sampling factor reconsidered.
+const WINDOW: Duration = Duration::from_millis(500);
+/// Never skip more than this many lines for every one parsed.
+const MAX_FACTOR: u32 = 1024;
+/// Sampling is relaxed only once the rate fits the smaller factor with this much room, so a rate
+/// hovering around the limit doesn't flap between two factors.
+const HYSTERESIS: f64 = 0.8;
+
+/// Bounds the CPU spent on a flood of input by parsing only every k-th line once more than
+/// `limit` lines per second arrive. k is a power of two and goes back to 1 when the rate drops.
+#[derive(Debug, Clone)]
+pub struct InputGuard {
+    limit: f64,
+    factor: u32,
+    window_start: Instant,
+    window_lines: usize,
+    seen: u64,
+}
+
+impl InputGuard {
+    pub fn new(limit: usize, now: Instant) -> Self {
+        Self {
+            limit: limit as f64,
+            factor: 1,
+            window_start: now,
+            window_lines: 0,
+            seen: 0,
+        }
+    }
+
+    /// Parse only one line in this many.
+    pub fn factor(&self) -> u32 {
+        self.factor
+    }
+
+    /// Count a line read at `now`. Returns whether it should be parsed, and the new sampling
+    /// factor when it changed.
+    pub fn admit(&mut self, now: Instant) -> (bool, Option<u32>) {
+        self.seen += 1;
+        self.window_lines += 1;
+        let elapsed = now.duration_since(self.window_start);
+        let mut changed = None;
+        if elapsed >= WINDOW {
+            let rate = self.window_lines as f64 / elapsed.as_secs_f64();
+            let factor = sampling_factor(rate, self.limit, self.factor);
+            if factor != self.factor {
+                self.factor = factor;
+                changed = Some(factor);
+            }
+            self.window_start = now;
+            self.window_lines = 0;
+        }
+        (self.seen.is_multiple_of(self.factor as u64), changed)
+    }
+}
+
+/// Lets through at most `rate` lines per second on average, with bursts of up to a second's
+/// worth, like a token bucket refilled as time passes.
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    rate: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(rate: f64, now: Instant) -> Self {
+        Self {
+            rate,
+            tokens: rate.max(1.0),
+            last: now,
+        }
+    }
+
+    /// Take a token for a line read at `now`. Returns whether there was one.
+    pub fn admit(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate.max(1.0));
+        self.last = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// The sampling factor for `rate` lines per second when at most `limit` should be parsed,
+/// given the `current` factor.
+pub fn sampling_factor(rate: f64, limit: f64, current: u32) -> u32 {
+    let factor_for = |limit: f64| {
+        ((rate / limit).ceil().max(1.0) as u32)
+            .next_power_of_two()
+            .min(MAX_FACTOR)
+    };
+    let needed = factor_for(limit);
+    if needed >= current {
+        needed
+    } else {
+        factor_for(limit * HYSTERESIS).min(current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_sampling_factor() {
+        assert_eq!(sampling_factor(10_000.0, 50_000.0, 1), 1);
+        assert_eq!(sampling_factor(50_000.0, 50_000.0, 1), 1);
+        assert_eq!(sampling_factor(60_000.0, 50_000.0, 1), 2);
+        assert_eq!(sampling_factor(1_500_000.0, 50_000.0, 1), 32);
+        assert_eq!(sampling_factor(1e12, 50_000.0, 1), MAX_FACTOR);
+    }
+
+    #[test]
+    fn test_relaxes_with_hysteresis() {
+        // just below the limit of the smaller factor isn't enough room
+        assert_eq!(sampling_factor(95_000.0, 50_000.0, 4), 4);
+        assert_eq!(sampling_factor(75_000.0, 50_000.0, 4), 2);
+        assert_eq!(sampling_factor(30_000.0, 50_000.0, 32), 1);
+    }
+
+    #[test]
+    fn test_token_bucket() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(100.0, start);
+        // a burst of a second's worth goes through at once
+        let burst = (0..150).filter(|_| bucket.admit(start)).count();
+        assert_eq!(burst, 100);
+        // then one line per 10ms
+        let at = |ms| start + Duration::from_millis(ms);
+        assert!(!bucket.admit(at(5)));
+        assert!(bucket.admit(at(10)));
+        assert!(!bucket.admit(at(10)));
+        // 2000 lines over 2 seconds keep to the rate
+        let steady = (0..2000).filter(|&i| bucket.admit(at(10 + i))).count();
+        assert_eq!(steady, 199);
+        // an idle bucket never saves up more than a second
+        let later = (0..500).filter(|_| bucket.admit(at(60_000))).count();
+        assert_eq!(later, 100);
+    }
+
+    #[test]
+    fn test_slow_token_bucket() {
+        // 5 lines per 2 minutes
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(5.0 / 120.0, start);
+        assert!(bucket.admit(start));
+        assert!(!bucket.admit(start + Duration::from_secs(20)));
+        assert!(bucket.admit(start + Duration::from_secs(25)));
+    }
+
+    #[test]
+    fn test_guard_engages_and_disengages() {
+        let start = Instant::now();
+        let mut guard = InputGuard::new(1000, start);
+        // 4000 lines in half a second are 8000 lines per second
+        let mut changes = vec![];
+        for i in 0..4000 {
+            let at = start + WINDOW.mul_f64(i as f64 / 3999.0);
+            changes.extend(guard.admit(at).1);
+        }
+        assert_eq!(changes, [8]);
+        let parsed = (0..400).filter(|_| guard.admit(start + WINDOW).0).count();
+        assert_eq!(parsed, 50);
+
+        // a trickle over the next second
+        let later = start + WINDOW * 3;
+        assert_eq!(guard.admit(later), (true, Some(1)));
+        assert_eq!(guard.factor(), 1);
+    }
+}
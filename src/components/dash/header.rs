@@ -0,0 +1,285 @@
+//! Column headers of whitespace separated input, like the ones `vmstat`, `iostat` and `pidstat`
+//! print. Once a header is found, values are taken by their position under it instead of by
+//! counting the numbers on the line, so columns that aren't numbers don't shift the others.
+
+use crate::cli;
+
+/// What [`HeaderDetector::feed`] makes of a line.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Feed {
+    /// A header, a banner or a repeated header, nothing to chart.
+    Skip,
+    /// There is no header, extract the line as usual.
+    Plain,
+    /// The values under the header as `(chart index, value)` pairs.
+    Values(Vec<(usize, f64)>),
+}
+
+#[derive(Debug, Clone)]
+enum Detection {
+    /// No line with numbers seen yet.
+    Looking {
+        candidate: Option<Vec<String>>,
+    },
+    /// The header and the position of the column every chart takes its value from.
+    Found {
+        tokens: Vec<String>,
+        columns: Vec<usize>,
+    },
+    NoHeader,
+}
+
+/// Finds the header of the input and takes the values of the following lines by column.
+#[derive(Debug, Clone)]
+pub struct HeaderDetector {
+    mode: cli::Header,
+    /// `--fields`, header names of the columns to chart.
+    fields: Option<Vec<String>>,
+    /// `--indices`, 1-based among the columns holding numbers.
+    indices: Option<Vec<usize>>,
+    detection: Detection,
+    /// The chart titles, until they are taken.
+    titles: Option<Vec<String>>,
+    /// `--fields` names the header doesn't have, until they are taken.
+    unknown_fields: Vec<String>,
+}
+
+impl HeaderDetector {
+    pub fn new(
+        mode: cli::Header,
+        fields: Option<Vec<String>>,
+        indices: Option<Vec<usize>>,
+    ) -> Self {
+        let detection = match mode {
+            cli::Header::None => Detection::NoHeader,
+            _ => Detection::Looking { candidate: None },
+        };
+        Self {
+            mode,
+            fields,
+            indices,
+            detection,
+            titles: None,
+            unknown_fields: vec![],
+        }
+    }
+
+    pub fn feed(&mut self, line: &str) -> Feed {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let numbers = tokens.iter().any(|token| token.parse::<f64>().is_ok());
+        match &mut self.detection {
+            Detection::NoHeader => Feed::Plain,
+            Detection::Looking { candidate } => {
+                if tokens.is_empty() {
+                    return Feed::Skip;
+                }
+                if !numbers {
+                    // `auto` takes the last line before the data, after banners like the
+                    // `procs ---memory---` line of vmstat; `first-line` keeps the first one
+                    if self.mode == cli::Header::Auto || candidate.is_none() {
+                        *candidate = Some(tokens.iter().map(|&token| token.into()).collect());
+                    }
+                    return Feed::Skip;
+                }
+                if self.mode == cli::Header::FirstLine && candidate.is_none() {
+                    // the first line holds numbers but is still the header
+                    *candidate = Some(tokens.iter().map(|&token| token.into()).collect());
+                    return Feed::Skip;
+                }
+                match candidate.take() {
+                    Some(header) => {
+                        self.found(header, &tokens);
+                        self.feed(line)
+                    }
+                    None => {
+                        self.detection = Detection::NoHeader;
+                        Feed::Plain
+                    }
+                }
+            }
+            Detection::Found {
+                tokens: header,
+                columns,
+            } => {
+                // headers are printed again every screenful by vmstat
+                if !numbers || tokens.iter().eq(header.iter()) {
+                    return Feed::Skip;
+                }
+                Feed::Values(
+                    columns
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(i, &column)| Some((i, tokens.get(column)?.parse().ok()?)))
+                        .collect(),
+                )
+            }
+        }
+    }
+
+    /// Pick the columns from the header and the first line of data: the ones holding numbers,
+    /// narrowed down by `--fields` or `--indices`.
+    fn found(&mut self, header: Vec<String>, data: &[&str]) {
+        let numeric: Vec<usize> = (0..data.len())
+            .filter(|&column| data[column].parse::<f64>().is_ok())
+            .collect();
+        let columns: Vec<usize> = match (&self.fields, &self.indices) {
+            (Some(fields), _) => fields
+                .iter()
+                .filter_map(|field| {
+                    let column = header.iter().position(|name| name == field);
+                    if column.is_none() {
+                        self.unknown_fields.push(field.clone());
+                    }
+                    column
+                })
+                .collect(),
+            (None, Some(indices)) => indices
+                .iter()
+                .filter_map(|&index| numeric.get(index.checked_sub(1)?).copied())
+                .collect(),
+            (None, None) => numeric,
+        };
+        self.titles = Some(
+            columns
+                .iter()
+                .map(|&column| match header.get(column) {
+                    Some(name) => name.clone(),
+                    None => format!("column {}", column + 1),
+                })
+                .collect(),
+        );
+        self.detection = Detection::Found {
+            tokens: header,
+            columns,
+        };
+    }
+
+    /// The titles of the charts once the header was found, only returned once.
+    pub fn take_titles(&mut self) -> Option<Vec<String>> {
+        self.titles.take()
+    }
+
+    /// `--fields` names missing from the header, only returned once.
+    pub fn take_unknown_fields(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.unknown_fields)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const VMSTAT: &str = "\
+procs -----------memory---------- ---swap-- -----io---- -system-- ------cpu-----
+ r  b   swpd   free   buff  cache   si   so    bi    bo   in   cs us sy id wa st
+ 2  0      0 812344 123456 2345678    0    0     5    10  100  200  3  1 95  1  0
+procs -----------memory---------- ---swap-- -----io---- -system-- ------cpu-----
+ r  b   swpd   free   buff  cache   si   so    bi    bo   in   cs us sy id wa st
+ 1  0      0 812000 123456 2345680    0    0     0    24  180  350 12  4 84  0  0
+";
+
+    const PIDSTAT: &str = "\
+Linux 6.8.0-45-generic (build01) \t10/16/2026 \t_x86_64_\t(8 CPU)
+
+03:14:01 PM   UID       PID    %usr %system  %guest   %wait    %CPU   CPU  Command
+03:14:02 PM  1000      4242   12.00    3.00    0.00    0.00   15.00     6  cargo
+03:14:03 PM  1000      4242   40.00    5.00    0.00    1.00   45.00     2  cargo
+";
+
+    fn run(detector: &mut HeaderDetector, input: &str) -> Vec<Feed> {
+        input.lines().map(|line| detector.feed(line)).collect()
+    }
+
+    #[test]
+    fn vmstat() {
+        let mut detector = HeaderDetector::new(cli::Header::Auto, None, None);
+        let feeds = run(&mut detector, VMSTAT);
+        assert_eq!(
+            detector.take_titles().unwrap(),
+            "r b swpd free buff cache si so bi bo in cs us sy id wa st"
+                .split(' ')
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(detector.take_titles(), None);
+        // the repeated header lines are skipped
+        assert_eq!(feeds[..2], [Feed::Skip, Feed::Skip]);
+        assert_eq!(feeds[3..5], [Feed::Skip, Feed::Skip]);
+        let Feed::Values(values) = &feeds[5] else {
+            panic!("{:?}", feeds[5]);
+        };
+        assert_eq!(values.len(), 17);
+        assert_eq!(values[3], (3, 812000.0));
+        assert_eq!(values[12], (12, 12.0));
+    }
+
+    #[test]
+    fn pidstat() {
+        let mut detector = HeaderDetector::new(cli::Header::Auto, None, None);
+        let feeds = run(&mut detector, PIDSTAT);
+        // the time and the command aren't numbers
+        assert_eq!(
+            detector.take_titles().unwrap(),
+            ["UID", "PID", "%usr", "%system", "%guest", "%wait", "%CPU", "CPU"]
+        );
+        assert_eq!(feeds[..3], [Feed::Skip, Feed::Skip, Feed::Skip]);
+        assert_eq!(
+            feeds[4],
+            Feed::Values(vec![
+                (0, 1000.0),
+                (1, 4242.0),
+                (2, 40.0),
+                (3, 5.0),
+                (4, 0.0),
+                (5, 1.0),
+                (6, 45.0),
+                (7, 2.0),
+            ])
+        );
+    }
+
+    #[test]
+    fn fields() {
+        let fields = ["us", "sy", "nope"].map(String::from).to_vec();
+        let mut detector = HeaderDetector::new(cli::Header::Auto, Some(fields), None);
+        let feeds = run(&mut detector, VMSTAT);
+        assert_eq!(detector.take_titles().unwrap(), ["us", "sy"]);
+        assert_eq!(detector.take_unknown_fields(), ["nope"]);
+        assert_eq!(feeds[5], Feed::Values(vec![(0, 12.0), (1, 4.0)]));
+    }
+
+    #[test]
+    fn indices_count_the_numeric_columns() {
+        let mut detector = HeaderDetector::new(cli::Header::Auto, None, Some(vec![3, 7]));
+        let feeds = run(&mut detector, PIDSTAT);
+        assert_eq!(detector.take_titles().unwrap(), ["%usr", "%CPU"]);
+        assert_eq!(feeds[3], Feed::Values(vec![(0, 12.0), (1, 15.0)]));
+    }
+
+    #[test]
+    fn auto_without_header() {
+        let mut detector = HeaderDetector::new(cli::Header::Auto, None, None);
+        assert_eq!(detector.feed("1 2 3"), Feed::Plain);
+        assert_eq!(detector.feed("4 5 6"), Feed::Plain);
+        assert_eq!(detector.take_titles(), None);
+    }
+
+    #[test]
+    fn first_line() {
+        let mut detector = HeaderDetector::new(cli::Header::FirstLine, None, None);
+        assert_eq!(detector.feed("2024 2025"), Feed::Skip);
+        assert_eq!(
+            detector.feed("10 20"),
+            Feed::Values(vec![(0, 10.0), (1, 20.0)])
+        );
+        assert_eq!(detector.take_titles().unwrap(), ["2024", "2025"]);
+    }
+
+    #[test]
+    fn none() {
+        let mut detector = HeaderDetector::new(cli::Header::None, None, None);
+        assert_eq!(detector.feed("a b"), Feed::Plain);
+        assert_eq!(detector.feed("1 2"), Feed::Plain);
+    }
+}
@@ -0,0 +1,188 @@
+//! The keys of the actions on a chart, like `r reset · space pause`, in the bottom border of the
+//! focused chart instead of its time markers. They are looked up in the keybindings, so a key
+//! bound elsewhere in the config is the one hinted at, and an unbound action has no hint.
+
+use std::collections::HashMap;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::text::{Line, Span};
+use unicode_width::UnicodeWidthStr;
+
+use super::DashView;
+use crate::{
+    action::Action,
+    config::{key_event_to_string, Theme},
+};
+
+/// The actions on the focused chart that get a hint, the most important first: the ones further
+/// down are dropped first when the border is too narrow.
+const CHART_HINTS: [(Action, &str); 10] = [
+    (Action::ClearChart, "reset"),
+    (Action::TogglePause, "pause"),
+    (Action::EditReference, "ref"),
+    (Action::ExportChart, "export"),
+    (Action::ToggleInspector, "inspect"),
+    (Action::ToggleInfo, "info"),
+    (Action::AddNote, "note"),
+    (Action::EditTitle, "title"),
+    (Action::TogglePin, "pin"),
+    (Action::HideChart, "hide"),
+];
+
+/// The key of an action and what it does, like `("r", "reset")`.
+pub type Hint = (String, &'static str);
+
+/// The hints of the actions bound in `keymap`, the most important first. An action bound to
+/// several keys is hinted with the shortest one.
+pub fn chart_hints(keymap: &HashMap<Vec<KeyEvent>, Action>) -> Vec<Hint> {
+    CHART_HINTS
+        .iter()
+        .filter_map(|(action, name)| {
+            let key = keymap
+                .iter()
+                .filter(|(_, bound)| *bound == action)
+                .map(|(keys, _)| key_label(keys))
+                .min_by_key(|label| (label.width(), label.clone()))?;
+            Some((key, *name))
+        })
+        .collect()
+}
+
+/// How many of the `hints` fit in `width` cells with `separator` between them, dropping the
+/// least important ones.
+pub fn fitting_hints(hints: &[Hint], width: usize, separator: &str) -> usize {
+    let mut used = 0;
+    for (fitting, (key, name)) in hints.iter().enumerate() {
+        let gap = if fitting == 0 { 0 } else { separator.width() };
+        used += gap + key.width() + 1 + name.width();
+        if used > width {
+            return fitting;
+        }
+    }
+    hints.len()
+}
+
+/// A key the way the hints show it: `E` rather than `shift-e`, `^r` for `ctrl-r`.
+fn key_label(keys: &[KeyEvent]) -> String {
+    keys.iter()
+        .map(|key| match (key.code, key.modifiers) {
+            (KeyCode::Char(c), modifiers)
+                if c != ' ' && modifiers - KeyModifiers::SHIFT == KeyModifiers::NONE =>
+            {
+                c.to_string()
+            }
+            (KeyCode::Char(c), KeyModifiers::CONTROL) => format!("^{c}"),
+            (KeyCode::F(n), KeyModifiers::NONE) => format!("F{n}"),
+            _ => key_event_to_string(key),
+        })
+        .collect()
+}
+
+impl DashView {
+    /// The hints that fit the bottom border of the focused chart, `width` cells between its
+    /// corners. `None` for the other charts, with `--no-hints` or when not one fits.
+    pub(super) fn hint_line(&self, i: usize, width: usize) -> Option<Line<'static>> {
+        if !self.hints_shown || self.view.focus != Some(i) {
+            return None;
+        }
+        let ascii = self.bar_set == super::ASCII_BARS;
+        let separator = if ascii { " | " } else { " · " };
+        // a line on either side of the hints
+        let fitting = fitting_hints(&self.hints, width.saturating_sub(2), separator);
+        if fitting == 0 {
+            return None;
+        }
+        Some(hint_spans(&self.hints[..fitting], separator, &self.theme))
+    }
+}
+
+fn hint_spans(hints: &[Hint], separator: &str, theme: &Theme) -> Line<'static> {
+    let mut spans = vec![Span::raw("─")];
+    for (n, (key, name)) in hints.iter().enumerate() {
+        if n > 0 {
+            spans.push(Span::raw(separator.to_string()));
+        }
+        spans.push(Span::styled(key.clone(), theme.time_label));
+        spans.push(Span::raw(format!(" {name}")));
+    }
+    spans.push(Span::raw("─"));
+    Line::from(spans).right_aligned()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::config::parse_key_sequence;
+
+    fn keymap(bindings: &[(&str, Action)]) -> HashMap<Vec<KeyEvent>, Action> {
+        bindings
+            .iter()
+            .map(|(keys, action)| (parse_key_sequence(keys).unwrap(), action.clone()))
+            .collect()
+    }
+
+    fn names(hints: &[Hint]) -> Vec<String> {
+        hints
+            .iter()
+            .map(|(key, name)| format!("{key} {name}"))
+            .collect()
+    }
+
+    #[test]
+    fn test_hint_priority() {
+        let bindings = keymap(&[
+            ("<Shift-i>", Action::ToggleInspector),
+            ("<q>", Action::Quit),
+            ("<Shift-e>", Action::ExportChart),
+            ("<F1>", Action::ToggleInfo),
+            ("<d>", Action::ToggleInfo),
+            ("<space>", Action::TogglePause),
+            ("<Ctrl-t>", Action::EditReference),
+            ("<r>", Action::ClearChart),
+        ]);
+        // in the order of importance whatever the bindings, unbound actions and keys that aren't
+        // about a chart left out
+        assert_eq!(
+            names(&chart_hints(&bindings)),
+            [
+                "r reset",
+                "space pause",
+                "^t ref",
+                "E export",
+                "I inspect",
+                "d info"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rebound_hint() {
+        let bindings = keymap(&[
+            ("<Shift-r>", Action::ClearChart),
+            ("<x><x>", Action::ClearChart),
+        ]);
+        assert_eq!(names(&chart_hints(&bindings)), ["R reset"]);
+        let bindings = keymap(&[("<x><x>", Action::ClearChart)]);
+        assert_eq!(names(&chart_hints(&bindings)), ["xx reset"]);
+    }
+
+    #[test]
+    fn test_hint_truncation() {
+        let hints = vec![
+            ("r".to_string(), "reset"),
+            ("space".to_string(), "pause"),
+            ("t".to_string(), "ref"),
+            ("E".to_string(), "export"),
+        ];
+        // r reset · space pause · t ref · E export
+        assert_eq!(fitting_hints(&hints, 40, " · "), 4);
+        assert_eq!(fitting_hints(&hints, 39, " · "), 3);
+        assert_eq!(fitting_hints(&hints, 29, " · "), 3);
+        assert_eq!(fitting_hints(&hints, 28, " · "), 2);
+        assert_eq!(fitting_hints(&hints, 7, " · "), 1);
+        assert_eq!(fitting_hints(&hints, 6, " · "), 0);
+        assert_eq!(fitting_hints(&[], 80, " · "), 0);
+    }
+}
@@ -0,0 +1,111 @@
+use ratatui::{prelude::*, Frame};
+use unicode_width::UnicodeWidthStr;
+
+use super::{DashState, DashView};
+use crate::memory;
+
+/// The most samples `--history auto` keeps for a chart, however wide the terminal.
+pub const MAX_AUTO_HISTORY: usize = 10_000;
+
+/// Samples needed to fill `columns` columns of `samples_per_column` samples with `--history
+/// auto`, within [`MAX_AUTO_HISTORY`] and what `charts` charts may keep under `memory_limit`.
+pub fn auto_history(
+    columns: usize,
+    samples_per_column: usize,
+    charts: usize,
+    memory_limit: Option<usize>,
+) -> usize {
+    let needed = (columns * samples_per_column).min(MAX_AUTO_HISTORY);
+    match memory_limit {
+        Some(limit) => needed.min(memory::history_within(limit, charts)),
+        None => needed,
+    }
+}
+
+impl DashView {
+    /// Grow the history of every chart to fill a view `columns` wide with `--history auto`. It
+    /// never shrinks again, a terminal made narrower keeps the samples for panning.
+    pub(super) fn fit_history(&mut self, columns: usize) {
+        if !self.history_auto {
+            return;
+        }
+        let mut state = self.model.state.write().unwrap();
+        let needed = auto_history(
+            columns,
+            self.samples_per_column(),
+            state.len(),
+            self.reader.max_memory,
+        );
+        self.history = self.history.max(needed);
+        for chart in state.iter_mut() {
+            chart.grow(needed);
+        }
+    }
+
+    /// Say why part of the chart is empty when the view is wider than the samples in it: still
+    /// collecting, or a fixed `--history` shorter than the width.
+    pub(super) fn draw_history_hint(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        chart: &DashState,
+        width: usize,
+    ) {
+        let inner = area.inner(Margin::new(1, 1));
+        let width = width.min(inner.width as usize);
+        let samples_per_column = self.samples_per_column();
+        let needed = width * samples_per_column;
+        let capacity = chart.data.size();
+        let hint = if chart.length < capacity.min(needed) {
+            if !self.history_auto && capacity >= needed {
+                return;
+            }
+            format!("collecting… {}/{}", chart.length, capacity.min(needed))
+        } else if capacity < needed && self.history_auto {
+            format!("history {capacity}/{needed}, the most it keeps")
+        } else if capacity < needed {
+            format!("history {capacity}/{needed}, --history auto fills the width")
+        } else {
+            return;
+        };
+        let shown = chart
+            .length
+            .saturating_sub(self.chart_offset(chart.interval))
+            .div_ceil(samples_per_column)
+            .min(width);
+        let empty = width - shown;
+        if inner.height == 0 || hint.width() + 2 > empty {
+            return;
+        }
+        // centered in the empty part, away from the newest side
+        let mut x = inner.x + ((empty - hint.width()) / 2) as u16;
+        if self.newest_left() {
+            x += shown as u16;
+        }
+        let y = inner.y + inner.height / 2;
+        frame
+            .buffer_mut()
+            .set_string(x, y, hint, Style::new().add_modifier(Modifier::DIM));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn fills_the_width() {
+        assert_eq!(auto_history(318, 1, 4, None), 318);
+        // zoomed out, every column averages four samples
+        assert_eq!(auto_history(318, 4, 4, None), 1272);
+    }
+
+    #[test]
+    fn capped() {
+        assert_eq!(auto_history(5000, 8, 1, None), MAX_AUTO_HISTORY);
+        let limit = memory::estimate_bytes(4, 500);
+        assert!(auto_history(2000, 1, 4, Some(limit)) <= 500);
+    }
+}
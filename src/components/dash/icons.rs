@@ -0,0 +1,192 @@
+//! The glyphs in front of a chart's title: what feeds the chart and how that source is doing.
+//! They are only drawn, the titles in summaries and exports go without them.
+
+use super::{inspect::Connection, DashState, DashView};
+
+/// What a chart reads its values from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceKind {
+    Stdin,
+    /// A `--file` or the `--input` file.
+    File,
+    /// A command run by ttydash, like an `ssh:` source.
+    Command,
+    /// Clients connecting to `--listen`.
+    Socket,
+}
+
+/// How the source of a chart is doing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceState {
+    Live,
+    /// Started or restarted, nothing was read yet.
+    Connecting,
+    Paused,
+    Failed,
+}
+
+impl SourceKind {
+    /// Stdin is what a chart reads without saying, it goes without a glyph.
+    fn glyph(self, ascii: bool) -> &'static str {
+        match (self, ascii) {
+            (SourceKind::Stdin, _) => "",
+            (SourceKind::File, false) => "⎙",
+            (SourceKind::File, true) => "f",
+            (SourceKind::Command, false) => "❯",
+            (SourceKind::Command, true) => "$",
+            (SourceKind::Socket, false) => "⇄",
+            (SourceKind::Socket, true) => "@",
+        }
+    }
+}
+
+impl SourceState {
+    fn glyph(self, ascii: bool) -> &'static str {
+        match (self, ascii) {
+            (SourceState::Live, false) => "▶",
+            (SourceState::Live, true) => ">",
+            (SourceState::Connecting, false) => "↻",
+            (SourceState::Connecting, true) => "~",
+            (SourceState::Paused, false) => "⏸",
+            (SourceState::Paused, true) => "=",
+            (SourceState::Failed, false) => "✖",
+            (SourceState::Failed, true) => "x",
+        }
+    }
+}
+
+/// The prefix of a title, `⎙▶ ` for a live file. `ascii` keeps to one byte per glyph, for the
+/// terminals `--low-bandwidth` is meant for.
+pub fn title_icons(kind: SourceKind, state: SourceState, ascii: bool) -> String {
+    format!("{}{} ", kind.glyph(ascii), state.glyph(ascii))
+}
+
+impl DashView {
+    pub(super) fn source_kind(&self, i: usize) -> SourceKind {
+        if self
+            .reader
+            .sources
+            .get(i)
+            .is_some_and(|source| source.ssh.is_some())
+            || self.reader.exec.is_some()
+        {
+            SourceKind::Command
+        } else if self.reader.listener.is_some()
+            || self.reader.udp_socket.is_some()
+            || self.reader.has_socket()
+        {
+            SourceKind::Socket
+        } else if !self.reader.files.is_empty() || self.reader.input.is_some() {
+            SourceKind::File
+        } else {
+            SourceKind::Stdin
+        }
+    }
+
+    /// The state of the source of chart `i`, from what its reader last saw.
+    pub(super) fn source_state(&self, i: usize, chart: &DashState) -> SourceState {
+        if chart.paused_since.is_some() {
+            return SourceState::Paused;
+        }
+        let status = self.model.source_status.lock().unwrap();
+        let Some(status) = status.get(&i) else {
+            return SourceState::Live;
+        };
+        if status.error.is_some() {
+            return SourceState::Failed;
+        }
+        match status.connection {
+            Connection::Live => SourceState::Live,
+            Connection::Connecting | Connection::Reconnecting => SourceState::Connecting,
+            Connection::GaveUp
+            | Connection::Missing
+            | Connection::Unreadable
+            | Connection::Closed => SourceState::Failed,
+        }
+    }
+
+    /// The glyphs in front of the title of chart `i`, nothing with `--no-icons`.
+    pub(super) fn chart_icons(&self, i: usize, chart: &DashState) -> String {
+        if !self.icons {
+            return String::new();
+        }
+        let ascii = self.bar_set == super::ASCII_BARS;
+        title_icons(self.source_kind(i), self.source_state(i, chart), ascii)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::cli::Cli;
+
+    #[test]
+    fn test_title_icons() {
+        assert_eq!(
+            title_icons(SourceKind::File, SourceState::Live, false),
+            "⎙▶ "
+        );
+        assert_eq!(
+            title_icons(SourceKind::Command, SourceState::Failed, false),
+            "❯✖ "
+        );
+        assert_eq!(
+            title_icons(SourceKind::Stdin, SourceState::Paused, false),
+            "⏸ "
+        );
+        // one byte and one column per glyph
+        for kind in [
+            SourceKind::Stdin,
+            SourceKind::File,
+            SourceKind::Command,
+            SourceKind::Socket,
+        ] {
+            for state in [
+                SourceState::Live,
+                SourceState::Connecting,
+                SourceState::Paused,
+                SourceState::Failed,
+            ] {
+                let icons = title_icons(kind, state, true);
+                assert!(icons.is_ascii(), "{icons:?}");
+                assert_eq!(icons.len(), kind.glyph(false).chars().count() + 2);
+            }
+        }
+        assert_eq!(
+            title_icons(SourceKind::Command, SourceState::Connecting, true),
+            "$~ "
+        );
+    }
+
+    #[test]
+    fn test_chart_icons() {
+        let dash =
+            |args: &[&str]| DashView::new(Cli::parse_from([&["ttydash"], args].concat())).unwrap();
+        let chart = DashState::default();
+        assert_eq!(dash(&[]).chart_icons(0, &chart), "▶ ");
+        assert_eq!(dash(&["--no-icons"]).chart_icons(0, &chart), "");
+        assert_eq!(
+            dash(&["--file", "a.log", "--low-bandwidth"]).chart_icons(0, &chart),
+            "f> "
+        );
+
+        let ssh = dash(&["--source", "ssh:web-1!vmstat 1"]);
+        ssh.model
+            .update_source(0, |status| status.connection = Connection::Reconnecting);
+        assert_eq!(ssh.chart_icons(0, &chart), "❯↻ ");
+        ssh.model.update_source(0, |status| {
+            status.error = Some("exited with 255".to_string())
+        });
+        assert_eq!(ssh.chart_icons(0, &chart), "❯✖ ");
+        ssh.model.update_source(0, |status| status.received("1"));
+        assert_eq!(ssh.chart_icons(0, &chart), "❯▶ ");
+        let paused = DashState {
+            paused_since: Some(ssh.instant()),
+            ..DashState::default()
+        };
+        assert_eq!(ssh.chart_icons(0, &paused), "❯⏸ ");
+    }
+}
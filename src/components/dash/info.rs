@@ -0,0 +1,146 @@
+use ratatui::{prelude::*, Frame};
+
+use super::{chart_title, cli, debug::draw_overlay, DashState, DashView};
+
+impl DashView {
+    /// Draw the `F1` popup about the current chart: what it shows, where its values come from
+    /// and its stats now.
+    pub(super) fn draw_info(&self, frame: &mut Frame, area: Rect) {
+        let Some(i) = self.current_chart() else {
+            return;
+        };
+        let state = self.model.state.read().unwrap();
+        if i >= state.len() {
+            return;
+        }
+        let rows = self.info_rows(&state, i);
+        draw_overlay(frame, area, "Info", &rows);
+    }
+
+    fn info_rows(&self, state: &[DashState], i: usize) -> Vec<(&'static str, String)> {
+        let chart = &state[i];
+        let mut rows = vec![("title", chart_title(&self.titles, state, i))];
+        if let Some(description) = &chart.description {
+            rows.push(("description", description.clone()));
+        }
+        let source = self
+            .reader
+            .sources
+            .get(i)
+            .map(ToString::to_string)
+            .filter(|source| !source.is_empty());
+        rows.push(("source", source.unwrap_or_else(|| self.reader.input_name())));
+        let extractor = match self.reader.format {
+            cli::Format::Graphite | cli::Format::Statsd => {
+                let protocol = format!("{:?}", self.reader.format).to_lowercase();
+                match &chart.name {
+                    Some(name) => format!("{protocol} metric {name}"),
+                    None => format!("{protocol} metric"),
+                }
+            }
+            cli::Format::Count => {
+                let regex = self.reader.count_by.as_ref().map(ToString::to_string);
+                let regex = regex.unwrap_or_default();
+                match &chart.name {
+                    Some(name) => format!("lines matching {regex} with {name}"),
+                    None => format!("lines matching {regex}"),
+                }
+            }
+            cli::Format::Number | cli::Format::Bool => self.reader.extractor().describe(i),
+        };
+        rows.push(("extractor", extractor));
+        if !chart.unit.is_empty() {
+            rows.push(("unit", chart.unit.clone()));
+        }
+        if let Some(reference) = self.references.get(i) {
+            rows.push(("reference", self.number(*reference, 2)));
+        }
+        if let Some(step) = self.reader.quantize.get(i) {
+            rows.push(("quantize", step.to_string()));
+        }
+        rows.push(("samples", chart.length.to_string()));
+        if chart.has_stats() {
+            let value = |value| {
+                let (value, unit) = self.scaled(value, 2, &chart.unit);
+                format!("{value} {unit}").trim_end().to_string()
+            };
+            if let Some(last) = chart.last() {
+                rows.push(("last", value(last)));
+            }
+            let (average, min, max) = chart.scoped_stats(self.stats_scope);
+            rows.push(("average", value(average)));
+            rows.push(("min", value(min)));
+            rows.push(("max", value(max)));
+        }
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use clap::Parser;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::cli::Cli;
+
+    fn rows(args: &[&str], values: &[f64]) -> Vec<(&'static str, String)> {
+        let dash = DashView::new(Cli::parse_from([&["ttydash"], args].concat())).unwrap();
+        let mut state = vec![dash.reader.new_chart(&[])];
+        let mut reader = super::super::reader::ReaderState::default();
+        for &value in values {
+            dash.reader
+                .store(&mut state, &mut reader, 0, value, Instant::now());
+        }
+        dash.info_rows(&state, 0)
+    }
+
+    #[test]
+    fn test_info_rows() {
+        let rows = rows(
+            &[
+                "-u",
+                "ms",
+                "-t",
+                "checkout",
+                "--descriptions",
+                "p99 of the checkout API, bad above 250",
+                "--reference",
+                "250",
+            ],
+            &[120.0, 180.0],
+        );
+        assert_eq!(
+            rows,
+            [
+                ("title", "checkout".to_string()),
+                (
+                    "description",
+                    "p99 of the checkout API, bad above 250".to_string()
+                ),
+                ("source", "stdin".to_string()),
+                ("extractor", "unit ms".to_string()),
+                ("unit", "ms".to_string()),
+                ("reference", "250.00".to_string()),
+                ("samples", "2".to_string()),
+                ("last", "180.00 ms".to_string()),
+                ("average", "150.00 ms".to_string()),
+                ("min", "120.00 ms".to_string()),
+                ("max", "180.00 ms".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_source_description() {
+        let rows = rows(&["--source", "every=5s,desc=load average"], &[]);
+        assert_eq!(rows[1], ("description", "load average".to_string()));
+        assert_eq!(
+            rows[2],
+            ("source", "every=5s,desc=load average".to_string())
+        );
+        assert_eq!(rows.last().unwrap(), &("samples", "0".to_string()));
+    }
+}
@@ -0,0 +1,262 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use ratatui::{prelude::*, Frame};
+
+use super::{
+    arrivals::format_counts, chart_title, debug::draw_overlay, stale::stale_label, ChartModel,
+    DashState, DashView,
+};
+use crate::memory::format_size;
+
+/// Lines of a command's stderr the `I` popup shows.
+pub const STDERR_LINES: usize = 5;
+
+/// Where the reader of an `ssh:`, `--file` or `--per-connection` source stands.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Connection {
+    /// The command was started and printed nothing yet.
+    #[default]
+    Connecting,
+    Live,
+    /// The command ended, it is run again after a backoff.
+    Reconnecting,
+    /// `--restart-on-failure` ran out of restarts.
+    GaveUp,
+    /// The file doesn't exist.
+    Missing,
+    /// The file exists but can't be read.
+    Unreadable,
+    /// The client hung up.
+    Closed,
+}
+
+impl fmt::Display for Connection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Connection::Connecting => "connecting",
+            Connection::Live => "live",
+            Connection::Reconnecting => "reconnecting",
+            Connection::GaveUp => "gave up",
+            Connection::Missing => "missing",
+            Connection::Unreadable => "unreadable",
+            Connection::Closed => "closed",
+        };
+        write!(f, "{label}")
+    }
+}
+
+/// What the reader of a chart's source last saw, kept up to date by the reader task for the
+/// error badge and the `I` popup.
+#[derive(Debug, Clone, Default)]
+pub struct SourceStatus {
+    /// The source as given, `ssh:host!command` or the path of a file.
+    pub spec: String,
+    pub connection: Connection,
+    /// Why the source is failing, shown as a badge until it recovers.
+    pub error: Option<String>,
+    /// Failed attempts in a row.
+    pub retries: u32,
+    /// When the command is run again while reconnecting.
+    pub next_retry: Option<Instant>,
+    /// The last [`STDERR_LINES`] lines of a command's stderr, the newest last.
+    pub stderr: Vec<String>,
+    pub lines: usize,
+    pub bytes: usize,
+}
+
+impl SourceStatus {
+    /// Count a line read from the source, which is live again if it was failing.
+    pub fn received(&mut self, line: &str) {
+        self.connection = Connection::Live;
+        self.error = None;
+        self.next_retry = None;
+        self.lines += 1;
+        // with the line break
+        self.bytes += line.len() + 1;
+    }
+
+    /// Keep a line of the command's stderr.
+    pub fn push_stderr(&mut self, line: String) {
+        if self.stderr.len() == STDERR_LINES {
+            self.stderr.remove(0);
+        }
+        self.stderr.push(line);
+    }
+}
+
+impl ChartModel {
+    /// Change the status of the source of chart `i`.
+    pub(super) fn update_source<T>(
+        &self,
+        i: usize,
+        update: impl FnOnce(&mut SourceStatus) -> T,
+    ) -> T {
+        update(self.source_status.lock().unwrap().entry(i).or_default())
+    }
+
+    /// Why the source of chart `i` is failing.
+    pub(super) fn source_error(&self, i: usize) -> Option<String> {
+        let status = self.source_status.lock().unwrap();
+        status.get(&i).and_then(|status| status.error.clone())
+    }
+}
+
+impl DashView {
+    /// Open or close the `I` popup about the source of the current chart.
+    pub(super) fn toggle_inspector(&mut self) {
+        self.inspecting = !self.inspecting;
+        if self.inspecting {
+            self.info = false;
+        }
+    }
+
+    /// Draw the `I` popup, from the status the reader keeps so it follows the source live.
+    pub(super) fn draw_inspector(&self, frame: &mut Frame, area: Rect) {
+        let Some(i) = self.current_chart() else {
+            return;
+        };
+        let state = self.model.state.read().unwrap();
+        if i >= state.len() {
+            return;
+        }
+        let rows = self.inspector_rows(&state, i, self.instant());
+        draw_overlay(frame, area, "Source", &rows);
+    }
+
+    fn inspector_rows(
+        &self,
+        state: &[DashState],
+        i: usize,
+        now: Instant,
+    ) -> Vec<(&'static str, String)> {
+        let chart = &state[i];
+        let mut rows = vec![("chart", chart_title(&self.titles, state, i))];
+        let status = self.model.source_status.lock().unwrap().get(&i).cloned();
+        match status {
+            Some(status) => {
+                rows.push(("source", status.spec));
+                rows.push(("state", status.connection.to_string()));
+                if status.retries > 0 {
+                    rows.push(("retries", status.retries.to_string()));
+                }
+                if let Some(next_retry) = status.next_retry {
+                    let eta = next_retry.saturating_duration_since(now);
+                    rows.push(("next retry", format!("in {}", format_eta(eta))));
+                }
+                if let Some(&restarts) = self.model.restarts.lock().unwrap().get(&i) {
+                    rows.push(("restarts", restarts.to_string()));
+                }
+                if let Some(error) = status.error {
+                    rows.push(("last error", error));
+                }
+                for (n, line) in status.stderr.into_iter().enumerate() {
+                    rows.push((if n == 0 { "stderr" } else { "" }, line));
+                }
+                rows.push(("lines", status.lines.to_string()));
+                rows.push(("bytes", format_size(status.bytes)));
+            }
+            None => rows.push(("source", self.reader.input_name())),
+        }
+        let counts = format_counts(chart.stored, chart.arrivals.per_second(now));
+        rows.push(("samples", counts));
+        if let Some(age) = self.stale_age(chart) {
+            rows.push(("last sample", stale_label(age)));
+        }
+        rows
+    }
+}
+
+/// `4s` or `1m 30s`, how long until the next retry.
+fn format_eta(eta: Duration) -> String {
+    let seconds = eta.as_secs_f64().ceil() as u64;
+    match seconds {
+        0..60 => format!("{seconds}s"),
+        _ => format!("{}m {}s", seconds / 60, seconds % 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::cli::Cli;
+
+    #[test]
+    fn test_format_eta() {
+        assert_eq!(format_eta(Duration::from_millis(3200)), "4s");
+        assert_eq!(format_eta(Duration::ZERO), "0s");
+        assert_eq!(format_eta(Duration::from_secs(90)), "1m 30s");
+    }
+
+    #[test]
+    fn test_status() {
+        let mut status = SourceStatus {
+            connection: Connection::Reconnecting,
+            error: Some("Connection refused".to_string()),
+            ..Default::default()
+        };
+        for n in 0..7 {
+            status.push_stderr(format!("line {n}"));
+        }
+        assert_eq!(
+            status.stderr,
+            ["line 2", "line 3", "line 4", "line 5", "line 6"]
+        );
+        status.received("12 ms");
+        assert_eq!(status.connection, Connection::Live);
+        assert_eq!(status.error, None);
+        assert_eq!((status.lines, status.bytes), (1, 6));
+    }
+
+    #[test]
+    fn test_inspector_rows() {
+        let args = Cli::parse_from(["ttydash", "--source", "ssh:web-1!vmstat 1"]);
+        let dash = DashView::new(args).unwrap();
+        let state = vec![dash.reader.new_chart(&[])];
+        let now = Instant::now();
+        dash.model.update_source(0, |status| {
+            status.spec = "ssh:web-1!vmstat 1".to_string();
+            status.received("1 2 3");
+            status.push_stderr("Warning: Permanently added 'web-1'".to_string());
+            status.push_stderr("Connection to web-1 closed".to_string());
+            status.connection = Connection::Reconnecting;
+            status.error = Some("ssh web-1: Connection to web-1 closed".to_string());
+            status.retries = 2;
+            status.next_retry = Some(now + Duration::from_secs(2));
+        });
+        dash.model.restarts.lock().unwrap().insert(0, 1);
+        assert_eq!(
+            dash.inspector_rows(&state, 0, now),
+            [
+                ("chart", "web-1".to_string()),
+                ("source", "ssh:web-1!vmstat 1".to_string()),
+                ("state", "reconnecting".to_string()),
+                ("retries", "2".to_string()),
+                ("next retry", "in 2s".to_string()),
+                ("restarts", "1".to_string()),
+                (
+                    "last error",
+                    "ssh web-1: Connection to web-1 closed".to_string()
+                ),
+                ("stderr", "Warning: Permanently added 'web-1'".to_string()),
+                ("", "Connection to web-1 closed".to_string()),
+                ("lines", "1".to_string()),
+                ("bytes", "6 B".to_string()),
+                ("samples", "n=0 @0.0/s".to_string()),
+            ]
+        );
+        assert_eq!(
+            dash.model.source_error(0).as_deref(),
+            Some("ssh web-1: Connection to web-1 closed")
+        );
+        // a stdin chart has no status to show
+        let dash = DashView::new(Cli::parse_from(["ttydash"])).unwrap();
+        let rows = dash.inspector_rows(&state, 0, now);
+        assert_eq!(rows[1], ("source", "stdin".to_string()));
+    }
+}
@@ -0,0 +1,260 @@
+//! `--listen`: clients connect over TCP, like `nc host 9999`, and send lines that are charted
+//! the way lines of stdin are. The lines of all connections go into the same charts, or with
+//! `--per-connection` every connection gets a chart of its own. A client hanging up never ends
+//! the dashboard, the next one can connect any time.
+
+use std::{fmt::Display, io, net::SocketAddr, sync::atomic::Ordering};
+
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt, BufReader, Lines},
+    net::{TcpListener, TcpStream},
+    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    task::JoinSet,
+};
+use tracing::{debug, info};
+
+use super::{
+    inspect::Connection,
+    reader::{ReaderState, SourceReader},
+};
+
+type ConnectionLines = Lines<BufReader<TcpStream>>;
+
+impl SourceReader {
+    /// The `--listen` address actually listened on, with the port the system picked for 0.
+    pub(super) fn listen_addr(&self) -> Option<SocketAddr> {
+        self.listener
+            .as_ref()
+            .and_then(|listener| listener.local_addr().ok())
+    }
+
+    /// Accept connections until the dashboard stops, reading each in its own task.
+    pub(super) async fn accept_connections(
+        &self,
+        listener: &std::net::TcpListener,
+        reader: ReaderState,
+    ) {
+        let listener = match listener.try_clone().and_then(TcpListener::from_std) {
+            Ok(listener) => listener,
+            Err(err) => {
+                self.report_failure(format!("Failed to accept connections: {err}"));
+                return;
+            }
+        };
+        let (lines_tx, lines_rx) = mpsc::unbounded_channel();
+        let mut tasks = JoinSet::new();
+        if !self.per_connection {
+            tasks.spawn(self.clone().ingest_merged(lines_rx, reader));
+        }
+        while !self.stop_signal.load(Ordering::Relaxed) {
+            let (stream, peer) = tokio::select! {
+                _ = self.cancellation_token.cancelled() => break,
+                accepted = listener.accept() => match accepted {
+                    Ok(accepted) => accepted,
+                    Err(err) => {
+                        debug!("Failed to accept a connection: {err}");
+                        continue;
+                    }
+                },
+            };
+            // forget the connections that ended
+            while tasks.try_join_next().is_some() {}
+            let lines = BufReader::new(stream).lines();
+            if self.per_connection {
+                let i = self.add_named_chart(peer.to_string());
+                info!("Accepted a connection from {peer} in chart {}", i + 1);
+                tasks.spawn(self.clone().read_connection(i, peer, lines));
+            } else {
+                info!("Accepted a connection from {peer}");
+                tasks.spawn(
+                    self.clone()
+                        .forward_connection(peer, lines, lines_tx.clone()),
+                );
+            }
+        }
+        // the connections still open are closed as their tasks are dropped
+        tasks.shutdown().await;
+    }
+
+    /// Chart the lines of every connection as they come, the metrics formats are aggregated
+    /// and stored once per update interval like the lines of stdin.
+    pub(super) async fn ingest_merged(
+        self,
+        mut lines: UnboundedReceiver<String>,
+        mut reader: ReaderState,
+    ) {
+        let mut interval = self.model.clock.interval(self.base_interval());
+        loop {
+            tokio::select! {
+                line = lines.recv() => {
+                    let Some(line) = line else {
+                        break;
+                    };
+                    self.ingest(&line, &mut reader);
+                }
+                _ = interval.tick() => {
+                    self.flush_pending(&mut reader);
+                    self.announce_updates(&mut reader);
+                }
+                _ = self.cancellation_token.cancelled() => break,
+            }
+        }
+    }
+
+    /// Hand the lines of a connection to [`Self::ingest_merged`] until the client hangs up.
+    pub(super) async fn forward_connection(
+        self,
+        peer: impl Display,
+        mut lines: Lines<impl AsyncBufRead + Unpin>,
+        tx: UnboundedSender<String>,
+    ) {
+        while let Some(line) = self.next_line(&peer, &mut lines).await {
+            if tx.send(line).is_err() {
+                return;
+            }
+        }
+        info!("{peer} disconnected");
+    }
+
+    /// Chart the lines of a connection in chart `i` until the client hangs up, the chart then
+    /// goes stale.
+    async fn read_connection(self, i: usize, peer: SocketAddr, mut lines: ConnectionLines) {
+        let mut reader = self.new_reader();
+        self.model.update_source(i, |status| {
+            status.spec = format!("tcp {peer}");
+            status.connection = Connection::Live;
+        });
+        while let Some(line) = self.next_line(&peer, &mut lines).await {
+            self.model.update_source(i, |status| status.received(&line));
+            self.ingest_source_line(i, &line, &mut reader);
+        }
+        if self.cancellation_token.is_cancelled() {
+            return;
+        }
+        info!("{peer} disconnected");
+        self.model
+            .update_source(i, |status| status.connection = Connection::Closed);
+        if let Some(chart) = self.model.state.write().unwrap().get_mut(i) {
+            chart.source_gone = true;
+        }
+    }
+
+    /// The next line of a connection, `None` once it is closed or the dashboard stops. A line
+    /// that isn't UTF-8 is skipped.
+    async fn next_line(
+        &self,
+        peer: impl Display,
+        lines: &mut Lines<impl AsyncBufRead + Unpin>,
+    ) -> Option<String> {
+        loop {
+            let line = tokio::select! {
+                _ = self.cancellation_token.cancelled() => return None,
+                line = lines.next_line() => line,
+            };
+            match line {
+                Ok(line) => return line,
+                Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+                    debug!("Skipping unreadable line from {peer}: {err}");
+                    self.model.skipped_lines.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(err) => {
+                    debug!("Connection from {peer} failed: {err}");
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tokio::io::AsyncWriteExt;
+
+    use super::super::{
+        chart_title,
+        model::ChartModel,
+        test_util::{new_source, parse, wait_for},
+    };
+    use super::*;
+
+    fn listen_source(args: &[&str]) -> SourceReader {
+        new_source(&[&["--listen", "127.0.0.1:0"], args].concat())
+    }
+
+    #[tokio::test]
+    async fn test_connections_are_merged() {
+        let source = listen_source(&["-u", "ms", "-u", "rps"]);
+        let addr = source.listen_addr().unwrap();
+        let task = tokio::spawn(source.clone().run());
+        let mut first = TcpStream::connect(addr).await.unwrap();
+        first.write_all(b"12ms 100rps\n").await.unwrap();
+        wait_for(&source, |state| state.len() == 2 && state[1].stored == 1).await;
+        // a client hanging up doesn't end the reader
+        drop(first);
+        let mut second = TcpStream::connect(addr).await.unwrap();
+        second.write_all(b"15ms 90rps\r\n").await.unwrap();
+        wait_for(&source, |state| state.len() == 2 && state[1].stored == 2).await;
+        source.stop();
+        task.await.unwrap();
+        let state = source.model.state.read().unwrap();
+        assert_eq!(state.len(), 2);
+        assert_eq!(state[0].samples()[..2], [12.0, 15.0]);
+        assert_eq!(state[1].samples()[..2], [100.0, 90.0]);
+        assert_eq!(source.input_name(), format!("tcp {addr}"));
+    }
+
+    #[tokio::test]
+    async fn test_per_connection() {
+        let source = listen_source(&["--per-connection", "-t", "first"]);
+        let addr = source.listen_addr().unwrap();
+        let task = tokio::spawn(source.clone().run());
+        let mut first = TcpStream::connect(addr).await.unwrap();
+        first.write_all(b"1\n").await.unwrap();
+        wait_for(&source, |state| state[0].stored == 1).await;
+        let mut second = TcpStream::connect(addr).await.unwrap();
+        let peer = second.local_addr().unwrap();
+        second.write_all(b"2\n3\n").await.unwrap();
+        wait_for(&source, |state| state.len() == 2 && state[1].stored == 2).await;
+        drop(first);
+        wait_for(&source, |state| state[0].source_gone).await;
+        first = TcpStream::connect(addr).await.unwrap();
+        first.write_all(b"4\n").await.unwrap();
+        wait_for(&source, |state| state.len() == 3 && state[2].stored == 1).await;
+        // stopping closes the connections still open
+        source.stop();
+        task.await.unwrap();
+        let state = source.model.state.read().unwrap();
+        assert_eq!(chart_title(&source.titles, &state, 0), "first");
+        assert_eq!(chart_title(&source.titles, &state, 1), peer.to_string());
+        assert_eq!(state[1].samples()[..2], [2.0, 3.0]);
+        assert!(!state[1].source_gone);
+        let status = source.model.source_status.lock().unwrap();
+        assert_eq!(status[&0].connection, Connection::Closed);
+        assert_eq!(status[&1].spec, format!("tcp {peer}"));
+        assert_eq!(status[&1].lines, 2);
+    }
+
+    #[test]
+    fn test_listen_flags() {
+        assert!(parse(&["--per-connection"]).is_err());
+        assert!(parse(&["--listen", "9999", "--exec", "date +%S"]).is_err());
+        assert_eq!(
+            parse(&["--listen", "9999"]).unwrap().listen,
+            Some(SocketAddr::from(([127, 0, 0, 1], 9999)))
+        );
+        let args = parse(&["--listen", "0", "--per-connection", "--format", "statsd"]).unwrap();
+        assert!(SourceReader::new(&args, ChartModel::default()).is_err());
+        // the port is taken
+        let source = listen_source(&[]);
+        let addr = source.listen_addr().unwrap().to_string();
+        let args = parse(&["--listen", &addr]).unwrap();
+        let err = SourceReader::new(&args, ChartModel::default()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Failed to listen on {addr}: {}", {
+                std::net::TcpListener::bind(&addr).unwrap_err()
+            })
+        );
+    }
+}
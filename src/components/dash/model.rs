@@ -0,0 +1,525 @@
+//! The charts as the reader stores them and the view draws them: a [`DashState`] per chart,
+//! shared with the counters about the input in a [`ChartModel`].
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU32, AtomicUsize},
+        Arc, Mutex, RwLock,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+use super::{
+    arrivals::ArrivalRate,
+    diagnostics::MatchCounts,
+    inspect::SourceStatus,
+    rolling::{RollingStats, SampleWindow},
+    sample_time,
+    sketch::SessionStats,
+    FLAT_HINT_SAMPLES,
+};
+use crate::{
+    cli::StatsScope,
+    clock::{Clock, RealClock},
+    highlight::TimeRange,
+    source::Warmup,
+    summary::Note,
+    text::sanitize,
+};
+
+/// Everything the reader and the view share. Cloning it is cheap and the clones see the same
+/// charts: the reader writes, the view and the HTTP server read.
+#[derive(Debug, Clone)]
+pub struct ChartModel {
+    pub(super) state: Arc<RwLock<Vec<DashState>>>,
+    /// Lines dropped by the `throttle=` of every chart.
+    pub(super) throttled: Arc<Mutex<HashMap<usize, usize>>>,
+    /// Values skipped by `--dedupe` for every chart.
+    pub(super) deduped: Arc<Mutex<HashMap<usize, usize>>>,
+    /// NaN and infinite values rejected for every chart.
+    pub(super) non_finite: Arc<Mutex<HashMap<usize, usize>>>,
+    /// Unit matches that weren't numbers for every chart, see `--on-parse-error`.
+    pub(super) parse_errors: Arc<Mutex<HashMap<usize, usize>>>,
+    /// What the reader of the `ssh:` or `--file` source of every chart last saw.
+    pub(super) source_status: Arc<Mutex<HashMap<usize, SourceStatus>>>,
+    /// How often the `ssh:` source of every chart was restarted after failing.
+    pub(super) restarts: Arc<Mutex<HashMap<usize, u32>>>,
+    /// Notes added with `n`.
+    pub(super) notes: Arc<Mutex<Vec<Note>>>,
+    /// Input lines that could not be parsed.
+    pub(super) skipped_lines: Arc<AtomicUsize>,
+    /// `--listen-udp` datagrams larger than the dashboard takes.
+    pub(super) oversized_datagrams: Arc<AtomicUsize>,
+    /// `--listen-udp` datagrams without a value in them.
+    pub(super) malformed_datagrams: Arc<AtomicUsize>,
+    /// Only one in this many input lines is parsed, see `InputGuard`.
+    pub(super) sampling: Arc<AtomicU32>,
+    /// Lines seen and matched by every unit, column or protocol parser.
+    pub(super) match_counts: Arc<Mutex<MatchCounts>>,
+    /// The time samples are stored at and the readers wait on, virtual in `ttydash simulate`.
+    pub(super) clock: Arc<dyn Clock>,
+}
+
+impl Default for ChartModel {
+    fn default() -> Self {
+        Self {
+            state: Arc::new(RwLock::new(vec![])),
+            throttled: Arc::new(Mutex::new(HashMap::new())),
+            deduped: Arc::new(Mutex::new(HashMap::new())),
+            non_finite: Arc::new(Mutex::new(HashMap::new())),
+            parse_errors: Arc::new(Mutex::new(HashMap::new())),
+            source_status: Arc::new(Mutex::new(HashMap::new())),
+            restarts: Arc::new(Mutex::new(HashMap::new())),
+            notes: Arc::new(Mutex::new(vec![])),
+            skipped_lines: Arc::new(AtomicUsize::new(0)),
+            oversized_datagrams: Arc::new(AtomicUsize::new(0)),
+            malformed_datagrams: Arc::new(AtomicUsize::new(0)),
+            sampling: Arc::new(AtomicU32::new(1)),
+            match_counts: Arc::new(Mutex::new(MatchCounts::default())),
+            clock: Arc::new(RealClock),
+        }
+    }
+}
+
+impl ChartModel {
+    /// The time on the model's clock.
+    pub(super) fn instant(&self) -> Instant {
+        self.clock.now()
+    }
+}
+
+/// One chart: its retained samples, their stats and what names it.
+#[derive(Debug, Clone)]
+pub struct DashState {
+    pub(super) data: SampleWindow,
+    /// When every sample of a `cadence=arrival` chart arrived, in seconds after `first_update`,
+    /// lined up with `data`. Interval charts tell the time of a sample from its position.
+    pub(super) arrival_times: Option<SampleWindow>,
+    /// Name of the metric feeding the chart, for keyed formats like Graphite and StatsD.
+    pub(super) name: Option<String>,
+    /// The name as it came in, when it had characters that can't be drawn, for the summary.
+    pub(super) raw_name: Option<String>,
+    /// What the chart shows, from `--descriptions` or the source's `desc=`.
+    pub(super) description: Option<String>,
+    /// Title edited at runtime, taking precedence over `--titles`.
+    pub(super) title: Option<String>,
+    /// Title derived when the chart was created, used when it has no other.
+    pub(super) auto_title: Option<String>,
+    pub(super) unit: String,
+    /// Time between two samples of the history, the update frequency or the source's `every`.
+    pub(super) interval: Duration,
+    /// When the history last advanced, for decimation and to tell the time of the samples.
+    pub(super) last_update: Option<Instant>,
+    /// Samples stored since `--backfill` seeded the chart, to mark where the backfill ends.
+    pub(super) live_samples: Option<usize>,
+    /// When the chart was paused, new values are dropped until it is resumed.
+    pub(super) paused_since: Option<Instant>,
+    /// Time spent paused before the current pause.
+    pub(super) paused_before: Duration,
+    /// The file of a `--file` chart is gone, the chart is stale until it is back.
+    pub(super) source_gone: bool,
+    /// Samples ever stored and when the first one was, to tell when `--warmup` is over.
+    pub(super) stored: usize,
+    /// How fast the samples arrived lately, for `--show-counts`.
+    pub(super) arrivals: ArrivalRate,
+    pub(super) first_update: Option<Instant>,
+    /// The oldest retained samples that are left out of the stats.
+    pub(super) warmup: usize,
+    pub(super) length: usize,
+    /// The stats samples, kept up to date as they come and go.
+    pub(super) stats: RollingStats,
+    pub(super) min_value: f64,
+    pub(super) max_value: f64,
+    pub(super) average: f64,
+    /// The stats of every sample after the warmup, including the ones the history dropped.
+    pub(super) session: SessionStats,
+}
+
+impl DashState {
+    pub(super) fn new(size: usize, interval: Duration) -> Self {
+        Self {
+            data: SampleWindow::new(size),
+            arrival_times: None,
+            name: None,
+            raw_name: None,
+            description: None,
+            title: None,
+            auto_title: None,
+            unit: String::new(),
+            interval,
+            last_update: None,
+            live_samples: None,
+            paused_since: None,
+            paused_before: Duration::ZERO,
+            source_gone: false,
+            stored: 0,
+            arrivals: ArrivalRate::default(),
+            first_update: None,
+            warmup: 0,
+            length: 0,
+            stats: RollingStats::default(),
+            min_value: f64::INFINITY,
+            max_value: f64::NEG_INFINITY,
+            average: 0.0,
+            session: SessionStats::default(),
+        }
+    }
+
+    /// Name the chart after a field, metric or file, keeping the raw name when it can't be drawn.
+    pub(super) fn set_name(&mut self, name: String) {
+        let display = sanitize(&name);
+        self.raw_name = (display != name).then_some(name);
+        self.name = Some(display);
+    }
+
+    /// The retained samples, oldest first.
+    pub(super) fn samples(&self) -> &[f64] {
+        let data = self.data.as_slice();
+        &data[data.len() - self.length..]
+    }
+
+    /// When the retained samples arrived, oldest first, for a `cadence=arrival` chart.
+    pub(super) fn times(&self) -> Option<&[f64]> {
+        let times = self.arrival_times.as_ref()?.as_slice();
+        Some(&times[times.len() - self.length..])
+    }
+
+    /// The retained samples after the warmup, the ones the stats are about.
+    pub(super) fn stats_samples(&self) -> &[f64] {
+        &self.samples()[self.warmup.min(self.length)..]
+    }
+
+    /// Whether there is any sample after the warmup.
+    pub(super) fn has_stats(&self) -> bool {
+        self.length > self.warmup
+    }
+
+    /// Compute the stats from scratch, after the samples changed other than by [`Self::update`].
+    pub(super) fn calculate_stats(&mut self) {
+        self.stats = RollingStats::new(self.stats_samples());
+        self.refresh_stats();
+    }
+
+    fn refresh_stats(&mut self) {
+        self.average = self.stats.average();
+        self.min_value = self.stats.min();
+        self.max_value = self.stats.max();
+    }
+
+    /// Average, min and max of the stats samples in `scope`.
+    pub(super) fn scoped_stats(&self, scope: StatsScope) -> (f64, f64, f64) {
+        match scope {
+            StatsScope::Window => (self.average, self.min_value, self.max_value),
+            StatsScope::Session => (
+                self.session.average(),
+                self.session.min(),
+                self.session.max(),
+            ),
+        }
+    }
+
+    /// The most recent sample, if any.
+    pub(super) fn last(&self) -> Option<f64> {
+        self.samples().last().copied()
+    }
+
+    /// The value of the newest [`FLAT_HINT_SAMPLES`] samples if they are all the same.
+    pub(super) fn flat_value(&self) -> Option<f64> {
+        let samples = self.samples();
+        let newest = samples.len().checked_sub(FLAT_HINT_SAMPLES)?;
+        let value = *samples.last()?;
+        samples[newest..]
+            .iter()
+            .all(|&sample| sample == value)
+            .then_some(value)
+    }
+
+    /// Wall clock time of the newest sample, when the clock the samples were stored on reads
+    /// `now` and the wall clock `wall`.
+    pub(super) fn newest_time(&self, now: Instant, wall: SystemTime) -> Option<SystemTime> {
+        self.last_update
+            .map(|updated| wall - now.saturating_duration_since(updated))
+    }
+
+    /// Wall clock time of the sample `age` samples older than the newest one, taken at `newest`.
+    pub(super) fn sample_time(&self, newest: SystemTime, age: usize) -> SystemTime {
+        match self.times() {
+            Some(times) => {
+                let newest_at = times[times.len() - 1];
+                let at = times[times.len() - 1 - age];
+                newest - Duration::from_secs_f64(newest_at - at)
+            }
+            None => sample_time(newest, self.interval, age),
+        }
+    }
+
+    /// Average, min and max of the samples taken within `range`, at `now` and `wall` like
+    /// [`Self::newest_time`].
+    pub(super) fn range_stats(
+        &self,
+        range: &TimeRange,
+        now: Instant,
+        wall: SystemTime,
+    ) -> Option<(f64, f64, f64)> {
+        let newest = self.newest_time(now, wall)?;
+        let values = self
+            .stats_samples()
+            .iter()
+            .rev()
+            .enumerate()
+            .filter(|&(j, _)| range.contains(self.sample_time(newest, j)))
+            .map(|(_, &value)| value)
+            .collect::<Vec<_>>();
+        if values.is_empty() {
+            return None;
+        }
+        let average = values.iter().sum::<f64>() / values.len() as f64;
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        Some((average, min, max))
+    }
+
+    /// Approximate bytes held by the chart, dominated by the history buffer.
+    pub(super) fn retained_bytes(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.data.capacity() * std::mem::size_of::<f64>()
+            + self
+                .arrival_times
+                .as_ref()
+                .map_or(0, |times| times.capacity() * std::mem::size_of::<f64>())
+            + self.stats.capacity() * std::mem::size_of::<(u64, f64)>()
+            + self.session.capacity() * std::mem::size_of::<u64>()
+            + self.unit.capacity()
+            + self.name.as_ref().map_or(0, String::capacity)
+            + self.raw_name.as_ref().map_or(0, String::capacity)
+            + self.auto_title.as_ref().map_or(0, String::capacity)
+    }
+
+    /// Keep only the newest `size` samples, releasing the memory of the others.
+    /// Make room for `size` samples, the new room is older than the retained samples.
+    pub(super) fn grow(&mut self, size: usize) {
+        if self.data.size() < size {
+            self.data.resize(size);
+            if let Some(times) = &mut self.arrival_times {
+                times.resize(size);
+            }
+        }
+    }
+
+    pub(super) fn shrink(&mut self, size: usize) {
+        if self.data.size() <= size {
+            return;
+        }
+        self.data.resize(size);
+        if let Some(times) = &mut self.arrival_times {
+            times.resize(size);
+        }
+        self.length = self.length.min(size);
+        self.warmup = self.warmup.min(self.length);
+        if self.length > 0 {
+            self.calculate_stats();
+        }
+    }
+
+    pub(super) fn toggle_pause(&mut self, now: Instant) {
+        match self.paused_since.take() {
+            Some(since) => self.paused_before += now.duration_since(since),
+            None => self.paused_since = Some(now),
+        }
+    }
+
+    /// Total time the chart was paused up to `now`.
+    pub(super) fn paused_time(&self, now: Instant) -> Duration {
+        self.paused_before
+            + self
+                .paused_since
+                .map_or(Duration::ZERO, |since| now.duration_since(since))
+    }
+
+    /// Drop every sample, keeping the chart and its settings.
+    pub(super) fn clear(&mut self) {
+        self.data.fill(0.0);
+        self.length = 0;
+        self.warmup = 0;
+        self.live_samples = None;
+        self.stats = RollingStats::default();
+        self.session = SessionStats::default();
+        self.refresh_stats();
+    }
+
+    /// Store `value` as the newest sample, in constant time however long the history is.
+    pub(super) fn update(&mut self, value: f64) {
+        let size = self.data.size();
+        if size == 0 {
+            return;
+        }
+        if self.length == size {
+            // the oldest sample is dropped, it leaves the stats unless it was part of the warmup
+            match self.warmup {
+                0 => self.stats.pop(self.data.as_slice()[0]),
+                _ => self.warmup -= 1,
+            }
+        }
+        self.data.push(value);
+        self.stats.push(value);
+        self.length = std::cmp::min(self.length + 1, size);
+        self.stored += 1;
+        if let Some(live) = &mut self.live_samples {
+            *live += 1;
+        }
+        if self.stored.is_multiple_of(size) {
+            // the running sum drifts a little with every sample that leaves it
+            self.calculate_stats();
+        } else {
+            self.refresh_stats();
+        }
+    }
+
+    /// Store `value` as the newest sample, which arrived at `now`. Only a `cadence=arrival`
+    /// chart keeps the time, once its first sample set `first_update`.
+    pub(super) fn update_at(&mut self, value: f64, now: Instant) {
+        if let (Some(times), Some(first)) = (&mut self.arrival_times, self.first_update) {
+            times.push(now.saturating_duration_since(first).as_secs_f64());
+        }
+        self.update(value);
+    }
+
+    /// Count a sample stored after the warmup in the session's stats.
+    pub(super) fn record_session(&mut self, value: f64) {
+        self.session.push(value);
+    }
+
+    /// Leave the newest sample out of the stats, it is still charted.
+    ///
+    /// The warmup samples are the oldest ones, so this is the oldest sample in the stats until
+    /// the warmup is over.
+    pub(super) fn mark_warmup(&mut self) {
+        if self.warmup < self.length {
+            self.stats.pop(self.samples()[self.warmup]);
+            self.warmup += 1;
+            self.refresh_stats();
+        }
+    }
+
+    /// Whether a sample stored at `now` is still part of the `warmup`.
+    pub(super) fn in_warmup(&self, warmup: Option<Warmup>, now: Instant) -> bool {
+        match warmup {
+            None => false,
+            Some(Warmup::Samples(samples)) => self.stored < samples,
+            Some(Warmup::Duration(duration)) => self
+                .first_update
+                .is_none_or(|first| now.saturating_duration_since(first) < duration),
+        }
+    }
+}
+
+impl Default for DashState {
+    fn default() -> Self {
+        Self::new(200, Duration::from_secs(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn stream(values: impl IntoIterator<Item = f64>) -> DashState {
+        let mut chart = DashState::new(100, Duration::from_secs(1));
+        for value in values {
+            chart.update(value);
+        }
+        chart
+    }
+
+    #[test]
+    fn test_flat_zero_stream() {
+        assert_eq!(stream([0.0; FLAT_HINT_SAMPLES - 1]).flat_value(), None);
+        assert_eq!(stream([0.0; FLAT_HINT_SAMPLES]).flat_value(), Some(0.0));
+        let mut chart = stream([0.0; 50]);
+        chart.update(1.0);
+        assert_eq!(chart.flat_value(), None);
+    }
+
+    #[test]
+    fn test_flat_constant_stream() {
+        let chart = stream((0..10).map(f64::from).chain([42.0; FLAT_HINT_SAMPLES]));
+        assert_eq!(chart.flat_value(), Some(42.0));
+        let chart = stream((0..FLAT_HINT_SAMPLES).map(|i| (i % 2) as f64));
+        assert_eq!(chart.flat_value(), None);
+    }
+
+    #[test]
+    fn test_shrink_keeps_newest() {
+        let mut chart = DashState::new(5, Duration::from_secs(1));
+        for value in 1..=4 {
+            chart.update(value as f64);
+        }
+        chart.shrink(2);
+        assert_eq!(chart.samples(), &[3.0, 4.0]);
+        assert_eq!((chart.min_value, chart.max_value), (3.0, 4.0));
+        assert_eq!(chart.data.capacity(), SampleWindow::new(2).capacity());
+    }
+
+    #[test]
+    fn test_grow_keeps_samples() {
+        let mut chart = DashState::new(3, Duration::from_secs(1));
+        for value in 1..=4 {
+            chart.update(value as f64);
+        }
+        chart.grow(6);
+        assert_eq!(chart.samples(), &[2.0, 3.0, 4.0]);
+        chart.update(5.0);
+        assert_eq!(chart.samples(), &[2.0, 3.0, 4.0, 5.0]);
+        chart.grow(2);
+        assert_eq!(chart.data.size(), 6);
+    }
+
+    #[test]
+    fn test_arrival_times() {
+        let start = Instant::now();
+        let mut chart = DashState::new(3, Duration::from_secs(1));
+        chart.arrival_times = Some(SampleWindow::new(3));
+        chart.first_update = Some(start);
+        for (value, millis) in [(1.0, 0), (2.0, 100), (3.0, 2500), (4.0, 2600)] {
+            chart.update_at(value, start + Duration::from_millis(millis));
+        }
+        assert_eq!(chart.times(), Some(&[0.1, 2.5, 2.6][..]));
+        let newest = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+        assert_eq!(
+            chart.sample_time(newest, 2),
+            newest - Duration::from_millis(2500)
+        );
+        chart.grow(5);
+        chart.update_at(5.0, start + Duration::from_secs(4));
+        assert_eq!(chart.times(), Some(&[0.1, 2.5, 2.6, 4.0][..]));
+        chart.shrink(2);
+        assert_eq!(chart.times(), Some(&[2.6, 4.0][..]));
+        assert_eq!(chart.samples(), &[4.0, 5.0]);
+    }
+
+    #[test]
+    fn test_session_outlives_history() {
+        let mut chart = DashState::new(10, Duration::from_secs(1));
+        for value in 1..=1000 {
+            chart.update(value as f64);
+            chart.record_session(value as f64);
+        }
+        assert_eq!(
+            chart.scoped_stats(StatsScope::Window),
+            (995.5, 991.0, 1000.0)
+        );
+        assert_eq!(
+            chart.scoped_stats(StatsScope::Session),
+            (500.5, 1.0, 1000.0)
+        );
+        let median = chart.session.percentile(50.0).unwrap();
+        assert!((median - 500.0).abs() <= 5.0, "{median}");
+        chart.shrink(5);
+        assert_eq!(chart.session.count(), 1000);
+        chart.clear();
+        assert_eq!(chart.session.count(), 0);
+    }
+}
@@ -0,0 +1,100 @@
+/// Bar height of the top of a normalized series, so normalized values keep their resolution when
+/// they are turned into the integer heights of the bar chart.
+pub const STEPS: f64 = 1000.0;
+
+/// Share of the range left below the lowest value of a `--y-from min` chart, so its lowest bar
+/// still shows.
+const ZOOM_MARGIN: f64 = 0.05;
+
+/// The lowest and highest value in the window, `None` when it holds no values.
+pub fn window_range(values: &[Option<f64>]) -> Option<(f64, f64)> {
+    values.iter().flatten().fold(None, |range, &value| {
+        let (min, max) = range.unwrap_or((value, value));
+        Some((min.min(value), max.max(value)))
+    })
+}
+
+/// The range a `--y-from min` chart zooms onto, the window and the reference so the reference
+/// stays in view. `None` for a flat or empty window, there is nothing to zoom onto.
+pub fn zoom_range(values: &[Option<f64>], reference: Option<f64>) -> Option<(f64, f64)> {
+    let (min, max) = window_range(values)?;
+    let (min, max) = match reference {
+        Some(reference) => (min.min(reference), max.max(reference)),
+        None => (min, max),
+    };
+    (max > min).then_some((min, max))
+}
+
+/// The bar height of `value` out of [`STEPS`] on a chart zoomed onto `(min, max)`.
+pub fn zoomed(value: f64, (min, max): (f64, f64)) -> f64 {
+    let floor = min - (max - min) * ZOOM_MARGIN;
+    (value - floor) / (max - floor) * STEPS
+}
+
+/// Rescale `values` into 0..=1 between their own lowest and highest value in the window, so
+/// series of different magnitudes can be compared by shape. A flat series sits in the middle.
+pub fn normalize(values: &[Option<f64>]) -> Vec<Option<f64>> {
+    let Some((min, max)) = window_range(values) else {
+        return values.to_vec();
+    };
+    values
+        .iter()
+        .map(|value| {
+            value.map(|value| {
+                if max > min {
+                    (value - min) / (max - min)
+                } else {
+                    0.5
+                }
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn range() {
+        assert_eq!(window_range(&[]), None);
+        assert_eq!(window_range(&[None, None]), None);
+        assert_eq!(
+            window_range(&[Some(3.0), None, Some(-1.0), Some(7.5)]),
+            Some((-1.0, 7.5))
+        );
+    }
+
+    #[test]
+    fn normalized() {
+        assert_eq!(
+            normalize(&[Some(100.0), None, Some(300.0), Some(200.0)]),
+            [Some(0.0), None, Some(1.0), Some(0.5)]
+        );
+        assert_eq!(
+            normalize(&[Some(0.25), Some(0.75)]),
+            normalize(&[Some(2500.0), Some(7500.0)])
+        );
+    }
+
+    #[test]
+    fn zoom() {
+        let values = [Some(3.30), None, Some(3.45), Some(3.40)];
+        let range = zoom_range(&values, None).unwrap();
+        assert_eq!(range, (3.30, 3.45));
+        // the lowest value keeps a sliver of a bar, the highest fills the chart
+        assert!((zoomed(3.30, range) - STEPS / 21.0).abs() < 1e-6);
+        assert!((zoomed(3.45, range) - STEPS).abs() < 1e-6);
+        assert_eq!(zoom_range(&values, Some(5.0)), Some((3.30, 5.0)));
+        assert_eq!(zoom_range(&[Some(2.0), Some(2.0)], None), None);
+        assert_eq!(zoom_range(&[None], Some(1.0)), None);
+    }
+
+    #[test]
+    fn flat_and_empty() {
+        assert_eq!(normalize(&[Some(4.0), Some(4.0)]), [Some(0.5), Some(0.5)]);
+        assert_eq!(normalize(&[None]), [None]);
+    }
+}
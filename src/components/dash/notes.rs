@@ -0,0 +1,114 @@
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use ratatui::{prelude::*, Frame};
+use tracing::warn;
+
+use super::{DashState, DashView};
+use crate::{
+    action::Action,
+    clock::{format_clock, unix_seconds},
+    summary::Note,
+};
+
+impl DashView {
+    /// Record a note at the current time, and append it to `--notes-file`.
+    pub(super) fn add_note(&self, text: String) {
+        let text = text.trim().to_string();
+        if text.is_empty() {
+            return;
+        }
+        let time = self.wall_now();
+        if let Some(path) = &self.notes_file {
+            let line = format!("{}\t{}\t{text}\n", unix_seconds(time), format_clock(time));
+            let written = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| file.write_all(line.as_bytes()));
+            if let Err(err) = written {
+                let message = format!("Failed to append the note to {}: {err}", path.display());
+                warn!("{message}");
+                if let Some(tx) = &self.command_tx {
+                    let _ = tx.send(Action::Toast(message));
+                }
+            }
+        }
+        let time = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        self.model.notes.lock().unwrap().push(Note {
+            time: time.as_secs_f64(),
+            text,
+        });
+    }
+
+    /// The column of `chart`, counted from the newest one, showing the sample taken at `time`.
+    /// A note newer than the newest sample sits on it.
+    fn time_column(&self, chart: &DashState, time: SystemTime) -> Option<usize> {
+        let newest = chart.newest_time(self.instant(), self.wall_now())?;
+        let back = newest.duration_since(time).unwrap_or_default();
+        // a note keeps its time in float seconds since the epoch, a few hundred nanoseconds off,
+        // so a note taken with a sample mustn't fall behind it
+        let samples = (back.as_secs_f64() / chart.interval.as_secs_f64() + 1e-6) as usize;
+        if samples >= chart.length {
+            return None;
+        }
+        let samples = samples.checked_sub(self.chart_offset(chart.interval))?;
+        Some(samples / self.samples_per_column())
+    }
+
+    /// The text of the notes in column `column` of `chart`.
+    pub(super) fn notes_at(&self, chart: &DashState, column: usize) -> Vec<String> {
+        self.model
+            .notes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|note| self.time_column(chart, note_time(note)) == Some(column))
+            .map(|note| note.text.clone())
+            .collect()
+    }
+
+    /// Mark the notes on the bottom border of a chart of `columns` columns, each
+    /// `column_width` cells wide, timed like `chart`.
+    pub(super) fn draw_note_markers(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        chart: &DashState,
+        columns: usize,
+        column_width: usize,
+    ) {
+        let inner = area.inner(Margin::new(1, 1));
+        let notes = self.model.notes.lock().unwrap();
+        for note in notes.iter() {
+            let Some(column) = self.time_column(chart, note_time(note)) else {
+                continue;
+            };
+            if column >= columns {
+                continue;
+            }
+            let position = if self.newest_left() {
+                column
+            } else {
+                columns - 1 - column
+            };
+            let x = inner.x as usize + position * column_width;
+            if x >= inner.right() as usize {
+                continue;
+            }
+            let cell = &mut frame.buffer_mut()[(x as u16, area.bottom() - 1)];
+            // never cover a time label
+            if matches!(cell.symbol(), "─" | "┴") {
+                cell.set_symbol(&self.theme.note_marker)
+                    .set_style(self.theme.note);
+            }
+        }
+    }
+}
+
+fn note_time(note: &Note) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs_f64(note.time)
+}
@@ -0,0 +1,161 @@
+use std::{
+    fmt::Write,
+    io::{BufRead, IsTerminal},
+};
+
+use color_eyre::Result;
+use crossterm::style::{Attribute, Attributes, Color as CColor, ContentStyle, StyledContent};
+use ratatui::{backend::TestBackend, buffer::Buffer, prelude::*, Terminal};
+
+use super::{chart_title, dense::supports_color, simulate::frame_text, DashView};
+
+impl DashView {
+    /// Chart all of `input` and draw a single frame of `size` for `--once`, followed by a line
+    /// of stats per chart. The frame keeps its colors when stdout is a terminal that supports
+    /// them.
+    pub fn once(&mut self, input: impl BufRead, size: Size) -> Result<String> {
+        self.replay(input, None, |_| Ok(()))?;
+        let mut terminal = Terminal::new(TestBackend::new(size.width, size.height))?;
+        self.draw_frame(&mut terminal)?;
+        let buffer = terminal.backend().buffer();
+        let mut text = if std::io::stdout().is_terminal() && supports_color() {
+            frame_ansi(buffer)
+        } else {
+            frame_text(buffer)
+        };
+        for line in self.stats_lines() {
+            text += &line;
+            text.push('\n');
+        }
+        Ok(text)
+    }
+
+    /// `latency: last 12 ms, avg 10.50 ms, min 9 ms, max 12 ms, 4 samples` for every chart.
+    fn stats_lines(&self) -> Vec<String> {
+        let state = self.model.state.read().unwrap();
+        state
+            .iter()
+            .enumerate()
+            .map(|(i, chart)| {
+                let title = chart_title(&self.titles, &state, i);
+                if !chart.has_stats() {
+                    return format!("{title}: no samples");
+                }
+                let value = |value| {
+                    let (value, unit) = self.scaled(value, 2, &chart.unit);
+                    format!("{value} {unit}").trim_end().to_string()
+                };
+                let last = chart.last().map(value).unwrap_or_default();
+                let (average, min, max) = chart.scoped_stats(self.stats_scope);
+                format!(
+                    "{title}: last {last}, avg {}, min {}, max {}, {} samples",
+                    value(average),
+                    value(min),
+                    value(max),
+                    chart.length
+                )
+            })
+            .collect()
+    }
+}
+
+/// The frame with its colors and modifiers as ANSI escape sequences, one line per row.
+pub fn frame_ansi(buffer: &Buffer) -> String {
+    let mut text = String::new();
+    for y in 0..buffer.area.height {
+        // cells of the same style are written as one run
+        let mut run = String::new();
+        let mut run_style = Style::default();
+        for x in 0..buffer.area.width {
+            let cell = &buffer[(x, y)];
+            let style = cell.style();
+            if style != run_style && !run.is_empty() {
+                let _ = write!(text, "{}", styled(&run, run_style));
+                run.clear();
+            }
+            run_style = style;
+            run.push_str(cell.symbol());
+        }
+        let _ = write!(text, "{}", styled(&run, run_style));
+        text.push('\n');
+    }
+    text
+}
+
+fn styled(content: &str, style: Style) -> StyledContent<&str> {
+    let modifiers = [
+        (Modifier::BOLD, Attribute::Bold),
+        (Modifier::DIM, Attribute::Dim),
+        (Modifier::ITALIC, Attribute::Italic),
+        (Modifier::UNDERLINED, Attribute::Underlined),
+        (Modifier::REVERSED, Attribute::Reverse),
+        (Modifier::CROSSED_OUT, Attribute::CrossedOut),
+    ];
+    let mut attributes = Attributes::default();
+    for (modifier, attribute) in modifiers {
+        if style.add_modifier.contains(modifier) {
+            attributes.set(attribute);
+        }
+    }
+    let color = |color: Option<Color>| {
+        color
+            .filter(|&color| color != Color::Reset)
+            .map(CColor::from)
+    };
+    StyledContent::new(
+        ContentStyle {
+            foreground_color: color(style.fg),
+            background_color: color(style.bg),
+            underline_color: None,
+            attributes,
+        },
+        content,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::cli::Cli;
+
+    fn once(args: &[&str], input: &str) -> String {
+        let mut dash =
+            DashView::new(Cli::parse_from([&["ttydash", "--once"], args].concat())).unwrap();
+        dash.once(input.as_bytes(), Size::new(40, 8)).unwrap()
+    }
+
+    #[test]
+    fn test_frame_and_stats() {
+        let text = once(
+            &["-u", "ms", "-t", "latency"],
+            "took 12ms\nnothing\ntook 9ms\n",
+        );
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 9);
+        assert!(lines[0].starts_with('╭'));
+        assert!(lines[7].starts_with('╰'));
+        assert_eq!(
+            lines[8],
+            "latency: last 9.00 ms, avg 10.50 ms, min 9.00 ms, max 12.00 ms, 2 samples"
+        );
+    }
+
+    #[test]
+    fn test_no_samples() {
+        assert!(once(&[], "").ends_with("Chart 1: no samples\n"));
+    }
+
+    #[test]
+    fn test_ansi() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 4, 1));
+        buffer.set_string(0, 0, "ab", Style::new().fg(Color::Green));
+        buffer.set_string(2, 0, "c", Style::new().dim());
+        assert_eq!(
+            frame_ansi(&buffer),
+            "\x1b[38;5;2mab\x1b[39m\x1b[2mc\x1b[0m \n"
+        );
+    }
+}
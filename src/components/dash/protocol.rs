@@ -0,0 +1,188 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricKind {
+    /// The latest value wins.
+    Gauge,
+    /// Increments, summed over every update interval.
+    Counter,
+    /// Individual measurements, averaged over every update interval.
+    Timer,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metric {
+    pub name: String,
+    pub value: f64,
+    pub kind: MetricKind,
+    /// Seconds since the epoch, as sent by Graphite.
+    pub timestamp: Option<f64>,
+    /// A StatsD gauge update relative to the current value (`+3`, `-1`).
+    pub relative: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    Malformed,
+    UnknownType(String),
+}
+
+/// Parse a Graphite plaintext line: `metric.path value [timestamp]`.
+pub fn parse_graphite(line: &str) -> Result<Metric, ParseError> {
+    let mut parts = line.split_whitespace();
+    let (Some(name), Some(value)) = (parts.next(), parts.next()) else {
+        return Err(ParseError::Malformed);
+    };
+    let value = value.parse::<f64>().map_err(|_| ParseError::Malformed)?;
+    let timestamp = match parts.next() {
+        Some(timestamp) => Some(
+            timestamp
+                .parse::<f64>()
+                .map_err(|_| ParseError::Malformed)?,
+        ),
+        None => None,
+    };
+    Ok(Metric {
+        name: name.to_string(),
+        value,
+        kind: MetricKind::Gauge,
+        timestamp,
+        relative: false,
+    })
+}
+
+/// Parse a StatsD line: `name:value|type[|@sample_rate]`, where a line may carry several
+/// `value|type` sections for the same name separated by `:`.
+pub fn parse_statsd(line: &str) -> Vec<Result<Metric, ParseError>> {
+    let line = line.trim();
+    let Some((name, rest)) = line.split_once(':') else {
+        return vec![Err(ParseError::Malformed)];
+    };
+    if name.is_empty() {
+        return vec![Err(ParseError::Malformed)];
+    }
+    rest.split(':')
+        .map(|section| parse_statsd_section(name, section))
+        .collect()
+}
+
+fn parse_statsd_section(name: &str, section: &str) -> Result<Metric, ParseError> {
+    let mut fields = section.split('|');
+    let (Some(raw_value), Some(kind)) = (fields.next(), fields.next()) else {
+        return Err(ParseError::Malformed);
+    };
+    let mut value = raw_value
+        .parse::<f64>()
+        .map_err(|_| ParseError::Malformed)?;
+    let kind = match kind {
+        "g" => MetricKind::Gauge,
+        "c" => MetricKind::Counter,
+        "ms" | "h" | "d" => MetricKind::Timer,
+        other => return Err(ParseError::UnknownType(other.to_string())),
+    };
+    for field in fields {
+        if let Some(rate) = field.strip_prefix('@') {
+            let rate = rate.parse::<f64>().map_err(|_| ParseError::Malformed)?;
+            // a sampled counter only reports a fraction of the increments
+            if kind == MetricKind::Counter && rate > 0.0 {
+                value /= rate;
+            }
+        }
+    }
+    Ok(Metric {
+        name: name.to_string(),
+        value,
+        kind,
+        timestamp: None,
+        relative: kind == MetricKind::Gauge && raw_value.starts_with(['+', '-']),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// Captured from collectd's write_graphite plugin.
+    const COLLECTD: &str = "\
+web01.cpu-0.cpu-idle 97.8 1727712000
+web01.cpu-0.cpu-user 1.6 1727712000
+web01.memory.memory-used 2147483648 1727712000
+web01.load.load.shortterm 0.15 1727712010
+";
+
+    /// Captured from a statsd client in a web application.
+    const STATSD: &str = "\
+api.requests:1|c
+api.requests:1|c|@0.5
+api.latency:320|ms
+api.queue.depth:12|g
+api.queue.depth:-2|g
+api.users:1234|s
+api.bytes:128|c:256|c
+";
+
+    #[test]
+    fn test_graphite_collectd() {
+        let metrics = COLLECTD
+            .lines()
+            .map(parse_graphite)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(metrics.len(), 4);
+        assert_eq!(metrics[0].name, "web01.cpu-0.cpu-idle");
+        assert_eq!(metrics[0].value, 97.8);
+        assert_eq!(metrics[0].timestamp, Some(1727712000.0));
+        assert_eq!(metrics[2].value, 2147483648.0);
+        assert!(metrics.iter().all(|m| m.kind == MetricKind::Gauge));
+    }
+
+    #[test]
+    fn test_graphite_without_timestamp() {
+        let metric = parse_graphite("foo.bar 42").unwrap();
+        assert_eq!(metric.timestamp, None);
+    }
+
+    #[test]
+    fn test_graphite_malformed() {
+        assert_eq!(parse_graphite("foo.bar"), Err(ParseError::Malformed));
+        assert_eq!(parse_graphite("foo.bar abc 1"), Err(ParseError::Malformed));
+        assert_eq!(parse_graphite("foo.bar 1 abc"), Err(ParseError::Malformed));
+    }
+
+    #[test]
+    fn test_statsd() {
+        let metrics = STATSD.lines().flat_map(parse_statsd).collect::<Vec<_>>();
+        assert_eq!(metrics.len(), 8);
+
+        let counter = metrics[0].as_ref().unwrap();
+        assert_eq!(counter.kind, MetricKind::Counter);
+        assert_eq!(counter.value, 1.0);
+        // sampled at 50%, so it stands for two increments
+        assert_eq!(metrics[1].as_ref().unwrap().value, 2.0);
+
+        let timer = metrics[2].as_ref().unwrap();
+        assert_eq!(timer.kind, MetricKind::Timer);
+        assert_eq!(timer.value, 320.0);
+
+        let gauge = metrics[3].as_ref().unwrap();
+        assert_eq!((gauge.kind, gauge.relative), (MetricKind::Gauge, false));
+        let delta = metrics[4].as_ref().unwrap();
+        assert_eq!((delta.value, delta.relative), (-2.0, true));
+
+        assert_eq!(metrics[5], Err(ParseError::UnknownType("s".to_string())));
+
+        let bytes = metrics[6..]
+            .iter()
+            .map(|m| m.as_ref().unwrap().value)
+            .collect::<Vec<_>>();
+        assert_eq!(bytes, vec![128.0, 256.0]);
+    }
+
+    #[test]
+    fn test_statsd_malformed() {
+        assert_eq!(parse_statsd("no colon"), vec![Err(ParseError::Malformed)]);
+        assert_eq!(parse_statsd(":1|c"), vec![Err(ParseError::Malformed)]);
+        assert_eq!(parse_statsd("a:1"), vec![Err(ParseError::Malformed)]);
+        assert_eq!(parse_statsd("a:x|c"), vec![Err(ParseError::Malformed)]);
+    }
+}
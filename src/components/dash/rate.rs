@@ -0,0 +1,147 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// Turns a monotonically increasing counter into a per-second rate.
+///
+/// Without a window the rate is the delta between two adjacent samples. With a window the rate
+/// is the total increase over all samples read within the window divided by the time they span,
+/// which smooths out jitter in the producer's timing.
+///
+/// A value lower than its predecessor is treated as a counter reset: the window is split at the
+/// reset and the new value counts as the increase since the counter restarted from zero.
+#[derive(Debug, Clone)]
+pub struct Rate {
+    window: Option<Duration>,
+    samples: VecDeque<(Instant, f64)>,
+}
+
+impl Rate {
+    pub fn new(window: Option<Duration>) -> Self {
+        Self {
+            window,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Record a counter value read at `at` and return the current rate, or `None` until enough
+    /// samples have been seen to compute one.
+    pub fn push(&mut self, at: Instant, value: f64) -> Option<f64> {
+        self.samples.push_back((at, value));
+        match self.window {
+            Some(window) => {
+                // keep one sample at or beyond the window edge so the span covers the window
+                while self.samples.len() > 2 && at.duration_since(self.samples[1].0) >= window {
+                    self.samples.pop_front();
+                }
+            }
+            None => {
+                while self.samples.len() > 2 {
+                    self.samples.pop_front();
+                }
+            }
+        }
+        let (oldest, _) = *self.samples.front()?;
+        let elapsed = at.duration_since(oldest).as_secs_f64();
+        if self.samples.len() < 2 || elapsed <= 0.0 {
+            return None;
+        }
+        let increase: f64 = self
+            .samples
+            .iter()
+            .zip(self.samples.iter().skip(1))
+            .map(|(&(_, previous), &(_, current))| {
+                if current >= previous {
+                    current - previous
+                } else {
+                    current
+                }
+            })
+            .sum();
+        Some(increase / elapsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::super::test_util::parse;
+    use super::*;
+
+    fn feed(rate: &mut Rate, samples: &[(u64, f64)]) -> Vec<Option<f64>> {
+        let start = Instant::now();
+        samples
+            .iter()
+            .map(|&(ms, value)| rate.push(start + Duration::from_millis(ms), value))
+            .collect()
+    }
+
+    #[test]
+    fn test_adjacent_samples() {
+        let mut rate = Rate::new(None);
+        let rates = feed(&mut rate, &[(0, 10.0), (1000, 20.0), (1500, 30.0)]);
+        assert_eq!(rates, vec![None, Some(10.0), Some(20.0)]);
+    }
+
+    #[test]
+    fn test_window_smooths_irregular_intervals() {
+        let mut rate = Rate::new(Some(Duration::from_secs(2)));
+        // a steady 10/s counter read with jittery timing
+        let rates = feed(
+            &mut rate,
+            &[
+                (0, 0.0),
+                (700, 7.0),
+                (1000, 10.0),
+                (1900, 19.0),
+                (2000, 20.0),
+            ],
+        );
+        for rate in rates.into_iter().flatten() {
+            assert!((rate - 10.0).abs() < 1e-9, "rate was {rate}");
+        }
+    }
+
+    #[test]
+    fn test_window_drops_old_samples() {
+        let mut rate = Rate::new(Some(Duration::from_secs(1)));
+        let rates = feed(&mut rate, &[(0, 0.0), (1000, 100.0), (2000, 110.0)]);
+        assert_eq!(rates[2], Some(10.0));
+    }
+
+    #[test]
+    fn test_counter_reset_within_window() {
+        let mut rate = Rate::new(Some(Duration::from_secs(10)));
+        // 0 -> 30 before the reset, then 0 -> 5 after it
+        let rates = feed(
+            &mut rate,
+            &[
+                (0, 0.0),
+                (1000, 10.0),
+                (2000, 30.0),
+                (3000, 2.0),
+                (4000, 5.0),
+            ],
+        );
+        assert_eq!(rates[4], Some((30.0 + 2.0 + 3.0) / 4.0));
+    }
+
+    #[test]
+    fn test_window_option() {
+        let args = parse(&["--rate", "--rate-window", "10"]).unwrap();
+        assert_eq!(args.rate_window, Some(Duration::from_secs(10)));
+        for window in ["-1", "nan", "0", "99999999999999999999"] {
+            let arg = format!("--rate-window={window}");
+            assert!(parse(&["--rate", &arg]).is_err(), "{window}");
+        }
+    }
+
+    #[test]
+    fn test_counter_reset_adjacent() {
+        let mut rate = Rate::new(None);
+        let rates = feed(&mut rate, &[(0, 50.0), (500, 4.0)]);
+        assert_eq!(rates[1], Some(8.0));
+    }
+}
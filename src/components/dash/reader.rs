@@ -0,0 +1,1832 @@
+//! The reader side of the dashboard. A [`SourceReader`] runs in its own task: it reads stdin,
+//! the `--file` files or the `ssh:` sources, takes the values apart with the extractors or the
+//! Graphite, StatsD and `--count-by` parsers, and stores them into the [`ChartModel`] it shares
+//! with the view. It tells the view which charts changed with `Action::DataUpdated`, and
+//! reports what went wrong with toasts; it never draws anything.
+
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fs::File,
+    io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, Instant, UNIX_EPOCH},
+};
+
+use color_eyre::{eyre::eyre, Result};
+use regex::Regex;
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt, BufReader, Lines},
+    sync::mpsc::UnboundedSender,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, warn};
+
+use super::{
+    chart_title,
+    diagnostics::{never_matched_message, MatchCounts},
+    extract::{extractor, ValueExtractor},
+    guard::{InputGuard, TokenBucket},
+    header::{Feed, HeaderDetector},
+    model::{ChartModel, DashState},
+    protocol::{parse_graphite, parse_statsd, Metric, MetricKind, ParseError},
+    rate::Rate,
+    rolling::SampleWindow,
+};
+use crate::{
+    action::Action,
+    backfill::{read_tail, stdin_file, Backfill},
+    cli::{self, Cli},
+    memory::{self, format_size},
+    number::quantize,
+    positional::{Given, Mismatch, Positional},
+    source::{Cadence, Source, Warmup},
+    summary::Note,
+};
+
+/// The values of a line as `(chart index, value)` pairs, and the charts whose match wasn't a
+/// number.
+pub(super) type Extracted = (Vec<(usize, f64)>, Vec<usize>);
+
+/// The lines of the `--input` file or of stdin.
+type InputLines = Lines<Box<dyn AsyncBufRead + Send + Unpin>>;
+
+/// Reader-side bookkeeping the renderer never needs to see.
+#[derive(Debug, Default)]
+pub(super) struct ReaderState {
+    pub(super) rates: Vec<Rate>,
+    pub(super) reported: HashSet<&'static str>,
+    /// Chart index of every named metric.
+    pub(super) names: HashMap<String, usize>,
+    /// Newest Graphite timestamp stored for every chart.
+    pub(super) timestamps: HashMap<usize, f64>,
+    /// StatsD values collected during the current update interval.
+    pub(super) pending: HashMap<usize, Pending>,
+    /// Charts that got a sample since the last `Action::DataUpdated` was sent.
+    pub(super) updated: BTreeSet<usize>,
+    /// The `throttle=` of every chart that has one.
+    pub(super) throttles: HashMap<usize, TokenBucket>,
+    /// The previous value received for every chart, before `--rate`, for `--dedupe`.
+    pub(super) last_values: HashMap<usize, f64>,
+    /// The previous value extracted for every chart, for `--on-parse-error last`.
+    pub(super) extracted: HashMap<usize, f64>,
+    /// Set with `--header` or `--fields` on whitespace separated numbers.
+    pub(super) header: Option<HeaderDetector>,
+    /// The unit glued to the first value of every chart that came with one, like `42ms`.
+    pub(super) suffixes: HashMap<usize, String>,
+    /// Charts that were warned about a value with another unit than the first.
+    pub(super) suffix_warned: HashSet<usize>,
+    /// Lines are read as they arrive, for the `cadence=arrival` charts, see
+    /// [`SourceReader::store_or_hold`].
+    pub(super) hold: bool,
+    /// The newest value of every interval chart while lines are read as they arrive, stored
+    /// at the next update interval.
+    pub(super) held: HashMap<usize, f64>,
+}
+
+/// A StatsD metric being aggregated until the next update interval.
+#[derive(Debug, Clone, Copy)]
+pub(super) enum Pending {
+    Gauge(f64),
+    Counter(f64),
+    Timer { sum: f64, count: usize },
+}
+
+/// Reads the input and stores its values into the model. Cloned into every task reading a
+/// source, the clones share the model and the stop signal.
+#[derive(Debug, Clone)]
+pub struct SourceReader {
+    pub(super) model: ChartModel,
+    pub(super) command_tx: Option<UnboundedSender<Action>>,
+    pub(super) update_frequency: u64,
+    pub(super) history: usize,
+    pub(super) format: cli::Format,
+    /// Takes the values of a line apart for the number and bool formats. Shared by the clones,
+    /// so the one rebuilt on a config reload reaches the running readers.
+    extractor: Arc<RwLock<Arc<dyn ValueExtractor>>>,
+    pub(super) units: Vec<String>,
+    pub(super) indices: Option<Vec<usize>>,
+    pub(super) header: cli::Header,
+    pub(super) fields: Option<Vec<String>>,
+    /// Counted lines are routed by what this captures, with `--count-by`.
+    pub(super) count_by: Option<Regex>,
+    /// Charts `--count-by` creates before folding further values into `other`.
+    pub(super) max_groups: usize,
+    pub(super) sources: Positional<Source>,
+    /// For the messages about a failing source.
+    pub(super) titles: Positional<String>,
+    pub(super) descriptions: Positional<String>,
+    /// `--quantize` steps.
+    pub(super) quantize: Positional<f64>,
+    /// `--file` paths and patterns.
+    pub(super) files: Vec<String>,
+    /// `--input`, `None` for stdin.
+    pub(super) input: Option<PathBuf>,
+    pub(super) loop_input: bool,
+    /// `--exec`, run every update interval instead of reading stdin.
+    pub(super) exec: Option<String>,
+    /// Bound to the `--listen` address before the terminal is taken over, so a taken port is
+    /// reported on the plain screen.
+    pub(super) listener: Option<Arc<std::net::TcpListener>>,
+    pub(super) per_connection: bool,
+    /// Bound to the `--listen-udp` address before the terminal is taken over, like `listener`.
+    pub(super) udp_socket: Option<Arc<std::net::UdpSocket>>,
+    /// Created at the `--socket` path before the terminal is taken over, like `listener`.
+    #[cfg(unix)]
+    pub(super) socket: Option<Arc<super::unix_socket::SocketFile>>,
+    /// How many values every per-chart option was given, checked as charts appear.
+    pub(super) given: Vec<Given>,
+    pub(super) rate: bool,
+    pub(super) rate_window: Option<Duration>,
+    pub(super) dedupe: bool,
+    pub(super) mark_transitions: bool,
+    pub(super) on_parse_error: cli::OnParseError,
+    pub(super) warmup: Option<Warmup>,
+    pub(super) strict: bool,
+    pub(super) max_memory: Option<usize>,
+    pub(super) backfill: Option<Backfill>,
+    pub(super) max_input_rate: usize,
+    pub(super) quiet_diagnostics: bool,
+    /// `--restart-on-failure`, with the most restarts if limited.
+    pub(super) restart_on_failure: Option<Option<u32>>,
+    pub(super) stop_signal: Arc<AtomicBool>,
+    pub(super) cancellation_token: CancellationToken,
+}
+
+impl SourceReader {
+    /// A reader for the input `args` describe, storing into `model`, which gets the first chart.
+    pub fn new(args: &Cli, model: ChartModel) -> Result<Self> {
+        let units = args.units.clone().unwrap_or_default();
+        let format = match args.count_by {
+            Some(_) => cli::Format::Count,
+            None => args.format,
+        };
+        if let (Some(Backfill::Duration(_)), false) =
+            (args.backfill, format == cli::Format::Graphite)
+        {
+            return Err(eyre!(
+                "--backfill with a duration needs timestamps, which only --format graphite has; \
+                 give a number of lines instead"
+            ));
+        }
+        // bool tokens come with the config, see `DashView::register_config_handler`
+        let extractor: Arc<dyn ValueExtractor> =
+            extractor(&units, args.indices.clone(), None)?.into();
+        *model.match_counts.lock().unwrap() =
+            MatchCounts::new(extractor_names(format, &*extractor));
+        let titles = Positional::new("titles", args.titles.clone());
+        let descriptions = Positional::new("descriptions", args.descriptions.clone());
+        let quantize = Positional::new("quantize", args.quantize.clone());
+        let sources = Positional::new("sources", args.sources.clone());
+        let given = vec![
+            titles.given(),
+            descriptions.given(),
+            Positional::new("colors", args.colors.clone()).given(),
+            Positional::new("references", args.references.clone()).given(),
+            quantize.given(),
+            sources.given(),
+        ];
+        let mut reader = Self {
+            model,
+            command_tx: None,
+            // the samples of `--exec` come every `--interval`
+            update_frequency: args.interval.unwrap_or(args.update_frequency),
+            history: args.history.samples(),
+            format,
+            extractor: Arc::new(RwLock::new(extractor)),
+            units,
+            indices: args.indices.clone(),
+            header: args.header,
+            fields: args.fields.clone(),
+            count_by: args.count_by.clone(),
+            max_groups: args.max_groups as usize,
+            sources,
+            titles,
+            descriptions,
+            quantize,
+            files: args.files.clone(),
+            input: args.input.clone().filter(|path| path != Path::new("-")),
+            loop_input: args.loop_input,
+            exec: args.exec.clone(),
+            listener: None,
+            per_connection: args.per_connection,
+            udp_socket: None,
+            #[cfg(unix)]
+            socket: None,
+            given,
+            rate: args.rate,
+            rate_window: args.rate_window,
+            dedupe: args.dedupe,
+            mark_transitions: args.mark_transitions,
+            on_parse_error: args.on_parse_error,
+            warmup: args.warmup,
+            strict: args.strict,
+            max_memory: args.max_memory,
+            backfill: args.backfill,
+            max_input_rate: args.max_input_rate,
+            quiet_diagnostics: args.quiet_diagnostics,
+            restart_on_failure: args.restart_on_failure,
+            stop_signal: Arc::new(AtomicBool::new(false)),
+            cancellation_token: CancellationToken::new(),
+        };
+        if !reader.files.is_empty() && reader.has_ssh_sources() {
+            return Err(eyre!("--file can't be combined with ssh: sources"));
+        }
+        if reader.exec.is_some() && reader.has_ssh_sources() {
+            return Err(eyre!("--exec can't be combined with ssh: sources"));
+        }
+        if let Some(addr) = args.listen {
+            if reader.has_ssh_sources() {
+                return Err(eyre!("--listen can't be combined with ssh: sources"));
+            }
+            if reader.per_connection
+                && !matches!(reader.format, cli::Format::Number | cli::Format::Bool)
+            {
+                return Err(eyre!(
+                    "--per-connection charts the values of every connection together, metrics \
+                     get a chart per name instead"
+                ));
+            }
+            let listener = std::net::TcpListener::bind(addr)
+                .map_err(|err| eyre!("Failed to listen on {addr}: {err}"))?;
+            listener.set_nonblocking(true)?;
+            reader.listener = Some(Arc::new(listener));
+        }
+        if let Some(addr) = args.listen_udp {
+            if reader.has_ssh_sources() {
+                return Err(eyre!("--listen-udp can't be combined with ssh: sources"));
+            }
+            let socket = std::net::UdpSocket::bind(addr)
+                .map_err(|err| eyre!("Failed to listen on udp {addr}: {err}"))?;
+            socket.set_nonblocking(true)?;
+            reader.udp_socket = Some(Arc::new(socket));
+        }
+        if let Some(path) = &args.socket {
+            if reader.has_ssh_sources() {
+                return Err(eyre!("--socket can't be combined with ssh: sources"));
+            }
+            #[cfg(unix)]
+            {
+                let socket = super::unix_socket::SocketFile::bind(path)
+                    .map_err(|err| eyre!("Failed to listen on {}: {err}", path.display()))?;
+                reader.socket = Some(Arc::new(socket));
+            }
+            #[cfg(not(unix))]
+            return Err(eyre!(
+                "--socket needs Unix sockets, {} wasn't created",
+                path.display()
+            ));
+        }
+        if let Some(path) = &reader.input {
+            if reader.has_ssh_sources() {
+                return Err(eyre!("--input can't be combined with ssh: sources"));
+            }
+            File::open(path).map_err(|err| eyre!("Can't read {}: {err}", path.display()))?;
+        } else if reader.loop_input {
+            return Err(eyre!(
+                "--loop needs an --input file, stdin can't be read again"
+            ));
+        }
+        reader.check_arrival_sources()?;
+        let chart = reader.new_chart(&[]);
+        reader.model.state.write().unwrap().push(chart);
+        if let (true, Some(charts)) = (reader.strict, reader.expected_charts()) {
+            if let Some(mismatch) = reader.positional_mismatches(charts).into_iter().next() {
+                return Err(eyre!("{mismatch}"));
+            }
+        }
+        Ok(reader)
+    }
+
+    /// Fail for the `cadence=arrival` charts of options that make a sample out of several
+    /// values, an arrival chart keeps every value as it is.
+    fn check_arrival_sources(&self) -> Result<()> {
+        for (i, source) in self.sources.values().iter().enumerate() {
+            if source.cadence != Cadence::Arrival {
+                continue;
+            }
+            let conflict = if source.every.is_some() {
+                "every="
+            } else if self.rate {
+                "--rate"
+            } else if self.count_by.is_some() {
+                "--count-by"
+            } else if self.format == cli::Format::Statsd {
+                "--format statsd"
+            } else {
+                continue;
+            };
+            return Err(eyre!(
+                "Chart {} samples every value as it arrives (cadence=arrival), which can't be \
+                 combined with {conflict}",
+                i + 1
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether chart `i` takes a sample for every value as it arrives, see `cadence=arrival`.
+    pub(super) fn samples_on_arrival(&self, i: usize) -> bool {
+        self.sources
+            .get(i)
+            .is_some_and(|source| source.cadence == Cadence::Arrival)
+    }
+
+    /// Make the reader loops return before their next line, and the ones waiting for a
+    /// connection or a run return right away.
+    pub(super) fn stop(&self) {
+        self.stop_signal.store(true, Ordering::Relaxed);
+        self.cancellation_token.cancel();
+    }
+
+    /// Now, on the model's clock.
+    pub(super) fn instant(&self) -> Instant {
+        self.model.instant()
+    }
+
+    pub(super) fn base_interval(&self) -> Duration {
+        Duration::from_millis(self.update_frequency)
+    }
+    /// A chart to be appended to `state`, keeping as much history as the existing ones.
+    pub(super) fn new_chart(&self, state: &[DashState]) -> DashState {
+        let every = self
+            .sources
+            .get(state.len())
+            .and_then(|source| source.every);
+        let interval = every.unwrap_or_default().max(self.base_interval());
+        let history = state
+            .first()
+            .map_or(self.history, |chart| chart.data.size());
+        let mut chart = DashState::new(history, interval);
+        if self.samples_on_arrival(state.len()) {
+            chart.arrival_times = Some(SampleWindow::new(history));
+        }
+        if let Some(unit) = self.units.get(state.len()) {
+            chart.unit = unit.to_string();
+        }
+        if let Some(source) = self.sources.get(state.len()) {
+            chart.name = source
+                .title
+                .clone()
+                .or_else(|| source.ssh.as_ref().map(|ssh| ssh.host.clone()));
+            chart.description = source.description.clone();
+        }
+        if chart.description.is_none() {
+            chart.description = self
+                .descriptions
+                .get(state.len())
+                .filter(|description| !description.is_empty())
+                .cloned();
+        }
+        chart.auto_title = self.auto_title(state.len());
+        chart
+    }
+
+    /// A chart titled `name`, for a file or a connection: the first one nothing was charted in
+    /// yet, or a new one.
+    pub(super) fn add_named_chart(&self, name: String) -> usize {
+        let mut state = self.model.state.write().unwrap();
+        let i = state
+            .iter()
+            .position(|chart| chart.name.is_none() && chart.length == 0);
+        let i = i.unwrap_or_else(|| {
+            let chart = self.new_chart(&state);
+            state.push(chart);
+            state.len() - 1
+        });
+        state[i].set_name(name);
+        i
+    }
+
+    /// The title of chart `i` when nothing names it: its unit, numbered when the unit is given
+    /// more than once, or else its `--source` spec.
+    fn auto_title(&self, i: usize) -> Option<String> {
+        if let Some(unit) = self.units.get(i).filter(|unit| !unit.is_empty()) {
+            let same = |other: &&String| *other == unit;
+            if self.units.iter().filter(same).count() == 1 {
+                return Some(unit.clone());
+            }
+            let occurrence = self.units[..=i].iter().filter(same).count();
+            return Some(format!("{unit} #{occurrence}"));
+        }
+        self.sources
+            .get(i)
+            .map(ToString::to_string)
+            .filter(|spec| !spec.is_empty())
+    }
+
+    /// Reduce the history of every chart when they would retain more than `--max-memory`,
+    /// counting at least `expected` charts.
+    pub(super) fn enforce_memory_limit(&self, state: &mut [DashState], expected: usize) {
+        let Some(limit) = self.max_memory else {
+            return;
+        };
+        let charts = state.len().max(expected);
+        let history = state
+            .first()
+            .map_or(self.history, |chart| chart.data.size());
+        if memory::estimate_bytes(charts, history) <= limit {
+            return;
+        }
+        let history = memory::history_within(limit, charts);
+        for chart in state.iter_mut() {
+            chart.shrink(history);
+        }
+        let message = format!(
+            "Memory limit of {} reached, keeping the last {history} samples per chart",
+            format_size(limit)
+        );
+        warn!("{message}");
+        if let Some(tx) = &self.command_tx {
+            let _ = tx.send(Action::Toast(message));
+        }
+    }
+
+    /// Number of charts implied by the flags, if it is known before any input is read.
+    pub(super) fn expected_charts(&self) -> Option<usize> {
+        if !self.units.is_empty() {
+            Some(self.units.len())
+        } else {
+            self.indices.as_ref().map(|indices| indices.len())
+        }
+    }
+
+    pub(super) fn positional_mismatches(&self, charts: usize) -> Vec<Mismatch> {
+        self.given
+            .iter()
+            .filter_map(|given| given.check(charts))
+            .collect()
+    }
+
+    /// Warn once per option about per-chart options that don't match the number of charts, or
+    /// fail under `--strict`.
+    pub(super) fn report_mismatches(&self, charts: usize, reported: &mut HashSet<&'static str>) {
+        for mismatch in self.positional_mismatches(charts) {
+            if !reported.insert(mismatch.name) {
+                continue;
+            }
+            let message = mismatch.to_string();
+            let action = if self.strict {
+                error!("{message}");
+                Action::Error(message)
+            } else {
+                warn!("{message}");
+                Action::Toast(message)
+            };
+            if let Some(tx) = &self.command_tx {
+                let _ = tx.send(action);
+            }
+        }
+    }
+
+    /// Store a sample into chart `i`, creating the chart if needed.
+    pub(super) fn store(
+        &self,
+        state: &mut Vec<DashState>,
+        reader: &mut ReaderState,
+        i: usize,
+        value: f64,
+        now: Instant,
+    ) {
+        while state.len() <= i {
+            state.push(self.new_chart(state));
+        }
+        if state[i].paused_since.is_some() || self.reject_non_finite(i, value) {
+            return;
+        }
+        let value = match self.quantize.get(i) {
+            _ if self.is_on_off(i) => (value != 0.0) as u8 as f64,
+            Some(&step) => quantize(value, step),
+            None => value,
+        };
+        // timestamped values are new samples as long as the timestamp moves, see `route_metric`
+        if self.dedupe
+            && !reader.timestamps.contains_key(&i)
+            && reader.last_values.insert(i, value) == Some(value)
+        {
+            *self.model.deduped.lock().unwrap().entry(i).or_default() += 1;
+            return;
+        }
+        if let Some(throttle) = self.sources.get(i).and_then(|source| source.throttle) {
+            let bucket = reader
+                .throttles
+                .entry(i)
+                .or_insert_with(|| TokenBucket::new(throttle.lines_per_second(), now));
+            if !bucket.admit(now) {
+                *self.model.throttled.lock().unwrap().entry(i).or_default() += 1;
+                return;
+            }
+        }
+        let value = if self.rate {
+            if reader.rates.len() <= i {
+                reader.rates.resize(i + 1, Rate::new(self.rate_window));
+            }
+            match reader.rates[i].push(now, value) {
+                Some(rate) if !self.reject_non_finite(i, rate) => rate,
+                _ => return,
+            }
+        } else {
+            value
+        };
+        if let Some(every) = self.sources.get(i).and_then(|source| source.every) {
+            // allow half an update of jitter so a 5s source fed every second keeps a 5s cadence
+            let due = |last: Instant| now.duration_since(last) + self.base_interval() / 2 >= every;
+            if !state[i].last_update.is_none_or(due) {
+                return;
+            }
+        }
+        if self.mark_transitions && self.is_on_off(i) {
+            self.mark_transition(state, i, value);
+        }
+        let warmup = state[i].in_warmup(self.warmup, now);
+        state[i].first_update.get_or_insert(now);
+        state[i].last_update = Some(now);
+        state[i].arrivals.record(now);
+        state[i].update_at(value, now);
+        if warmup {
+            state[i].mark_warmup();
+        } else {
+            state[i].record_session(value);
+        }
+        reader.updated.insert(i);
+    }
+
+    /// Whether chart `i` shows on/off values, with `--format bool` or `format=bool`.
+    pub(super) fn is_on_off(&self, i: usize) -> bool {
+        self.format == cli::Format::Bool || self.sources.get(i).is_some_and(|source| source.on_off)
+    }
+
+    /// Whether any chart shows on/off values, so the words for on and off are values too.
+    pub(super) fn has_on_off(&self) -> bool {
+        self.format == cli::Format::Bool || self.sources.values().iter().any(|source| source.on_off)
+    }
+
+    /// Add a note when on/off chart `i` turns on or off with `value`, for `--mark-transitions`.
+    fn mark_transition(&self, state: &[DashState], i: usize, value: f64) {
+        match state[i].last() {
+            Some(last) if last != value => {}
+            _ => return,
+        }
+        let state_name = if value > 0.0 { "on" } else { "off" };
+        let time = self.model.clock.wall().duration_since(UNIX_EPOCH);
+        self.model.notes.lock().unwrap().push(Note {
+            time: time.unwrap_or_default().as_secs_f64(),
+            text: format!("{} {state_name}", chart_title(&self.titles, state, i)),
+        });
+    }
+
+    /// Count a NaN or infinite value of chart `i` instead of storing it, where it would poison
+    /// the stats and the scale for good. Returns whether `value` was rejected.
+    pub(super) fn reject_non_finite(&self, i: usize, value: f64) -> bool {
+        if value.is_finite() {
+            return false;
+        }
+        *self.model.non_finite.lock().unwrap().entry(i).or_default() += 1;
+        true
+    }
+
+    /// Store the values extracted from a line, and for the charts in `failed`, whose match
+    /// wasn't a number, count a parse error and store what `--on-parse-error` asks for.
+    pub(super) fn store_extracted(
+        &self,
+        state: &mut Vec<DashState>,
+        reader: &mut ReaderState,
+        (values, failed): Extracted,
+        now: Instant,
+    ) {
+        for (i, value) in values {
+            reader.extracted.insert(i, value);
+            self.store_or_hold(state, reader, i, value, now);
+        }
+        for i in failed {
+            *self
+                .model
+                .parse_errors
+                .lock()
+                .unwrap()
+                .entry(i)
+                .or_default() += 1;
+            let value = match self.on_parse_error {
+                cli::OnParseError::Skip => continue,
+                cli::OnParseError::Zero => 0.0,
+                cli::OnParseError::Last => match reader.extracted.get(&i) {
+                    Some(&value) => value,
+                    None => continue,
+                },
+            };
+            self.store_or_hold(state, reader, i, value, now);
+        }
+    }
+
+    /// Store a value of chart `i`, unless lines are read as they arrive and the chart takes a
+    /// sample per update interval: then the value is held until [`Self::store_held`], a newer
+    /// one replacing it.
+    fn store_or_hold(
+        &self,
+        state: &mut Vec<DashState>,
+        reader: &mut ReaderState,
+        i: usize,
+        value: f64,
+        now: Instant,
+    ) {
+        if !reader.hold || self.samples_on_arrival(i) {
+            self.store(state, reader, i, value, now);
+            return;
+        }
+        // the chart is there as soon as its first value is
+        while state.len() <= i {
+            state.push(self.new_chart(state));
+        }
+        reader.held.insert(i, value);
+    }
+
+    /// Store the value held for every interval chart since the previous update interval.
+    fn store_held(&self, reader: &mut ReaderState) {
+        if reader.held.is_empty() {
+            return;
+        }
+        let now = self.instant();
+        let mut state = self.model.state.write().unwrap();
+        for (i, value) in std::mem::take(&mut reader.held) {
+            self.store(&mut state, reader, i, value, now);
+        }
+        drop(state);
+        self.announce_updates(reader);
+    }
+
+    /// Tell the other components which charts got a sample. Called once the state lock is
+    /// released, so whoever handles the action sees the new samples.
+    pub(super) fn announce_updates(&self, reader: &mut ReaderState) {
+        let updated = std::mem::take(&mut reader.updated);
+        if let Some(tx) = &self.command_tx {
+            for i in updated {
+                let _ = tx.send(Action::DataUpdated(i));
+            }
+        }
+    }
+
+    /// Chart one line in the `--format` of the input, whatever source it came from. Returns
+    /// whether it had a value or a metric.
+    pub(super) fn ingest(&self, line: &str, reader: &mut ReaderState) -> bool {
+        match self.format {
+            cli::Format::Number | cli::Format::Bool => self.ingest_line(line, reader),
+            cli::Format::Graphite | cli::Format::Statsd | cli::Format::Count => {
+                self.ingest_metrics(line, reader, 1)
+            }
+        }
+    }
+
+    /// Chart the values of one input line. Returns whether the line had a value for any chart.
+    pub(super) fn ingest_line(&self, line: &str, reader: &mut ReaderState) -> bool {
+        let line = normalize_line(line);
+        let now = self.instant();
+        let Some(extracted) = self.extract_values(line, reader) else {
+            return false;
+        };
+        let found = !extracted.0.is_empty() || !extracted.1.is_empty();
+        if !line.trim().is_empty() {
+            let (values, failed) = &extracted;
+            self.record_matches(line, |part| {
+                self.extractor().matched(values, part) || failed.contains(&part)
+            });
+        }
+        let glued = extracted.0.first().map(|&(i, _)| i);
+        let mut state = self.model.state.write().unwrap();
+        let charts = state.len();
+        self.store_extracted(&mut state, reader, extracted, now);
+        if let Some(i) = glued {
+            self.adopt_suffix(&mut state, reader, i, line);
+        }
+        if state.len() != charts {
+            self.report_mismatches(state.len(), &mut reader.reported);
+            self.enforce_memory_limit(&mut state, 0);
+        }
+        drop(state);
+        self.announce_updates(reader);
+        found
+    }
+
+    /// Give chart `i` the unit glued to the value of `line`, like `ms` in `42ms`, the first time
+    /// one comes. A later line with another unit is charted all the same, with a warning.
+    pub(super) fn adopt_suffix(
+        &self,
+        state: &mut [DashState],
+        reader: &mut ReaderState,
+        i: usize,
+        line: &str,
+    ) {
+        let Some(suffix) = self.extractor().suffix(line) else {
+            return;
+        };
+        let Some(chart) = state.get_mut(i) else {
+            return;
+        };
+        let Some(first) = reader.suffixes.get(&i) else {
+            if chart.unit.is_empty() {
+                chart.unit = suffix.clone();
+            }
+            reader.suffixes.insert(i, suffix);
+            return;
+        };
+        if *first != suffix && reader.suffix_warned.insert(i) {
+            let message = format!(
+                "Chart {} got a value in {suffix} after ones in {first}, its unit stays {first}",
+                i + 1
+            );
+            warn!("{message}");
+            if let Some(tx) = &self.command_tx {
+                let _ = tx.send(Action::Toast(message));
+            }
+        }
+    }
+
+    /// The values of a number or bool line, `None` for header lines.
+    pub(super) fn extract_values(&self, line: &str, reader: &mut ReaderState) -> Option<Extracted> {
+        let Some(header) = &mut reader.header else {
+            return Some(self.extractor().extract_checked(line));
+        };
+        let values = match header.feed(line) {
+            Feed::Skip => return None,
+            Feed::Plain => return Some(self.extractor().extract_checked(line)),
+            Feed::Values(values) => values,
+        };
+        let unknown = header.take_unknown_fields();
+        if !unknown.is_empty() {
+            let message = format!("--fields not in the header: {}", unknown.join(", "));
+            warn!("{message}");
+            if let Some(tx) = &self.command_tx {
+                let _ = tx.send(Action::Toast(message));
+            }
+        }
+        if let Some(titles) = header.take_titles() {
+            debug!("Found the header {titles:?}");
+            let mut state = self.model.state.write().unwrap();
+            for (i, title) in titles.into_iter().enumerate() {
+                while state.len() <= i {
+                    let chart = self.new_chart(&state);
+                    state.push(chart);
+                }
+                if state[i].name.is_none() {
+                    state[i].set_name(title);
+                }
+            }
+        }
+        Some((values, vec![]))
+    }
+
+    /// Where a reader starts, before the first line.
+    pub(super) fn new_reader(&self) -> ReaderState {
+        let mut reader = ReaderState::default();
+        if self.format == cli::Format::Number
+            && self.units.is_empty()
+            && (self.header != cli::Header::None || self.fields.is_some())
+        {
+            let mode = match (self.header, &self.fields) {
+                // names need a header to resolve against
+                (cli::Header::None, Some(_)) => cli::Header::Auto,
+                (mode, _) => mode,
+            };
+            reader.header = Some(HeaderDetector::new(
+                mode,
+                self.fields.clone(),
+                self.indices.clone(),
+            ));
+        }
+        reader
+    }
+
+    /// Read the input until the dashboard stops: the `--file` files, the `ssh:` sources, the
+    /// `--exec` command, the `--listen` connections, the `--listen-udp` datagrams, the `--input`
+    /// file or stdin.
+    pub(super) async fn run(self) {
+        let mut reader = self.new_reader();
+        if let Some(charts) = self.expected_charts() {
+            self.report_mismatches(charts, &mut reader.reported);
+        }
+        if !self.files.is_empty() {
+            self.follow_files().await;
+            return;
+        }
+        if self.has_ssh_sources() {
+            // the remote commands replace stdin
+            self.follow_ssh_sources().await;
+            return;
+        }
+        if let Some(command) = &self.exec {
+            self.run_exec(command, reader).await;
+            return;
+        }
+        if let Some(listener) = &self.listener {
+            self.accept_connections(listener, reader).await;
+            return;
+        }
+        if let Some(socket) = &self.udp_socket {
+            self.receive_datagrams(socket, reader).await;
+            return;
+        }
+        #[cfg(unix)]
+        if let Some(socket) = &self.socket {
+            self.accept_writers(socket, reader).await;
+            return;
+        }
+        let input = match &self.input {
+            Some(path) => match File::open(path) {
+                Ok(file) => Some(file),
+                Err(err) => return self.report_unreadable(path, err),
+            },
+            None => None,
+        };
+        if let Some(backfill) = self.backfill {
+            self.backfill(backfill, input.as_ref(), &mut reader);
+        }
+        let lines = input_lines(input);
+        match self.format {
+            cli::Format::Graphite | cli::Format::Statsd | cli::Format::Count => {
+                self.update_metrics(lines, reader).await
+            }
+            cli::Format::Number | cli::Format::Bool => self.update_values(lines, reader).await,
+        }
+    }
+
+    /// Whether the lines come from the writers of a `--socket`.
+    pub(super) fn has_socket(&self) -> bool {
+        #[cfg(unix)]
+        return self.socket.is_some();
+        #[cfg(not(unix))]
+        false
+    }
+
+    /// The name of the input in the info and inspector rows.
+    pub(super) fn input_name(&self) -> String {
+        if let Some(addr) = self.listen_addr() {
+            return format!("tcp {addr}");
+        }
+        if let Some(addr) = self.udp_addr() {
+            return format!("udp {addr}");
+        }
+        #[cfg(unix)]
+        if let Some(socket) = &self.socket {
+            return format!("socket {}", socket.path().display());
+        }
+        match (&self.exec, &self.input) {
+            (Some(command), _) => command.clone(),
+            (None, Some(path)) => path.display().to_string(),
+            (None, None) => "stdin".to_string(),
+        }
+    }
+
+    /// Start the `--input` file over from its first line with `--loop`. Returns whether it did.
+    fn restart_input(&self, lines: &mut InputLines) -> bool {
+        let (true, Some(path)) = (self.loop_input, &self.input) else {
+            return false;
+        };
+        match File::open(path) {
+            Ok(file) => {
+                debug!("Reading {} again from the start", path.display());
+                *lines = input_lines(Some(file));
+                true
+            }
+            Err(err) => {
+                self.report_unreadable(path, err);
+                false
+            }
+        }
+    }
+
+    fn report_unreadable(&self, path: &Path, err: io::Error) {
+        self.report_failure(format!("Can't read {}: {err}", path.display()));
+    }
+
+    /// A source that fails once the dashboard runs is a toast, not a reason to quit: what was
+    /// charted stays on screen. `Action::Error` is left for failing to start.
+    pub(super) fn report_failure(&self, message: String) {
+        error!("{message}");
+        if let Some(tx) = &self.command_tx {
+            let _ = tx.send(Action::Toast(message));
+        }
+    }
+
+    async fn update_values(&self, mut lines: InputLines, mut reader: ReaderState) {
+        if self
+            .sources
+            .values()
+            .iter()
+            .any(|source| source.cadence == Cadence::Arrival)
+        {
+            return self.update_on_arrival(lines, reader).await;
+        }
+        while !self.stop_signal.load(Ordering::Relaxed) {
+            self.model
+                .clock
+                .sleep(Duration::from_millis(self.update_frequency))
+                .await;
+            let line = match lines.next_line().await {
+                Ok(Some(line)) => line,
+                // nothing to read for now, the file may still grow or start over with `--loop`
+                Ok(None) => {
+                    self.restart_input(&mut lines);
+                    continue;
+                }
+                Err(err) => {
+                    debug!("Skipping unreadable input: {err}");
+                    self.model.skipped_lines.fetch_add(1, Ordering::Relaxed);
+                    continue;
+                }
+            };
+            self.ingest_line(&line, &mut reader);
+        }
+        // release the IO
+        drop(lines);
+    }
+
+    /// Read the lines as they arrive when some chart takes a sample for every value: those
+    /// charts store their values right away, the others the newest one once per update
+    /// interval.
+    async fn update_on_arrival(&self, mut lines: InputLines, mut reader: ReaderState) {
+        reader.hold = true;
+        let mut interval = self.model.clock.interval(self.base_interval());
+        // the first tick is right away, with nothing held yet
+        interval.tick().await;
+        while !self.stop_signal.load(Ordering::Relaxed) {
+            let line = tokio::select! {
+                line = lines.next_line() => line,
+                _ = interval.tick() => {
+                    self.store_held(&mut reader);
+                    continue;
+                }
+            };
+            match line {
+                Ok(Some(line)) => {
+                    self.ingest_line(&line, &mut reader);
+                }
+                // nothing to read for now, wait for the file to grow like `update_values`
+                Ok(None) => {
+                    if !self.restart_input(&mut lines) {
+                        interval.tick().await;
+                        self.store_held(&mut reader);
+                    }
+                }
+                Err(err) => {
+                    debug!("Skipping unreadable input: {err}");
+                    self.model.skipped_lines.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+        drop(lines);
+    }
+
+    /// Read keyed metrics as fast as they arrive, every metric name gets its own chart. StatsD
+    /// metrics and `--count-by` counts are aggregated and stored once per update interval.
+    async fn update_metrics(&self, mut lines: InputLines, mut reader: ReaderState) {
+        // an empty file isn't started over and over again
+        let mut read = false;
+        let mut interval = self
+            .model
+            .clock
+            .interval(Duration::from_millis(self.update_frequency));
+        let mut guard = InputGuard::new(self.max_input_rate, self.instant());
+        while !self.stop_signal.load(Ordering::Relaxed) {
+            tokio::select! {
+                line = lines.next_line() => {
+                    let Ok(Some(line)) = line else {
+                        if std::mem::take(&mut read) && self.restart_input(&mut lines) {
+                            continue;
+                        }
+                        // nothing to read for now, keep the interval's metrics and wait for
+                        // the file to grow like `update_values`
+                        interval.tick().await;
+                        self.flush_pending(&mut reader);
+                        self.announce_updates(&mut reader);
+                        continue;
+                    };
+                    read = true;
+                    let (parse, changed) = guard.admit(self.instant());
+                    if let Some(factor) = changed {
+                        self.report_sampling(factor);
+                    }
+                    if parse {
+                        self.ingest_metrics(&line, &mut reader, guard.factor());
+                    }
+                }
+                _ = interval.tick() => {
+                    self.flush_pending(&mut reader);
+                    self.announce_updates(&mut reader);
+                }
+            }
+        }
+    }
+
+    /// Route the metrics of one Graphite, StatsD or `--count-by` line, while only every
+    /// `factor`-th line is parsed. Returns whether the line had a metric.
+    pub(super) fn ingest_metrics(&self, line: &str, reader: &mut ReaderState, factor: u32) -> bool {
+        let line = normalize_line(line);
+        if line.trim().is_empty() {
+            return false;
+        }
+        let metrics = self.parse_metrics(line, reader);
+        let matched = metrics.iter().any(Result::is_ok);
+        self.record_matches(line, |_| matched);
+        let mut state = self.model.state.write().unwrap();
+        let charts = state.len();
+        for metric in metrics {
+            match metric {
+                Ok(mut metric) => {
+                    // the skipped lines would have added to the count too
+                    if metric.kind == MetricKind::Counter {
+                        metric.value *= factor as f64;
+                    }
+                    self.route_metric(&mut state, reader, metric)
+                }
+                Err(err) => {
+                    debug!("Skipping {line:?}: {err:?}");
+                    self.model.skipped_lines.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+        if state.len() != charts {
+            self.report_mismatches(state.len(), &mut reader.reported);
+            self.enforce_memory_limit(&mut state, 0);
+        }
+        drop(state);
+        self.announce_updates(reader);
+        matched
+    }
+
+    /// Chart the end of the `--input` file at once before following it, or of stdin when it is a
+    /// file. The input is left at its end.
+    pub(super) fn backfill(
+        &self,
+        backfill: Backfill,
+        input: Option<&File>,
+        reader: &mut ReaderState,
+    ) {
+        let file = match input {
+            // a clone shares the position with the lines read after
+            Some(file) => file.try_clone(),
+            None => stdin_file(),
+        };
+        let lines = file.and_then(|mut file| read_backfill(backfill, &mut file));
+        let lines = match lines {
+            Ok(lines) => lines,
+            Err(err) => {
+                let message = format!("Nothing to backfill: {err}");
+                warn!("{message}");
+                if let Some(tx) = &self.command_tx {
+                    let _ = tx.send(Action::Toast(message));
+                }
+                return;
+            }
+        };
+
+        let now = self.instant();
+        for (line, time) in self.spread_over_past(&lines, now) {
+            match self.format {
+                cli::Format::Number | cli::Format::Bool => {
+                    let Some(extracted) = self.extract_values(line, reader) else {
+                        continue;
+                    };
+                    let mut state = self.model.state.write().unwrap();
+                    self.store_extracted(&mut state, reader, extracted, time);
+                }
+                cli::Format::Graphite | cli::Format::Statsd | cli::Format::Count => {
+                    let metrics = self.parse_metrics(line, reader);
+                    let mut state = self.model.state.write().unwrap();
+                    for metric in metrics.into_iter().flatten() {
+                        self.route_metric(&mut state, reader, metric);
+                    }
+                    drop(state);
+                    if self.format != cli::Format::Graphite {
+                        self.flush_pending(reader);
+                    }
+                }
+            }
+        }
+
+        let mut state = self.model.state.write().unwrap();
+        for chart in state.iter_mut().filter(|chart| chart.length > 0) {
+            chart.live_samples = Some(0);
+        }
+        self.report_mismatches(state.len(), &mut reader.reported);
+        self.enforce_memory_limit(&mut state, 0);
+        drop(state);
+        self.announce_updates(reader);
+        debug!("Backfilled {} lines", lines.len());
+    }
+
+    /// Pair the backfilled `lines` with times an update interval apart, the last one at `now`,
+    /// so rates and `every` see their usual cadence.
+    pub(super) fn spread_over_past<'a>(
+        &self,
+        lines: &'a [String],
+        now: Instant,
+    ) -> impl Iterator<Item = (&'a str, Instant)> {
+        let interval = self.base_interval();
+        lines
+            .iter()
+            .rev()
+            .enumerate()
+            .rev()
+            .map(move |(age, line)| {
+                let time = now.checked_sub(interval * age as u32).unwrap_or(now);
+                (line.as_str(), time)
+            })
+    }
+
+    /// The extractor for the number and bool formats, as the config last set it.
+    pub(super) fn extractor(&self) -> Arc<dyn ValueExtractor> {
+        self.extractor.read().unwrap().clone()
+    }
+
+    /// Replace the extractor of this reader and of all its clones already reading a source.
+    pub(super) fn set_extractor(&self, extractor: Arc<dyn ValueExtractor>) {
+        *self.extractor.write().unwrap() = extractor;
+    }
+
+    /// Count what the extractors matched on `line`, and report the ones that never match.
+    pub(super) fn record_matches(&self, line: &str, matched: impl Fn(usize) -> bool) {
+        let never_matched = self.model.match_counts.lock().unwrap().record(matched);
+        for extractor in never_matched {
+            let message = never_matched_message(&extractor, line);
+            warn!("{message}");
+            if let (Some(tx), false) = (&self.command_tx, self.quiet_diagnostics) {
+                let _ = tx.send(Action::Toast(message));
+            }
+        }
+    }
+
+    /// Tell the user why the input is being sampled, or that it no longer is.
+    pub(super) fn report_sampling(&self, factor: u32) {
+        self.model.sampling.store(factor, Ordering::Relaxed);
+        let message = if factor > 1 {
+            format!(
+                "More than {} lines per second, parsing only 1 in {factor} lines",
+                self.max_input_rate
+            )
+        } else {
+            "The input rate dropped, parsing every line again".to_string()
+        };
+        warn!("{message}");
+        if let Some(tx) = &self.command_tx {
+            let _ = tx.send(Action::Toast(message));
+        }
+    }
+
+    /// Store one aggregated value per StatsD metric, like a StatsD server flushing to its backend.
+    pub(super) fn flush_pending(&self, reader: &mut ReaderState) {
+        let now = self.instant();
+        let mut state = self.model.state.write().unwrap();
+        let pending = reader.pending.clone();
+        for (i, aggregate) in pending {
+            // gauges keep their value, counters and timers start over every interval
+            let (value, next) = match aggregate {
+                Pending::Gauge(value) => (Some(value), aggregate),
+                Pending::Counter(sum) => (Some(sum), Pending::Counter(0.0)),
+                Pending::Timer { sum, count } => (
+                    (count > 0).then(|| sum / count as f64),
+                    Pending::Timer { sum: 0.0, count: 0 },
+                ),
+            };
+            if let Some(value) = value {
+                self.store(&mut state, reader, i, value, now);
+            }
+            reader.pending.insert(i, next);
+        }
+    }
+
+    /// The metrics of a Graphite, StatsD or `--count-by` line.
+    pub(super) fn parse_metrics(
+        &self,
+        line: &str,
+        reader: &ReaderState,
+    ) -> Vec<Result<Metric, ParseError>> {
+        match self.format {
+            cli::Format::Graphite => vec![parse_graphite(line)],
+            cli::Format::Statsd => parse_statsd(line),
+            cli::Format::Count => self
+                .count_metric(line, reader)
+                .into_iter()
+                .map(Ok)
+                .collect(),
+            cli::Format::Number | cli::Format::Bool => Vec::new(),
+        }
+    }
+
+    /// Find or create the chart of a metric and store or aggregate its value.
+    pub(super) fn route_metric(
+        &self,
+        state: &mut Vec<DashState>,
+        reader: &mut ReaderState,
+        metric: Metric,
+    ) {
+        let i = match reader.names.get(&metric.name) {
+            Some(&i) => i,
+            None => {
+                let i = state.iter().position(|s| s.name.is_none() && s.length == 0);
+                let i = i.unwrap_or_else(|| {
+                    state.push(self.new_chart(state));
+                    state.len() - 1
+                });
+                state[i].set_name(metric.name.clone());
+                reader.names.insert(metric.name.clone(), i);
+                i
+            }
+        };
+        // kept out of the StatsD aggregates too, a NaN would spoil the whole interval
+        if self.reject_non_finite(i, metric.value) {
+            return;
+        }
+        if let Some(timestamp) = metric.timestamp {
+            // Graphite may resend or reorder points, only keep the ones moving forward in time
+            let last = reader.timestamps.get(&i).copied();
+            if last.is_some_and(|last| timestamp <= last) {
+                let heartbeat = self.dedupe
+                    && last == Some(timestamp)
+                    && reader.last_values.get(&i) == Some(&metric.value);
+                if heartbeat {
+                    *self.model.deduped.lock().unwrap().entry(i).or_default() += 1;
+                } else {
+                    self.model.skipped_lines.fetch_add(1, Ordering::Relaxed);
+                }
+                return;
+            }
+            reader.timestamps.insert(i, timestamp);
+            reader.last_values.insert(i, metric.value);
+        }
+        if self.format == cli::Format::Graphite {
+            self.store(state, reader, i, metric.value, self.instant());
+            return;
+        }
+        let aggregate = match (metric.kind, reader.pending.get(&i).copied()) {
+            (MetricKind::Gauge, Some(Pending::Gauge(value))) if metric.relative => {
+                Pending::Gauge(value + metric.value)
+            }
+            (MetricKind::Gauge, _) => Pending::Gauge(metric.value),
+            (MetricKind::Counter, Some(Pending::Counter(sum))) => {
+                Pending::Counter(sum + metric.value)
+            }
+            (MetricKind::Counter, _) => Pending::Counter(metric.value),
+            (MetricKind::Timer, Some(Pending::Timer { sum, count })) => Pending::Timer {
+                sum: sum + metric.value,
+                count: count + 1,
+            },
+            (MetricKind::Timer, _) => Pending::Timer {
+                sum: metric.value,
+                count: 1,
+            },
+        };
+        reader.pending.insert(i, aggregate);
+    }
+}
+
+/// The lines of `input`, or of stdin without one.
+fn input_lines(input: Option<File>) -> InputLines {
+    let input: Box<dyn AsyncBufRead + Send + Unpin> = match input {
+        Some(file) => Box::new(BufReader::new(tokio::fs::File::from_std(file))),
+        None => Box::new(BufReader::new(tokio::io::stdin())),
+    };
+    input.lines()
+}
+
+/// What the diagnostics call the ways values are taken from a line, see [`MatchCounts`].
+pub(super) fn extractor_names(format: cli::Format, extractor: &dyn ValueExtractor) -> Vec<String> {
+    match format {
+        cli::Format::Graphite => vec!["graphite".to_string()],
+        cli::Format::Statsd => vec!["statsd".to_string()],
+        cli::Format::Count => vec!["count-by".to_string()],
+        cli::Format::Number | cli::Format::Bool => extractor.names(),
+    }
+}
+
+/// Strip the `\r` left over from CRLF line endings, e.g. from producers on Windows, so units
+/// at the end of a line still match.
+pub(super) fn normalize_line(line: &str) -> &str {
+    line.trim_end_matches('\r')
+}
+
+/// The lines at the end of `file` that `backfill` asks for, oldest first. The file is left at
+/// its end.
+pub(super) fn read_backfill(backfill: Backfill, file: &mut File) -> io::Result<Vec<String>> {
+    match backfill {
+        Backfill::Lines(count) => {
+            let mut kept = 0;
+            read_tail(file, |_| {
+                kept += 1;
+                kept <= count
+            })
+        }
+        Backfill::Duration(duration) => {
+            // Graphite lines are newest last, so the first one read has the newest timestamp
+            let mut newest = None;
+            read_tail(file, |line| {
+                let Ok(Some(timestamp)) = parse_graphite(line).map(|metric| metric.timestamp)
+                else {
+                    return true;
+                };
+                let newest = *newest.get_or_insert(timestamp);
+                newest - timestamp <= duration.as_secs_f64()
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use pretty_assertions::assert_eq;
+
+    use super::super::test_util::new_source;
+    use super::*;
+    use crate::components::dash::chart_title;
+
+    #[test]
+    fn test_crlf_line_endings() {
+        assert_eq!(normalize_line("12 ms\r"), "12 ms");
+        assert_eq!(normalize_line("a.b 1 100\r\r"), "a.b 1 100");
+        assert_eq!(normalize_line("no newline"), "no newline");
+
+        let source = new_source(&["-u", "%", "-u", "ms"]);
+        assert_eq!(
+            source
+                .extractor()
+                .extract(normalize_line("cpu 50% latency 12ms\r")),
+            vec![(0, 50.0), (1, 12.0)]
+        );
+        let source = new_source(&[]);
+        assert_eq!(
+            source.extractor().extract(normalize_line("1 2.5\r")),
+            vec![(0, 1.0), (1, 2.5)]
+        );
+    }
+
+    #[test]
+    fn test_pause_and_clear_one_chart() {
+        let source = new_source(&[]);
+        let mut state = vec![];
+        let mut reader = ReaderState::default();
+        let start = Instant::now();
+        source.store(&mut state, &mut reader, 1, 1.0, start);
+        state[0].toggle_pause(start);
+        for value in [2.0, 3.0] {
+            source.store(&mut state, &mut reader, 0, value, start);
+            source.store(&mut state, &mut reader, 1, value, start);
+        }
+        assert_eq!(state[0].length, 0);
+        assert_eq!(state[1].samples(), &[1.0, 2.0, 3.0]);
+
+        let later = start + Duration::from_secs(2);
+        assert_eq!(state[0].paused_time(later), Duration::from_secs(2));
+        state[0].toggle_pause(later);
+        assert_eq!(
+            state[0].paused_time(later + Duration::from_secs(5)),
+            Duration::from_secs(2)
+        );
+        source.store(&mut state, &mut reader, 0, 4.0, later);
+        assert_eq!(state[0].samples(), &[4.0]);
+
+        state[1].clear();
+        assert_eq!(state[1].length, 0);
+        assert_eq!(state[1].last(), None);
+    }
+
+    #[test]
+    fn test_dedupe() {
+        let source = new_source(&["--dedupe", "--rate"]);
+        let mut state = vec![];
+        let mut reader = ReaderState::default();
+        let start = Instant::now();
+        // a counter re-sent every second while it doesn't move
+        for (seconds, value) in [(0, 10.0), (1, 10.0), (2, 10.0), (3, 14.0), (4, 14.0)] {
+            let at = start + Duration::from_secs(seconds);
+            source.store(&mut state, &mut reader, 0, value, at);
+        }
+        // the heartbeats neither add samples nor break up the rate over the quiet stretch
+        assert_eq!(state[0].samples(), &[4.0 / 3.0]);
+        assert_eq!(source.model.deduped.lock().unwrap()[&0], 3);
+
+        // a value coming back after a change is a new sample
+        let source = new_source(&["--dedupe"]);
+        let mut state = vec![];
+        for value in [1.0, 1.0, 2.0, 1.0] {
+            source.store(&mut state, &mut reader, 0, value, start);
+        }
+        assert_eq!(state[0].samples(), &[1.0, 2.0, 1.0]);
+    }
+
+    #[test]
+    fn test_non_finite_rejected() {
+        let source = new_source(&[]);
+        let mut state = vec![];
+        let mut reader = ReaderState::default();
+        let start = Instant::now();
+        for value in [1.0, f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 3.0] {
+            source.store(&mut state, &mut reader, 0, value, start);
+        }
+        assert_eq!(state[0].samples(), &[1.0, 3.0]);
+        assert_eq!(
+            (state[0].min_value, state[0].max_value, state[0].average),
+            (1.0, 3.0, 2.0)
+        );
+        assert_eq!(source.model.non_finite.lock().unwrap()[&0], 3);
+
+        // a column that only ever says nan gets a chart but no samples
+        let source = new_source(&[]);
+        source.ingest_line("1 nan inf", &mut reader);
+        source.ingest_line("2 NaN 1e999", &mut reader);
+        let state = source.model.state.read().unwrap();
+        assert_eq!(state[0].samples(), &[1.0, 2.0]);
+        assert!(state[1..].iter().all(|chart| chart.samples().is_empty()));
+        assert_eq!(
+            *source.model.non_finite.lock().unwrap(),
+            HashMap::from([(1, 2), (2, 2)])
+        );
+
+        // a counter jumping to infinity has no rate
+        let source = new_source(&["--rate"]);
+        let mut state = vec![];
+        let mut reader = ReaderState::default();
+        for (seconds, value) in [(0, -f64::MAX), (1, f64::MAX), (2, f64::MAX)] {
+            let at = start + Duration::from_secs(seconds);
+            source.store(&mut state, &mut reader, 0, value, at);
+        }
+        assert_eq!(state[0].samples(), &[0.0]);
+        assert_eq!(source.model.non_finite.lock().unwrap()[&0], 1);
+    }
+
+    #[test]
+    fn test_non_finite_metrics_rejected() {
+        let source = new_source(&["--format", "graphite"]);
+        let mut reader = ReaderState::default();
+        for line in ["a.b 1 10", "a.b nan 11", "a.b -inf 12", "a.b 2 13"] {
+            source.ingest_metrics(line, &mut reader, 1);
+        }
+        assert_eq!(source.model.state.read().unwrap()[0].samples(), &[1.0, 2.0]);
+        assert_eq!(source.model.non_finite.lock().unwrap()[&0], 2);
+
+        // a NaN would otherwise stay in the gauge and the counter sum until the next flush
+        let source = new_source(&["--format", "statsd"]);
+        let mut reader = ReaderState::default();
+        for line in ["g:5|g", "g:nan|g", "c:1|c", "c:inf|c", "c:2|c"] {
+            source.ingest_metrics(line, &mut reader, 1);
+        }
+        source.flush_pending(&mut reader);
+        let state = source.model.state.read().unwrap();
+        assert_eq!(state[0].samples(), &[5.0]);
+        assert_eq!(state[1].samples(), &[3.0]);
+        assert_eq!(
+            *source.model.non_finite.lock().unwrap(),
+            HashMap::from([(0, 1), (1, 1)])
+        );
+    }
+
+    #[test]
+    fn test_on_parse_error() {
+        // the second match overflows, then there's none
+        let huge = format!("{} ms", "9".repeat(400));
+        let lines = [
+            "4 ms 5 ms".to_string(),
+            format!("6 ms {huge}"),
+            "7 ms".to_string(),
+        ];
+        let samples = |mode: &str| {
+            let source = new_source(&["-u", "ms", "-u", "ms", "--on-parse-error", mode]);
+            let mut reader = ReaderState::default();
+            for line in &lines {
+                source.ingest_line(line, &mut reader);
+            }
+            assert_eq!(
+                *source.model.parse_errors.lock().unwrap(),
+                HashMap::from([(1, 1)])
+            );
+            let state = source.model.state.read().unwrap();
+            assert_eq!(state[0].samples(), &[4.0, 6.0, 7.0]);
+            state[1].samples().to_vec()
+        };
+        assert_eq!(samples("skip"), [5.0]);
+        assert_eq!(samples("zero"), [5.0, 0.0]);
+        assert_eq!(samples("last"), [5.0, 5.0]);
+        assert_eq!(
+            Cli::parse_from(["ttydash"]).on_parse_error,
+            cli::OnParseError::Skip
+        );
+    }
+
+    #[test]
+    fn test_auto_titles() {
+        let titles = |args: &[&str], lines: &[&str]| {
+            let source = new_source(args);
+            let mut reader = source.new_reader();
+            for line in lines {
+                source.ingest(line, &mut reader);
+            }
+            let state = source.model.state.read().unwrap();
+            (0..state.len())
+                .map(|i| chart_title(&source.titles, &state, i))
+                .collect::<Vec<_>>()
+        };
+        // units, numbered when repeated, below the titles given
+        assert_eq!(
+            titles(&["-u", "ms", "-u", "%", "-u", "ms"], &["1 ms 2% 3 ms"]),
+            ["ms #1", "%", "ms #2"]
+        );
+        assert_eq!(
+            titles(&["-u", "ms", "-u", "%", "-t", "latency"], &["1 ms 2%"]),
+            ["latency", "%"]
+        );
+        // header fields
+        assert_eq!(
+            titles(&["--header", "first-line"], &["us sy", "3 1"]),
+            ["us", "sy"]
+        );
+        // metric names
+        assert_eq!(
+            titles(&["--format", "graphite", "-u", "%"], &["web.cpu 5 10"]),
+            ["web.cpu"]
+        );
+        // source specs, after a title or host of their own
+        assert_eq!(
+            titles(
+                &["--source", "every=5s", "--source", "every=5s,title=disk"],
+                &["1 2 3"]
+            ),
+            ["every=5s", "disk", "Chart 3"]
+        );
+        assert_eq!(titles(&[], &["1 2"]), ["Chart 1", "Chart 2"]);
+    }
+
+    #[test]
+    fn test_glued_unit() {
+        let mut source = new_source(&[]);
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        source.command_tx = Some(tx);
+        let mut reader = ReaderState::default();
+        for line in ["42ms", "43ms", "1.5s", "2s", "44ms"] {
+            source.ingest_line(line, &mut reader);
+        }
+        let state = source.model.state.read().unwrap();
+        assert_eq!(state[0].unit, "ms");
+        assert_eq!(state[0].samples(), &[42.0, 43.0, 1.5, 2.0, 44.0]);
+        let toasts = std::iter::from_fn(|| rx.try_recv().ok())
+            .filter_map(|action| match action {
+                Action::Toast(message) => Some(message),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            toasts,
+            ["Chart 1 got a value in s after ones in ms, its unit stays ms"]
+        );
+        drop(state);
+
+        // a unit the chart already has wins
+        let source = new_source(&[]);
+        let mut reader = ReaderState::default();
+        source.model.state.write().unwrap()[0].unit = "°C".to_string();
+        source.ingest_line("-3.5C", &mut reader);
+        assert_eq!(source.model.state.read().unwrap()[0].unit, "°C");
+    }
+
+    /// Run a reader with `args` until its first chart stored `samples` samples.
+    async fn read_samples(args: &[&str], samples: usize) -> Vec<f64> {
+        let source = new_source(&[&["--update-frequency", "1"], args].concat());
+        let task = tokio::spawn(source.clone().run());
+        while source.model.state.read().unwrap()[0].stored < samples {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        source.stop();
+        task.await.unwrap();
+        let state = source.model.state.read().unwrap();
+        state[0].samples().to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_input_file() {
+        let path = std::env::temp_dir().join(format!("ttydash-input-{}.txt", std::process::id()));
+        std::fs::write(&path, "3 ms\n1 ms\n\n4 ms\n").unwrap();
+        let input = path.to_str().unwrap();
+
+        // the same as the lines piped in
+        let piped = new_source(&[]);
+        let mut reader = piped.new_reader();
+        for line in std::fs::read_to_string(&path).unwrap().lines() {
+            piped.ingest_line(line, &mut reader);
+        }
+        let samples = read_samples(&["--input", input], 3).await;
+        assert_eq!(samples, piped.model.state.read().unwrap()[0].samples());
+        assert_eq!(samples, [3.0, 1.0, 4.0]);
+
+        let samples = read_samples(&["--input", input, "--loop"], 7).await;
+        assert_eq!(samples[..7], [3.0, 1.0, 4.0, 3.0, 1.0, 4.0, 3.0]);
+
+        let args = |args: &[&str]| Cli::parse_from([&["ttydash"], args].concat());
+        let err = SourceReader::new(&args(&["--input", "-", "--loop"]), ChartModel::default());
+        assert!(err
+            .unwrap_err()
+            .to_string()
+            .contains("stdin can't be read again"));
+        std::fs::remove_file(&path).unwrap();
+        let err = SourceReader::new(&args(&["--input", input]), ChartModel::default());
+        assert!(err.unwrap_err().to_string().starts_with("Can't read"));
+    }
+
+    #[test]
+    fn test_failure_is_a_toast() {
+        let mut source = new_source(&["--listen", "0"]);
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        source.command_tx = Some(tx);
+        source.report_failure("Failed to accept connections: gone".to_string());
+        // the dashboard keeps showing what was charted instead of quitting
+        assert_eq!(
+            rx.try_recv().unwrap(),
+            Action::Toast("Failed to accept connections: gone".to_string())
+        );
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_statsd_input_file() {
+        let path = std::env::temp_dir().join(format!("ttydash-statsd-{}.txt", std::process::id()));
+        std::fs::write(&path, "g:5|g\nc:1|c\nc:2|c\n").unwrap();
+        let input = path.to_str().unwrap();
+
+        let source = new_source(&[
+            "--input",
+            input,
+            "--format",
+            "statsd",
+            "--update-frequency",
+            "1",
+        ]);
+        let task = tokio::spawn(source.clone().run());
+        let flushed =
+            |state: &[DashState]| state.len() == 2 && state.iter().all(|chart| chart.stored >= 2);
+        while !flushed(&source.model.state.read().unwrap()) {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        source.stop();
+        task.await.unwrap();
+        let state = source.model.state.read().unwrap();
+        // the last interval's metrics are stored once the whole file was read
+        assert_eq!(state[0].samples()[..2], [5.0, 5.0]);
+        // the counter starts over once its interval was stored, the file doesn't grow
+        assert_eq!(state[1].samples()[..2], [3.0, 0.0]);
+        drop(state);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_on_off_source() {
+        let source = new_source(&[
+            "--source",
+            "",
+            "--source",
+            "format=bool,title=health",
+            "--mark-transitions",
+        ]);
+        let mut reader = source.new_reader();
+        for line in ["12.5 1", "13 0", "14 0", "15 7"] {
+            source.ingest_line(line, &mut reader);
+        }
+        let state = source.model.state.read().unwrap();
+        assert_eq!(state[0].samples(), &[12.5, 13.0, 14.0, 15.0]);
+        assert_eq!(state[1].samples(), &[1.0, 0.0, 0.0, 1.0]);
+        let notes = source.model.notes.lock().unwrap();
+        let notes = notes
+            .iter()
+            .map(|note| note.text.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(notes, ["health off", "health on"]);
+    }
+
+    #[test]
+    fn test_quantize_then_dedupe() {
+        let source = new_source(&["--quantize", "0.01", "--quantize", "5", "--dedupe"]);
+        let mut state = vec![];
+        let mut reader = ReaderState::default();
+        let start = Instant::now();
+        for value in [20.001, 20.003, 20.002, 20.012] {
+            source.store(&mut state, &mut reader, 0, value, start);
+        }
+        // the jitter of a sensor is gone, only the real change is left
+        assert_eq!(state[0].samples(), &[20.0, 20.01]);
+        source.store(&mut state, &mut reader, 1, 12.6, start);
+        assert_eq!(state[1].samples(), &[15.0]);
+        assert!(Cli::try_parse_from(["ttydash", "--quantize", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_dedupe_timestamps() {
+        let source = new_source(&["--dedupe", "--format", "graphite"]);
+        let mut state = vec![];
+        let mut reader = ReaderState::default();
+        for line in [
+            "cpu 5 100",
+            // resent as is
+            "cpu 5 100",
+            // the same value at a later time is a sample
+            "cpu 5 101",
+            // an older or conflicting point is skipped, not deduped
+            "cpu 6 101",
+        ] {
+            let metric = parse_graphite(line).unwrap();
+            source.route_metric(&mut state, &mut reader, metric);
+        }
+        assert_eq!(state[0].samples(), &[5.0, 5.0]);
+        assert_eq!(source.model.deduped.lock().unwrap()[&0], 1);
+        assert_eq!(source.model.skipped_lines.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_data_updated_after_state_is_released() {
+        let mut source = new_source(&[]);
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        source.command_tx = Some(tx);
+        let mut reader = ReaderState::default();
+        source.ingest_line("1 2 3", &mut reader);
+        source.ingest_line("no numbers", &mut reader);
+        let mut updated = vec![];
+        while let Ok(action) = rx.try_recv() {
+            if let Action::DataUpdated(i) = action {
+                // the lock is free and the sample is already there
+                let state = source.model.state.try_read().unwrap();
+                assert!(state[i].last().is_some());
+                updated.push(i);
+            }
+        }
+        assert_eq!(updated, [0, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_cadence_arrival() {
+        let path = std::env::temp_dir().join(format!("ttydash-arrival-{}.txt", std::process::id()));
+        std::fs::write(&path, "1ms 10rps\n2ms 20rps\n3ms 30rps\n").unwrap();
+        let mut source = new_source(&[
+            "--input",
+            path.to_str().unwrap(),
+            "-u",
+            "ms",
+            "-u",
+            "rps",
+            "--source",
+            "cadence=arrival",
+        ]);
+        let start = Instant::now();
+        let clock = crate::clock::MockClock::new(start);
+        source.model.clock = Arc::new(clock.clone());
+        let task = tokio::spawn(source.clone().run());
+        let stored = |i: usize| {
+            let state = source.model.state.read().unwrap();
+            state.get(i).map_or(0, |chart| chart.stored)
+        };
+        // the burst is read at once, without waiting for the update interval
+        while stored(0) < 3 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        assert_eq!(stored(1), 0);
+        clock.advance(source.base_interval());
+        while stored(1) < 1 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        source.stop();
+        clock.advance(source.base_interval());
+        task.await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let state = source.model.state.read().unwrap();
+        assert_eq!(state[0].samples()[..3], [1.0, 2.0, 3.0]);
+        assert_eq!(state[0].times().unwrap()[..3], [0.0; 3]);
+        // the interval chart keeps the newest value of the interval
+        assert_eq!(state[1].samples(), [30.0]);
+        assert_eq!(state[1].times(), None);
+    }
+
+    #[test]
+    fn test_cadence_arrival_conflicts() {
+        let err = |args: &[&str]| {
+            let args = Cli::parse_from([&["ttydash"], args].concat());
+            SourceReader::new(&args, ChartModel::default())
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            err(&["--source", "", "--source", "cadence=arrival", "--rate"]),
+            "Chart 2 samples every value as it arrives (cadence=arrival), which can't be \
+             combined with --rate"
+        );
+        assert!(err(&["--source", "cadence=arrival,every=5s"]).ends_with("with every="));
+        assert!(err(&["--source", "cadence=arrival", "--format", "statsd"]).ends_with("statsd"));
+        assert!(
+            err(&["--source", "cadence=arrival", "--count-by", "(GET|POST)"])
+                .ends_with("--count-by")
+        );
+        let args = Cli::parse_from([
+            "ttydash",
+            "--source",
+            "cadence=arrival",
+            "--format",
+            "graphite",
+        ]);
+        assert!(SourceReader::new(&args, ChartModel::default()).is_ok());
+    }
+}
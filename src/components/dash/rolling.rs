@@ -0,0 +1,325 @@
+//! The retained samples of a chart and their stats, kept up to date at a constant cost per
+//! sample so the reader's work doesn't grow with `--history`.
+
+use std::collections::VecDeque;
+
+/// The newest `size` samples in one slice, with room for more after them. A sample is written
+/// behind the others and the window moves back to the start of the buffer once the room is used
+/// up, so storing a sample costs O(1) amortized instead of shifting the whole history.
+#[derive(Debug, Clone)]
+pub struct SampleWindow {
+    buffer: Vec<f64>,
+    size: usize,
+    /// Where the window ends in the buffer.
+    end: usize,
+}
+
+impl SampleWindow {
+    /// A window of `size` zeros.
+    pub fn new(size: usize) -> Self {
+        Self {
+            buffer: vec![0.0; size + room(size)],
+            size,
+            end: size,
+        }
+    }
+
+    /// The samples the window holds, filled or not.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The window, oldest first.
+    pub fn as_slice(&self) -> &[f64] {
+        &self.buffer[self.end - self.size..self.end]
+    }
+
+    /// Store `value` as the newest sample, dropping the oldest.
+    pub fn push(&mut self, value: f64) {
+        if self.size == 0 {
+            return;
+        }
+        if self.end == self.buffer.len() {
+            // every sample but the oldest moves back to the start, once per `room` samples
+            self.buffer
+                .copy_within(self.end + 1 - self.size..self.end, 0);
+            self.end = self.size - 1;
+        }
+        self.buffer[self.end] = value;
+        self.end += 1;
+    }
+
+    pub fn fill(&mut self, value: f64) {
+        let start = self.end - self.size;
+        self.buffer[start..self.end].fill(value);
+    }
+
+    /// Hold `size` samples, the newest ones when there are fewer, and zeros older than the
+    /// others when there are more.
+    pub fn resize(&mut self, size: usize) {
+        let kept = &self.as_slice()[self.size.saturating_sub(size)..];
+        let mut buffer = Vec::with_capacity(size + room(size));
+        buffer.resize(size - kept.len(), 0.0);
+        buffer.extend_from_slice(kept);
+        buffer.resize(size + room(size), 0.0);
+        *self = Self {
+            buffer,
+            size,
+            end: size,
+        };
+    }
+
+    /// Samples the buffer has room for, the window and what follows it.
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+}
+
+/// The room kept after a window of `size` samples: a quarter of it, so the window moves back
+/// after at most four samples of its own.
+fn room(size: usize) -> usize {
+    size / 4 + 1
+}
+
+/// Sum, min and max of a run of samples that gains its newest and loses its oldest one at a
+/// time. The sum is kept running with Neumaier's compensated summation, so a spike leaving the
+/// run doesn't take the smaller samples' digits with it, the min and max in monotonic deques of
+/// the samples that can still become the min or max once the older ones are gone.
+#[derive(Debug, Clone, Default)]
+pub struct RollingStats {
+    sum: f64,
+    /// The low-order digits the running sum lost.
+    compensation: f64,
+    count: usize,
+    /// The position and value of the candidates, the current min first.
+    mins: VecDeque<(u64, f64)>,
+    /// The position and value of the candidates, the current max first.
+    maxes: VecDeque<(u64, f64)>,
+    /// The position of the oldest sample.
+    oldest: u64,
+    /// The position of the next sample.
+    next: u64,
+}
+
+impl RollingStats {
+    pub fn new(samples: &[f64]) -> Self {
+        let mut stats = Self::default();
+        for &value in samples {
+            stats.push(value);
+        }
+        stats
+    }
+
+    /// Add `value` as the newest sample.
+    pub fn push(&mut self, value: f64) {
+        let position = self.next;
+        self.next += 1;
+        self.add(value);
+        self.count += 1;
+        // a sample that isn't lower than a newer one can't be the min anymore
+        while self.mins.back().is_some_and(|&(_, min)| min >= value) {
+            self.mins.pop_back();
+        }
+        self.mins.push_back((position, value));
+        while self.maxes.back().is_some_and(|&(_, max)| max <= value) {
+            self.maxes.pop_back();
+        }
+        self.maxes.push_back((position, value));
+    }
+
+    /// Remove the oldest sample, which is `value`.
+    pub fn pop(&mut self, value: f64) {
+        if self.count == 0 {
+            return;
+        }
+        self.add(-value);
+        self.count -= 1;
+        if self
+            .mins
+            .front()
+            .is_some_and(|&(position, _)| position == self.oldest)
+        {
+            self.mins.pop_front();
+        }
+        if self
+            .maxes
+            .front()
+            .is_some_and(|&(position, _)| position == self.oldest)
+        {
+            self.maxes.pop_front();
+        }
+        self.oldest += 1;
+    }
+
+    fn add(&mut self, value: f64) {
+        let sum = self.sum + value;
+        self.compensation += if self.sum.abs() >= value.abs() {
+            (self.sum - sum) + value
+        } else {
+            (value - sum) + self.sum
+        };
+        self.sum = sum;
+    }
+
+    pub fn min(&self) -> f64 {
+        self.mins.front().map_or(f64::INFINITY, |&(_, min)| min)
+    }
+
+    pub fn max(&self) -> f64 {
+        self.maxes
+            .front()
+            .map_or(f64::NEG_INFINITY, |&(_, max)| max)
+    }
+
+    /// Candidates kept for the min and max, the memory the stats hold.
+    pub fn capacity(&self) -> usize {
+        self.mins.capacity() + self.maxes.capacity()
+    }
+
+    pub fn average(&self) -> f64 {
+        match self.count {
+            0 => 0.0,
+            count => (self.sum + self.compensation) / count as f64,
+        }
+    }
+}
+
+/// The nearest-rank `p`th percentile of `samples`, which it sorts.
+pub fn percentile(samples: &mut [f64], p: f64) -> Option<f64> {
+    if samples.is_empty() {
+        return None;
+    }
+    samples.sort_by(f64::total_cmp);
+    let rank = (p / 100.0 * samples.len() as f64).ceil() as usize;
+    Some(samples[rank.clamp(1, samples.len()) - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// A reproducible sequence of `len` values in `0..range`, with runs of equal values.
+    fn random(len: usize, range: u64, seed: u64) -> Vec<f64> {
+        let mut state = seed.max(1);
+        (0..len)
+            .map(|_| {
+                // xorshift64
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                (state % range) as f64
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_window() {
+        let mut window = SampleWindow::new(3);
+        for value in 1..=10 {
+            window.push(value as f64);
+        }
+        assert_eq!(window.as_slice(), [8.0, 9.0, 10.0]);
+        window.resize(5);
+        assert_eq!(window.as_slice(), [0.0, 0.0, 8.0, 9.0, 10.0]);
+        window.push(11.0);
+        assert_eq!(window.as_slice(), [0.0, 8.0, 9.0, 10.0, 11.0]);
+        window.resize(2);
+        assert_eq!(window.as_slice(), [10.0, 11.0]);
+        window.fill(0.0);
+        assert_eq!(window.as_slice(), [0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_rolling_matches_naive() {
+        for (seed, (len, window, range)) in [(300, 1, 10), (1000, 7, 3), (5000, 64, 1000)]
+            .into_iter()
+            .enumerate()
+        {
+            let values = random(len, range, seed as u64 + 1);
+            let mut stats = RollingStats::default();
+            for (i, &value) in values.iter().enumerate() {
+                stats.push(value);
+                if i >= window {
+                    stats.pop(values[i - window]);
+                }
+                let naive = &values[(i + 1).saturating_sub(window)..=i];
+                let min = naive.iter().copied().fold(f64::INFINITY, f64::min);
+                let max = naive.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                let average = naive.iter().sum::<f64>() / naive.len() as f64;
+                assert_eq!((stats.min(), stats.max()), (min, max), "sample {i}");
+                assert!((stats.average() - average).abs() < 1e-9, "sample {i}");
+            }
+        }
+        let mut stats = RollingStats::new(&[4.0]);
+        stats.pop(4.0);
+        assert_eq!(
+            (stats.min(), stats.max(), stats.average()),
+            (f64::INFINITY, f64::NEG_INFINITY, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_spike_leaves_the_average() {
+        let mut stats = RollingStats::default();
+        stats.push(1e17);
+        stats.push(1.0);
+        stats.pop(1e17);
+        assert_eq!(stats.average(), 1.0);
+        for value in [2.0, 3.0, 1e300, 4.0] {
+            stats.push(value);
+        }
+        for value in [1.0, 2.0, 3.0, 1e300] {
+            stats.pop(value);
+        }
+        assert_eq!(stats.average(), 4.0);
+    }
+
+    #[test]
+    fn test_percentile() {
+        let mut samples = (1..=20).rev().map(f64::from).collect::<Vec<_>>();
+        assert_eq!(percentile(&mut samples, 95.0), Some(19.0));
+        assert_eq!(percentile(&mut samples, 50.0), Some(10.0));
+        assert_eq!(percentile(&mut samples, 0.0), Some(1.0));
+        assert_eq!(percentile(&mut [], 95.0), None);
+    }
+
+    /// Per-sample cost of storing a sample and its stats, the way it was done before by
+    /// shifting the history and scanning it, and now. Run with
+    /// `cargo test --release bench_per_sample -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_per_sample() {
+        for size in [200, 10_000, 100_000] {
+            let values = random(size * 2, 1000, 7);
+            let samples = (1_000_000 / size).clamp(200, 20_000);
+
+            let mut data = vec![0.0; size];
+            let start = Instant::now();
+            for &value in values.iter().cycle().take(samples) {
+                data.rotate_left(1);
+                data[size - 1] = value;
+                let sum = data.iter().sum::<f64>();
+                let min = data.iter().copied().fold(f64::INFINITY, f64::min);
+                let max = data.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                std::hint::black_box((sum / size as f64, min, max));
+            }
+            let before = start.elapsed() / samples as u32;
+
+            let mut window = SampleWindow::new(size);
+            let mut stats = RollingStats::new(window.as_slice());
+            let start = Instant::now();
+            for &value in values.iter().cycle().take(samples) {
+                stats.pop(window.as_slice()[0]);
+                window.push(value);
+                stats.push(value);
+                std::hint::black_box((stats.average(), stats.min(), stats.max()));
+            }
+            let after = start.elapsed() / samples as u32;
+            println!("{size:>7} samples: {before:>10.2?} before, {after:>8.2?} after");
+        }
+    }
+}
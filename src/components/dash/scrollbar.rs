@@ -0,0 +1,76 @@
+use ratatui::{prelude::*, Frame};
+
+use super::{DashState, DashView};
+
+impl DashView {
+    /// Thicken the part of the bottom border that spans the samples in view, when the history
+    /// holds more than fits. Time labels stay on top of it.
+    pub(super) fn draw_scrollbar(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        chart: &DashState,
+        width: usize,
+    ) {
+        let inner = area.inner(Margin::new(1, 1));
+        let width = width.min(inner.width as usize);
+        let visible = width * self.samples_per_column();
+        let offset = self.chart_offset(chart.interval);
+        let Some((start, length)) = thumb(chart.length, visible, offset, width) else {
+            return;
+        };
+        for column in start..start + length {
+            let position = if self.newest_left() {
+                column
+            } else {
+                width - 1 - column
+            };
+            let cell = &mut frame.buffer_mut()[(inner.x + position as u16, area.bottom() - 1)];
+            if cell.symbol() == "─" {
+                cell.set_symbol("━").set_fg(Color::Cyan);
+            }
+        }
+    }
+}
+
+/// The thumb of a scrollbar `width` cells wide as `(start, length)`, counted from the newest
+/// end, for `visible` of `total` samples starting `offset` samples back. `None` when everything
+/// fits.
+pub fn thumb(total: usize, visible: usize, offset: usize, width: usize) -> Option<(usize, usize)> {
+    if total <= visible || width == 0 {
+        return None;
+    }
+    let cells = |samples: usize| (samples as f64 / total as f64 * width as f64).round() as usize;
+    let length = cells(visible).clamp(1, width);
+    let start = cells(offset).min(width - length);
+    Some((start, length))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn fits() {
+        assert_eq!(thumb(50, 80, 0, 80), None);
+        assert_eq!(thumb(80, 80, 0, 80), None);
+        assert_eq!(thumb(200, 80, 0, 0), None);
+    }
+
+    #[test]
+    fn position() {
+        // a quarter of the history in view, at the newest end
+        assert_eq!(thumb(400, 100, 0, 100), Some((0, 25)));
+        // panned half way back
+        assert_eq!(thumb(400, 100, 200, 100), Some((50, 25)));
+        // the oldest samples, the thumb stops at the far end
+        assert_eq!(thumb(400, 100, 399, 100), Some((75, 25)));
+    }
+
+    #[test]
+    fn tiny_thumb() {
+        assert_eq!(thumb(100_000, 10, 0, 10), Some((0, 1)));
+    }
+}
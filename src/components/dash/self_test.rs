@@ -0,0 +1,164 @@
+use std::{
+    io::{BufRead, Write},
+    time::Instant,
+};
+
+use color_eyre::Result;
+
+use super::{
+    chart_title, cli,
+    diagnostics::truncate,
+    reader::{normalize_line, ReaderState},
+    DashState, DashView,
+};
+
+/// Width of the raw line column before it is truncated.
+const RAW_WIDTH: usize = 32;
+
+/// A value found in an input line and the chart it went to.
+#[derive(Debug, Clone, PartialEq)]
+struct Extraction {
+    extractor: String,
+    chart: usize,
+    value: f64,
+}
+
+impl DashView {
+    /// Feed up to `lines` lines of `input` through the same parsing and routing as the readers
+    /// and print a table of what every line produced to `out`. Values are shown as parsed, before
+    /// `--rate` and StatsD aggregation. Returns whether any line produced a value.
+    pub fn self_test(
+        &self,
+        input: impl BufRead,
+        lines: usize,
+        out: &mut impl Write,
+    ) -> Result<bool> {
+        let mut state = vec![self.reader.new_chart(&[])];
+        let mut reader = ReaderState::default();
+        let mut matched = false;
+        writeln!(
+            out,
+            "{:>5}  {:<RAW_WIDTH$}  {:<16}  {:<20}  VALUE",
+            "LINE", "RAW", "EXTRACTOR", "CHART"
+        )?;
+        for (number, line) in input.lines().take(lines).enumerate() {
+            let line = line?;
+            let line = normalize_line(&line);
+            let raw = truncate(line, RAW_WIDTH);
+            let extractions = self.extract(&mut state, &mut reader, line);
+            if extractions.is_empty() {
+                writeln!(out, "{:>5}  {raw:<RAW_WIDTH$}  no match", number + 1)?;
+            }
+            for extraction in extractions {
+                matched = true;
+                writeln!(
+                    out,
+                    "{:>5}  {raw:<RAW_WIDTH$}  {:<16}  {:<20}  {}",
+                    number + 1,
+                    extraction.extractor,
+                    truncate(&chart_title(&self.titles, &state, extraction.chart), 20),
+                    extraction.value
+                )?;
+            }
+        }
+        Ok(matched)
+    }
+
+    /// Parse one line and route its values to charts, creating them like the readers do.
+    fn extract(
+        &self,
+        state: &mut Vec<DashState>,
+        reader: &mut ReaderState,
+        line: &str,
+    ) -> Vec<Extraction> {
+        match self.reader.format {
+            cli::Format::Graphite | cli::Format::Statsd | cli::Format::Count => {
+                let metrics = self.reader.parse_metrics(line, reader);
+                metrics
+                    .into_iter()
+                    .flatten()
+                    .map(|metric| {
+                        let value = metric.value;
+                        let extractor = match self.reader.format {
+                            cli::Format::Graphite => "graphite".to_string(),
+                            cli::Format::Count => "count-by".to_string(),
+                            _ => format!("statsd {:?}", metric.kind).to_lowercase(),
+                        };
+                        let name = metric.name.clone();
+                        self.reader.route_metric(state, reader, metric);
+                        Extraction {
+                            extractor,
+                            chart: reader.names[&name],
+                            value,
+                        }
+                    })
+                    .collect()
+            }
+            cli::Format::Number | cli::Format::Bool => self
+                .reader
+                .extractor()
+                .extract(line)
+                .into_iter()
+                .map(|(i, value)| {
+                    let extractor = self.reader.extractor().describe(i);
+                    self.reader.store(state, reader, i, value, Instant::now());
+                    Extraction {
+                        extractor,
+                        chart: i,
+                        value,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::cli::Cli;
+
+    fn run(args: &[&str], input: &str, lines: usize) -> (bool, Vec<String>) {
+        let dash = DashView::new(Cli::parse_from([&["ttydash"], args].concat())).unwrap();
+        let mut out = vec![];
+        let matched = dash.self_test(input.as_bytes(), lines, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        (matched, out.lines().skip(1).map(str::to_string).collect())
+    }
+
+    #[test]
+    fn test_units() {
+        let (matched, rows) = run(
+            &["-u", "ms", "-t", "latency"],
+            "took 12ms\r\nnothing here\n",
+            10,
+        );
+        assert!(matched);
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].contains("unit ms") && rows[0].contains("latency"));
+        assert!(rows[0].ends_with("12"));
+        assert!(rows[1].ends_with("no match"));
+    }
+
+    #[test]
+    fn test_line_limit_and_no_values() {
+        let (matched, rows) = run(&[], "a\nb\nc\n", 2);
+        assert!(!matched);
+        assert_eq!(rows.len(), 2);
+    }
+
+    #[test]
+    fn test_graphite_routing() {
+        let (matched, rows) = run(
+            &["--format", "graphite"],
+            "a.b 1 100\nc.d 2 100\na.b 3 101\n",
+            10,
+        );
+        assert!(matched);
+        assert!(rows[2].contains("a.b") && rows[2].ends_with('3'));
+        assert!(rows[1].contains("c.d"));
+    }
+}
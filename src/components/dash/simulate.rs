@@ -0,0 +1,152 @@
+use std::{io::BufRead, sync::Arc, time::Instant};
+
+use color_eyre::Result;
+use ratatui::{backend::TestBackend, buffer::Buffer, layout::Size, Terminal};
+
+use super::DashView;
+use crate::{clock::MockClock, components::Component};
+
+impl DashView {
+    /// Chart `input` without a terminal, for `ttydash simulate`. Lines go through the same
+    /// parsing and storing as the readers, one per `--update-frequency` on a virtual clock, and a
+    /// frame of `size` is drawn after each of them, for at most `frames` lines. Time labels are
+    /// drawn like with `--deterministic`. Returns the last frame as text.
+    pub fn simulate(
+        &mut self,
+        input: impl BufRead,
+        frames: Option<usize>,
+        size: Size,
+    ) -> Result<String> {
+        self.deterministic = true;
+        let mut terminal = Terminal::new(TestBackend::new(size.width, size.height))?;
+        let lines = self.replay(input, frames, |dash| dash.draw_frame(&mut terminal))?;
+        if lines == 0 {
+            self.draw_frame(&mut terminal)?;
+        }
+        Ok(frame_text(terminal.backend().buffer()))
+    }
+
+    /// Store the values of at most `limit` lines of `input` the way the readers do, one line per
+    /// `--update-frequency` on a virtual clock, calling `after_line` after every line. Returns
+    /// the number of lines read.
+    pub(super) fn replay(
+        &mut self,
+        input: impl BufRead,
+        limit: Option<usize>,
+        mut after_line: impl FnMut(&mut Self) -> Result<()>,
+    ) -> Result<usize> {
+        let clock = MockClock::new(Instant::now());
+        self.set_clock(Arc::new(clock.clone()));
+        let mut reader = self.reader.new_reader();
+        let charts = self.reader.expected_charts().unwrap_or(1);
+        self.reader
+            .enforce_memory_limit(&mut self.model.state.write().unwrap(), charts);
+        let mut count = 0;
+        for line in input.lines().take(limit.unwrap_or(usize::MAX)) {
+            let line = line?;
+            if count > 0 {
+                clock.advance(self.base_interval());
+            }
+            self.reader.ingest(&line, &mut reader);
+            // every line is an update interval of its own
+            self.reader.flush_pending(&mut reader);
+            after_line(self)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    pub(super) fn draw_frame(&mut self, terminal: &mut Terminal<TestBackend>) -> Result<()> {
+        let mut result = Ok(());
+        terminal.draw(|frame| result = self.draw(frame, frame.area()))?;
+        result
+    }
+}
+
+/// The symbols of a drawn frame, one line per row.
+pub fn frame_text(buffer: &Buffer) -> String {
+    let mut text = String::new();
+    for y in 0..buffer.area.height {
+        for x in 0..buffer.area.width {
+            text.push_str(buffer[(x, y)].symbol());
+        }
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::cli::Cli;
+
+    fn simulate(args: &[&str], input: &str, frames: Option<usize>) -> (DashView, String) {
+        let mut dash = DashView::new(Cli::parse_from([&["ttydash"], args].concat())).unwrap();
+        let frame = dash
+            .simulate(input.as_bytes(), frames, Size::new(60, 10))
+            .unwrap();
+        (dash, frame)
+    }
+
+    #[test]
+    fn test_stats_and_frame() {
+        let (dash, frame) = simulate(&["-u", "ms", "-t", "latency"], "1ms\n2ms\n6ms\n", None);
+        let summary = dash.summary();
+        assert_eq!(summary.charts[0].samples, 3);
+        assert_eq!(summary.charts[0].average, Some(3.0));
+        assert!(frame.contains("latency"));
+        assert_eq!(frame.lines().count(), 10);
+    }
+
+    #[test]
+    fn test_frame_limit() {
+        let (dash, _) = simulate(&[], "1\n2\n3\n4\n", Some(2));
+        assert_eq!(dash.summary().charts[0].last, Some(2.0));
+    }
+
+    #[test]
+    fn test_rate_follows_virtual_clock() {
+        // one line per second, counting up by 10
+        let (dash, _) = simulate(&["--rate"], "0\n10\n20\n30\n", None);
+        assert_eq!(dash.summary().charts[0].last, Some(10.0));
+    }
+
+    #[test]
+    fn test_note_follows_virtual_clock() {
+        let mut dash = DashView::new(Cli::parse_from(["ttydash"])).unwrap();
+        let mut lines = 0;
+        dash.replay("1\n2\n3\n4\n5\n".as_bytes(), None, |dash| {
+            lines += 1;
+            if lines == 2 {
+                dash.add_note("deploy".to_string());
+            }
+            Ok(())
+        })
+        .unwrap();
+        // three samples, a second apart on the virtual clock, came after the note
+        let state = dash.model.state.read().unwrap();
+        let columns = (0..5)
+            .filter(|&column| !dash.notes_at(&state[0], column).is_empty())
+            .collect::<Vec<_>>();
+        assert_eq!(columns, [3]);
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let args = ["-u", "ms", "--time-axis", "clock"];
+        let input = "3ms\n5ms\n4ms\n";
+        assert_eq!(
+            simulate(&args, input, None).1,
+            simulate(&args, input, None).1
+        );
+    }
+
+    #[test]
+    fn test_empty_input_draws_a_frame() {
+        let (_, frame) = simulate(&[], "", None);
+        assert_eq!(frame.lines().count(), 10);
+    }
+}
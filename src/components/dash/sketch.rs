@@ -0,0 +1,315 @@
+//! Stats of every sample a chart got in the session, however few of them its history keeps:
+//! running aggregates and a quantile sketch, both updated in constant time per sample.
+
+/// Relative error of the quantiles of a [`QuantileSketch`].
+pub const RELATIVE_ACCURACY: f64 = 0.01;
+/// Bins a [`QuantileSketch`] keeps per sign, the lowest ones are merged past that. 2048 bins
+/// cover magnitudes from 1e-9 to 1e9 without merging.
+const MAX_BINS: usize = 2048;
+/// Magnitudes below this are counted as zero.
+const MIN_MAGNITUDE: f64 = 1e-12;
+
+/// Quantiles of a stream within [`RELATIVE_ACCURACY`] of the exact ones, in the way of
+/// DDSketch: a value goes into the bin of its logarithm to the base `gamma`, so every bin spans
+/// values at most `gamma` apart and its midpoint is close enough to all of them.
+#[derive(Debug, Clone)]
+pub struct QuantileSketch {
+    gamma: f64,
+    ln_gamma: f64,
+    positive: Bins,
+    negative: Bins,
+    zeros: u64,
+}
+
+impl Default for QuantileSketch {
+    fn default() -> Self {
+        let gamma = (1.0 + RELATIVE_ACCURACY) / (1.0 - RELATIVE_ACCURACY);
+        Self {
+            gamma,
+            ln_gamma: gamma.ln(),
+            positive: Bins::default(),
+            negative: Bins::default(),
+            zeros: 0,
+        }
+    }
+}
+
+impl QuantileSketch {
+    pub fn push(&mut self, value: f64) {
+        if value.abs() < MIN_MAGNITUDE {
+            self.zeros += 1;
+        } else if value > 0.0 {
+            self.positive.add(self.key(value));
+        } else {
+            self.negative.add(self.key(-value));
+        }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.positive.count + self.negative.count + self.zeros
+    }
+
+    /// The nearest-rank `p`th percentile, like [`super::rolling::percentile`] gives for all of
+    /// the samples, within [`RELATIVE_ACCURACY`] of it.
+    pub fn percentile(&self, p: f64) -> Option<f64> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+        let rank = ((p / 100.0 * count as f64).ceil() as u64).clamp(1, count) - 1;
+        // the negative values come first, the largest magnitude lowest
+        if rank < self.negative.count {
+            let key = self.negative.key_at(self.negative.count - 1 - rank);
+            return Some(-self.value(key));
+        }
+        let rank = rank - self.negative.count;
+        if rank < self.zeros {
+            return Some(0.0);
+        }
+        Some(self.value(self.positive.key_at(rank - self.zeros)))
+    }
+
+    /// Memory held by the bins, in bins.
+    pub fn capacity(&self) -> usize {
+        self.positive.counts.capacity() + self.negative.counts.capacity()
+    }
+
+    /// The bin of a magnitude: values in `(gamma^(key - 1), gamma^key]`.
+    fn key(&self, magnitude: f64) -> i32 {
+        (magnitude.ln() / self.ln_gamma).ceil() as i32
+    }
+
+    /// The value of bin `key`, the same relative distance from both of its bounds.
+    fn value(&self, key: i32) -> f64 {
+        2.0 * self.gamma.powi(key) / (self.gamma + 1.0)
+    }
+}
+
+/// The counts of a range of adjacent bins.
+#[derive(Debug, Clone, Default)]
+struct Bins {
+    /// The key of `counts[0]`.
+    offset: i32,
+    counts: Vec<u64>,
+    count: u64,
+}
+
+impl Bins {
+    fn add(&mut self, key: i32) {
+        if self.counts.is_empty() {
+            self.offset = key;
+            self.counts.push(0);
+        }
+        let key = if key < self.offset {
+            let missing = (self.offset - key) as usize;
+            if self.counts.len() + missing > MAX_BINS {
+                // the lowest bins are merged rather than the ones the high quantiles are in
+                self.offset
+            } else {
+                self.counts.splice(0..0, std::iter::repeat_n(0, missing));
+                self.offset = key;
+                key
+            }
+        } else {
+            let index = (key - self.offset) as usize;
+            if index >= self.counts.len() {
+                self.counts.resize(index + 1, 0);
+                self.collapse();
+            }
+            key
+        };
+        self.counts[(key - self.offset) as usize] += 1;
+        self.count += 1;
+    }
+
+    /// Merge the lowest bins into the lowest one kept, to have at most [`MAX_BINS`].
+    fn collapse(&mut self) {
+        let excess = self.counts.len().saturating_sub(MAX_BINS);
+        if excess == 0 {
+            return;
+        }
+        let merged = self.counts.drain(..excess).sum::<u64>();
+        self.counts[0] += merged;
+        self.offset += excess as i32;
+    }
+
+    /// The key of the bin the value of 0-based `rank` is in.
+    fn key_at(&self, rank: u64) -> i32 {
+        let mut seen = 0;
+        for (index, &count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen > rank {
+                return self.offset + index as i32;
+            }
+        }
+        self.offset + self.counts.len() as i32 - 1
+    }
+}
+
+/// Count, average, min, max and quantiles of every sample stored since the chart started or
+/// was cleared, for `--stats-scope session` and the summary.
+#[derive(Debug, Clone)]
+pub struct SessionStats {
+    count: usize,
+    sum: f64,
+    min: f64,
+    max: f64,
+    sketch: QuantileSketch,
+}
+
+impl Default for SessionStats {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            sketch: QuantileSketch::default(),
+        }
+    }
+}
+
+impl SessionStats {
+    pub fn push(&mut self, value: f64) {
+        self.count += 1;
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.sketch.push(value);
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn average(&self) -> f64 {
+        match self.count {
+            0 => 0.0,
+            count => self.sum / count as f64,
+        }
+    }
+
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    /// The `p`th percentile, within [`RELATIVE_ACCURACY`] and never outside the min and max.
+    pub fn percentile(&self, p: f64) -> Option<f64> {
+        self.sketch
+            .percentile(p)
+            .map(|value| value.clamp(self.min, self.max))
+    }
+
+    /// Bins held by the sketch, the memory the stats hold.
+    pub fn capacity(&self) -> usize {
+        self.sketch.capacity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::super::rolling::percentile;
+    use super::*;
+
+    const PERCENTILES: [f64; 8] = [0.0, 1.0, 25.0, 50.0, 90.0, 95.0, 99.0, 99.9];
+
+    /// A reproducible sequence of `len` numbers in `0..1`.
+    fn uniform(len: usize, seed: u64) -> Vec<f64> {
+        let mut state = seed.max(1);
+        (0..len)
+            .map(|_| {
+                // xorshift64
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                (state >> 11) as f64 / (1u64 << 53) as f64
+            })
+            .collect()
+    }
+
+    /// Every percentile of the sketch of `values` is within the stated error of the exact one.
+    fn assert_accurate(name: &str, values: &[f64]) {
+        let mut stats = SessionStats::default();
+        for &value in values {
+            stats.push(value);
+        }
+        let mut sorted = values.to_vec();
+        for p in PERCENTILES {
+            let exact = percentile(&mut sorted, p).unwrap();
+            let estimate = stats.percentile(p).unwrap();
+            assert!(
+                (estimate - exact).abs() <= RELATIVE_ACCURACY * exact.abs() + 1e-12,
+                "{name} p{p}: {estimate} for {exact}"
+            );
+        }
+        assert_eq!(stats.count(), values.len());
+        assert_eq!(stats.min(), sorted[0]);
+        assert_eq!(stats.max(), sorted[sorted.len() - 1]);
+        let average = values.iter().sum::<f64>() / values.len() as f64;
+        assert!((stats.average() - average).abs() < 1e-9 * average.abs().max(1.0));
+    }
+
+    #[test]
+    fn test_sketch_accuracy() {
+        let uniform_values = uniform(100_000, 1)
+            .into_iter()
+            .map(|u| u * 1000.0)
+            .collect::<Vec<_>>();
+        assert_accurate("uniform", &uniform_values);
+
+        // latencies with a long tail
+        let exponential = uniform(100_000, 2)
+            .into_iter()
+            .map(|u| -(1.0 - u).ln() * 20.0)
+            .collect::<Vec<_>>();
+        assert_accurate("exponential", &exponential);
+
+        // magnitudes over nine orders
+        let log_uniform = uniform(50_000, 3)
+            .into_iter()
+            .map(|u| 10f64.powf(u * 9.0 - 3.0))
+            .collect::<Vec<_>>();
+        assert_accurate("log-uniform", &log_uniform);
+
+        let signed = uniform(50_000, 4)
+            .into_iter()
+            .map(|u| ((u * 2000.0).round() - 1000.0) / 4.0)
+            .collect::<Vec<_>>();
+        assert_accurate("signed", &signed);
+
+        assert_accurate("constant", &[42.0; 1000]);
+        assert_accurate("single", &[-3.5]);
+    }
+
+    #[test]
+    fn test_sketch_stays_compact() {
+        let mut sketch = QuantileSketch::default();
+        for u in uniform(200_000, 5) {
+            sketch.push(10f64.powf(u * 30.0 - 15.0));
+        }
+        assert_eq!(sketch.count(), 200_000);
+        assert!(sketch.capacity() <= 2 * MAX_BINS, "{}", sketch.capacity());
+        // the bins that were merged are the low ones, the high quantiles stay accurate
+        let p99 = sketch.percentile(99.0).unwrap();
+        assert!(
+            (p99 / 10f64.powf(0.99 * 30.0 - 15.0) - 1.0).abs() < 0.05,
+            "{p99}"
+        );
+    }
+
+    #[test]
+    fn test_empty_session() {
+        let stats = SessionStats::default();
+        assert_eq!(stats.percentile(50.0), None);
+        assert_eq!(
+            (stats.average(), stats.min(), stats.max()),
+            (0.0, f64::INFINITY, f64::NEG_INFINITY)
+        );
+    }
+}
@@ -0,0 +1,489 @@
+//! Golden-frame tests: representative dashboards are drawn into a [`TestBackend`] and compared
+//! with the frames checked in under `snapshots/`. Run with `UPDATE_SNAPSHOTS=1` to rewrite them
+//! after an intended change and review the diff.
+
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use clap::Parser;
+use pretty_assertions::assert_eq;
+use ratatui::{backend::TestBackend, Terminal};
+
+use super::{
+    protocol::{Metric, MetricKind},
+    *,
+};
+use crate::{clock::MockClock, source::Warmup};
+
+/// A chart of `history` samples following a fixed, uneven pattern.
+fn chart(history: usize, seed: usize) -> DashState {
+    let mut chart = DashState::new(history, Duration::from_secs(1));
+    for i in 0..history {
+        chart.update(((i * 7 + seed * 5) % 23 + seed) as f64);
+    }
+    chart
+}
+
+/// Draw `charts` charts with the flags `args` into a `width` x `height` frame.
+fn render(args: &[&str], charts: usize, width: u16, height: u16) -> String {
+    let args = Cli::parse_from([&["ttydash", "--deterministic"], args].concat());
+    let state = (0..charts)
+        .map(|seed| {
+            let mut chart = DashState {
+                unit: args
+                    .units
+                    .iter()
+                    .flatten()
+                    .nth(seed)
+                    .cloned()
+                    .unwrap_or_default(),
+                ..chart(args.history.samples(), seed)
+            };
+            if let Some(Warmup::Samples(samples)) = args.warmup {
+                chart.warmup = samples;
+                chart.calculate_stats();
+            }
+            chart
+        })
+        .collect();
+    draw(
+        &mut DashView::with_states(state, args).unwrap(),
+        width,
+        height,
+    )
+}
+
+fn draw(dash: &mut DashView, width: u16, height: u16) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal
+        .draw(|frame| dash.draw(frame, frame.area()).unwrap())
+        .unwrap();
+    simulate::frame_text(terminal.backend().buffer())
+}
+
+fn assert_snapshot(name: &str, frame: String) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src/components/dash/snapshots")
+        .join(format!("{name}.txt"));
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, &frame).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("missing {}, run with UPDATE_SNAPSHOTS=1", path.display()));
+    assert_eq!(frame, expected, "{name} differs from {}", path.display());
+}
+
+#[test]
+fn test_single_chart() {
+    assert_snapshot(
+        "single_chart",
+        render(&["-t", "latency", "-u", "ms", "--history", "60"], 1, 60, 12),
+    );
+}
+
+#[test]
+fn test_five_charts() {
+    assert_snapshot("five_charts", render(&["--history", "40"], 5, 80, 24));
+}
+
+#[test]
+fn test_grouped() {
+    assert_snapshot("grouped", render(&["-g", "--history", "40"], 3, 70, 12));
+}
+
+#[test]
+fn test_grouped_mixed_units() {
+    // the max belongs to the third series, the label takes its unit and color, and the titles
+    // are cut to fit
+    assert_snapshot(
+        "grouped_mixed_units",
+        render(
+            &[
+                "-g",
+                "-t",
+                "latency",
+                "-t",
+                "throughput",
+                "-t",
+                "errors",
+                "-u",
+                "ms",
+                "-u",
+                "B",
+                "-u",
+                "s",
+                "--history",
+                "40",
+            ],
+            3,
+            30,
+            12,
+        ),
+    );
+}
+
+#[test]
+fn test_grouped_shared_unit() {
+    assert_snapshot(
+        "grouped_shared_unit",
+        render(
+            &[
+                "-g",
+                "-t",
+                "p50",
+                "-t",
+                "p99",
+                "-u",
+                "ms",
+                "-u",
+                "ms",
+                "--history",
+                "40",
+            ],
+            2,
+            70,
+            12,
+        ),
+    );
+}
+
+#[test]
+fn test_grouped_normalized() {
+    assert_snapshot(
+        "grouped_normalized",
+        render(
+            &["-g", "--group-scale", "normalized", "--history", "40"],
+            2,
+            70,
+            12,
+        ),
+    );
+}
+
+#[test]
+fn test_grouped_dense() {
+    assert_snapshot(
+        "grouped_dense",
+        render(&["-g", "--dense", "--history", "80"], 2, 70, 10),
+    );
+}
+
+#[test]
+fn test_horizontal_layout() {
+    assert_snapshot(
+        "horizontal_layout",
+        render(&["-l", "horizontal", "--history", "40"], 3, 90, 10),
+    );
+}
+
+#[test]
+fn test_tiny_area() {
+    assert_snapshot("tiny_area", render(&["--history", "20"], 2, 12, 5));
+}
+
+#[test]
+fn test_stats_panel_right() {
+    assert_snapshot(
+        "stats_panel_right",
+        render(
+            &[
+                "--stats-panel",
+                "right",
+                "-u",
+                "ms",
+                "-u",
+                "%",
+                "--history",
+                "40",
+            ],
+            2,
+            100,
+            12,
+        ),
+    );
+}
+
+#[test]
+fn test_stats_panel_bottom() {
+    assert_snapshot(
+        "stats_panel_bottom",
+        render(&["--stats-panel", "bottom", "--history", "40"], 3, 70, 16),
+    );
+}
+
+#[test]
+fn test_ticker() {
+    let args = [
+        "--mode",
+        "ticker",
+        "-u",
+        "ms",
+        "--reference",
+        "20",
+        "--history",
+        "40",
+    ];
+    assert_snapshot("ticker", render(&args, 6, 90, 10));
+}
+
+#[test]
+fn test_wrapped_title() {
+    let title = "p99 request latency for checkout-service (us-east-1)";
+    assert_snapshot(
+        "wrapped_title",
+        render(&["-t", title, "--history", "60"], 1, 60, 10),
+    );
+}
+
+#[test]
+fn test_title_beside_long_stats() {
+    // too little room to wrap into, the whole title goes below the stats instead of over them
+    let args = ["-t", "second", "--history", "60"];
+    assert_snapshot("title_beside_long_stats", render(&args, 1, 44, 6));
+}
+
+#[test]
+fn test_clock_axis_is_deterministic() {
+    let args = ["--time-axis", "clock", "--history", "60"];
+    assert_eq!(render(&args, 1, 60, 8), render(&args, 1, 60, 8));
+}
+
+#[test]
+fn test_reference_line() {
+    assert_snapshot(
+        "reference_line",
+        render(
+            &["--reference", "15", "-u", "ms", "--history", "80"],
+            1,
+            90,
+            10,
+        ),
+    );
+}
+
+#[test]
+fn test_y_from_min() {
+    // a supply voltage, every bar would be full height from zero
+    let mut chart = DashState::new(60, Duration::from_secs(1));
+    for i in 0..60 {
+        chart.update(3.30 + ((i * 7) % 23) as f64 * 0.005);
+    }
+    chart.unit = "V".to_string();
+    let args = [
+        "ttydash",
+        "--deterministic",
+        "--y-from",
+        "min",
+        "--history",
+        "60",
+    ];
+    let mut dash = DashView::with_states(vec![chart], Cli::parse_from(args)).unwrap();
+    assert_snapshot("y_from_min", draw(&mut dash, 70, 9));
+}
+
+#[test]
+fn test_toggle_y_from_keeps_history() {
+    let args = Cli::parse_from(["ttydash", "--deterministic", "--history", "60"]);
+    let mut dash = DashView::with_states(vec![chart(60, 1)], args).unwrap();
+    let before = draw(&mut dash, 70, 9);
+    dash.update(Action::ToggleYFrom).unwrap();
+    assert!(draw(&mut dash, 70, 9).contains("↕ zoomed"));
+    dash.update(Action::ToggleYFrom).unwrap();
+    assert_eq!(draw(&mut dash, 70, 9), before);
+    assert_eq!(
+        dash.model.state.read().unwrap()[0].samples(),
+        chart(60, 1).samples()
+    );
+}
+
+#[test]
+fn test_warmup() {
+    assert_snapshot(
+        "warmup",
+        render(&["--warmup", "20", "-u", "ms", "--history", "60"], 1, 70, 9),
+    );
+}
+
+#[test]
+fn test_history_hint() {
+    assert_snapshot("history_hint", render(&["--history", "30"], 1, 90, 8));
+}
+
+#[test]
+fn test_history_auto_follows_resizes() {
+    let args = Cli::parse_from(["ttydash", "--history", "auto"]);
+    let mut dash = DashView::with_states(vec![chart(50, 0)], args).unwrap();
+    // narrower again keeps the grown history, for panning
+    for (width, history) in [(300, 299), (3, 299), (40, 299), (500, 499)] {
+        let mut terminal = Terminal::new(TestBackend::new(width, 10)).unwrap();
+        terminal
+            .draw(|frame| dash.draw(frame, frame.area()).unwrap())
+            .unwrap();
+        assert_eq!(dash.model.state.read().unwrap()[0].data.size(), history);
+    }
+    assert_eq!(
+        dash.model.state.read().unwrap()[0].samples(),
+        chart(50, 0).samples()
+    );
+}
+
+#[test]
+fn test_grouped_stale() {
+    let args = Cli::parse_from([
+        "ttydash",
+        "--deterministic",
+        "-g",
+        "--history",
+        "40",
+        "--stale-after",
+        "1m",
+    ]);
+    let now = Instant::now();
+    let live = DashState {
+        last_update: Some(now),
+        ..chart(40, 0)
+    };
+    // a series that died on a spike, it would flatten the live one
+    let mut dead = chart(40, 1);
+    dead.update(400.0);
+    dead.last_update = now.checked_sub(Duration::from_secs(180));
+    let mut dash = DashView::with_states(vec![live, dead], args).unwrap();
+    dash.set_clock(Arc::new(MockClock::new(now)));
+    let mut terminal = Terminal::new(TestBackend::new(70, 12)).unwrap();
+    terminal
+        .draw(|frame| dash.draw(frame, frame.area()).unwrap())
+        .unwrap();
+    assert_snapshot(
+        "grouped_stale",
+        simulate::frame_text(terminal.backend().buffer()),
+    );
+}
+
+#[test]
+fn test_min_bar() {
+    // one event against a burst of a thousand
+    let mut chart = DashState::new(20, Duration::from_secs(1));
+    for value in [1000.0, 0.0, 1.0, 0.0] {
+        chart.update(value);
+    }
+    let bottom_row = |args: &[&str]| {
+        let args = Cli::parse_from([&["ttydash", "--history", "20"], args].concat());
+        let mut dash = DashView::with_states(vec![chart.clone()], args).unwrap();
+        let frame = draw(&mut dash, 20, 6);
+        let row = frame.lines().nth(4).unwrap().to_string();
+        // the newest samples are drawn on the right
+        row.chars().rev().skip(1).take(4).collect::<String>()
+    };
+    assert_eq!(bottom_row(&[]), "⢀ ⣿ ");
+    assert_eq!(bottom_row(&["--no-min-bar"]), "  ⣿ ");
+}
+
+#[test]
+fn test_grid_baseline() {
+    // a few events in a quiet series
+    let mut chart = DashState::new(40, Duration::from_secs(1));
+    for i in 0..40 {
+        chart.update(if i % 13 == 5 { (i % 4 + 1) as f64 } else { 0.0 });
+    }
+    let args = Cli::parse_from([
+        "ttydash",
+        "--deterministic",
+        "--history",
+        "40",
+        "--grid",
+        "baseline",
+    ]);
+    let mut dash = DashView::with_states(vec![chart], args).unwrap();
+    assert_snapshot("grid_baseline", draw(&mut dash, 48, 10));
+}
+
+#[test]
+fn test_low_bandwidth() {
+    let frame = render(&["--low-bandwidth", "--history", "60"], 1, 60, 12);
+    assert!(!frame.contains(['⣿', '⣤', '⢀']));
+    assert_snapshot("low_bandwidth", frame);
+}
+
+#[test]
+fn test_show_counts() {
+    let start = Instant::now();
+    let mut chart = DashState::new(20, Duration::from_secs(1));
+    for value in [3.0, 5.0, 4.0] {
+        chart.arrivals.record(start);
+        chart.update(value);
+    }
+    let top_line = |args: &[&str], width: u16| {
+        let args = [&["ttydash", "--stats-panel", "bottom"], args].concat();
+        let mut dash = DashView::with_states(vec![chart.clone()], Cli::parse_from(args)).unwrap();
+        dash.set_clock(Arc::new(MockClock::new(start)));
+        let frame = draw(&mut dash, width, 8);
+        frame.lines().next().unwrap().to_string()
+    };
+    let args = ["-t", "requests per host", "--show-counts"];
+    assert!(top_line(&args, 40).ends_with("requests per host n=3 @3.0/s╮"));
+    // a narrow chart cuts the title, not the counts
+    assert!(top_line(&args, 24).ends_with("requests n=3 @3.0/s╮"));
+    assert!(!top_line(&args[..2], 40).contains("n=3"));
+}
+
+#[test]
+fn test_unsafe_names() {
+    let args = Cli::parse_from(["ttydash", "--deterministic", "--format", "graphite"]);
+    let mut dash = DashView::new(args).unwrap();
+    let mut reader = dash.reader.new_reader();
+    let names = [
+        "\x1b[2Jcpu\x1b[31m",
+        "disk\nio\r\nsda",
+        "net\u{200b}\u{202e}rx",
+    ];
+    {
+        let mut state = dash.model.state.write().unwrap();
+        for name in names {
+            let metric = Metric {
+                name: name.to_string(),
+                value: 4.0,
+                kind: MetricKind::Gauge,
+                timestamp: None,
+                relative: false,
+            };
+            dash.reader.route_metric(&mut state, &mut reader, metric);
+        }
+    }
+    let frame = draw(&mut dash, 100, 12);
+    let rows = frame.lines().collect::<Vec<_>>();
+    assert_eq!(rows.len(), 12);
+    assert!(rows.iter().all(|row| row.chars().count() == 100));
+    assert!(!frame.chars().any(|c| c.is_control() && c != '\n'));
+    // every title stays in the top border of its chart
+    assert!(rows[0].starts_with("╭─Avg: 4.00"));
+    assert!(rows[0].ends_with("─▶ cpu╮"));
+    assert!(rows[1].starts_with("│ 4 "));
+    assert!(rows[6].contains("─▶ disk io sda╮╭─Avg: 4.00"));
+    assert!(rows[6].ends_with("─▶ netrx╮"));
+    assert!(rows[7].starts_with("│ 4 "));
+    // the summary keeps the names as they came in, without the icons
+    let summary = dash.summary();
+    let raw_names = summary.charts.iter().map(|chart| chart.raw_name.as_deref());
+    assert_eq!(raw_names.collect::<Vec<_>>(), names.map(Some));
+    assert_eq!(summary.charts[1].title, "disk io sda");
+}
+
+#[test]
+fn test_focused_hints() {
+    // the focused chart shows the keys of its actions instead of the time markers, as many as
+    // fit the narrower one
+    let args = Cli::parse_from(["ttydash", "--deterministic", "--history", "40"]);
+    let state = vec![chart(40, 0), chart(40, 1)];
+    let mut dash = DashView::with_states(state, args).unwrap();
+    dash.register_config_handler(crate::config::Config::builtin())
+        .unwrap();
+    dash.view.focus = Some(0);
+    assert_snapshot("focused_hints", draw(&mut dash, 70, 12));
+    dash.view.focus = Some(1);
+    dash.layout = cli::Layout::Horizontal;
+    assert_snapshot("focused_hints_narrow", draw(&mut dash, 70, 8));
+}
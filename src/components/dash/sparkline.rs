@@ -0,0 +1,50 @@
+/// Block characters from the lowest to the highest level.
+const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Draw `values`, oldest first, as a line of at most `width` block characters, each averaging an
+/// equal share of the values. Every cell is scaled between the lowest and highest cell, a flat
+/// line sits in the middle.
+pub fn sparkline(values: &[f64], width: usize) -> String {
+    if values.is_empty() || width == 0 {
+        return String::new();
+    }
+    let cells = values.len().min(width);
+    let averages = (0..cells)
+        .map(|cell| {
+            let bucket = &values[cell * values.len() / cells..(cell + 1) * values.len() / cells];
+            bucket.iter().sum::<f64>() / bucket.len() as f64
+        })
+        .collect::<Vec<_>>();
+    let min = averages.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = averages.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let top = LEVELS.len() - 1;
+    averages
+        .into_iter()
+        .map(|average| {
+            let level = if max > min {
+                ((average - min) / (max - min) * top as f64).round() as usize
+            } else {
+                top / 2
+            };
+            LEVELS[level.min(top)]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[], 10), "");
+        assert_eq!(sparkline(&[1.0, 2.0], 0), "");
+        assert_eq!(sparkline(&[0.0, 7.0, 3.5], 10), "▁█▅");
+        assert_eq!(sparkline(&[5.0; 4], 10), "▄▄▄▄");
+        // pairs are averaged into one cell each
+        assert_eq!(sparkline(&[0.0, 2.0, 4.0, 6.0, 8.0, 10.0], 3), "▁▅█");
+        assert_eq!(sparkline(&[1.0; 100], 10).chars().count(), 10);
+    }
+}
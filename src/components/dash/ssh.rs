@@ -0,0 +1,329 @@
+use std::{
+    collections::VecDeque,
+    process::{ExitStatus, Stdio},
+    sync::atomic::Ordering,
+    time::{Duration, Instant},
+};
+
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    process::Command,
+    task,
+};
+use tracing::{debug, warn};
+
+use super::{
+    chart_title,
+    inspect::Connection,
+    reader::{normalize_line, ReaderState, SourceReader},
+};
+use crate::{action::Action, source::Ssh, text::sanitize};
+
+/// Longest wait before reconnecting to a failing host.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// Lines of stderr kept to explain why a command ended.
+const STDERR_TAIL: usize = 10;
+
+/// How one run of a source's command ended.
+struct Ended {
+    /// Whether it printed anything.
+    received: bool,
+    /// Why it ended, its last complaint on stderr if it made one.
+    error: String,
+    /// `None` when it couldn't be run.
+    status: Option<ExitStatus>,
+    /// The last [`STDERR_TAIL`] lines of its stderr.
+    stderr: Vec<String>,
+}
+
+impl SourceReader {
+    /// Whether the charts are fed by `ssh:` sources instead of stdin.
+    pub(super) fn has_ssh_sources(&self) -> bool {
+        self.sources
+            .values()
+            .iter()
+            .any(|source| source.ssh.is_some())
+    }
+
+    /// Follow every `ssh:` source in its own task, so one failing host never holds up the others.
+    pub(super) async fn follow_ssh_sources(&self) {
+        {
+            let mut state = self.model.state.write().unwrap();
+            // create the charts up front, so the hosts that never connect show their errors
+            while state.len() < self.sources.values().len() {
+                let chart = self.new_chart(&state);
+                state.push(chart);
+            }
+        }
+        let tasks = self
+            .sources
+            .values()
+            .iter()
+            .enumerate()
+            .filter_map(|(i, source)| {
+                Some(task::spawn(self.clone().follow_ssh(i, source.ssh.clone()?)))
+            })
+            .collect::<Vec<_>>();
+        for task in tasks {
+            let _ = task.await;
+        }
+    }
+
+    /// Run the command of chart `i` over and over, backing off while the host keeps failing.
+    async fn follow_ssh(self, i: usize, ssh: Ssh) {
+        let mut reader = ReaderState::default();
+        let mut failures = 0;
+        self.model.update_source(i, |status| {
+            status.spec = format!("ssh:{}!{}", ssh.host, ssh.command)
+        });
+        while !self.stop_signal.load(Ordering::Relaxed) {
+            let ended = tokio::select! {
+                ended = self.run_ssh(&ssh, i, &mut reader) => ended,
+                _ = self.cancellation_token.cancelled() => return,
+            };
+            failures = if ended.received { 1 } else { failures + 1 };
+            let message = format!("ssh {}: {}", ssh.host, ended.error);
+            if !ended.stderr.is_empty() {
+                warn!(
+                    "ssh {} ended, the last of its stderr:\n{}",
+                    ssh.host,
+                    ended.stderr.join("\n")
+                );
+            }
+            let failed = ended.status.is_none_or(|status| !status.success());
+            if let (true, Some(limit)) = (failed, self.restart_on_failure) {
+                if !self.count_restart(i, &ended, &message, limit) {
+                    return;
+                }
+            }
+            warn!("{message}, reconnecting");
+            let wait = backoff(failures);
+            self.model.update_source(i, |status| {
+                status.connection = Connection::Reconnecting;
+                status.error = Some(message);
+                status.retries = failures;
+                status.next_retry = Some(self.instant() + wait);
+            });
+            tokio::select! {
+                _ = self.model.clock.sleep(wait) => {}
+                _ = self.cancellation_token.cancelled() => return,
+            }
+        }
+    }
+
+    /// Count a failed run of the command of chart `i` for `--restart-on-failure` and add a note
+    /// about it. Returns whether to run it again, `limit` restarts are allowed.
+    fn count_restart(&self, i: usize, ended: &Ended, message: &str, limit: Option<u32>) -> bool {
+        let title = chart_title(&self.titles, &self.model.state.read().unwrap(), i);
+        let status = match ended.status {
+            Some(status) => status.to_string(),
+            None => "not started".to_string(),
+        };
+        // the view adds the note, it keeps `--notes-file`
+        if let Some(tx) = &self.command_tx {
+            let note = format!("{title} failed ({status}): {}", ended.error);
+            let _ = tx.send(Action::Mark(note));
+        }
+        let mut restarts = self.model.restarts.lock().unwrap();
+        let restarts = restarts.entry(i).or_default();
+        if limit.is_some_and(|limit| *restarts >= limit) {
+            let message = format!("{message}, gave up after {restarts} restarts");
+            warn!("{message}");
+            self.model.update_source(i, |status| {
+                status.connection = Connection::GaveUp;
+                status.error = Some(message.clone());
+                status.next_retry = None;
+            });
+            if let Some(tx) = &self.command_tx {
+                let _ = tx.send(Action::Toast(message));
+            }
+            return false;
+        }
+        *restarts += 1;
+        true
+    }
+
+    /// Chart the output of one run of the command until it exits.
+    async fn run_ssh(&self, ssh: &Ssh, i: usize, reader: &mut ReaderState) -> Ended {
+        self.model.update_source(i, |status| {
+            status.connection = Connection::Connecting;
+            status.next_retry = None;
+        });
+        let child = Command::new("ssh")
+            .args(ssh.args())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(err) => {
+                return Ended {
+                    received: false,
+                    error: format!("failed to run ssh: {err}"),
+                    status: None,
+                    stderr: vec![],
+                }
+            }
+        };
+        // keep the last complaints, like "Permission denied", to explain why the command ended
+        let source_status = self.model.source_status.clone();
+        let stderr = child.stderr.take().map(|stderr| {
+            task::spawn(async move {
+                let mut lines = BufReader::new(stderr).lines();
+                let mut tail = VecDeque::new();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    let line = sanitize(&line);
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    if tail.len() == STDERR_TAIL {
+                        tail.pop_front();
+                    }
+                    let mut status = source_status.lock().unwrap();
+                    status.entry(i).or_default().push_stderr(line.clone());
+                    tail.push_back(line);
+                }
+                Vec::from(tail)
+            })
+        });
+        let mut received = false;
+        if let Some(stdout) = child.stdout.take() {
+            let mut lines = BufReader::new(stdout).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                received = true;
+                self.model.update_source(i, |status| status.received(&line));
+                self.ingest_source_line(i, &line, reader);
+            }
+        }
+        let status = child.wait().await;
+        let stderr = match stderr {
+            Some(stderr) => stderr.await.unwrap_or_default(),
+            None => vec![],
+        };
+        let error = match (stderr.last(), &status) {
+            (Some(complaint), _) => complaint.clone(),
+            (None, Ok(status)) => format!("command ended ({status})"),
+            (None, Err(err)) => err.to_string(),
+        };
+        Ended {
+            received,
+            error,
+            status: status.ok(),
+            stderr,
+        }
+    }
+
+    /// Chart the first value of a line from the source of chart `i`, a command or a `--file`.
+    pub(super) fn ingest_source_line(&self, i: usize, line: &str, reader: &mut ReaderState) {
+        self.store_source_line(i, line, reader, self.instant());
+        self.announce_updates(reader);
+    }
+
+    /// Store the first value of a line from the source of chart `i` as a sample taken at `now`.
+    pub(super) fn store_source_line(
+        &self,
+        i: usize,
+        line: &str,
+        reader: &mut ReaderState,
+        now: Instant,
+    ) {
+        let line = normalize_line(line);
+        let (values, failed) = self.extractor().extract_checked(line);
+        // the first value or match of the line goes to the chart of the source
+        let extracted = match (values.first(), failed.first()) {
+            (Some(&(part, _)), Some(&failed)) if failed < part => (vec![], vec![i]),
+            (Some(&(_, value)), _) => (vec![(i, value)], vec![]),
+            (None, Some(_)) => (vec![], vec![i]),
+            (None, None) => {
+                debug!("Skipping {line:?} from the source of chart {}", i + 1);
+                self.model.skipped_lines.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+        };
+        let glued = !extracted.0.is_empty();
+        let mut state = self.model.state.write().unwrap();
+        self.store_extracted(&mut state, reader, extracted, now);
+        if glued {
+            self.adopt_suffix(&mut state, reader, i, line);
+        }
+    }
+}
+
+/// How long to wait before the next attempt after `failures` failed ones in a row, doubling from
+/// a second up to [`MAX_BACKOFF`].
+fn backoff(failures: u32) -> Duration {
+    let doublings = failures.saturating_sub(1).min(5);
+    (Duration::from_secs(1) * 2u32.pow(doublings)).min(MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::super::model::ChartModel;
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let waits = (1..=7).map(backoff).collect::<Vec<_>>();
+        let seconds = |s: &[u64]| {
+            s.iter()
+                .map(|&s| Duration::from_secs(s))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(waits, seconds(&[1, 2, 4, 8, 16, 30, 30]));
+    }
+
+    #[test]
+    fn test_restart_limit() {
+        use clap::Parser;
+
+        let args = crate::cli::Cli::parse_from([
+            "ttydash",
+            "--source",
+            "ssh:web-1!vmstat 1",
+            "--restart-on-failure=2",
+        ]);
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut reader = SourceReader::new(&args, ChartModel::default()).unwrap();
+        reader.command_tx = Some(tx);
+        let ended = Ended {
+            received: true,
+            error: "Connection refused".to_string(),
+            status: None,
+            stderr: vec!["Connection refused".to_string()],
+        };
+        let restarted = (0..3)
+            .map(|_| reader.count_restart(0, &ended, "ssh web-1: Connection refused", Some(2)))
+            .collect::<Vec<_>>();
+        assert_eq!(restarted, [true, true, false]);
+        assert_eq!(reader.model.restarts.lock().unwrap()[&0], 2);
+        let note = "web-1 failed (not started): Connection refused".to_string();
+        for _ in 0..3 {
+            assert_eq!(rx.try_recv(), Ok(Action::Mark(note.clone())));
+        }
+        assert!(reader
+            .model
+            .source_error(0)
+            .unwrap()
+            .ends_with("gave up after 2 restarts"));
+        assert_eq!(
+            reader.model.source_status.lock().unwrap()[&0].connection,
+            Connection::GaveUp
+        );
+    }
+
+    #[test]
+    fn test_restart_on_failure_flag() {
+        use clap::Parser;
+
+        let parse = |args: &[&str]| {
+            crate::cli::Cli::parse_from([&["ttydash"], args].concat()).restart_on_failure
+        };
+        assert_eq!(parse(&[]), None);
+        assert_eq!(parse(&["--restart-on-failure"]), Some(None));
+        assert_eq!(parse(&["--restart-on-failure=3"]), Some(Some(3)));
+    }
+}
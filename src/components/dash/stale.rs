@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use super::{DashState, DashView};
+
+impl DashView {
+    /// How long `chart` has gone without a sample, when that is longer than `--stale-after` or
+    /// its file is gone. A paused chart or one that never got a sample isn't stale.
+    pub(super) fn stale_age(&self, chart: &DashState) -> Option<Duration> {
+        let threshold = match chart.source_gone {
+            true => Duration::ZERO,
+            false => self.stale_after?,
+        };
+        if chart.paused_since.is_some() {
+            return None;
+        }
+        let age = self.instant().saturating_duration_since(chart.last_update?);
+        (age >= threshold).then_some(age)
+    }
+}
+
+/// `(stale 3m)`, how long a chart has gone without a sample in its largest whole unit.
+pub fn stale_label(age: Duration) -> String {
+    let seconds = age.as_secs();
+    let age = match seconds {
+        0..60 => format!("{seconds}s"),
+        60..3600 => format!("{}m", seconds / 60),
+        _ => format!("{}h", seconds / 3600),
+    };
+    format!("(stale {age})")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Instant};
+
+    use clap::Parser;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{cli::Cli, clock::MockClock};
+
+    #[test]
+    fn test_stale_label() {
+        assert_eq!(stale_label(Duration::from_secs(45)), "(stale 45s)");
+        assert_eq!(stale_label(Duration::from_secs(200)), "(stale 3m)");
+        assert_eq!(stale_label(Duration::from_secs(7300)), "(stale 2h)");
+    }
+
+    #[test]
+    fn test_stale_age() {
+        let mut dash = DashView::new(Cli::parse_from(["ttydash", "--stale-after", "30s"])).unwrap();
+        let now = Instant::now();
+        dash.set_clock(Arc::new(MockClock::new(now + Duration::from_secs(40))));
+        let mut chart = DashState::default();
+        assert_eq!(dash.stale_age(&chart), None);
+        chart.last_update = Some(now + Duration::from_secs(20));
+        assert_eq!(dash.stale_age(&chart), None);
+        chart.last_update = Some(now);
+        assert_eq!(dash.stale_age(&chart), Some(Duration::from_secs(40)));
+        chart.paused_since = Some(now);
+        assert_eq!(dash.stale_age(&chart), None);
+
+        dash.stale_after = None;
+        chart.paused_since = None;
+        assert_eq!(dash.stale_age(&chart), None);
+    }
+}
@@ -0,0 +1,216 @@
+use ratatui::{prelude::*, widgets::*};
+use unicode_width::UnicodeWidthStr;
+
+use std::time::{Duration, Instant};
+
+use super::{chart_title, cli, rolling::percentile, sparkline::sparkline, DashView};
+
+const HEADER: [&str; 8] = ["Chart", "Trend", "Last", "Avg", "Min", "Max", "p95", "Unit"];
+/// Columns holding numbers, right aligned.
+const NUMBER_COLUMNS: std::ops::Range<usize> = 2..7;
+/// The p95 column, filled in once the charts are released.
+const P95_COLUMN: usize = 6;
+/// The trend column, left out before the chart names get cut.
+const TREND_COLUMN: usize = 1;
+/// Cells of the trend column.
+const TREND_WIDTH: usize = 10;
+/// The newest samples summarized by the trend column.
+const TREND_SAMPLES: usize = 60;
+/// The trend column is redrawn at most this often, so it doesn't flicker with every sample.
+const TREND_REFRESH: Duration = Duration::from_secs(1);
+/// The panel never takes more than this share of the width or height.
+const MAX_SHARE: u16 = 2;
+
+/// The index of a chart and the cells of its row.
+pub(super) type StatsRow = (usize, [String; 8]);
+
+impl DashView {
+    /// Redraw the trend of every chart when it is due, for the stats panel and the ticker.
+    pub(super) fn refresh_trends(&mut self) {
+        let shown =
+            self.stats_panel != cli::StatsPanel::Off || self.mode == cli::DisplayMode::Ticker;
+        if !shown
+            || self
+                .trends_updated
+                .is_some_and(|updated| updated.elapsed() < TREND_REFRESH)
+        {
+            return;
+        }
+        let state = self.model.state.read().unwrap();
+        self.trends = state
+            .iter()
+            .map(|chart| {
+                let samples = chart.samples();
+                sparkline(
+                    &samples[samples.len().saturating_sub(TREND_SAMPLES)..],
+                    TREND_WIDTH,
+                )
+            })
+            .collect();
+        self.trends_updated = Some(Instant::now());
+    }
+
+    /// Split `area` into the charts and the `--stats-panel`, if there is one, with its rows.
+    pub(super) fn split_stats_panel(&self, area: Rect) -> (Rect, Option<(Rect, Vec<StatsRow>)>) {
+        if self.stats_panel == cli::StatsPanel::Off {
+            return (area, None);
+        }
+        let rows = self.stats_rows();
+        let (area, panel) = match self.stats_panel {
+            cli::StatsPanel::Off => (area, None),
+            cli::StatsPanel::Right => {
+                let widths = column_widths(&rows);
+                let max_width = (area.width / MAX_SHARE) as usize;
+                let mut width = panel_width(&widths);
+                if width > max_width {
+                    // the trend and its gap go first
+                    width -= widths[TREND_COLUMN] + 1;
+                }
+                let width = width.min(max_width) as u16;
+                let [charts, panel] =
+                    Layout::horizontal([Constraint::Min(0), Constraint::Length(width)]).areas(area);
+                (charts, Some(panel))
+            }
+            cli::StatsPanel::Bottom => {
+                // the rows, the header and the borders
+                let height = (rows.len() as u16 + 3).min(area.height / MAX_SHARE);
+                let [charts, panel] =
+                    Layout::vertical([Constraint::Min(0), Constraint::Length(height)]).areas(area);
+                (charts, Some(panel))
+            }
+        };
+        (area, panel.map(|panel| (panel, rows)))
+    }
+
+    /// One row per chart in display order, the chart index and its cells.
+    ///
+    /// The p95 of the window is computed once the charts are released, on a copy of their
+    /// samples, so the reader isn't kept waiting while they are sorted. That of the session
+    /// comes from its sketch right away.
+    fn stats_rows(&self) -> Vec<StatsRow> {
+        let state = self.model.state.read().unwrap();
+        let order = if self.order.len() == state.len() {
+            self.order.clone()
+        } else {
+            (0..state.len()).collect()
+        };
+        let rows = order
+            .into_iter()
+            .map(|i| {
+                let chart = &state[i];
+                let has_samples = chart.has_stats();
+                let number = |value: Option<f64>| {
+                    value
+                        .filter(|_| has_samples)
+                        .map_or("-".to_string(), |value| self.number(value, 2))
+                };
+                let (average, min, max) = chart.scoped_stats(self.stats_scope);
+                let mut cells = [
+                    chart_title(&self.titles, &state, i),
+                    self.trends.get(i).cloned().unwrap_or_default(),
+                    number(chart.last()),
+                    number(Some(average)),
+                    number(Some(min)),
+                    number(Some(max)),
+                    String::new(),
+                    chart.unit.clone(),
+                ];
+                let samples = match self.stats_scope {
+                    cli::StatsScope::Window => Some(chart.stats_samples().to_vec()),
+                    cli::StatsScope::Session => {
+                        cells[P95_COLUMN] = number(chart.session.percentile(95.0));
+                        None
+                    }
+                };
+                (i, cells, samples)
+            })
+            .collect::<Vec<_>>();
+        drop(state);
+        rows.into_iter()
+            .map(|(i, mut cells, samples)| {
+                if let Some(mut samples) = samples {
+                    cells[P95_COLUMN] = percentile(&mut samples, 95.0)
+                        .map_or("-".to_string(), |value| self.number(value, 2));
+                }
+                (i, cells)
+            })
+            .collect()
+    }
+
+    /// Draw a table of the `rows` of every chart, the focused one highlighted.
+    pub(super) fn draw_stats_panel(&self, frame: &mut Frame, area: Rect, rows: Vec<StatsRow>) {
+        let widths = column_widths(&rows);
+        let trend = panel_width(&widths) <= area.width as usize;
+        let shown = |column: &usize| *column != TREND_COLUMN || trend;
+        // the name column takes what is left, so it is the one cut when space runs out
+        let constraints = widths
+            .iter()
+            .enumerate()
+            .filter(|(column, _)| shown(column))
+            .map(|(column, &width)| match column {
+                0 => Constraint::Fill(1),
+                _ => Constraint::Length(width as u16),
+            });
+        let table_rows = rows.into_iter().map(|(i, cells)| {
+            let style = if self.view.focus == Some(i) {
+                Style::default().fg(Color::Cyan)
+            } else {
+                Style::default()
+            };
+            let cells = cells
+                .into_iter()
+                .enumerate()
+                .filter(|(column, _)| shown(column));
+            let cells = cells.map(|(column, cell)| {
+                // numbers line up on the right
+                if NUMBER_COLUMNS.contains(&column) {
+                    Cell::from(Text::from(cell).right_aligned())
+                } else {
+                    Cell::from(cell)
+                }
+            });
+            Row::new(cells).style(style)
+        });
+        let table = Table::new(table_rows, constraints)
+            .header(
+                Row::new(
+                    HEADER
+                        .iter()
+                        .enumerate()
+                        .filter(|(column, _)| shown(column))
+                        .map(|(column, &name)| {
+                            if NUMBER_COLUMNS.contains(&column) {
+                                Cell::from(Text::from(name).right_aligned())
+                            } else {
+                                Cell::from(name)
+                            }
+                        }),
+                )
+                .style(Style::new().dim()),
+            )
+            .column_spacing(1)
+            .block(
+                Block::default()
+                    .title("Stats")
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded),
+            );
+        frame.render_widget(table, area);
+    }
+}
+
+/// The width of a panel showing every column in full, with their gaps and the borders.
+fn panel_width(widths: &[usize]) -> usize {
+    widths.iter().sum::<usize>() + widths.len() - 1 + 2
+}
+
+/// The widest cell of every column, including the header.
+fn column_widths(rows: &[StatsRow]) -> [usize; 8] {
+    let mut widths = HEADER.map(UnicodeWidthStr::width);
+    for (_, cells) in rows {
+        for (width, cell) in widths.iter_mut().zip(cells) {
+            *width = (*width).max(cell.width());
+        }
+    }
+    widths
+}
@@ -0,0 +1,29 @@
+//! Helpers shared by the source tests.
+
+use std::time::Duration;
+
+use clap::Parser;
+
+use super::{model::ChartModel, reader::SourceReader, DashState};
+use crate::cli::Cli;
+
+/// Parse `args` as if they followed `ttydash` on the command line.
+pub(super) fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+    Cli::try_parse_from([&["ttydash"], args].concat())
+}
+
+/// A reader for the flags `args`, charting into a fresh model.
+pub(super) fn new_source(args: &[&str]) -> SourceReader {
+    SourceReader::new(&parse(args).unwrap(), ChartModel::default()).unwrap()
+}
+
+/// Wait until `done` holds for the charts of `source`, a few seconds at most.
+pub(super) async fn wait_for(source: &SourceReader, done: impl Fn(&[DashState]) -> bool) {
+    for _ in 0..400 {
+        if done(&source.model.state.read().unwrap()) {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    panic!("timed out");
+}
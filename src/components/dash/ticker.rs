@@ -0,0 +1,191 @@
+use color_eyre::Result;
+use ratatui::{prelude::*, widgets::*};
+use unicode_width::UnicodeWidthStr;
+
+use super::{chart_title, DashView};
+
+/// The detail pane is left out when less than this many columns would be left for it.
+const MIN_DETAIL_WIDTH: u16 = 30;
+/// The name column is cut beyond this many cells.
+const MAX_NAME_WIDTH: usize = 30;
+
+/// A line of the ticker: the chart index, its name, last value, trend arrow and sparkline, and
+/// whether it is above its reference.
+struct TickerRow {
+    i: usize,
+    cells: [String; 4],
+    alert: bool,
+}
+
+impl DashView {
+    /// Draw `--mode ticker`: a line per chart in display order next to the full chart of the
+    /// focused one, or only that chart while it is expanded.
+    pub(super) fn draw_ticker(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let shared = self.model.state.clone();
+        let state = shared.read().unwrap();
+        self.refresh_order(&state);
+        drop(state);
+        let Some(current) = self.current_chart() else {
+            return Ok(());
+        };
+        if self.view.expanded {
+            return self.draw_chart(frame, &area, current);
+        }
+        let rows = self.ticker_rows();
+        let widths = (0..4).map(|column| {
+            let widest = rows.iter().map(|row| row.cells[column].width()).max();
+            widest.unwrap_or(0)
+        });
+        let widths = widths.collect::<Vec<_>>();
+        // the columns, their gaps and the borders
+        let list_width = widths[0].min(MAX_NAME_WIDTH) + widths[1..].iter().sum::<usize>() + 3 + 2;
+        let detail_width = area.width.saturating_sub(list_width as u16);
+        let (list, detail) = if detail_width >= MIN_DETAIL_WIDTH {
+            let [list, detail] =
+                Layout::horizontal([Constraint::Length(list_width as u16), Constraint::Min(0)])
+                    .areas(area);
+            (list, Some(detail))
+        } else {
+            (area, None)
+        };
+        let selected = rows.iter().position(|row| row.i == current);
+        let table_rows = rows.into_iter().map(|row| {
+            let [name, value, arrow, trend] = row.cells;
+            let value = Text::from(value).right_aligned();
+            let value = match row.alert {
+                true => Cell::from(value).red(),
+                false => Cell::from(value),
+            };
+            Row::new([
+                Cell::from(name),
+                value,
+                Cell::from(arrow),
+                Cell::from(trend),
+            ])
+        });
+        let constraints = [
+            Constraint::Fill(1),
+            Constraint::Length(widths[1] as u16),
+            Constraint::Length(widths[2] as u16),
+            Constraint::Length(widths[3] as u16),
+        ];
+        let table = Table::new(table_rows, constraints)
+            .column_spacing(1)
+            .highlight_style(Style::default().fg(Color::Cyan))
+            .block(
+                Block::default()
+                    .title("Ticker")
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded),
+            );
+        // the selected row is scrolled into view
+        let mut table_state = TableState::default().with_selected(selected);
+        frame.render_stateful_widget(table, list, &mut table_state);
+        if let Some(detail) = detail {
+            self.draw_chart(frame, &detail, current)?;
+        }
+        Ok(())
+    }
+
+    /// One line per chart in display order.
+    fn ticker_rows(&self) -> Vec<TickerRow> {
+        let state = self.model.state.read().unwrap();
+        self.order
+            .iter()
+            .filter(|&&i| i < state.len())
+            .map(|&i| {
+                let chart = &state[i];
+                let samples = chart.samples();
+                let value = match chart.last() {
+                    Some(last) => match self.scaled(last, 2, &chart.unit) {
+                        (value, unit) if unit.is_empty() => value,
+                        (value, unit) => format!("{value} {unit}"),
+                    },
+                    None => "-".to_string(),
+                };
+                TickerRow {
+                    i,
+                    cells: [
+                        chart_title(&self.titles, &state, i),
+                        value,
+                        trend_arrow(samples).to_string(),
+                        self.trends.get(i).cloned().unwrap_or_default(),
+                    ],
+                    alert: self.above_reference(chart, i),
+                }
+            })
+            .collect()
+    }
+}
+
+/// How the newest sample moved from the one before it.
+fn trend_arrow(samples: &[f64]) -> &'static str {
+    match samples {
+        [.., previous, last] if last > previous => "↑",
+        [.., previous, last] if last < previous => "↓",
+        [.., _, _] => "→",
+        _ => " ",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use clap::Parser;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{action::Action, cli::Cli, components::Component};
+
+    fn ticker(args: &[&str], values: &[f64]) -> DashView {
+        let state = values
+            .iter()
+            .map(|&value| {
+                let mut chart = super::super::DashState::new(10, Duration::from_secs(1));
+                chart.update(value);
+                chart
+            })
+            .collect();
+        let args = Cli::parse_from([&["ttydash", "--mode", "ticker"], args].concat());
+        DashView::with_states(state, args).unwrap()
+    }
+
+    #[test]
+    fn test_alerts_first() {
+        let references = ["--reference", "20"].repeat(4);
+        let args = [&["--sort", "alert"], &references[..]].concat();
+        let mut dash = ticker(&args, &[5.0, 30.0, 10.0, 40.0]);
+        let shared = dash.model.state.clone();
+        dash.refresh_order(&shared.read().unwrap());
+        let rows = dash.ticker_rows();
+        let order = rows
+            .iter()
+            .map(|row| (row.i, row.alert))
+            .collect::<Vec<_>>();
+        assert_eq!(order, [(1, true), (3, true), (0, false), (2, false)]);
+        assert_eq!(rows[0].cells[1], "30.00");
+    }
+
+    #[test]
+    fn test_expand() {
+        let mut dash = ticker(&[], &[1.0]);
+        dash.update(Action::ToggleExpand).unwrap();
+        assert!(dash.view.expanded);
+        dash.update(Action::ToggleExpand).unwrap();
+        assert!(!dash.view.expanded);
+
+        let mut dash = DashView::new(Cli::parse_from(["ttydash"])).unwrap();
+        dash.update(Action::ToggleExpand).unwrap();
+        assert!(!dash.view.expanded);
+    }
+
+    #[test]
+    fn test_trend_arrow() {
+        assert_eq!(trend_arrow(&[]), " ");
+        assert_eq!(trend_arrow(&[3.0]), " ");
+        assert_eq!(trend_arrow(&[1.0, 3.0]), "↑");
+        assert_eq!(trend_arrow(&[5.0, 3.0, 2.0]), "↓");
+        assert_eq!(trend_arrow(&[2.0, 2.0]), "→");
+    }
+}
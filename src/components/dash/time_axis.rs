@@ -0,0 +1,133 @@
+use std::time::{Duration, SystemTime};
+
+use ratatui::prelude::*;
+use unicode_width::UnicodeWidthStr;
+
+use super::format_seconds;
+use crate::{cli, clock::format_clock, config::Theme};
+
+/// A labelled column on the time axis, counted from the newest (right) edge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeMarker {
+    pub column: usize,
+    pub label: String,
+}
+
+/// Place markers every `spacing` columns of a `width` columns wide chart, doubling the step
+/// until the labels no longer overlap.
+pub fn generate_time_markers(
+    width: usize,
+    spacing: usize,
+    label: impl Fn(usize) -> String,
+) -> Vec<TimeMarker> {
+    let mut step = spacing.max(1);
+    loop {
+        let markers = (1..)
+            .map(|i| i * step)
+            .take_while(|&column| column <= width.saturating_sub(5))
+            .map(|column| TimeMarker {
+                column,
+                label: label(column),
+            })
+            .collect::<Vec<_>>();
+        let widest = markers.iter().map(|m| m.label.width()).max().unwrap_or(0);
+        // one column for the marker glyph and at least one to separate the labels
+        if widest + 2 <= step || markers.len() <= 1 {
+            return markers;
+        }
+        step *= 2;
+    }
+}
+
+/// Label of the column `column` columns away from the newest edge.
+pub fn time_label(
+    mode: cli::TimeAxis,
+    column: usize,
+    seconds_per_column: f64,
+    offset_seconds: f64,
+    now: SystemTime,
+) -> String {
+    let seconds = offset_seconds + column as f64 * seconds_per_column;
+    match mode {
+        cli::TimeAxis::Clock => format_clock(now - Duration::from_secs_f64(seconds)),
+        _ => format_seconds(seconds),
+    }
+}
+
+/// Render markers into the bottom border, each column being `column_width` cells wide. The
+/// newest edge is on the right unless `newest_left` is set.
+pub fn marker_line(
+    markers: &[TimeMarker],
+    column_width: usize,
+    theme: &Theme,
+    newest_left: bool,
+) -> Line<'static> {
+    let glyph_width = theme.time_marker.width();
+    let mut used = 0;
+    let mut spans = vec![];
+    for marker in markers {
+        let position = marker.column * column_width;
+        spans.push(Span::raw("─".repeat(position.saturating_sub(used))));
+        spans.push(Span::raw(theme.time_marker.clone()));
+        spans.push(Span::styled(marker.label.clone(), theme.time_label));
+        used = used.max(position) + glyph_width + marker.label.width();
+    }
+    if newest_left {
+        return Line::from(spans).left_aligned();
+    }
+    // the marker closest to the newest edge goes last, with its label on the older side
+    spans.reverse();
+    Line::from(spans).right_aligned()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn relative(column: usize) -> String {
+        time_label(cli::TimeAxis::Relative, column, 1.0, 0.0, SystemTime::now())
+    }
+
+    fn columns(markers: &[TimeMarker]) -> Vec<usize> {
+        markers.iter().map(|m| m.column).collect()
+    }
+
+    #[test]
+    fn test_markers_every_spacing() {
+        let markers = generate_time_markers(100, 30, relative);
+        assert_eq!(columns(&markers), vec![30, 60, 90]);
+        assert_eq!(markers[1].label, "60s");
+    }
+
+    #[test]
+    fn test_narrow_chart_has_no_markers() {
+        assert_eq!(generate_time_markers(20, 30, relative), vec![]);
+    }
+
+    #[test]
+    fn test_density_reduced_when_labels_overlap() {
+        // two digit labels need 3 columns plus the glyph and a gap, more than 4
+        let markers = generate_time_markers(100, 4, relative);
+        assert_eq!(columns(&markers)[..3], [8, 16, 24]);
+        // clock labels are 8 wide and need 10 columns
+        let clock = |column| time_label(cli::TimeAxis::Clock, column, 1.0, 0.0, SystemTime::now());
+        let markers = generate_time_markers(100, 4, clock);
+        assert_eq!(columns(&markers)[..2], [16, 32]);
+    }
+
+    #[test]
+    fn test_marker_spans() {
+        let markers = generate_time_markers(70, 30, relative);
+        let text = |newest_left| {
+            marker_line(&markers, 1, &Theme::default(), newest_left)
+                .iter()
+                .map(|span| span.content.to_string())
+                .collect::<String>()
+        };
+        let dashes = "─".repeat(26);
+        assert_eq!(text(false), format!("60s├{dashes}30s├{}", "─".repeat(30)));
+        assert_eq!(text(true), format!("{}├30s{dashes}├60s", "─".repeat(30)));
+    }
+}
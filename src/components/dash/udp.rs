@@ -0,0 +1,182 @@
+//! `--listen-udp`: fire-and-forget clients, like `echo 42 | nc -u -w0 host 8125`, send
+//! datagrams of numbers that are charted the way lines of stdin are. There is no client to tell
+//! about a datagram that can't be charted, so those are counted on the stats line instead.
+
+use std::{net::SocketAddr, sync::atomic::Ordering};
+
+use tokio::net::UdpSocket;
+use tracing::debug;
+
+use super::{
+    reader::{ReaderState, SourceReader},
+    DashView,
+};
+
+/// The largest datagram charted, a larger one would have its last number cut off.
+const MAX_DATAGRAM: usize = 8192;
+
+impl SourceReader {
+    /// The `--listen-udp` address actually listened on, with the port the system picked for 0.
+    pub(super) fn udp_addr(&self) -> Option<SocketAddr> {
+        self.udp_socket
+            .as_ref()
+            .and_then(|socket| socket.local_addr().ok())
+    }
+
+    /// Chart the datagrams that come until the dashboard stops. The metrics formats are
+    /// aggregated and stored once per update interval like the lines of stdin.
+    pub(super) async fn receive_datagrams(
+        &self,
+        socket: &std::net::UdpSocket,
+        mut reader: ReaderState,
+    ) {
+        let socket = match socket.try_clone().and_then(UdpSocket::from_std) {
+            Ok(socket) => socket,
+            Err(err) => {
+                self.report_failure(format!("Failed to receive datagrams: {err}"));
+                return;
+            }
+        };
+        // a byte more than is charted, to tell a datagram that doesn't fit
+        let mut buffer = vec![0; MAX_DATAGRAM + 1];
+        let mut interval = self.model.clock.interval(self.base_interval());
+        while !self.stop_signal.load(Ordering::Relaxed) {
+            tokio::select! {
+                received = socket.recv_from(&mut buffer) => match received {
+                    Ok((len, peer)) => self.ingest_datagram(&buffer[..len], peer, &mut reader),
+                    Err(err) => debug!("Failed to receive a datagram: {err}"),
+                },
+                _ = interval.tick() => {
+                    self.flush_pending(&mut reader);
+                    self.announce_updates(&mut reader);
+                }
+                _ = self.cancellation_token.cancelled() => break,
+            }
+        }
+    }
+
+    /// Chart every line of a datagram, or count it when it is too large or has no value.
+    fn ingest_datagram(&self, datagram: &[u8], peer: SocketAddr, reader: &mut ReaderState) {
+        if datagram.len() > MAX_DATAGRAM {
+            debug!("Dropping a datagram of more than {MAX_DATAGRAM} bytes from {peer}");
+            self.model
+                .oversized_datagrams
+                .fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        let mut charted = false;
+        if let Ok(text) = std::str::from_utf8(datagram) {
+            for line in text.lines() {
+                charted |= self.ingest(line, reader);
+            }
+        }
+        if !charted {
+            let text = String::from_utf8_lossy(datagram);
+            debug!("Dropping a datagram without a value from {peer}: {text:?}");
+            self.model
+                .malformed_datagrams
+                .fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl DashView {
+    /// The datagrams that couldn't be charted, for the stats line.
+    pub(super) fn dropped_datagrams(&self) -> Option<String> {
+        dropped_label(
+            self.model.oversized_datagrams.load(Ordering::Relaxed),
+            self.model.malformed_datagrams.load(Ordering::Relaxed),
+        )
+    }
+}
+
+/// `✉ 2 oversized, 1 malformed datagrams`, `None` when every datagram was charted.
+fn dropped_label(oversized: usize, malformed: usize) -> Option<String> {
+    let counts = [(oversized, "oversized"), (malformed, "malformed")]
+        .into_iter()
+        .filter(|&(count, _)| count > 0)
+        .map(|(count, kind)| format!("{count} {kind}"))
+        .collect::<Vec<_>>();
+    if counts.is_empty() {
+        return None;
+    }
+    let noun = match oversized + malformed {
+        1 => "datagram",
+        _ => "datagrams",
+    };
+    Some(format!("✉ {} {noun}", counts.join(", ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::super::{
+        model::ChartModel,
+        test_util::{new_source, parse, wait_for},
+    };
+    use super::*;
+
+    fn udp_source(args: &[&str]) -> SourceReader {
+        new_source(&[&["--listen-udp", "127.0.0.1:0"], args].concat())
+    }
+
+    #[tokio::test]
+    async fn test_datagrams() {
+        let source = udp_source(&["-u", "ms", "-u", "rps"]);
+        let addr = source.udp_addr().unwrap();
+        let task = tokio::spawn(source.clone().run());
+        let client = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        client.send_to(b"12ms 100rps\n", addr).unwrap();
+        // lines of one datagram are charted one after another
+        client.send_to(b"15ms 90rps\n17ms 80rps", addr).unwrap();
+        wait_for(&source, |state| {
+            state.len() == 2 && state.iter().all(|chart| chart.stored == 3)
+        })
+        .await;
+        client.send_to(&[b'1'; MAX_DATAGRAM + 1], addr).unwrap();
+        client.send_to(b"hello", addr).unwrap();
+        client.send_to(b"\xff\xfe", addr).unwrap();
+        wait_for(&source, |_| {
+            source.model.malformed_datagrams.load(Ordering::Relaxed) == 2
+                && source.model.oversized_datagrams.load(Ordering::Relaxed) == 1
+        })
+        .await;
+        source.stop();
+        task.await.unwrap();
+        let state = source.model.state.read().unwrap();
+        assert_eq!(state[0].samples()[..3], [12.0, 15.0, 17.0]);
+        assert_eq!(state[1].samples()[..3], [100.0, 90.0, 80.0]);
+        assert_eq!(source.input_name(), format!("udp {addr}"));
+    }
+
+    #[test]
+    fn test_dropped_label() {
+        assert_eq!(dropped_label(0, 0), None);
+        assert_eq!(
+            dropped_label(1, 0),
+            Some("✉ 1 oversized datagram".to_string())
+        );
+        assert_eq!(
+            dropped_label(2, 1),
+            Some("✉ 2 oversized, 1 malformed datagrams".to_string())
+        );
+    }
+
+    #[test]
+    fn test_listen_udp_flags() {
+        assert!(parse(&["--listen-udp", "8125", "--listen", "9999"]).is_err());
+        assert_eq!(
+            parse(&["--listen-udp", "0.0.0.0:8125"]).unwrap().listen_udp,
+            Some(SocketAddr::from(([0, 0, 0, 0], 8125)))
+        );
+        // the port is taken
+        let source = udp_source(&[]);
+        let addr = source.udp_addr().unwrap().to_string();
+        let args = parse(&["--listen-udp", &addr]).unwrap();
+        let err = SourceReader::new(&args, ChartModel::default()).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with(&format!("Failed to listen on udp {addr}: ")));
+    }
+}
@@ -0,0 +1,217 @@
+//! `--socket`: programs on the same host, like a daemon reporting its queue length, connect to
+//! a Unix socket and write lines that are charted the way lines of stdin are, without tying up
+//! stdin. Like `--listen`, writers come and go and their lines go into the same charts. The
+//! socket file is removed on exit.
+
+use std::{
+    fs, io,
+    os::unix::{fs::FileTypeExt, net},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    net::UnixListener,
+    sync::mpsc,
+    task::JoinSet,
+};
+use tracing::{debug, info, warn};
+
+use super::reader::{ReaderState, SourceReader};
+
+/// The listening socket and its file, removed once the dashboard is done with it.
+#[derive(Debug)]
+pub(super) struct SocketFile {
+    listener: net::UnixListener,
+    path: PathBuf,
+    removed: AtomicBool,
+}
+
+impl SocketFile {
+    /// Create the socket at `path`, replacing one left behind by a session that crashed.
+    pub(super) fn bind(path: &Path) -> io::Result<Self> {
+        let listener = match net::UnixListener::bind(path) {
+            Err(err) if err.kind() == io::ErrorKind::AddrInUse && !is_socket(path) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    "a file that isn't a socket is in the way",
+                ));
+            }
+            Err(err) if err.kind() == io::ErrorKind::AddrInUse && is_stale(path) => {
+                fs::remove_file(path)?;
+                net::UnixListener::bind(path)?
+            }
+            result => result?,
+        };
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            path: path.to_path_buf(),
+            removed: AtomicBool::new(false),
+        })
+    }
+
+    pub(super) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Remove the socket file, once: a later session may have created its own at the path.
+    pub(super) fn remove(&self) {
+        if self.removed.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        match fs::remove_file(&self.path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                warn!("Failed to remove {}: {err}", self.path.display());
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Drop for SocketFile {
+    fn drop(&mut self) {
+        self.remove();
+    }
+}
+
+fn is_socket(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket())
+}
+
+/// Whether `path` is a socket that nobody listens on anymore.
+fn is_stale(path: &Path) -> bool {
+    is_socket(path)
+        && net::UnixStream::connect(path)
+            .is_err_and(|err| err.kind() == io::ErrorKind::ConnectionRefused)
+}
+
+impl SourceReader {
+    /// Accept writers until the dashboard stops, reading each in its own task and charting
+    /// their lines together.
+    pub(super) async fn accept_writers(&self, socket: &SocketFile, reader: ReaderState) {
+        let listener = match socket.listener.try_clone().and_then(UnixListener::from_std) {
+            Ok(listener) => listener,
+            Err(err) => {
+                self.report_failure(format!("Failed to accept writers: {err}"));
+                return;
+            }
+        };
+        let (lines_tx, lines_rx) = mpsc::unbounded_channel();
+        let mut tasks = JoinSet::new();
+        tasks.spawn(self.clone().ingest_merged(lines_rx, reader));
+        let mut writers = 0;
+        while !self.stop_signal.load(Ordering::Relaxed) {
+            let stream = tokio::select! {
+                _ = self.cancellation_token.cancelled() => break,
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => stream,
+                    Err(err) => {
+                        debug!("Failed to accept a writer: {err}");
+                        continue;
+                    }
+                },
+            };
+            // forget the writers that are done
+            while tasks.try_join_next().is_some() {}
+            // the writers of a socket have no address, they are told apart by a count
+            writers += 1;
+            let writer = format!("writer {writers}");
+            info!("Accepted {writer} on {}", socket.path.display());
+            let lines = BufReader::new(stream).lines();
+            tasks.spawn(
+                self.clone()
+                    .forward_connection(writer, lines, lines_tx.clone()),
+            );
+        }
+        // the writers still connected are closed as their tasks are dropped
+        tasks.shutdown().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tokio::{io::AsyncWriteExt, net::UnixStream};
+
+    use super::super::{
+        model::ChartModel,
+        test_util::{new_source, parse, wait_for},
+    };
+    use super::*;
+
+    fn socket_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("ttydash-{name}-{}.sock", std::process::id()))
+    }
+
+    fn socket_source(path: &Path, args: &[&str]) -> SourceReader {
+        new_source(&[&["--socket", path.to_str().unwrap()], args].concat())
+    }
+
+    #[tokio::test]
+    async fn test_writers() {
+        let path = socket_path("writers");
+        let source = socket_source(&path, &["-i", "3", "-i", "1"]);
+        let task = tokio::spawn(source.clone().run());
+        let mut first = UnixStream::connect(&path).await.unwrap();
+        first.write_all(b"12 5 100\n").await.unwrap();
+        wait_for(&source, |state| state.len() == 2 && state[1].stored == 1).await;
+        // a writer hanging up doesn't end the reader, and two can write at once
+        drop(first);
+        let mut second = UnixStream::connect(&path).await.unwrap();
+        let mut third = UnixStream::connect(&path).await.unwrap();
+        second.write_all(b"15 6 90\n").await.unwrap();
+        wait_for(&source, |state| state[1].stored == 2).await;
+        third.write_all(b"17 7 80\n").await.unwrap();
+        wait_for(&source, |state| state[1].stored == 3).await;
+        source.stop();
+        task.await.unwrap();
+        let state = source.model.state.read().unwrap();
+        assert_eq!(state.len(), 2);
+        assert_eq!(state[0].samples()[..3], [100.0, 90.0, 80.0]);
+        assert_eq!(state[1].samples()[..3], [12.0, 15.0, 17.0]);
+        drop(state);
+        assert_eq!(source.input_name(), format!("socket {}", path.display()));
+        assert!(path.exists());
+        drop(source);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_socket_file() {
+        let path = socket_path("file");
+        // left behind by a session that crashed
+        drop(net::UnixListener::bind(&path).unwrap());
+        let socket = SocketFile::bind(&path).unwrap();
+        // a socket still listened on is in use
+        assert_eq!(
+            SocketFile::bind(&path).err().map(|err| err.kind()),
+            Some(io::ErrorKind::AddrInUse)
+        );
+        socket.remove();
+        assert!(!path.exists());
+        // a file of another session at the path is left alone
+        fs::write(&path, "").unwrap();
+        drop(socket);
+        assert!(path.exists());
+        // and isn't replaced
+        assert_eq!(
+            SocketFile::bind(&path).unwrap_err().to_string(),
+            "a file that isn't a socket is in the way"
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_socket_flags() {
+        assert!(parse(&["--socket", "a.sock", "--listen", "9999"]).is_err());
+        assert!(parse(&["--socket", "a.sock", "--input", "a.log"]).is_err());
+        let path = std::env::temp_dir().join("ttydash-missing").join("a.sock");
+        let args = parse(&["--socket", path.to_str().unwrap()]).unwrap();
+        let err = SourceReader::new(&args, ChartModel::default()).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with(&format!("Failed to listen on {}: ", path.display())));
+    }
+}
@@ -0,0 +1,242 @@
+use serde::{Deserialize, Serialize};
+
+use super::{cli, DashView};
+use crate::action::Action;
+
+/// What was done with the charts, as opposed to how they were configured. Focus, pins, the
+/// display order and hidden charts are kept per chart, by the chart's index, so they follow the
+/// chart wherever it is drawn; zoom, pan and the crosshair are about time and hold for every
+/// chart. All of it survives grouping and layout toggles and resizes, and is only cleared by
+/// the keys that reset it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ViewState {
+    pub(super) focus: Option<usize>,
+    /// Charts kept on top, in the order they were pinned.
+    pub(super) pinned: Vec<usize>,
+    /// Display order the charts were moved into, followed by the others without `--sort`.
+    pub(super) arranged: Vec<usize>,
+    /// Charts left out of the view with `h`, in the order they were hidden.
+    pub(super) hidden: Vec<usize>,
+    /// Samples per column is 2^zoom.
+    pub(super) zoom: u32,
+    /// Number of samples between the newest sample and the right edge of the view.
+    pub(super) offset: usize,
+    /// Columns between the crosshair and the newest edge.
+    pub(super) crosshair: Option<usize>,
+    /// The focused chart fills the ticker, toggled with `Enter`.
+    pub(super) expanded: bool,
+}
+
+impl DashView {
+    /// Handle `h`: leave the current chart out of the view, the focus moving on to the next one.
+    pub(super) fn hide_chart(&mut self) {
+        let Some(i) = self.current_chart() else {
+            return;
+        };
+        let Some(position) = self.order.iter().position(|&shown| shown == i) else {
+            return;
+        };
+        if self.order.len() == 1 {
+            if let Some(tx) = &self.command_tx {
+                let _ = tx.send(Action::Toast("The last chart can't be hidden".to_string()));
+            }
+            return;
+        }
+        self.order.remove(position);
+        if self.view.focus == Some(i) {
+            // the chart that took its place, or the one before when it was the last
+            self.view.focus = Some(self.order[position.min(self.order.len() - 1)]);
+        }
+        self.view.hidden.push(i);
+        self.order_updated = None;
+        self.order.clear();
+    }
+
+    /// Handle `H`: bring back the hidden charts.
+    pub(super) fn show_all(&mut self) {
+        self.view.hidden.clear();
+        self.order_updated = None;
+        self.order.clear();
+    }
+
+    /// Handle `g`: draw the charts in one group chart, or in `--group-by` panels, or apart.
+    pub(super) fn toggle_group(&mut self) {
+        self.group = !self.group;
+    }
+
+    /// Handle `l`: switch to the next layout.
+    pub(super) fn cycle_layout(&mut self) {
+        self.layout = match self.layout {
+            cli::Layout::Auto => cli::Layout::Horizontal,
+            cli::Layout::Horizontal => cli::Layout::Vertical,
+            cli::Layout::Vertical => cli::Layout::Auto,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use clap::Parser;
+    use pretty_assertions::assert_eq;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    use super::{
+        super::{model::DashState, simulate},
+        *,
+    };
+    use crate::{cli::Cli, components::Component};
+
+    fn dash(args: &[&str], charts: usize) -> DashView {
+        let args = Cli::parse_from([&["ttydash", "--deterministic"], args].concat());
+        let state = (0..charts)
+            .map(|n| {
+                let mut chart = DashState::new(400, Duration::from_secs(1));
+                for i in 0..400 {
+                    chart.update(((i * 7 + n * 5) % 23) as f64);
+                }
+                chart
+            })
+            .collect();
+        DashView::with_states(state, args).unwrap()
+    }
+
+    fn draw(dash: &mut DashView, width: u16, height: u16) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|frame| dash.draw(frame, frame.area()).unwrap())
+            .unwrap();
+        simulate::frame_text(terminal.backend().buffer())
+    }
+
+    fn press(dash: &mut DashView, actions: impl IntoIterator<Item = Action>) {
+        for action in actions {
+            dash.update(action).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_pan_zoomed_then_group() {
+        let mut dash = dash(&[], 3);
+        draw(&mut dash, 80, 24);
+        press(
+            &mut dash,
+            [
+                Action::ZoomOut,
+                Action::PanLeft,
+                Action::FocusNext,
+                Action::FocusNext,
+            ],
+        );
+        let view = dash.view.clone();
+        assert_eq!((view.zoom, view.offset, view.focus), (1, 20, Some(1)));
+
+        dash.update(Action::ToggleGroup).unwrap();
+        let frame = draw(&mut dash, 80, 24);
+        assert!(frame.contains("Group Chart ×2 2s/col -20s"), "{frame}");
+        dash.update(Action::ToggleGroup).unwrap();
+        draw(&mut dash, 80, 24);
+        assert_eq!(dash.view, view);
+
+        // the layout goes the same way
+        press(&mut dash, [Action::CycleLayout, Action::CycleLayout]);
+        assert_eq!(dash.layout, cli::Layout::Vertical);
+        draw(&mut dash, 80, 24);
+        assert_eq!(dash.view, view);
+
+        // until reset on purpose
+        dash.update(Action::PanReset).unwrap();
+        assert_eq!(dash.view.offset, 0);
+    }
+
+    #[test]
+    fn test_hide_focused() {
+        let mut dash = dash(&[], 3);
+        draw(&mut dash, 80, 24);
+        press(&mut dash, [Action::FocusNext, Action::FocusNext]);
+        dash.update(Action::HideChart).unwrap();
+        draw(&mut dash, 80, 24);
+        // the focus moves on to the chart taking its place
+        assert_eq!((dash.order.clone(), dash.view.focus), (vec![0, 2], Some(2)));
+        dash.update(Action::HideChart).unwrap();
+        draw(&mut dash, 80, 24);
+        assert_eq!((dash.order.clone(), dash.view.focus), (vec![0], Some(0)));
+        // one chart stays
+        dash.update(Action::HideChart).unwrap();
+        draw(&mut dash, 80, 24);
+        assert_eq!(dash.order, [0]);
+
+        // hidden in a group chart too
+        dash.update(Action::ToggleGroup).unwrap();
+        let frame = draw(&mut dash, 80, 24);
+        assert!(!frame.contains("Chart 2"), "{frame}");
+        assert_eq!(dash.view.hidden, [1, 2]);
+
+        dash.update(Action::ShowAll).unwrap();
+        dash.update(Action::ToggleGroup).unwrap();
+        draw(&mut dash, 80, 24);
+        assert_eq!(
+            (dash.order.clone(), dash.view.focus),
+            (vec![0, 1, 2], Some(0))
+        );
+    }
+
+    #[test]
+    fn test_resize_while_inspecting() {
+        let mut dash = dash(&["--source", "ssh:web-1!vmstat 1"], 1);
+        draw(&mut dash, 80, 12);
+        press(
+            &mut dash,
+            [Action::ToggleCrosshair, Action::ToggleInspector],
+        );
+        for _ in 0..60 {
+            dash.update(Action::PanLeft).unwrap();
+        }
+        let view = dash.view.clone();
+        assert_eq!(view.crosshair, Some(60));
+
+        dash.update(Action::Resize(40, 12)).unwrap();
+        draw(&mut dash, 40, 12);
+        // out of view for now, and back where it was once there is room again
+        assert_eq!(dash.view, view);
+        assert!(dash.inspecting);
+        dash.update(Action::Resize(80, 12)).unwrap();
+        let frame = draw(&mut dash, 80, 12);
+        assert_eq!(dash.view, view);
+        assert!(frame.contains("⌖"), "{frame}");
+        assert!(frame.contains("Source"), "{frame}");
+
+        // moving it in a narrow view brings it back in
+        dash.update(Action::Resize(40, 12)).unwrap();
+        draw(&mut dash, 40, 12);
+        dash.update(Action::PanLeft).unwrap();
+        assert_eq!(dash.view.crosshair, Some(38));
+    }
+
+    #[test]
+    fn test_serde() {
+        let view = ViewState {
+            focus: Some(2),
+            pinned: vec![1],
+            hidden: vec![3],
+            zoom: 2,
+            offset: 40,
+            crosshair: Some(5),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&view).unwrap();
+        assert_eq!(serde_json::from_str::<ViewState>(&json).unwrap(), view);
+    }
+
+    #[test]
+    fn test_draws_at_any_size() {
+        // a terminal dragged to nothing passes through these on its way
+        for args in [&[][..], &["-g"], &["--history", "auto"]] {
+            let mut dash = dash(args, 2);
+            for (width, height) in [(0, 0), (0, 12), (1, 1), (2, 3), (80, 0), (3, 24)] {
+                draw(&mut dash, width, height);
+            }
+        }
+    }
+}
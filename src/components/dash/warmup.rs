@@ -0,0 +1,50 @@
+use ratatui::{prelude::*, Frame};
+
+use super::{DashState, DashView};
+
+impl DashView {
+    /// Dim the columns holding only `--warmup` samples and draw a dotted line through the empty
+    /// cells of the column where the warmup ends.
+    pub(super) fn draw_warmup(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        chart: &DashState,
+        width: usize,
+    ) {
+        if chart.warmup == 0 {
+            return;
+        }
+        let inner = area.inner(Margin::new(1, 1));
+        let width = width.min(inner.width as usize);
+        let after_warmup = chart.length - chart.warmup;
+        let divider = self.divider_position(chart, width, after_warmup);
+        let newest_left = self.newest_left();
+        for position in 0..width {
+            // older columns lie away from the newest side
+            let warmup = match divider {
+                Some(divider) if newest_left => position > divider,
+                Some(divider) => position < divider,
+                // the whole view is older than the warmup's end, or newer than it
+                None => {
+                    let offset = self.chart_offset(chart.interval);
+                    after_warmup <= offset
+                }
+            };
+            let x = inner.x + position as u16;
+            if warmup {
+                let column = Rect::new(x, inner.y, 1, inner.height);
+                frame
+                    .buffer_mut()
+                    .set_style(column, Style::new().add_modifier(Modifier::DIM));
+            } else if divider == Some(position) {
+                for y in inner.top()..inner.bottom() {
+                    let cell = &mut frame.buffer_mut()[(x, y)];
+                    if cell.symbol() == " " {
+                        cell.set_symbol("┊").set_style(Style::new().dim());
+                    }
+                }
+            }
+        }
+    }
+}
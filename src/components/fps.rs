@@ -5,7 +5,7 @@ use ratatui::{
     layout::{Constraint, Layout, Rect},
     style::{Style, Stylize},
     text::Span,
-    widgets::{Block, Padding, Paragraph},
+    widgets::{Block, Clear, Padding, Paragraph},
     Frame,
 };
 
@@ -13,8 +13,12 @@ use super::Component;
 
 use crate::action::Action;
 
+/// The measured tick, frame and data update rates, shown in the top right corner along with
+/// the debug overlay.
 #[derive(Debug, Clone, PartialEq)]
 pub struct FpsCounter {
+    visible: bool,
+
     last_tick_update: Instant,
     tick_count: u32,
     ticks_per_second: f64,
@@ -22,6 +26,10 @@ pub struct FpsCounter {
     last_frame_update: Instant,
     frame_count: u32,
     frames_per_second: f64,
+
+    last_data_update: Instant,
+    data_count: u32,
+    data_per_second: f64,
 }
 
 impl Default for FpsCounter {
@@ -33,12 +41,16 @@ impl Default for FpsCounter {
 impl FpsCounter {
     pub fn new() -> Self {
         Self {
+            visible: false,
             last_tick_update: Instant::now(),
             tick_count: 0,
             ticks_per_second: 0.0,
             last_frame_update: Instant::now(),
             frame_count: 0,
             frames_per_second: 0.0,
+            last_data_update: Instant::now(),
+            data_count: 0,
+            data_per_second: 0.0,
         }
     }
 
@@ -65,6 +77,18 @@ impl FpsCounter {
         }
         Ok(())
     }
+
+    fn data_tick(&mut self) -> Result<()> {
+        self.data_count += 1;
+        let now = Instant::now();
+        let elapsed = (now - self.last_data_update).as_secs_f64();
+        if elapsed >= 1.0 {
+            self.data_per_second = self.data_count as f64 / elapsed;
+            self.last_data_update = now;
+            self.data_count = 0;
+        }
+        Ok(())
+    }
 }
 
 impl Component for FpsCounter {
@@ -72,22 +96,80 @@ impl Component for FpsCounter {
         match action {
             Action::Tick => self.app_tick()?,
             Action::Render => self.render_tick()?,
+            Action::DataUpdated(_) => self.data_tick()?,
+            Action::ToggleDebug => self.visible = !self.visible,
             _ => {}
         };
         Ok(None)
     }
 
     fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        if !self.visible {
+            return Ok(());
+        }
         let [top, _] = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(area);
         let message = format!(
-            "{:.2} ticks/sec, {:.2} FPS",
-            self.ticks_per_second, self.frames_per_second
+            "{:.2} ticks/sec, {:.2} FPS, {:.2} updates/sec",
+            self.ticks_per_second, self.frames_per_second, self.data_per_second
         );
+        let width = (message.len() as u16 + 4).min(top.width);
+        let top = Rect::new(top.right() - width, top.y, width, top.height);
         let span = Span::styled(message, Style::new().dim());
         let paragraph = Paragraph::new(span)
             .right_aligned()
             .block(Block::default().padding(Padding::horizontal(2)));
+        frame.render_widget(Clear, top);
         frame.render_widget(paragraph, top);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    use super::*;
+
+    #[test]
+    fn test_data_updates_per_second() {
+        let mut fps = FpsCounter::new();
+        for chart in [0, 1, 0, 2] {
+            fps.update(Action::DataUpdated(chart)).unwrap();
+        }
+        assert_eq!(fps.data_count, 4);
+        assert_eq!(fps.data_per_second, 0.0);
+        // the fifth update comes two seconds after the count started
+        fps.last_data_update -= Duration::from_secs(2);
+        fps.update(Action::DataUpdated(1)).unwrap();
+        assert_eq!(fps.data_count, 0);
+        assert!(
+            (fps.data_per_second - 2.5).abs() < 0.1,
+            "{}",
+            fps.data_per_second
+        );
+    }
+
+    #[test]
+    fn test_shown_with_the_debug_overlay() {
+        let mut fps = FpsCounter::new();
+        let mut terminal = Terminal::new(TestBackend::new(60, 2)).unwrap();
+        let mut top_line = |fps: &mut FpsCounter| {
+            let frame = terminal
+                .draw(|frame| fps.draw(frame, frame.area()).unwrap())
+                .unwrap();
+            let buffer = frame.buffer;
+            (0..buffer.area.width)
+                .map(|x| buffer[(x, 0)].symbol())
+                .collect::<String>()
+        };
+        assert_eq!(top_line(&mut fps).trim(), "");
+        fps.update(Action::ToggleDebug).unwrap();
+        assert_eq!(
+            top_line(&mut fps).trim(),
+            "0.00 ticks/sec, 0.00 FPS, 0.00 updates/sec"
+        );
+    }
+}
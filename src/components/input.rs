@@ -0,0 +1,256 @@
+use color_eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::*, widgets::*};
+use unicode_width::UnicodeWidthStr;
+
+use super::Component;
+use crate::{action::Action, text::sanitize};
+
+const INPUT_WIDTH: u16 = 50;
+/// Longest text the input takes, in characters.
+const MAX_INPUT_CHARS: usize = 200;
+
+/// A one-line text prompt opened with [`Action::OpenInput`]. While it is open the app is in
+/// [`crate::app::Mode::Input`] so the keybindings don't fire, Enter submits the text with
+/// [`Action::SubmitInput`] and Esc cancels with [`Action::CancelInput`]. Pasted text is taken as
+/// one line, and the text is limited to [`MAX_INPUT_CHARS`].
+#[derive(Debug, Default)]
+pub struct TextInput {
+    prompt: Option<String>,
+    value: String,
+    /// Cursor position in characters.
+    cursor: usize,
+}
+
+impl TextInput {
+    fn open(&mut self, prompt: String, value: String) {
+        self.cursor = value.chars().count();
+        self.prompt = Some(prompt);
+        self.value = value;
+    }
+
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.value.len(), |(i, _)| i)
+    }
+
+    fn insert(&mut self, c: char) {
+        if self.is_full() {
+            return;
+        }
+        let index = self.byte_index(self.cursor);
+        self.value.insert(index, c);
+        self.cursor += 1;
+    }
+
+    fn is_full(&self) -> bool {
+        self.value.chars().count() >= MAX_INPUT_CHARS
+    }
+
+    fn backspace(&mut self) {
+        if self.cursor == 0 {
+            return;
+        }
+        self.cursor -= 1;
+        let index = self.byte_index(self.cursor);
+        self.value.remove(index);
+    }
+
+    fn delete(&mut self) {
+        if self.cursor < self.value.chars().count() {
+            let index = self.byte_index(self.cursor);
+            self.value.remove(index);
+        }
+    }
+
+    fn close(&mut self) -> String {
+        self.prompt = None;
+        self.cursor = 0;
+        std::mem::take(&mut self.value)
+    }
+}
+
+impl Component for TextInput {
+    /// Without an open input a paste does nothing, rather than firing keybindings.
+    fn handle_paste_event(&mut self, text: String) -> Result<Option<Action>> {
+        if self.prompt.is_some() {
+            for c in sanitize(&text).chars() {
+                self.insert(c);
+            }
+        }
+        Ok(None)
+    }
+
+    fn handle_key_event(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if self.prompt.is_none() {
+            return Ok(None);
+        }
+        match key.code {
+            KeyCode::Enter => return Ok(Some(Action::SubmitInput(self.close()))),
+            KeyCode::Esc => {
+                self.close();
+                return Ok(Some(Action::CancelInput));
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.value.clear();
+                self.cursor = 0;
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => self.insert(c),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.value.chars().count()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.value.chars().count(),
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        if let Action::OpenInput(prompt, value) = action {
+            self.open(prompt, value);
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let Some(prompt) = &self.prompt else {
+            return Ok(());
+        };
+        let width = INPUT_WIDTH.min(area.width);
+        let input_area = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + area.height.saturating_sub(3) / 2,
+            width,
+            3.min(area.height),
+        );
+        // keep the cursor visible by scrolling long values
+        let inner_width = width.saturating_sub(2) as usize;
+        let before_cursor = self.value[..self.byte_index(self.cursor)].width();
+        let scroll = (before_cursor + 1).saturating_sub(inner_width);
+        // a full input says so instead of dropping the keys silently
+        let (hint, color) = if self.is_full() {
+            (
+                format!(" {MAX_INPUT_CHARS} characters at most "),
+                Color::Yellow,
+            )
+        } else {
+            (" Enter to apply, Esc to cancel ".to_string(), Color::Cyan)
+        };
+        let paragraph = Paragraph::new(self.value.as_str())
+            .scroll((0, scroll as u16))
+            .block(
+                Block::default()
+                    .title(format!(" {prompt} "))
+                    .title_bottom(Line::from(hint).right_aligned())
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(color)),
+            );
+        frame.render_widget(Clear, input_area);
+        frame.render_widget(paragraph, input_area);
+        frame.set_cursor_position(Position::new(
+            input_area.x + 1 + (before_cursor - scroll) as u16,
+            input_area.y + 1,
+        ));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn press(input: &mut TextInput, code: KeyCode) -> Option<Action> {
+        input
+            .handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+            .unwrap()
+    }
+
+    fn opened(value: &str) -> TextInput {
+        let mut input = TextInput::default();
+        input
+            .update(Action::OpenInput("Title".into(), value.into()))
+            .unwrap();
+        input
+    }
+
+    #[test]
+    fn test_closed_input_ignores_keys() {
+        let mut input = TextInput::default();
+        assert_eq!(press(&mut input, KeyCode::Char('q')), None);
+        assert_eq!(press(&mut input, KeyCode::Enter), None);
+    }
+
+    #[test]
+    fn test_edit_and_submit() {
+        let mut input = opened("latncy");
+        for _ in 0..3 {
+            press(&mut input, KeyCode::Left);
+        }
+        press(&mut input, KeyCode::Char('e'));
+        press(&mut input, KeyCode::End);
+        press(&mut input, KeyCode::Backspace);
+        press(&mut input, KeyCode::Char('y'));
+        assert_eq!(
+            press(&mut input, KeyCode::Enter),
+            Some(Action::SubmitInput("latency".into()))
+        );
+        // closed again
+        assert_eq!(press(&mut input, KeyCode::Char('q')), None);
+    }
+
+    #[test]
+    fn test_multibyte_characters() {
+        let mut input = opened("µs");
+        press(&mut input, KeyCode::Home);
+        press(&mut input, KeyCode::Delete);
+        press(&mut input, KeyCode::Char('m'));
+        assert_eq!(
+            press(&mut input, KeyCode::Enter),
+            Some(Action::SubmitInput("ms".into()))
+        );
+    }
+
+    #[test]
+    fn test_paste() {
+        let mut input = opened("api ");
+        let paste = |input: &mut TextInput, text: &str| {
+            input.handle_paste_event(text.to_string()).unwrap();
+        };
+        paste(&mut input, "p99\r\n\r\nlatency\x07\t\x1b[31m(ms)\x1b[0m\n");
+        assert_eq!(input.value, "api p99 latency (ms)");
+        assert_eq!(input.cursor, input.value.chars().count());
+        // a newline doesn't submit
+        assert_eq!(input.prompt.as_deref(), Some("Title"));
+
+        // pasted and typed text stop at the limit
+        paste(&mut input, &"x".repeat(MAX_INPUT_CHARS));
+        press(&mut input, KeyCode::Char('y'));
+        assert_eq!(input.value.chars().count(), MAX_INPUT_CHARS);
+        assert!(input.value.ends_with('x'));
+        press(&mut input, KeyCode::Backspace);
+        press(&mut input, KeyCode::Char('y'));
+        assert!(input.value.ends_with('y'));
+    }
+
+    #[test]
+    fn test_closed_input_ignores_paste() {
+        let mut input = TextInput::default();
+        let action = input.handle_events(Some(crate::tui::Event::Paste("q\nq".into())));
+        assert_eq!(action.unwrap(), None);
+        assert_eq!(input.value, "");
+    }
+
+    #[test]
+    fn test_cancel() {
+        let mut input = opened("title");
+        press(&mut input, KeyCode::Char('x'));
+        assert_eq!(press(&mut input, KeyCode::Esc), Some(Action::CancelInput));
+    }
+}
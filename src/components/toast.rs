@@ -0,0 +1,75 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use color_eyre::Result;
+use ratatui::{prelude::*, widgets::*};
+use unicode_width::UnicodeWidthStr;
+
+use super::Component;
+use crate::action::Action;
+
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+const TOAST_MAX_WIDTH: u16 = 60;
+
+/// Short-lived notifications shown in the bottom right corner, on top of the other components.
+#[derive(Debug, Default)]
+pub struct Toast {
+    messages: VecDeque<(Instant, String)>,
+}
+
+impl Component for Toast {
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::Toast(message) => self.messages.push_back((Instant::now(), message)),
+            Action::Tick => self
+                .messages
+                .retain(|(shown, _)| shown.elapsed() < TOAST_DURATION),
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        if self.messages.is_empty() {
+            return Ok(());
+        }
+        let longest = self
+            .messages
+            .iter()
+            .map(|(_, message)| message.width() as u16)
+            .max()
+            .unwrap_or_default();
+        let width = (longest + 4).min(TOAST_MAX_WIDTH).min(area.width);
+        let inner_width = width.saturating_sub(4).max(1) as usize;
+        let lines = self
+            .messages
+            .iter()
+            .map(|(_, message)| message.width().max(1).div_ceil(inner_width) as u16)
+            .sum::<u16>();
+        let height = (lines + 2).min(area.height);
+        let toast_area = Rect::new(
+            area.right().saturating_sub(width),
+            area.bottom().saturating_sub(height + 1),
+            width,
+            height,
+        );
+
+        let text = self
+            .messages
+            .iter()
+            .map(|(_, message)| Line::from(message.as_str()))
+            .collect::<Vec<_>>();
+        let paragraph = Paragraph::new(text).wrap(Wrap { trim: true }).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(Color::Yellow))
+                .padding(Padding::horizontal(1)),
+        );
+        frame.render_widget(Clear, toast_area);
+        frame.render_widget(paragraph, toast_area);
+        Ok(())
+    }
+}
@@ -1,19 +1,32 @@
 #![allow(dead_code)]
-use std::{collections::HashMap, env, path::PathBuf};
+use std::{
+    collections::HashMap,
+    env, fmt, fs,
+    path::{Path, PathBuf},
+};
 
-use color_eyre::Result;
+use color_eyre::{eyre::eyre, Result};
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use derive_deref::{Deref, DerefMut};
 use directories::ProjectDirs;
 use lazy_static::lazy_static;
 use ratatui::style::{Color, Modifier, Style};
-use serde::{de::Deserializer, Deserialize};
+use serde::{de::Deserializer, Deserialize, Serialize};
 use tracing::error;
 
-use crate::{action::Action, app::Mode};
+use crate::{action::Action, app::Mode, profile::Profile};
 
 const CONFIG: &str = include_str!("../.config/config.json5");
 
+/// Config file names looked up in the config directory, in order.
+const CONFIG_FILES: [(&str, config::FileFormat); 5] = [
+    ("config.json5", config::FileFormat::Json5),
+    ("config.json", config::FileFormat::Json),
+    ("config.yaml", config::FileFormat::Yaml),
+    ("config.toml", config::FileFormat::Toml),
+    ("config.ini", config::FileFormat::Ini),
+];
+
 #[derive(Clone, Debug, Deserialize, Default)]
 pub struct AppConfig {
     #[serde(default)]
@@ -32,6 +45,71 @@ pub struct Config {
     pub styles: Styles,
     #[serde(default)]
     pub match_rules: MatchRule,
+    #[serde(default)]
+    pub bool_tokens: BoolTokens,
+    #[serde(default)]
+    pub theme: Theme,
+    #[serde(default)]
+    pub profiles: HashMap<String, Profile>,
+}
+
+/// Look of the chart decorations that the styles don't cover.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    /// Glyph drawn on the bottom border at every time marker.
+    pub time_marker: String,
+    /// Style of the time marker labels, e.g. "bold white".
+    #[serde(deserialize_with = "deserialize_style")]
+    pub time_label: Style,
+    /// Minimum number of columns between two time markers.
+    pub time_marker_spacing: usize,
+    /// Style patched onto the columns of the highlighted range, e.g. "on gray4".
+    #[serde(deserialize_with = "deserialize_style")]
+    pub highlight: Style,
+    /// Style patched onto the crosshair column.
+    #[serde(deserialize_with = "deserialize_style")]
+    pub crosshair: Style,
+    /// Glyph drawn on the bottom border where a note was added.
+    pub note_marker: String,
+    /// Style of the note markers.
+    #[serde(deserialize_with = "deserialize_style")]
+    pub note: Style,
+    /// Style of the `--reference` line.
+    #[serde(deserialize_with = "deserialize_style")]
+    pub reference: Style,
+    /// Glyph of the empty cells with `--grid dots`.
+    pub grid_dot: String,
+    /// Glyph of the empty cells on the bottom row with `--grid baseline`.
+    pub grid_baseline: String,
+    /// Style of the `--grid` glyphs.
+    #[serde(deserialize_with = "deserialize_style")]
+    pub grid: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            time_marker: "├".to_string(),
+            time_label: Style::default().fg(Color::Gray),
+            time_marker_spacing: 30,
+            highlight: Style::default().bg(Color::Indexed(236)),
+            crosshair: Style::default().bg(Color::Indexed(240)),
+            note_marker: "▲".to_string(),
+            note: Style::default().fg(Color::Yellow),
+            reference: Style::default().fg(Color::Magenta),
+            grid_dot: "·".to_string(),
+            grid_baseline: "⡀".to_string(),
+            grid: Style::default().fg(Color::Indexed(238)),
+        }
+    }
+}
+
+fn deserialize_style<'de, D>(deserializer: D) -> Result<Style, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(parse_style(&String::deserialize(deserializer)?))
 }
 
 lazy_static! {
@@ -55,15 +133,12 @@ impl Config {
             .set_default("data_dir", data_dir.to_str().unwrap())?
             .set_default("config_dir", config_dir.to_str().unwrap())?;
 
-        let config_files = [
-            ("config.json5", config::FileFormat::Json5),
-            ("config.json", config::FileFormat::Json),
-            ("config.yaml", config::FileFormat::Yaml),
-            ("config.toml", config::FileFormat::Toml),
-            ("config.ini", config::FileFormat::Ini),
-        ];
         let mut found_config = false;
-        for (file, format) in &config_files {
+        for (file, format) in &CONFIG_FILES {
+            // an empty file is no settings rather than a syntax error
+            if fs::read_to_string(config_dir.join(file)).is_ok_and(|text| text.trim().is_empty()) {
+                continue;
+            }
             let source = config::File::from(config_dir.join(file))
                 .format(*format)
                 .required(false);
@@ -98,13 +173,380 @@ impl Config {
                 user_rules.entry(rule_key.clone()).or_insert(rule.clone());
             }
         }
+        for (mode, default_tokens) in default_config.bool_tokens.iter() {
+            let user_tokens = cfg.bool_tokens.entry(*mode).or_default();
+            for (token, value) in default_tokens.iter() {
+                user_tokens.entry(token.clone()).or_insert(*value);
+            }
+        }
 
         Ok(cfg)
     }
+
+    /// The settings that come with ttydash alone, for when the config file can't be read.
+    pub fn builtin() -> Self {
+        let mut config: Config = json5::from_str(CONFIG).unwrap();
+        config.config = AppConfig {
+            data_dir: get_data_dir(),
+            config_dir: get_config_dir(),
+        };
+        config
+    }
+}
+
+/// Why [`Config::new`] failed, with the line and column of a syntax error in a JSON5 or JSON
+/// config file.
+pub fn load_error(err: config::ConfigError) -> color_eyre::Report {
+    let path = get_config_file();
+    match read_config_value(&path) {
+        Err(corrupt) if corrupt.is::<CorruptConfig>() => corrupt,
+        _ => eyre!(
+            "Can't load {}: {err}. Run `ttydash config validate` to check it",
+            path.display()
+        ),
+    }
+}
+
+/// A config file that doesn't parse.
+#[derive(Debug)]
+pub struct CorruptConfig {
+    pub path: PathBuf,
+    /// Line and column the parser gave up at, one-based.
+    pub location: Option<(usize, usize)>,
+    pub message: String,
+    /// The parser's excerpt of the file pointing at the problem.
+    pub detail: String,
+}
+
+impl CorruptConfig {
+    fn new(path: &Path, err: json5::Error) -> Self {
+        let json5::Error::Message { msg, location } = err;
+        // pest puts the expectation last, after an excerpt of the file
+        let message = msg
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("= "))
+            .unwrap_or(&msg)
+            .to_string();
+        Self {
+            path: path.to_path_buf(),
+            location: location.map(|location| (location.line, location.column)),
+            message,
+            detail: msg,
+        }
+    }
+}
+
+impl fmt::Display for CorruptConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Can't parse {}", self.path.display())?;
+        if let Some((line, column)) = self.location {
+            write!(f, " at line {line}, column {column}")?;
+        }
+        write!(
+            f,
+            ": {}. Run `ttydash config validate` to check it",
+            self.message
+        )
+    }
+}
+
+impl std::error::Error for CorruptConfig {}
+
+/// Run `ttydash config validate` on the config file at `path`: what is wrong with it, nothing
+/// when it is fine or missing. Beyond the syntax of JSON5 and JSON files, the regexes and key
+/// bindings are checked.
+pub fn validate(path: &Path) -> Vec<String> {
+    if !is_writable_format(path) {
+        let format = CONFIG_FILES
+            .iter()
+            .find(|(file, _)| path.file_name().is_some_and(|name| name == *file))
+            .map_or(config::FileFormat::Json5, |(_, format)| *format);
+        let parsed = config::Config::builder()
+            .add_source(config::File::from(path).format(format).required(false))
+            .build();
+        return match parsed {
+            Ok(_) => vec![],
+            Err(err) => vec![format!("Can't parse {}: {err}", path.display())],
+        };
+    }
+    let value = match read_config_value(path) {
+        Ok(value) => value,
+        Err(err) => {
+            return match err.downcast_ref::<CorruptConfig>() {
+                Some(corrupt) => vec![corrupt.to_string(), corrupt.detail.clone()],
+                None => vec![err.to_string()],
+            }
+        }
+    };
+    let mut problems = vec![];
+    match read_regex_store(path) {
+        Ok(entries) => problems.extend(entries.iter().filter_map(|entry| {
+            let err = regex::Regex::new(&entry.regex).err()?;
+            Some(format!("Regex {} does not compile: {err}", entry.name))
+        })),
+        Err(err) => problems.push(err.to_string()),
+    }
+    let bindings = value.get("keybindings").and_then(|modes| modes.as_object());
+    for (mode, keys) in bindings.into_iter().flatten() {
+        for (key, action) in keys.as_object().into_iter().flatten() {
+            if let Err(err) = parse_key_sequence(key) {
+                problems.push(format!("Key binding {key} in {mode}: {err}"));
+            }
+            if serde_json::from_value::<Action>(action.clone()).is_err() {
+                problems.push(format!(
+                    "Key binding {key} in {mode}: unknown action {action}"
+                ));
+            }
+        }
+    }
+    problems
+}
+
+/// Mode the regexes of the store are kept under in `match_rules`.
+const REGEX_MODE: &str = "Home";
+
+/// A named regex, as stored in `match_rules` and exchanged with `import` and `export`. Regexes
+/// with a description are stored as `{ "regex": …, "description": … }`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegexEntry {
+    pub name: String,
+    pub regex: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+}
+
+/// A `match_rules` value, a bare regex or one with a description.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+enum StoredRegex {
+    Regex(String),
+    Described {
+        regex: String,
+        #[serde(default)]
+        description: String,
+    },
+}
+
+impl StoredRegex {
+    fn into_entry(self, name: String) -> RegexEntry {
+        match self {
+            StoredRegex::Regex(regex) => RegexEntry {
+                name,
+                regex,
+                description: String::new(),
+            },
+            StoredRegex::Described { regex, description } => RegexEntry {
+                name,
+                regex,
+                description,
+            },
+        }
+    }
+}
+
+/// Whether a config file can be rewritten without losing its format. Comments are lost either
+/// way.
+pub fn is_writable_format(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("json5" | "json")
+    )
+}
+
+/// Read the JSON5 or JSON config file at `path`, an empty object if it doesn't exist.
+pub fn read_config_value(path: &Path) -> Result<serde_json::Value> {
+    match fs::read_to_string(path) {
+        Ok(text) if text.trim().is_empty() => Ok(serde_json::json!({})),
+        Ok(text) => json5::from_str::<serde_json::Value>(&text)
+            .map_err(|err| CorruptConfig::new(path, err).into()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(serde_json::json!({})),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Replace the JSON5 or JSON config file at `path` with `config`, creating it if needed. The
+/// file is replaced atomically, so a reader sees either the old or the new one. Hold a
+/// [`ConfigLock`] from reading the file to writing it back.
+pub fn write_config_value(path: &Path, config: &serde_json::Value) -> Result<()> {
+    if !is_writable_format(path) {
+        return Err(eyre!(
+            "Only JSON5 and JSON config files can be written, not {}",
+            path.display()
+        ));
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = with_suffix(path, &format!(".{}.tmp", std::process::id()));
+    fs::write(&tmp, serde_json::to_string_pretty(config)? + "\n")?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// `config.json5.lock` for `config.json5` and `.lock`.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+/// An exclusive advisory lock on a config file, held across a read-modify-write so that another
+/// ttydash changing the same file waits instead of one of the changes getting lost. The lock is
+/// taken on a `.lock` file next to it, since every write replaces the config file itself, and
+/// released when dropped. Without `flock`, on Windows, it doesn't lock.
+#[derive(Debug)]
+pub struct ConfigLock {
+    _file: fs::File,
+}
+
+impl ConfigLock {
+    /// Wait until the config file at `path` is no longer locked, then lock it.
+    pub fn acquire(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let lock_path = with_suffix(path, ".lock");
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .map_err(|err| eyre!("Can't lock {}: {err}", path.display()))?;
+        #[cfg(unix)]
+        loop {
+            use std::os::fd::AsRawFd;
+
+            if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } == 0 {
+                break;
+            }
+            let err = std::io::Error::last_os_error();
+            if err.kind() != std::io::ErrorKind::Interrupted {
+                return Err(eyre!("Can't lock {}: {err}", path.display()));
+            }
+        }
+        Ok(Self { _file: file })
+    }
+}
+
+/// The object stored under `key` in `object`, created if missing.
+pub fn object_entry<'a>(
+    object: &'a mut serde_json::Value,
+    key: &str,
+    path: &Path,
+) -> Result<&'a mut serde_json::Map<String, serde_json::Value>> {
+    let object = object
+        .as_object_mut()
+        .ok_or_else(|| eyre!("{} does not contain an object", path.display()))?;
+    object
+        .entry(key)
+        .or_insert_with(|| serde_json::json!({}))
+        .as_object_mut()
+        .ok_or_else(|| eyre!("`{key}` in {} is not an object", path.display()))
+}
+
+/// The regexes added to the config file at `path`, sorted by name.
+pub fn read_regex_store(path: &Path) -> Result<Vec<RegexEntry>> {
+    let config = read_config_value(path)?;
+    let Some(rules) = config
+        .get("match_rules")
+        .and_then(|rules| rules.get(REGEX_MODE))
+    else {
+        return Ok(vec![]);
+    };
+    let rules = HashMap::<String, StoredRegex>::deserialize(rules)
+        .map_err(|err| eyre!("Invalid `match_rules` in {}: {err}", path.display()))?;
+    let mut entries = rules
+        .into_iter()
+        .map(|(name, regex)| regex.into_entry(name))
+        .collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(entries)
+}
+
+/// Replace the regexes in the config file at `path` with `entries`, keeping all other keys.
+pub fn write_regex_store(path: &Path, entries: &[RegexEntry]) -> Result<()> {
+    let mut config = read_config_value(path)?;
+    let rules = object_entry(&mut config, "match_rules", path)?;
+    let rules = rules
+        .entry(REGEX_MODE)
+        .or_insert_with(|| serde_json::json!({}));
+    *rules = entries
+        .iter()
+        .map(|entry| {
+            let value = if entry.description.is_empty() {
+                serde_json::json!(entry.regex)
+            } else {
+                serde_json::json!({ "regex": entry.regex, "description": entry.description })
+            };
+            (entry.name.clone(), value)
+        })
+        .collect::<serde_json::Map<_, _>>()
+        .into();
+    write_config_value(path, &config)
+}
+
+/// The regexes that come with ttydash.
+pub fn builtin_regexes() -> Vec<RegexEntry> {
+    let config: Config = json5::from_str(CONFIG).unwrap();
+    let mut entries = config
+        .match_rules
+        .get(&Mode::Home)
+        .into_iter()
+        .flatten()
+        .map(|(name, regex)| RegexEntry {
+            name: name.clone(),
+            regex: regex.clone(),
+            description: String::new(),
+        })
+        .collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    entries
+}
+
+/// Parse a list of regexes, a JSON array of entries or TOML `[[regexes]]` tables.
+pub fn parse_regex_entries(text: &str) -> Result<Vec<RegexEntry>> {
+    if let Ok(entries) = json5::from_str::<Vec<RegexEntry>>(text) {
+        return Ok(entries);
+    }
+    #[derive(Deserialize)]
+    struct TomlEntries {
+        regexes: Vec<RegexEntry>,
+    }
+    let toml = config::Config::builder()
+        .add_source(config::File::from_str(text, config::FileFormat::Toml))
+        .build()
+        .and_then(|toml| toml.try_deserialize::<TomlEntries>());
+    match toml {
+        Ok(toml) => Ok(toml.regexes),
+        Err(err) => Err(eyre!(
+            "Expected a JSON list of {{name, regex, description}} or TOML [[regexes]]: {err}"
+        )),
+    }
+}
+
+/// Write `entries` as TOML `[[regexes]]` tables.
+pub fn regexes_to_toml(entries: &[RegexEntry]) -> String {
+    // JSON string escapes are valid in TOML basic strings
+    let quote = |value: &str| serde_json::to_string(value).unwrap();
+    entries
+        .iter()
+        .map(|entry| {
+            let mut table = format!(
+                "[[regexes]]\nname = {}\nregex = {}\n",
+                quote(&entry.name),
+                quote(&entry.regex)
+            );
+            if !entry.description.is_empty() {
+                table += &format!("description = {}\n", quote(&entry.description));
+            }
+            table
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 pub fn get_regexes() -> Result<HashMap<String, String>> {
-    let config = Config::new()?;
+    let config = Config::new().map_err(load_error)?;
     let mut regexes = HashMap::new();
     for (_, rules) in config.match_rules.iter() {
         for (name, regex) in rules.iter() {
@@ -125,6 +567,16 @@ pub fn get_data_dir() -> PathBuf {
     directory
 }
 
+/// The config file settings are written to: the first one that exists, or a new `config.json5`.
+pub fn get_config_file() -> PathBuf {
+    let config_dir = get_config_dir();
+    CONFIG_FILES
+        .iter()
+        .map(|(file, _)| config_dir.join(file))
+        .find(|path| path.exists())
+        .unwrap_or_else(|| config_dir.join(CONFIG_FILES[0].0))
+}
+
 pub fn get_config_dir() -> PathBuf {
     let directory = if let Some(s) = CONFIG_FOLDER.clone() {
         s
@@ -473,8 +925,44 @@ impl<'de> Deserialize<'de> for MatchRule {
     where
         D: Deserializer<'de>,
     {
-        let parsed_map = HashMap::<Mode, HashMap<String, String>>::deserialize(deserializer)?;
-        Ok(MatchRule(parsed_map))
+        let parsed_map = HashMap::<Mode, HashMap<String, StoredRegex>>::deserialize(deserializer)?;
+        let rules = parsed_map
+            .into_iter()
+            .map(|(mode, rules)| {
+                let rules = rules
+                    .into_iter()
+                    .map(|(name, regex)| {
+                        let entry = regex.into_entry(name);
+                        (entry.name, entry.regex)
+                    })
+                    .collect();
+                (mode, rules)
+            })
+            .collect();
+        Ok(MatchRule(rules))
+    }
+}
+
+#[derive(Clone, Debug, Default, Deref, DerefMut)]
+pub struct BoolTokens(pub HashMap<Mode, HashMap<String, bool>>);
+
+impl<'de> Deserialize<'de> for BoolTokens {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let parsed_map = HashMap::<Mode, HashMap<String, bool>>::deserialize(deserializer)?;
+        let tokens = parsed_map
+            .into_iter()
+            .map(|(mode, inner_map)| {
+                let converted_inner_map = inner_map
+                    .into_iter()
+                    .map(|(token, value)| (token.to_lowercase(), value))
+                    .collect();
+                (mode, converted_inner_map)
+            })
+            .collect();
+        Ok(BoolTokens(tokens))
     }
 }
 
@@ -484,6 +972,136 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_regex_store_round_trip() -> Result<()> {
+        let dir = env::temp_dir().join(format!("ttydash-regexes-{}", std::process::id()));
+        let path = dir.join("config.json5");
+        fs::create_dir_all(&dir)?;
+        fs::write(
+            &path,
+            r#"{
+                "keybindings": { "Home": { "<q>": "Quit" } },
+                "match_rules": { "Home": { "old": "x(\\d+)" } },
+            }"#,
+        )?;
+        assert_eq!(
+            read_regex_store(&path)?,
+            [RegexEntry {
+                name: "old".to_string(),
+                regex: r"x(\d+)".to_string(),
+                description: String::new(),
+            }]
+        );
+        let entries = vec![
+            RegexEntry {
+                name: "latency".to_string(),
+                regex: r"(\d+)ms".to_string(),
+                description: "request latency".to_string(),
+            },
+            RegexEntry {
+                name: "load".to_string(),
+                regex: r"load (\d+)".to_string(),
+                description: String::new(),
+            },
+        ];
+        write_regex_store(&path, &entries)?;
+        assert_eq!(read_regex_store(&path)?, entries);
+        let text = fs::read_to_string(&path)?;
+        assert!(text.contains("keybindings"));
+        // described regexes still load as plain match rules
+        let config: Config = json5::from_str(&text)?;
+        assert_eq!(config.match_rules[&Mode::Home]["latency"], r"(\d+)ms");
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_corrupted_empty_and_missing() {
+        let dir = env::temp_dir().join(format!("ttydash-corrupt-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json5");
+
+        fs::write(
+            &path,
+            "{\n  match_rules: {\n    Home: { ms: \"(\\\\d+)ms\" \n}\n",
+        )
+        .unwrap();
+        let err = read_regex_store(&path).unwrap_err();
+        let corrupt = err.downcast_ref::<CorruptConfig>().unwrap();
+        assert_eq!(corrupt.location, Some((3, 17)));
+        let message = err.to_string();
+        assert!(message.starts_with(&format!(
+            "Can't parse {} at line 3, column 17: ",
+            path.display()
+        )));
+        assert!(message.ends_with("Run `ttydash config validate` to check it"));
+        let problems = validate(&path);
+        assert_eq!(problems.len(), 2);
+        assert!(problems[1].contains("3 |"));
+
+        fs::write(&path, " \n").unwrap();
+        assert_eq!(read_regex_store(&path).unwrap(), vec![]);
+        assert_eq!(validate(&path), Vec::<String>::new());
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(read_regex_store(&path).unwrap(), vec![]);
+        assert_eq!(validate(&path), Vec::<String>::new());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_validate() {
+        let dir = env::temp_dir().join(format!("ttydash-validate-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json5");
+        fs::write(
+            &path,
+            r#"{
+                keybindings: { Home: { "<q>": "Quit", "<x": "ZoomOut", "<y>": "Fly" } },
+                match_rules: { Home: { ok: "(\\d+)ms", broken: "(\\d+" } },
+            }"#,
+        )
+        .unwrap();
+        let problems = validate(&path);
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(problems.len(), 3, "{problems:?}");
+        assert!(problems[0].starts_with("Regex broken does not compile"));
+        assert!(problems.contains(&"Key binding <x in Home: Unable to parse `<x`".to_string()));
+        assert!(problems.contains(&"Key binding <y> in Home: unknown action \"Fly\"".to_string()));
+    }
+
+    #[test]
+    fn test_parse_regex_entries() -> Result<()> {
+        let entries = vec![
+            RegexEntry {
+                name: "temp".to_string(),
+                regex: r#"temp="?(\d+)\s*°C"#.to_string(),
+                description: "sensor\ttemperature".to_string(),
+            },
+            RegexEntry {
+                name: "load".to_string(),
+                regex: r"load (\d+)".to_string(),
+                description: String::new(),
+            },
+        ];
+        let json = serde_json::to_string(&entries)?;
+        assert_eq!(parse_regex_entries(&json)?, entries);
+        assert_eq!(parse_regex_entries(&regexes_to_toml(&entries))?, entries);
+        assert!(parse_regex_entries("not a list").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_windows_config_paths() {
+        let dir = r"C:\Users\me\AppData\Roaming\ttydash\config";
+        assert!(is_writable_format(Path::new(&format!(
+            r"{dir}\config.json5"
+        ))));
+        assert!(!is_writable_format(Path::new(&format!(
+            r"{dir}\config.toml"
+        ))));
+    }
+
     #[test]
     fn test_parse_style_default() {
         let style = parse_style("");
@@ -545,6 +1163,29 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_bool_tokens() -> Result<()> {
+        let c = Config::new()?;
+        let tokens = c.bool_tokens.get(&Mode::Home).unwrap();
+        assert_eq!(tokens.get("ok"), Some(&true));
+        assert_eq!(tokens.get("down"), Some(&false));
+        Ok(())
+    }
+
+    #[test]
+    fn test_theme() {
+        let theme: Theme =
+            json5::from_str(r#"{ "time_marker": "┴", "time_label": "bold red" }"#).unwrap();
+        assert_eq!(theme.time_marker, "┴");
+        assert_eq!(
+            theme.time_label,
+            Style::default()
+                .fg(Color::Indexed(1))
+                .add_modifier(Modifier::BOLD)
+        );
+        assert_eq!(theme.time_marker_spacing, 30);
+    }
+
     #[test]
     fn test_simple_keys() {
         assert_eq!(
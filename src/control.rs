@@ -0,0 +1,237 @@
+//! `--control-fifo`: a named pipe scripts write commands to, one per line, e.g.
+//! `echo "mark deploy" > control`. Every command becomes the `Action` a key would send.
+
+use std::{
+    ffi::CString,
+    fs, io,
+    os::unix::{ffi::OsStrExt, fs::FileTypeExt},
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{eyre::eyre, Result};
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    net::unix::pipe,
+    sync::mpsc::UnboundedSender,
+    task::JoinHandle,
+};
+use tracing::{info, warn};
+
+use crate::action::Action;
+
+/// Reads the commands written to the FIFO while the dashboard runs, and removes it on close.
+pub struct ControlFifo {
+    path: PathBuf,
+    task: JoinHandle<()>,
+}
+
+impl ControlFifo {
+    /// Create the FIFO at `path`, reusing one left behind by a session that crashed, and start
+    /// reading it.
+    pub fn spawn(path: PathBuf, action_tx: UnboundedSender<Action>) -> Result<Self> {
+        create_fifo(&path).map_err(|err| eyre!("Can't create {}: {err}", path.display()))?;
+        let receiver = pipe::OpenOptions::new().open_receiver(&path)?;
+        // a writer of our own, so the reader doesn't see the end of the file every time a
+        // script is done writing
+        let sender = pipe::OpenOptions::new().open_sender(&path)?;
+        info!("Reading commands from {}", path.display());
+        let task = tokio::spawn(forward(receiver, sender, action_tx));
+        Ok(Self { path, task })
+    }
+
+    pub fn close(&self) {
+        self.task.abort();
+        if let Err(err) = fs::remove_file(&self.path) {
+            warn!("Failed to remove {}: {err}", self.path.display());
+        }
+    }
+}
+
+fn create_fifo(path: &Path) -> io::Result<()> {
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.file_type().is_fifo() => return Ok(()),
+        Ok(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "a file that isn't a FIFO is in the way",
+            ))
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err),
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let path = CString::new(path.as_os_str().as_bytes())?;
+    // SAFETY: `path` is a valid NUL-terminated string for the duration of the call
+    if unsafe { libc::mkfifo(path.as_ptr(), 0o600) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+async fn forward(
+    receiver: pipe::Receiver,
+    _sender: pipe::Sender,
+    action_tx: UnboundedSender<Action>,
+) {
+    let mut lines = BufReader::new(receiver).lines();
+    loop {
+        let line = match lines.next_line().await {
+            Ok(Some(line)) => line,
+            Ok(None) => return,
+            Err(err) => {
+                warn!("Failed to read the control FIFO: {err}");
+                return;
+            }
+        };
+        let action = match parse_command(&line) {
+            Ok(Some(action)) => action,
+            Ok(None) => continue,
+            Err(err) => {
+                warn!("Ignoring control command {line:?}: {err}");
+                Action::Toast(format!("Control FIFO: {err}"))
+            }
+        };
+        if action_tx.send(action).is_err() {
+            return;
+        }
+    }
+}
+
+/// The action for a line written to the FIFO, `None` for a blank line or a `#` comment.
+///
+/// - `pause [N]` and `resume [N]` pause or resume chart N, or every chart
+/// - `reset [N]` drops the samples of chart N, or of every chart
+/// - `threshold N V` sets the reference of chart N to V
+/// - `mark LABEL` adds a note
+/// - `snapshot` writes the summary of every chart to the export directory
+/// - `quit`
+///
+/// Charts are counted from 1, like on the command line.
+pub fn parse_command(line: &str) -> Result<Option<Action>, String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let rest = rest.trim();
+    let args = rest.split_whitespace().collect::<Vec<_>>();
+    let action = match (command, args.as_slice()) {
+        ("pause" | "resume", [] | [_]) => {
+            let chart = args.first().map(|n| chart(n)).transpose()?;
+            Action::SetPaused(chart, command == "pause")
+        }
+        ("reset", []) => Action::ClearAll,
+        ("reset", [n]) => Action::ResetChart(chart(n)?),
+        ("threshold", [n, value]) => {
+            let value = value
+                .parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite())
+                .ok_or_else(|| format!("`{value}` is not a number"))?;
+            Action::SetReference(chart(n)?, value)
+        }
+        ("mark", [_, ..]) => Action::Mark(rest.to_string()),
+        ("snapshot", []) => Action::Snapshot,
+        ("quit", []) => Action::Quit,
+        ("pause" | "resume" | "reset", _) => return Err(format!("usage: {command} [N]")),
+        ("threshold", _) => return Err("usage: threshold N VALUE".to_string()),
+        ("mark", _) => return Err("usage: mark LABEL".to_string()),
+        ("snapshot" | "quit", _) => return Err(format!("`{command}` takes no arguments")),
+        _ => return Err(format!("unknown command `{command}`")),
+    };
+    Ok(Some(action))
+}
+
+/// The index of the chart numbered `n`, counted from 1.
+fn chart(n: &str) -> Result<usize, String> {
+    match n.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n - 1),
+        _ => Err(format!("`{n}` is not a chart number, they start at 1")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+    use tokio::{io::AsyncWriteExt, sync::mpsc};
+
+    use super::*;
+
+    async fn timeout<T>(future: impl std::future::Future<Output = T>) -> T {
+        tokio::time::timeout(Duration::from_secs(5), future)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_command() {
+        let parse = |line| parse_command(line).unwrap();
+        assert_eq!(parse("pause"), Some(Action::SetPaused(None, true)));
+        assert_eq!(parse("resume 2"), Some(Action::SetPaused(Some(1), false)));
+        assert_eq!(parse("reset"), Some(Action::ClearAll));
+        assert_eq!(parse("  reset 3\n"), Some(Action::ResetChart(2)));
+        assert_eq!(
+            parse("threshold 1 250.5"),
+            Some(Action::SetReference(0, 250.5))
+        );
+        assert_eq!(
+            parse("mark deploy  v1.2"),
+            Some(Action::Mark("deploy  v1.2".to_string()))
+        );
+        assert_eq!(parse("snapshot"), Some(Action::Snapshot));
+        assert_eq!(parse("quit"), Some(Action::Quit));
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("# a comment"), None);
+    }
+
+    #[test]
+    fn test_bad_commands() {
+        let error = |line| parse_command(line).unwrap_err();
+        assert_eq!(error("stop"), "unknown command `stop`");
+        assert_eq!(
+            error("reset 0"),
+            "`0` is not a chart number, they start at 1"
+        );
+        assert_eq!(
+            error("pause all"),
+            "`all` is not a chart number, they start at 1"
+        );
+        assert_eq!(error("pause 1 2"), "usage: pause [N]");
+        assert_eq!(error("threshold 1"), "usage: threshold N VALUE");
+        assert_eq!(error("threshold 1 NaN"), "`NaN` is not a number");
+        assert_eq!(error("mark"), "usage: mark LABEL");
+        assert_eq!(error("quit now"), "`quit` takes no arguments");
+    }
+
+    #[tokio::test]
+    async fn test_fifo() {
+        let path = std::env::temp_dir().join(format!("ttydash-control-{}", std::process::id()));
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let fifo = ControlFifo::spawn(path.clone(), tx).unwrap();
+        assert!(fs::metadata(&path).unwrap().file_type().is_fifo());
+
+        // one script after the other, the reader keeps going when the first is done
+        for script in ["pause 1\n", "frobnicate\nquit\n"] {
+            let mut writer = pipe::OpenOptions::new().open_sender(&path).unwrap();
+            writer.write_all(script.as_bytes()).await.unwrap();
+        }
+        assert_eq!(
+            timeout(rx.recv()).await,
+            Some(Action::SetPaused(Some(0), true))
+        );
+        assert_eq!(
+            timeout(rx.recv()).await,
+            Some(Action::Toast(
+                "Control FIFO: unknown command `frobnicate`".to_string()
+            ))
+        );
+        assert_eq!(timeout(rx.recv()).await, Some(Action::Quit));
+
+        fifo.close();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,250 @@
+//! `ttydash doctor`: what a bug report needs to know about the build, the terminal and the
+//! settings in effect, printed without starting the TUI.
+
+use std::{fmt::Write, path::PathBuf};
+
+use crate::{
+    cli::{Cli, VERSION_MESSAGE},
+    components::{banner::summary_lines, dash::supports_color},
+    config::{get_config_dir, get_config_file, get_data_dir},
+    profile::DEFAULT_PROFILE,
+    tui::TerminalOutput,
+};
+
+/// Environment variables that decide how the terminal is driven, in the order they are listed.
+const TERMINAL_VARIABLES: [&str; 6] = [
+    "TERM",
+    "COLORTERM",
+    "NO_COLOR",
+    "LC_ALL",
+    "LC_CTYPE",
+    "LANG",
+];
+
+/// What the dashboard would find about the terminal it runs in, from the probes it uses at
+/// startup. None of them writes to the terminal.
+#[derive(Debug, Clone)]
+pub struct Probes {
+    /// Whether the dashboard is drawn on the controlling terminal rather than stdout.
+    pub tty: bool,
+    /// Columns and rows, `None` when there is no terminal to ask.
+    pub size: Option<(u16, u16)>,
+    /// Whether two colors are drawn in one cell with `--dense`.
+    pub color: bool,
+    /// Whether the session runs over SSH.
+    pub ssh: bool,
+    /// The [`TERMINAL_VARIABLES`] that are set.
+    pub variables: Vec<(&'static str, String)>,
+}
+
+impl Probes {
+    pub fn detect() -> Self {
+        Self {
+            tty: matches!(TerminalOutput::open(), TerminalOutput::Tty(_)),
+            size: crossterm::terminal::size().ok(),
+            color: supports_color(),
+            ssh: std::env::var_os("SSH_CONNECTION").is_some(),
+            variables: TERMINAL_VARIABLES
+                .into_iter()
+                .filter_map(|name| Some((name, std::env::var(name).ok()?)))
+                .collect(),
+        }
+    }
+}
+
+/// How the config was loaded.
+#[derive(Debug, Clone)]
+pub struct ConfigStatus {
+    pub file: PathBuf,
+    /// Why the file was ignored in favor of the built-in settings.
+    pub problem: Option<String>,
+    pub profile_found: bool,
+}
+
+impl ConfigStatus {
+    pub fn new(problem: Option<String>, profile_found: bool) -> Self {
+        Self {
+            file: get_config_file(),
+            problem,
+            profile_found,
+        }
+    }
+}
+
+/// The report of `ttydash doctor` for `args`, with the profile already applied.
+pub fn report(args: &Cli, probes: &Probes, config: &ConfigStatus) -> String {
+    let mut report = String::new();
+    let mut section = |title: &str, rows: Vec<String>| {
+        let _ = writeln!(report, "{title}:");
+        for row in rows {
+            let _ = writeln!(report, "  {row}");
+        }
+    };
+    let rows = |rows: Vec<(&str, String)>| {
+        rows.into_iter()
+            .map(|(name, value)| format!("{name}: {value}"))
+            .collect()
+    };
+
+    section(
+        "Build",
+        rows(vec![
+            ("version", VERSION_MESSAGE.to_string()),
+            (
+                "target",
+                format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS),
+            ),
+            (
+                "profile",
+                if cfg!(debug_assertions) {
+                    "debug"
+                } else {
+                    "release"
+                }
+                .to_string(),
+            ),
+            ("features", features().join(", ")),
+        ]),
+    );
+
+    let mut terminal = vec![
+        (
+            "output",
+            if probes.tty {
+                "the controlling terminal".to_string()
+            } else {
+                "stdout, there is no controlling terminal".to_string()
+            },
+        ),
+        (
+            "size",
+            match probes.size {
+                Some((columns, rows)) => format!("{columns}x{rows}"),
+                None => "unknown".to_string(),
+            },
+        ),
+        (
+            "colors",
+            if probes.color {
+                "yes".to_string()
+            } else {
+                "no, --dense draws one series per cell".to_string()
+            },
+        ),
+        (
+            "bars",
+            if args.low_bandwidth {
+                "ASCII, one byte per cell".to_string()
+            } else {
+                "braille, assumed one column wide".to_string()
+            },
+        ),
+        (
+            "over ssh",
+            if probes.ssh { "yes" } else { "no" }.to_string(),
+        ),
+    ];
+    terminal.extend(
+        probes
+            .variables
+            .iter()
+            .map(|(name, value)| (*name, value.clone())),
+    );
+    section("Terminal", rows(terminal));
+
+    let profile = args.profile.as_deref().unwrap_or(DEFAULT_PROFILE);
+    section(
+        "Config",
+        rows(vec![
+            ("config directory", get_config_dir().display().to_string()),
+            ("data directory", get_data_dir().display().to_string()),
+            (
+                "config file",
+                match (&config.problem, config.file.exists()) {
+                    (Some(problem), _) => problem.clone(),
+                    (None, true) => config.file.display().to_string(),
+                    (None, false) => format!("{} (not found)", config.file.display()),
+                },
+            ),
+            (
+                "profile",
+                match config.profile_found {
+                    true => profile.to_string(),
+                    false if profile == DEFAULT_PROFILE => "none".to_string(),
+                    false => format!("{profile} (not found)"),
+                },
+            ),
+        ]),
+    );
+
+    let config_file = config.file.exists().then_some(config.file.as_path());
+    section(
+        "Flags",
+        summary_lines(args, config_file, profile, config.profile_found),
+    );
+    report
+}
+
+/// The parts of ttydash that are only built for some platforms.
+fn features() -> Vec<&'static str> {
+    let features = [
+        (cfg!(unix), "control FIFO"),
+        (cfg!(unix), "signals"),
+        (cfg!(unix), "config file locking"),
+    ];
+    let features = features
+        .into_iter()
+        .filter_map(|(built, feature)| built.then_some(feature))
+        .collect::<Vec<_>>();
+    match features.is_empty() {
+        true => vec!["none"],
+        false => features,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let args = Cli::parse_from(["ttydash", "-u", "ms", "--low-bandwidth"]);
+        let probes = Probes {
+            tty: false,
+            size: Some((120, 40)),
+            color: false,
+            ssh: true,
+            variables: vec![("TERM", "xterm-256color".to_string())],
+        };
+        let config = ConfigStatus {
+            file: PathBuf::from("/nonexistent/config.json5"),
+            problem: None,
+            profile_found: false,
+        };
+        let report = report(&args, &probes, &config);
+        let sections = report
+            .lines()
+            .filter(|line| !line.starts_with(' '))
+            .collect::<Vec<_>>();
+        assert_eq!(sections, ["Build:", "Terminal:", "Config:", "Flags:"]);
+        for row in [
+            "  output: stdout, there is no controlling terminal",
+            "  size: 120x40",
+            "  colors: no, --dense draws one series per cell",
+            "  bars: ASCII, one byte per cell",
+            "  over ssh: yes",
+            "  TERM: xterm-256color",
+            "  config file: /nonexistent/config.json5 (not found)",
+            "  profile: none",
+            "  Input: stdin, format number, units ms",
+        ] {
+            assert!(
+                report.lines().any(|line| line == row),
+                "{row:?} in\n{report}"
+            );
+        }
+    }
+}
@@ -1,7 +1,10 @@
-use std::env;
+use std::{
+    env,
+    io::{self, Write},
+};
 
-use color_eyre::Result;
-use tracing::error;
+use color_eyre::{Report, Result};
+use tracing::{error, warn};
 
 pub fn init() -> Result<()> {
     let (panic_hook, eyre_hook) = color_eyre::config::HookBuilder::default()
@@ -44,11 +47,31 @@ pub fn init() -> Result<()> {
                 .create_panic_handler()(panic_info);
         }
 
-        std::process::exit(libc::EXIT_FAILURE);
+        std::process::exit(1);
     }));
     Ok(())
 }
 
+/// Whether `err` comes from writing to a pipe that was closed, like stdout after `| head` quit.
+/// Rust ignores SIGPIPE from the start, so such a write fails instead of killing the process.
+pub fn is_broken_pipe(err: &Report) -> bool {
+    err.chain().any(|cause| {
+        cause
+            .downcast_ref::<io::Error>()
+            .is_some_and(|err| err.kind() == io::ErrorKind::BrokenPipe)
+    })
+}
+
+/// Warn that `what` wasn't written because stdout was closed, on stderr unless that is closed
+/// too.
+pub fn report_closed_stdout(what: &str) {
+    warn!("Stdout was closed, the {what} wasn't written");
+    let _ = writeln!(
+        io::stderr(),
+        "Warning: stdout was closed, the {what} wasn't written"
+    );
+}
+
 /// Similar to the `std::dbg!` macro, but generates `tracing` events rather
 /// than printing to stdout.
 ///
@@ -0,0 +1,141 @@
+use std::time::{Duration, SystemTime};
+
+use crate::clock::local_seconds_of_day;
+
+/// A `--highlight-range`, either sample numbers counted from the first sample or times of day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HighlightRange {
+    Samples(u64, u64),
+    /// Seconds since local midnight.
+    Clock(u32, u32),
+}
+
+/// A highlighted stretch of time, inclusive on both ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeRange {
+    pub start: SystemTime,
+    pub end: SystemTime,
+}
+
+/// Parse `<start>..<end>`, where both ends are sample numbers like `120` or times like `14:02`
+/// or `14:02:30`.
+pub fn parse_highlight_range(value: &str) -> Result<HighlightRange, String> {
+    let Some((start, end)) = value.split_once("..") else {
+        return Err(format!("expected `<start>..<end>`, got `{value}`"));
+    };
+    let (start, end) = (start.trim(), end.trim());
+    if let (Ok(start), Ok(end)) = (start.parse::<u64>(), end.parse::<u64>()) {
+        return Ok(HighlightRange::Samples(start.min(end), start.max(end)));
+    }
+    match (parse_time_of_day(start), parse_time_of_day(end)) {
+        (Some(start), Some(end)) => Ok(HighlightRange::Clock(start, end)),
+        _ => Err(format!(
+            "expected sample numbers like `100..200` or times like `14:02..14:05`, got `{value}`"
+        )),
+    }
+}
+
+fn parse_time_of_day(value: &str) -> Option<u32> {
+    let mut parts = value.split(':').map(|part| part.parse::<u32>().ok());
+    let hours = parts.next()??;
+    let minutes = parts.next()??;
+    let seconds = parts.next().unwrap_or(Some(0))?;
+    if parts.next().is_some() || hours > 23 || minutes > 59 || seconds > 59 {
+        return None;
+    }
+    Some(hours * 3600 + minutes * 60 + seconds)
+}
+
+impl HighlightRange {
+    /// Turn the range into wall clock times. Sample numbers count `interval`s from `started`,
+    /// times of day are taken from the day of `now`.
+    pub fn resolve(&self, started: SystemTime, interval: Duration, now: SystemTime) -> TimeRange {
+        match *self {
+            HighlightRange::Samples(start, end) => TimeRange {
+                start: started + interval.mul_f64(start as f64),
+                end: started + interval.mul_f64(end as f64),
+            },
+            HighlightRange::Clock(start, end) => {
+                let midnight = now - Duration::from_secs(local_seconds_of_day(now) as u64);
+                let start = midnight + Duration::from_secs(start as u64);
+                let mut end = midnight + Duration::from_secs(end as u64);
+                // a range like 23:50..00:10 ends on the next day
+                if end < start {
+                    end += Duration::from_secs(24 * 3600);
+                }
+                TimeRange { start, end }
+            }
+        }
+    }
+}
+
+impl TimeRange {
+    /// A range from two points in time given in any order.
+    pub fn between(a: SystemTime, b: SystemTime) -> Self {
+        Self {
+            start: a.min(b),
+            end: a.max(b),
+        }
+    }
+
+    /// Whether the range overlaps `[start, end]`.
+    pub fn overlaps(&self, start: SystemTime, end: SystemTime) -> bool {
+        start <= self.end && end >= self.start
+    }
+
+    pub fn contains(&self, time: SystemTime) -> bool {
+        self.overlaps(time, time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_samples() {
+        assert_eq!(
+            parse_highlight_range("100..200"),
+            Ok(HighlightRange::Samples(100, 200))
+        );
+        assert_eq!(
+            parse_highlight_range("200..100"),
+            Ok(HighlightRange::Samples(100, 200))
+        );
+    }
+
+    #[test]
+    fn test_parse_clock() {
+        assert_eq!(
+            parse_highlight_range("14:02..14:05:30"),
+            Ok(HighlightRange::Clock(50520, 50730))
+        );
+        assert!(parse_highlight_range("14:02").is_err());
+        assert!(parse_highlight_range("25:00..26:00").is_err());
+        assert!(parse_highlight_range("a..b").is_err());
+    }
+
+    #[test]
+    fn test_resolve_samples() {
+        let started = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        let range =
+            HighlightRange::Samples(10, 20).resolve(started, Duration::from_millis(500), started);
+        assert_eq!(range.start, started + Duration::from_secs(5));
+        assert_eq!(range.end, started + Duration::from_secs(10));
+        assert!(range.contains(started + Duration::from_secs(7)));
+        assert!(!range.contains(started + Duration::from_secs(11)));
+    }
+
+    #[test]
+    fn test_resolve_clock_across_midnight() {
+        let now = SystemTime::now();
+        let range =
+            HighlightRange::Clock(23 * 3600 + 50 * 60, 600).resolve(now, Duration::ZERO, now);
+        assert_eq!(
+            range.end.duration_since(range.start).unwrap(),
+            Duration::from_secs(20 * 60)
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    time::Duration,
+};
+
+use color_eyre::{eyre::eyre, Result};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpListener,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
+
+use crate::summary::Summary;
+
+/// Requests larger than this are rejected, nothing we serve needs a body.
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
+/// A client that didn't send its whole request by then is dropped, so idle connections don't
+/// pile up.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Parse the `--http` address, a bare port listens on the loopback interface.
+pub fn parse_addr(value: &str) -> Result<SocketAddr, String> {
+    if let Ok(port) = value.parse::<u16>() {
+        return Ok(SocketAddr::from((Ipv4Addr::LOCALHOST, port)));
+    }
+    value
+        .parse::<SocketAddr>()
+        .map_err(|err| format!("{err}, expected a port or an address like 127.0.0.1:8080"))
+}
+
+/// Answer `GET /stats` with the live summary and `GET /healthz` with `ok` until cancelled.
+pub async fn serve<F>(listener: TcpListener, snapshot: F, cancellation_token: CancellationToken)
+where
+    F: Fn() -> Summary + Clone + Send + 'static,
+{
+    loop {
+        let stream = tokio::select! {
+            _ = cancellation_token.cancelled() => break,
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(err) => {
+                    debug!("Failed to accept HTTP connection: {err}");
+                    continue;
+                }
+            },
+        };
+        let snapshot = snapshot.clone();
+        tokio::spawn(async move {
+            if let Err(err) = handle(stream, snapshot, REQUEST_TIMEOUT).await {
+                debug!("Failed to answer HTTP request: {err}");
+            }
+        });
+    }
+}
+
+async fn handle<S, F>(mut stream: S, snapshot: F, timeout: Duration) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    F: Fn() -> Summary,
+{
+    let request = tokio::time::timeout(timeout, read_request(&mut stream))
+        .await
+        .map_err(|_| eyre!("no request within {timeout:?}"))??;
+    let request_line = String::from_utf8_lossy(&request);
+    let request_line = request_line.lines().next().unwrap_or_default();
+    let response = respond(request_line, snapshot)?;
+    stream.write_all(&response).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Read up to the end of the request headers, or [`MAX_REQUEST_SIZE`] bytes.
+async fn read_request<S: AsyncRead + Unpin>(stream: &mut S) -> Result<Vec<u8>> {
+    let mut request = Vec::new();
+    let mut buf = [0; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST_SIZE {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+    Ok(request)
+}
+
+fn respond<F>(request_line: &str, snapshot: F) -> Result<Vec<u8>>
+where
+    F: Fn() -> Summary,
+{
+    let mut parts = request_line.split_whitespace();
+    // the query string doesn't change what we serve
+    let (method, path) = (
+        parts.next(),
+        parts
+            .next()
+            .map(|target| target.split('?').next().unwrap_or(target)),
+    );
+    let (status, content_type, body) = match (method, path) {
+        (Some("GET"), Some("/stats")) => ("200 OK", "application/json", snapshot().to_json()?),
+        (Some("GET"), Some("/healthz")) => ("200 OK", "text/plain", "ok".to_string()),
+        (Some("GET"), _) => ("404 Not Found", "text/plain", "not found".to_string()),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain",
+            "method not allowed".to_string(),
+        ),
+    };
+    Ok(format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+    .into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::summary::ChartSummary;
+
+    fn snapshot() -> Summary {
+        Summary {
+            charts: vec![ChartSummary {
+                title: "latency".to_string(),
+                samples: 1,
+                last: Some(1.5),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn respond_str(request_line: &str) -> String {
+        String::from_utf8(respond(request_line, snapshot).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_addr() {
+        assert_eq!(parse_addr("8080").unwrap().to_string(), "127.0.0.1:8080");
+        assert_eq!(parse_addr("0.0.0.0:80").unwrap().to_string(), "0.0.0.0:80");
+        assert!(parse_addr("localhost").is_err());
+    }
+
+    #[test]
+    fn test_stats() {
+        let response = respond_str("GET /stats HTTP/1.1");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Type: application/json"));
+        assert!(response.contains(r#""title": "latency""#));
+    }
+
+    #[test]
+    fn test_healthz() {
+        let response = respond_str("GET /healthz HTTP/1.1");
+        assert!(response.ends_with("\r\n\r\nok"));
+    }
+
+    #[test]
+    fn test_unknown() {
+        assert!(respond_str("GET / HTTP/1.1").starts_with("HTTP/1.1 404"));
+        assert!(respond_str("POST /stats HTTP/1.1").starts_with("HTTP/1.1 405"));
+        assert!(respond_str("").starts_with("HTTP/1.1 405"));
+    }
+
+    #[test]
+    fn test_query_string() {
+        assert!(respond_str("GET /stats?x=1 HTTP/1.1").starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(respond_str("GET /healthz? HTTP/1.1").ends_with("\r\n\r\nok"));
+        assert!(respond_str("GET /?stats HTTP/1.1").starts_with("HTTP/1.1 404"));
+    }
+
+    #[tokio::test]
+    async fn test_idle_client_times_out() {
+        let (_client, server) = tokio::io::duplex(64);
+        let err = handle(server, snapshot, Duration::from_millis(10))
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "no request within 10ms");
+
+        let (mut client, server) = tokio::io::duplex(1024);
+        client
+            .write_all(b"GET /healthz HTTP/1.1\r\n\r\n")
+            .await
+            .unwrap();
+        handle(server, snapshot, Duration::from_millis(10))
+            .await
+            .unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+        assert!(response.ends_with("\r\n\r\nok"));
+    }
+}
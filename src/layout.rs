@@ -0,0 +1,223 @@
+use ratatui::layout::Rect;
+
+use crate::cli::Layout;
+
+/// Tweaks to how [`compute_grid`] places the charts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayoutOpts {
+    /// Under [`Layout::Auto`], give the first chart a row of its own when the count is prime, so
+    /// the others fill a grid without a hole in it. Otherwise a prime count is laid out in a
+    /// single row.
+    pub wide_first: bool,
+}
+
+impl Default for LayoutOpts {
+    fn default() -> Self {
+        Self { wide_first: true }
+    }
+}
+
+/// Split `area` into `n` rects, one per chart in drawing order.
+///
+/// - [`Layout::Vertical`] stacks the charts, [`Layout::Horizontal`] puts them side by side and
+///   [`Layout::Auto`] arranges them in rows of equal height, using the largest divisor of the
+///   count below itself as the number of rows, so 6 charts are 3 rows of 2 and 9 charts 3 rows
+///   of 3. A prime count is laid out as one more than it, see [`LayoutOpts::wide_first`].
+/// - The rects never overlap and together cover `area` exactly. Cells that don't divide evenly
+///   go one each to the first rows and columns, so sizes differ by at most one cell.
+/// - No rect is empty as long as `area` has a cell for every row and column of the grid; when it
+///   doesn't, the charts that don't fit get empty rects at the area's edge.
+pub fn compute_grid(n: usize, area: Rect, mode: Layout, opts: &LayoutOpts) -> Vec<Rect> {
+    if n == 0 {
+        return vec![];
+    }
+    match mode {
+        Layout::Vertical => rows(area, n).collect(),
+        Layout::Horizontal => columns(area, n).collect(),
+        Layout::Auto if opts.wide_first && is_prime(n) => {
+            let (grid_rows, cols) = grid(n - 1);
+            let mut rows = rows(area, grid_rows + 1);
+            let mut rects = rows.next().into_iter().collect::<Vec<_>>();
+            rects.extend(rows.flat_map(|row| columns(row, cols)));
+            rects
+        }
+        Layout::Auto => {
+            let (grid_rows, cols) = grid(n);
+            rows(area, grid_rows)
+                .flat_map(|row| columns(row, cols))
+                .collect()
+        }
+    }
+}
+
+/// The rows and columns for `n` charts, as many rows as the largest divisor below `n`.
+fn grid(n: usize) -> (usize, usize) {
+    match n {
+        0 | 1 => (1, 1),
+        2 => (1, 2),
+        _ => {
+            let rows = (2..n).rev().find(|&i| n.is_multiple_of(i)).unwrap_or(1);
+            (rows, n / rows)
+        }
+    }
+}
+
+/// `area` split into `n` rows.
+fn rows(area: Rect, n: usize) -> impl Iterator<Item = Rect> {
+    split(area.y, area.height, n).map(move |(y, height)| Rect { y, height, ..area })
+}
+
+/// `area` split into `n` columns.
+fn columns(area: Rect, n: usize) -> impl Iterator<Item = Rect> {
+    split(area.x, area.width, n).map(move |(x, width)| Rect { x, width, ..area })
+}
+
+/// `n` consecutive spans covering `length` cells from `start`, the first `length % n` of them
+/// a cell longer than the others.
+fn split(start: u16, length: u16, n: usize) -> impl Iterator<Item = (u16, u16)> {
+    let base = length as usize / n;
+    let longer = length as usize % n;
+    (0..n).map(move |i| {
+        let offset = i * base + i.min(longer);
+        let size = base + usize::from(i < longer);
+        (start + offset as u16, size as u16)
+    })
+}
+
+fn is_prime(n: usize) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for i in 2..=((n as f64).sqrt() as usize) {
+        if n.is_multiple_of(i) {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const MODES: [Layout; 3] = [Layout::Vertical, Layout::Horizontal, Layout::Auto];
+
+    /// Terminal sizes with odd and even sides, all with a cell for each of up to 16 charts.
+    const SIZES: [(u16, u16); 7] = [
+        (80, 24),
+        (81, 25),
+        (100, 33),
+        (120, 40),
+        (203, 61),
+        (16, 16),
+        (17, 19),
+    ];
+
+    /// Check the contract of [`compute_grid`] for `rects` laid out in `area`.
+    fn assert_tiles(rects: &[Rect], area: Rect, context: &str) {
+        for (i, rect) in rects.iter().enumerate() {
+            assert_eq!(rect.intersection(area), *rect, "{context}: #{i} sticks out");
+            for (j, other) in rects.iter().enumerate().skip(i + 1) {
+                let overlap = rect.intersection(*other);
+                assert!(overlap.is_empty(), "{context}: #{i} overlaps #{j}");
+            }
+        }
+        let cells = |rect: &Rect| rect.width as u32 * rect.height as u32;
+        let covered: u32 = rects.iter().map(cells).sum();
+        assert_eq!(covered, cells(&area), "{context}: cells left over");
+    }
+
+    #[test]
+    fn test_all_counts_and_sizes() {
+        for (width, height) in SIZES {
+            for offset in [0, 3] {
+                let area = Rect::new(offset, offset, width, height);
+                for mode in MODES {
+                    for opts in [LayoutOpts::default(), LayoutOpts { wide_first: false }] {
+                        for n in 1..=16 {
+                            let rects = compute_grid(n, area, mode, &opts);
+                            let context = format!("{n} charts {mode:?} {opts:?} in {area}");
+                            assert_eq!(rects.len(), n, "{context}");
+                            assert_tiles(&rects, area, &context);
+                            assert!(
+                                rects.iter().all(|rect| !rect.is_empty()),
+                                "{context}: {rects:?}"
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_even_sizes() {
+        // 100 / 3 percent used to leave a column unused on the right
+        let area = Rect::new(0, 0, 100, 30);
+        let widths = compute_grid(3, area, Layout::Horizontal, &LayoutOpts::default())
+            .iter()
+            .map(|rect| rect.width)
+            .collect::<Vec<_>>();
+        assert_eq!(widths, [34, 33, 33]);
+        let heights = compute_grid(
+            4,
+            Rect::new(0, 0, 80, 23),
+            Layout::Vertical,
+            &LayoutOpts::default(),
+        )
+        .iter()
+        .map(|rect| rect.height)
+        .collect::<Vec<_>>();
+        assert_eq!(heights, [6, 6, 6, 5]);
+    }
+
+    #[test]
+    fn test_auto_grid() {
+        let area = Rect::new(0, 0, 60, 30);
+        let shape = |n, opts| {
+            let rects = compute_grid(n, area, Layout::Auto, &opts);
+            let mut rows = vec![];
+            for rect in rects {
+                match rows.last_mut() {
+                    Some((y, count)) if *y == rect.y => *count += 1,
+                    _ => rows.push((rect.y, 1)),
+                }
+            }
+            rows.into_iter().map(|(_, count)| count).collect::<Vec<_>>()
+        };
+        let opts = LayoutOpts::default();
+        assert_eq!(shape(1, opts), [1]);
+        assert_eq!(shape(2, opts), [1, 1]);
+        assert_eq!(shape(3, opts), [1, 2]);
+        assert_eq!(shape(4, opts), [2, 2]);
+        assert_eq!(shape(5, opts), [1, 2, 2]);
+        assert_eq!(shape(6, opts), [2, 2, 2]);
+        assert_eq!(shape(7, opts), [1, 2, 2, 2]);
+        assert_eq!(shape(9, opts), [3, 3, 3]);
+        let single_row = LayoutOpts { wide_first: false };
+        assert_eq!(shape(2, single_row), [2]);
+        assert_eq!(shape(5, single_row), [5]);
+        assert_eq!(shape(6, single_row), [2, 2, 2]);
+    }
+
+    #[test]
+    fn test_too_small() {
+        // more charts than rows: the last ones are empty but everything still adds up
+        let area = Rect::new(0, 0, 40, 5);
+        let rects = compute_grid(8, area, Layout::Vertical, &LayoutOpts::default());
+        assert_eq!(rects.len(), 8);
+        assert_tiles(&rects, area, "8 charts in 5 rows");
+        let empty = rects.iter().filter(|rect| rect.is_empty()).count();
+        assert_eq!(empty, 3);
+        assert!(rects[..5].iter().all(|rect| rect.height == 1));
+
+        assert_eq!(
+            compute_grid(0, area, Layout::Auto, &LayoutOpts::default()),
+            []
+        );
+        let rects = compute_grid(4, Rect::default(), Layout::Auto, &LayoutOpts::default());
+        assert_eq!(rects.len(), 4);
+    }
+}
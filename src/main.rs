@@ -1,14 +1,34 @@
 use crate::app::App;
 use clap::Parser;
-use cli::{Cli, Commands};
+use cli::{Cli, Commands, ConfigCommands};
 
 mod action;
 mod app;
+mod backfill;
 mod cli;
+mod clock;
 mod components;
 mod config;
+#[cfg(unix)]
+mod control;
+mod doctor;
 mod errors;
+mod highlight;
+mod http;
+mod layout;
 mod logging;
+mod memory;
+mod notify;
+mod number;
+mod positional;
+mod profile;
+mod rates;
+mod regexes;
+#[cfg(unix)]
+mod signals;
+mod source;
+mod summary;
+mod text;
 mod tui;
 
 #[tokio::main]
@@ -16,21 +36,61 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     crate::errors::init()?;
     crate::logging::init()?;
 
-    let args = Cli::parse();
-    if let Some(cmd) = &args.cmd {
+    let mut args = Cli::parse();
+    if let Some(cmd) = args.cmd.take() {
         match cmd {
-            Commands::Add(_) => {}
-            Commands::Remove(_) => {}
+            Commands::Add(add) => regexes::add(&add.name, &add.regex, add.force)?,
+            Commands::Remove(remove) => regexes::remove(&remove.name)?,
+            Commands::Import(import) => regexes::import(&import.file, import.overwrite)?,
+            Commands::Export(export) => {
+                regexes::export(export.file.as_deref(), export.include_builtin)?
+            }
             Commands::List => {
-                let regexes = config::get_regexes().unwrap();
+                let regexes = config::get_regexes().unwrap_or_else(|err| {
+                    eprintln!("Warning: {err}\nListing the built-in regexes only");
+                    let builtins = config::builtin_regexes().into_iter();
+                    builtins.map(|entry| (entry.name, entry.regex)).collect()
+                });
                 for (name, regex) in regexes {
                     println!("{:<10}: {}", name, regex);
                 }
             }
+            Commands::Config(ConfigCommands::Validate) => {
+                let path = config::get_config_file();
+                if !path.exists() {
+                    println!(
+                        "There is no {}, the built-in settings are used",
+                        path.display()
+                    );
+                }
+                let problems = config::validate(&path);
+                if !problems.is_empty() {
+                    for problem in problems {
+                        eprintln!("{problem}");
+                    }
+                    std::process::exit(1);
+                }
+                if path.exists() {
+                    println!("{} is valid", path.display());
+                }
+            }
+            Commands::Test(test) => {
+                if !app::self_test(args, test.lines)? {
+                    eprintln!("No line produced a value");
+                    std::process::exit(1);
+                }
+            }
+            Commands::Doctor => app::doctor(args)?,
+            Commands::Simulate(simulate) => app::simulate(args, simulate)?,
         }
+    } else if args.once {
+        app::once(args)?;
     } else {
         let mut app = App::new(args)?;
         app.run().await?;
+        if let Some(code) = app.exit_code() {
+            std::process::exit(code);
+        }
     }
     Ok(())
 }
@@ -0,0 +1,90 @@
+use std::mem::size_of;
+
+/// Bytes a chart needs besides its samples: the chart state itself with its session stats, plus
+/// a short title and unit.
+const CHART_OVERHEAD: usize = 512;
+/// Bytes a sample needs, with its share of the room the history keeps after the newest one.
+const BYTES_PER_SAMPLE: usize = size_of::<f64>() * 5 / 4;
+
+/// Parse a `--max-memory` size like `256M`, `1G` or `512K`, a bare number is in bytes.
+pub fn parse_size(value: &str) -> Result<usize, String> {
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number = number
+        .parse::<f64>()
+        .map_err(|_| format!("invalid size `{value}`"))?;
+    let multiplier = match unit.to_ascii_uppercase().trim_end_matches(['B', 'I']) {
+        "" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        _ => {
+            return Err(format!(
+                "invalid size `{value}`, expected a unit of K, M or G"
+            ))
+        }
+    };
+    Ok((number * multiplier as f64) as usize)
+}
+
+/// Format a byte count with a binary unit, e.g. `1.5 MiB`.
+pub fn format_size(bytes: usize) -> String {
+    let units = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", units[unit])
+    }
+}
+
+/// Approximate bytes retained by `charts` charts keeping `history` samples each.
+pub fn estimate_bytes(charts: usize, history: usize) -> usize {
+    charts * (history * BYTES_PER_SAMPLE + CHART_OVERHEAD)
+}
+
+/// The longest history `charts` charts can keep within `limit` bytes, at least one sample.
+pub fn history_within(limit: usize, charts: usize) -> usize {
+    let per_chart = limit / charts.max(1);
+    (per_chart.saturating_sub(CHART_OVERHEAD) / BYTES_PER_SAMPLE).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("256M"), Ok(256 << 20));
+        assert_eq!(parse_size("1G"), Ok(1 << 30));
+        assert_eq!(parse_size("1.5k"), Ok(1536));
+        assert_eq!(parse_size("64MiB"), Ok(64 << 20));
+        assert_eq!(parse_size("1000"), Ok(1000));
+        assert!(parse_size("1T").is_err());
+        assert!(parse_size("M").is_err());
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(256 << 20), "256.0 MiB");
+    }
+
+    #[test]
+    fn test_history_within() {
+        let history = history_within(1 << 20, 4);
+        assert!(estimate_bytes(4, history) <= 1 << 20);
+        assert!(estimate_bytes(4, history + 1) > 1 << 20);
+        assert_eq!(history_within(10, 4), 1);
+    }
+}
@@ -0,0 +1,120 @@
+//! Notifications for a chart going above its `--reference`, for dashboards left in a background
+//! tmux window or terminal tab. They are written through the terminal the dashboard is drawn on,
+//! between frames.
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+use crate::cli::NotifyChannel;
+
+/// A chart notifies at most once in this long, however often it crosses its reference.
+const MIN_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Write `message` to every channel in `channels`.
+pub fn notify(
+    output: &mut impl Write,
+    channels: &[NotifyChannel],
+    message: &str,
+) -> io::Result<()> {
+    // control characters would end the escape sequences early
+    let message = message.replace(|c: char| c.is_control(), " ");
+    for channel in channels {
+        match channel {
+            // tmux flags the window of a pane ringing the bell
+            NotifyChannel::Bell => output.write_all(b"\x07")?,
+            NotifyChannel::Osc9 => write!(output, "\x1b]9;{message}\x07")?,
+            NotifyChannel::Title => write!(output, "\x1b]2;{message}\x07")?,
+        }
+    }
+    output.flush()
+}
+
+/// Tracks which charts are above their reference, so only the crossing notifies.
+#[derive(Debug, Clone, Default)]
+pub struct Alerts {
+    /// Charts above their reference.
+    above: HashMap<usize, bool>,
+    last_notified: HashMap<usize, Instant>,
+}
+
+impl Alerts {
+    /// Record whether chart `i` is above its reference, returns whether to notify now.
+    pub fn update(&mut self, i: usize, above: bool, now: Instant) -> bool {
+        let was_above = self.above.insert(i, above).unwrap_or(false);
+        if !above || was_above {
+            return false;
+        }
+        let due = self
+            .last_notified
+            .get(&i)
+            .is_none_or(|&last| now.duration_since(last) >= MIN_INTERVAL);
+        if due {
+            self.last_notified.insert(i, now);
+        }
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn written(channels: &[NotifyChannel], message: &str) -> String {
+        let mut output = vec![];
+        notify(&mut output, channels, message).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_bell() {
+        assert_eq!(written(&[NotifyChannel::Bell], "cpu above 90"), "\x07");
+    }
+
+    #[test]
+    fn test_osc9() {
+        assert_eq!(
+            written(&[NotifyChannel::Osc9], "cpu above 90"),
+            "\x1b]9;cpu above 90\x07"
+        );
+    }
+
+    #[test]
+    fn test_title() {
+        assert_eq!(
+            written(&[NotifyChannel::Title], "cpu\x07 above\n90"),
+            "\x1b]2;cpu  above 90\x07"
+        );
+    }
+
+    #[test]
+    fn test_channels_in_order() {
+        assert_eq!(
+            written(&[NotifyChannel::Title, NotifyChannel::Bell], "x"),
+            "\x1b]2;x\x07\x07"
+        );
+        assert_eq!(written(&[], "x"), "");
+    }
+
+    #[test]
+    fn test_only_crossings_notify() {
+        let mut alerts = Alerts::default();
+        let start = Instant::now();
+        let at = |seconds| start + Duration::from_secs(seconds);
+        assert!(!alerts.update(0, false, at(0)));
+        assert!(alerts.update(0, true, at(1)));
+        // still above, every breaching sample stays quiet
+        assert!(!alerts.update(0, true, at(2)));
+        assert!(!alerts.update(0, false, at(3)));
+        // crossing again within the minute
+        assert!(!alerts.update(0, true, at(30)));
+        // another chart has its own limit
+        assert!(alerts.update(1, true, at(30)));
+        assert!(!alerts.update(0, false, at(40)));
+        assert!(alerts.update(0, true, at(61)));
+    }
+}
@@ -0,0 +1,349 @@
+use crate::cli::{LabelFormat, NumberLocale, UnitPrefix};
+
+const SI_PREFIXES: [&str; 5] = ["k", "M", "G", "T", "P"];
+const BINARY_PREFIXES: [&str; 5] = ["K", "M", "G", "T", "P"];
+
+impl NumberLocale {
+    /// The thousands separator, if digits are grouped, and the decimal separator.
+    fn separators(self) -> (Option<char>, char) {
+        match self {
+            NumberLocale::Plain => (None, '.'),
+            NumberLocale::En => (Some(','), '.'),
+            NumberLocale::De => (Some('.'), ','),
+            // a no-break space, so a number is never wrapped in the middle
+            NumberLocale::Fr => (Some('\u{a0}'), ','),
+        }
+    }
+}
+
+/// Format `value` with `decimals` digits after the decimal separator for display. Parsing is
+/// never affected by the locale.
+pub fn format_number(value: f64, decimals: usize, locale: NumberLocale) -> String {
+    let formatted = format!("{value:.decimals$}");
+    if !value.is_finite() {
+        return formatted;
+    }
+    let (sign, digits) = match formatted.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", formatted.as_str()),
+    };
+    let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let (group, decimal) = locale.separators();
+
+    let mut number = String::from(sign);
+    for (i, digit) in integer.chars().enumerate() {
+        if let Some(group) = group.filter(|_| i > 0 && (integer.len() - i) % 3 == 0) {
+            number.push(group);
+        }
+        number.push(digit);
+    }
+    if !fraction.is_empty() {
+        number.push(decimal);
+        number.push_str(fraction);
+    }
+    number
+}
+
+/// Scale a value of at least a thousand `unit`s down and prefix the unit, e.g. 2000000000 `B`
+/// to 1.86 `GB`. `auto` uses powers of 1024 for byte units like `B`, `MB` or `GiB` and of 1000
+/// for everything else. Returns `None` when the value is shown as is.
+pub fn scale_unit(value: f64, unit: &str, format: LabelFormat) -> Option<(f64, String)> {
+    scale_to(value, unit, format, prefix_steps(value, unit, format))
+}
+
+/// The base, the prefixes, the prefix `unit` already carries and `unit` without it, `None` when
+/// values are never scaled.
+fn prefix_scale(unit: &str, format: LabelFormat) -> Option<(f64, &[&str], usize, &str)> {
+    let bytes = byte_unit(unit);
+    let binary = match format {
+        LabelFormat::Plain => return None,
+        LabelFormat::Auto => bytes.is_some(),
+        LabelFormat::Si => false,
+        LabelFormat::Binary => true,
+    };
+    let (base, prefixes) = if binary {
+        (1024.0, &BINARY_PREFIXES)
+    } else {
+        (1000.0, &SI_PREFIXES)
+    };
+    // a byte unit may already carry a prefix, scaling continues from it
+    let (start, unit) = bytes.unwrap_or((0, unit));
+    Some((base, prefixes, start, unit))
+}
+
+/// How many prefixes `value` climbs above `unit`, 0 when it is shown as is.
+fn prefix_steps(value: f64, unit: &str, format: LabelFormat) -> usize {
+    let Some((base, prefixes, start, _)) = prefix_scale(unit, format) else {
+        return 0;
+    };
+    if !value.is_finite() {
+        return 0;
+    }
+    let mut scaled = value;
+    let mut prefix = start;
+    while scaled.abs() >= base && prefix < prefixes.len() {
+        scaled /= base;
+        prefix += 1;
+    }
+    prefix - start
+}
+
+/// Scale `value` `steps` prefixes above `unit`, `None` for 0 steps.
+pub fn scale_to(
+    value: f64,
+    unit: &str,
+    format: LabelFormat,
+    steps: usize,
+) -> Option<(f64, String)> {
+    let (base, prefixes, start, unit) = prefix_scale(unit, format)?;
+    if steps == 0 || !value.is_finite() {
+        return None;
+    }
+    let prefix = (start + steps).min(prefixes.len());
+    let scaled = value / base.powi((prefix - start) as i32);
+    Some((scaled, format!("{}{unit}", prefixes[prefix - 1])))
+}
+
+/// How far past a prefix boundary a value has to move before `--unit-prefix auto` switches.
+const HYSTERESIS: f64 = 0.2;
+
+/// The prefix of one label across redraws, so it doesn't flip back and forth while the value
+/// hovers around a boundary like `1000 kB`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PrefixState {
+    steps: Option<usize>,
+}
+
+impl PrefixState {
+    /// The prefix steps to show `value` with, see [`scale_to`]. `lock` keeps the prefix of the
+    /// first value other than zero, `auto` moves to another prefix only once the value is 20%
+    /// past the boundary.
+    pub fn pick(&mut self, value: f64, unit: &str, format: LabelFormat, mode: UnitPrefix) -> usize {
+        let natural = |value: f64| prefix_steps(value, unit, format);
+        let steps = match (self.steps, mode) {
+            (Some(steps), UnitPrefix::Lock) => steps,
+            (Some(steps), UnitPrefix::Auto) if value.is_finite() => {
+                let up = natural(value / (1.0 + HYSTERESIS));
+                let down = natural(value / (1.0 - HYSTERESIS));
+                if up > steps {
+                    up
+                } else if down < steps {
+                    down
+                } else {
+                    steps
+                }
+            }
+            (Some(steps), UnitPrefix::Auto) => steps,
+            (None, _) => natural(value),
+        };
+        // a zero max says nothing about the magnitude to come
+        if value.is_finite() && value != 0.0 {
+            self.steps = Some(steps);
+        }
+        steps
+    }
+}
+
+/// Parse a `--quantize` step, a positive number.
+pub fn parse_step(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(step) if step > 0.0 && step.is_finite() => Ok(step),
+        Ok(_) => Err(format!("step `{value}` must be a positive number")),
+        Err(_) => Err(format!("invalid step `{value}`")),
+    }
+}
+
+/// Round `value` to the nearest multiple of `step`, without the float noise a step like `0.1`
+/// would add: to no more decimals than the step has.
+pub fn quantize(value: f64, step: f64) -> f64 {
+    let decimals = step
+        .to_string()
+        .split_once('.')
+        .map_or(0, |(_, fraction)| fraction.len());
+    let scale = 10f64.powi(decimals as i32);
+    ((value / step).round() * step * scale).round() / scale
+}
+
+/// Split a byte unit like `B`, `kB` or `GiB` into the number of its prefix, 0 without one, and
+/// the unit without the prefix.
+fn byte_unit(unit: &str) -> Option<(usize, &str)> {
+    let base = ["iB", "B"].into_iter().find(|base| unit.ends_with(base))?;
+    let prefix = &unit[..unit.len() - base.len()];
+    if prefix.is_empty() {
+        return Some((0, base));
+    }
+    BINARY_PREFIXES
+        .iter()
+        .position(|known| known.eq_ignore_ascii_case(prefix))
+        .map(|position| (position + 1, base))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn all(value: f64, decimals: usize) -> [String; 4] {
+        [
+            NumberLocale::Plain,
+            NumberLocale::En,
+            NumberLocale::De,
+            NumberLocale::Fr,
+        ]
+        .map(|locale| format_number(value, decimals, locale))
+    }
+
+    #[test]
+    fn test_grouping() {
+        assert_eq!(
+            all(1234.56, 2),
+            ["1234.56", "1,234.56", "1.234,56", "1\u{a0}234,56"]
+        );
+        assert_eq!(all(123.0, 2), ["123.00", "123.00", "123,00", "123,00"]);
+        assert_eq!(
+            all(1_234_567_890.0, 0),
+            [
+                "1234567890",
+                "1,234,567,890",
+                "1.234.567.890",
+                "1\u{a0}234\u{a0}567\u{a0}890"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_quantize() {
+        assert_eq!(quantize(20.003, 0.01), 20.0);
+        assert_eq!(quantize(20.006, 0.01), 20.01);
+        assert_eq!(quantize(0.3, 0.1), 0.3);
+        assert_eq!(quantize(1234.0, 50.0), 1250.0);
+        assert_eq!(quantize(-7.4, 5.0), -5.0);
+        assert_eq!(quantize(0.74, 0.25), 0.75);
+    }
+
+    #[test]
+    fn test_parse_step() {
+        assert_eq!(parse_step("0.5"), Ok(0.5));
+        assert!(parse_step("0").is_err());
+        assert!(parse_step("-1").is_err());
+        assert!(parse_step("inf").is_err());
+        assert!(parse_step("fine").is_err());
+    }
+
+    #[test]
+    fn test_negative() {
+        assert_eq!(
+            all(-98765.4321, 2),
+            ["-98765.43", "-98,765.43", "-98.765,43", "-98\u{a0}765,43"]
+        );
+        assert_eq!(all(-999.0, 0), ["-999", "-999", "-999", "-999"]);
+    }
+
+    #[test]
+    fn test_small_and_special() {
+        assert_eq!(all(0.000012, 2), ["0.00", "0.00", "0,00", "0,00"]);
+        assert_eq!(all(0.5, 3), ["0.500", "0.500", "0,500", "0,500"]);
+        assert_eq!(format_number(f64::INFINITY, 2, NumberLocale::De), "inf");
+        assert_eq!(format_number(f64::NAN, 2, NumberLocale::Fr), "NaN");
+    }
+
+    #[test]
+    fn test_scale_unit() {
+        let scale = |value, unit| scale_unit(value, unit, LabelFormat::Auto);
+        assert_eq!(scale(1024.0, "B"), Some((1.0, "KB".to_string())));
+        assert_eq!(scale(1023.0, "B"), None);
+        assert_eq!(scale(3.0 * 1024.0, "MiB"), Some((3.0, "GiB".to_string())));
+        assert_eq!(scale(1536.0, "kB"), Some((1.5, "MB".to_string())));
+        assert_eq!(scale(2048.0, "iB"), Some((2.0, "KiB".to_string())));
+        assert_eq!(scale(1000.0, "req"), Some((1.0, "kreq".to_string())));
+        // not a byte unit, so decimal
+        assert_eq!(scale(1000.0, "QB"), Some((1.0, "kQB".to_string())));
+        assert_eq!(scale(999.0, ""), None);
+        assert_eq!(scale(2e6, ""), Some((2.0, "M".to_string())));
+        // past the largest prefix the value grows instead
+        assert_eq!(scale(2e18, ""), Some((2000.0, "P".to_string())));
+    }
+
+    #[test]
+    fn test_scale_negative_and_special() {
+        let scale = |value, unit| scale_unit(value, unit, LabelFormat::Auto);
+        assert_eq!(scale(-2048.0, "B"), Some((-2.0, "KB".to_string())));
+        assert_eq!(scale(-500.0, "B"), None);
+        assert_eq!(scale(f64::INFINITY, "B"), None);
+        assert_eq!(scale(f64::NEG_INFINITY, "ms"), None);
+    }
+
+    #[test]
+    fn test_label_format_overrides() {
+        assert_eq!(
+            scale_unit(2000.0, "B", LabelFormat::Si),
+            Some((2.0, "kB".to_string()))
+        );
+        assert_eq!(
+            scale_unit(2048.0, "ms", LabelFormat::Binary),
+            Some((2.0, "Kms".to_string()))
+        );
+        assert_eq!(scale_unit(2e9, "B", LabelFormat::Plain), None);
+    }
+
+    /// The label for `value` after picking the prefix with `state`.
+    fn label(state: &mut PrefixState, value: f64, mode: UnitPrefix) -> String {
+        let steps = state.pick(value, "B", LabelFormat::Si, mode);
+        match scale_to(value, "B", LabelFormat::Si, steps) {
+            Some((value, unit)) => format!("{value:.1} {unit}"),
+            None => format!("{value:.0} B"),
+        }
+    }
+
+    #[test]
+    fn test_prefix_hysteresis() {
+        let mut state = PrefixState::default();
+        let labels = [950e3, 1.1e6, 1.19e6, 1.25e6, 0.9e6, 0.85e6, 0.79e6]
+            .map(|value| label(&mut state, value, UnitPrefix::Auto));
+        assert_eq!(
+            labels,
+            [
+                "950.0 kB",
+                "1100.0 kB",
+                "1190.0 kB",
+                "1.2 MB",
+                "0.9 MB",
+                "0.8 MB",
+                "790.0 kB"
+            ]
+        );
+        // a big jump skips the hysteresis
+        assert_eq!(label(&mut state, 5e9, UnitPrefix::Auto), "5.0 GB");
+    }
+
+    #[test]
+    fn test_prefix_lock() {
+        let mut state = PrefixState::default();
+        // zero doesn't lock anything yet
+        assert_eq!(label(&mut state, 0.0, UnitPrefix::Lock), "0 B");
+        let labels =
+            [0.9e6, 1.1e6, 250e6, 40e3].map(|value| label(&mut state, value, UnitPrefix::Lock));
+        assert_eq!(labels, ["900.0 kB", "1100.0 kB", "250000.0 kB", "40.0 kB"]);
+    }
+
+    #[test]
+    fn test_scale_to_prefixed_unit() {
+        assert_eq!(
+            scale_to(3.0 * 1024.0 * 1024.0, "KB", LabelFormat::Auto, 1),
+            Some((3.0 * 1024.0, "MB".to_string()))
+        );
+        assert_eq!(scale_to(5e3, "ms", LabelFormat::Auto, 0), None);
+        assert_eq!(scale_to(5e3, "ms", LabelFormat::Plain, 1), None);
+    }
+
+    #[test]
+    fn test_plain_matches_std() {
+        for value in [0.0, 1.5, -2.25, 1e6, 123456.789] {
+            assert_eq!(
+                format_number(value, 2, NumberLocale::Plain),
+                format!("{value:.2}")
+            );
+        }
+    }
+}
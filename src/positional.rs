@@ -0,0 +1,172 @@
+use std::{collections::HashMap, fmt};
+
+/// A per-chart option given positionally on the command line, e.g. `-t first -t second`, where
+/// the n-th value applies to the n-th chart.
+#[derive(Debug, Clone)]
+pub struct Positional<T> {
+    name: &'static str,
+    values: Vec<T>,
+    /// Values set while the dashboard runs, e.g. through `--control-fifo`, by chart.
+    overrides: HashMap<usize, T>,
+}
+
+/// Describes how the number of values of a [`Positional`] option disagrees with the number of
+/// charts, and how that was resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub name: &'static str,
+    pub given: usize,
+    pub charts: usize,
+}
+
+/// How many values a [`Positional`] option was given, to check against the number of charts
+/// where the values themselves aren't needed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Given {
+    pub name: &'static str,
+    pub count: usize,
+}
+
+impl<T> Positional<T> {
+    pub fn new(name: &'static str, values: Option<Vec<T>>) -> Self {
+        Self {
+            name,
+            values: values.unwrap_or_default(),
+            overrides: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.overrides
+            .get(&index)
+            .or_else(|| self.values.get(index))
+    }
+
+    /// Replace the value of chart `index`, also past the ones given. [`Self::values`] and
+    /// [`Self::check`] still see the values as given.
+    pub fn set(&mut self, index: usize, value: T) {
+        self.overrides.insert(index, value);
+    }
+
+    /// The values as given, one per chart.
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    pub fn given(&self) -> Given {
+        Given {
+            name: self.name,
+            count: self.values.len(),
+        }
+    }
+}
+
+impl Given {
+    /// Compare the number of values against the number of charts. Options that were not given
+    /// at all never mismatch.
+    pub fn check(self, charts: usize) -> Option<Mismatch> {
+        if self.count == 0 || self.count == charts {
+            return None;
+        }
+        Some(Mismatch {
+            name: self.name,
+            given: self.count,
+            charts,
+        })
+    }
+}
+
+impl<T> Default for Positional<T> {
+    fn default() -> Self {
+        Self {
+            name: "",
+            values: vec![],
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let charts = if self.charts == 1 { "chart" } else { "charts" };
+        write!(
+            f,
+            "{} {} given for {} {}, ",
+            self.given, self.name, self.charts, charts
+        )?;
+        if self.given < self.charts {
+            let first = self.given + 1;
+            if first == self.charts {
+                write!(f, "chart {} uses the default", first)
+            } else {
+                write!(f, "charts {}-{} use the default", first, self.charts)
+            }
+        } else {
+            match self.given - self.charts {
+                1 => write!(f, "the last one is ignored"),
+                extra => write!(f, "the last {} are ignored", extra),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_not_given() {
+        let titles: Positional<String> = Positional::new("titles", None);
+        assert_eq!(titles.given().check(3), None);
+        assert_eq!(titles.get(0), None);
+    }
+
+    #[test]
+    fn test_matching() {
+        let titles = Positional::new("titles", Some(vec!["a", "b"]));
+        assert_eq!(titles.given().check(2), None);
+        assert_eq!(titles.get(1), Some(&"b"));
+    }
+
+    #[test]
+    fn test_too_few() {
+        let titles = Positional::new("titles", Some(vec!["a", "b", "c"]));
+        let mismatch = titles.given().check(5).unwrap();
+        assert_eq!(
+            mismatch.to_string(),
+            "3 titles given for 5 charts, charts 4-5 use the default"
+        );
+        assert_eq!(
+            titles.given().check(4).unwrap().to_string(),
+            "3 titles given for 4 charts, chart 4 uses the default"
+        );
+    }
+
+    #[test]
+    fn test_too_many() {
+        let colors = Positional::new("colors", Some(vec![1, 2, 3, 4, 5]));
+        assert_eq!(
+            colors.given().check(3).unwrap().to_string(),
+            "5 colors given for 3 charts, the last 2 are ignored"
+        );
+        assert_eq!(
+            colors.given().check(4).unwrap().to_string(),
+            "5 colors given for 4 charts, the last one is ignored"
+        );
+    }
+
+    #[test]
+    fn test_set() {
+        let mut references = Positional::new("references", Some(vec![80.0]));
+        references.set(0, 90.0);
+        references.set(2, 5.0);
+        assert_eq!(references.get(0), Some(&90.0));
+        assert_eq!(references.get(1), None);
+        assert_eq!(references.get(2), Some(&5.0));
+        // still one given for one chart
+        assert_eq!(references.values(), [80.0]);
+        assert_eq!(references.given().check(1), None);
+    }
+}
@@ -0,0 +1,156 @@
+use std::path::Path;
+
+use color_eyre::Result;
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cli::{Cli, Layout, Sort},
+    config::{object_entry, read_config_value, write_config_value, ConfigLock},
+};
+
+/// The profile used when `--profile` isn't given.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Dashboard settings stored under `profiles.<name>` in the config file. Command line flags
+/// take precedence over the profile.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub titles: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub descriptions: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub units: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub colors: Option<Vec<Color>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layout: Option<Layout>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<Sort>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<bool>,
+    /// Chart numbers in display order, like `--order`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<Vec<usize>>,
+}
+
+impl Profile {
+    /// Fill in the flags that weren't given on the command line.
+    pub fn apply(&self, args: &mut Cli) {
+        args.titles = args.titles.take().or_else(|| self.titles.clone());
+        args.descriptions = args
+            .descriptions
+            .take()
+            .or_else(|| self.descriptions.clone());
+        args.units = args.units.take().or_else(|| self.units.clone());
+        args.colors = args.colors.take().or_else(|| self.colors.clone());
+        args.layout = args.layout.take().or(self.layout);
+        args.sort = args.sort.or(self.sort);
+        args.group = args.group.or(self.group);
+        args.order = args.order.take().or_else(|| self.order.clone());
+    }
+}
+
+/// Store `profile` as `profiles.<name>` in the JSON5 or JSON config file at `path`, keeping all
+/// other keys. The file is created if it doesn't exist and replaced atomically.
+pub fn write_profile(path: &Path, name: &str, profile: &Profile) -> Result<()> {
+    let _lock = ConfigLock::acquire(path)?;
+    let mut config = read_config_value(path)?;
+    let profiles = object_entry(&mut config, "profiles", path)?;
+    profiles.insert(name.to_string(), serde_json::to_value(profile)?);
+    write_config_value(path, &config)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use clap::Parser;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// Read `profiles.<name>` from the JSON5 or JSON config file at `path`.
+    fn read_profile(path: &Path, name: &str) -> Result<Option<Profile>> {
+        let config = json5::from_str::<serde_json::Value>(&fs::read_to_string(path)?)?;
+        match config
+            .get("profiles")
+            .and_then(|profiles| profiles.get(name))
+        {
+            Some(profile) => Ok(Some(serde_json::from_value(profile.clone())?)),
+            None => Ok(None),
+        }
+    }
+
+    fn temp_config(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("ttydash-profile-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let path = temp_config("round-trip.json5");
+        fs::write(
+            &path,
+            r#"{
+                // unrelated settings survive
+                "keybindings": { "Home": { "<q>": "Quit" } },
+                "profiles": {
+                    "ops": { "titles": ["cpu", "mem"], "layout": "vertical" },
+                    "other": { "sort": "name" },
+                },
+            }"#,
+        )?;
+        let mut profile = read_profile(&path, "ops")?.unwrap();
+        profile.titles = Some(vec!["cpu %".into(), "mem".into()]);
+        profile.colors = Some(vec![Color::Cyan, Color::Rgb(255, 136, 0)]);
+        profile.sort = Some(Sort::Value);
+        write_profile(&path, "ops", &profile)?;
+
+        assert_eq!(read_profile(&path, "ops")?, Some(profile));
+        assert_eq!(
+            read_profile(&path, "other")?.unwrap().sort,
+            Some(Sort::Name)
+        );
+        let text = fs::read_to_string(&path)?;
+        assert!(text.contains("keybindings"));
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_creates_missing_file() -> Result<()> {
+        let path = temp_config("missing.json5");
+        let _ = fs::remove_file(&path);
+        let profile = Profile {
+            group: Some(true),
+            ..Default::default()
+        };
+        write_profile(&path, DEFAULT_PROFILE, &profile)?;
+        assert_eq!(read_profile(&path, DEFAULT_PROFILE)?, Some(profile));
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_refuses_other_formats() {
+        let path = temp_config("config.toml");
+        assert!(write_profile(&path, DEFAULT_PROFILE, &Profile::default()).is_err());
+    }
+
+    #[test]
+    fn test_flags_take_precedence() {
+        let profile = Profile {
+            titles: Some(vec!["from profile".into()]),
+            layout: Some(Layout::Vertical),
+            ..Default::default()
+        };
+        let mut args = Cli::parse_from(["ttydash", "-t", "from flag"]);
+        profile.apply(&mut args);
+        assert_eq!(args.titles, Some(vec!["from flag".to_string()]));
+        assert_eq!(args.layout, Some(Layout::Vertical));
+    }
+}
@@ -0,0 +1,129 @@
+use color_eyre::{eyre::eyre, Result};
+
+/// The most ticks or frames per second, the same as the finest `--update-frequency` of 1 ms.
+/// Beyond that the event loop only burns CPU.
+pub const MAX_RATE: f64 = 1000.0;
+
+/// The most frames per second with `--low-bandwidth`.
+pub const LOW_BANDWIDTH_FRAME_RATE: f64 = 10.0;
+
+/// The tick and frame rates the app runs with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rates {
+    pub tick_rate: f64,
+    pub frame_rate: f64,
+}
+
+/// Cross-check `--update-frequency`, `--tick-rate` and `--frame-rate`. Rates that can't run at
+/// all are errors. A tick rate below the sampling rate is raised to it, since the component
+/// updates driven by ticks would otherwise lag behind the samples, and rates above
+/// [`MAX_RATE`] are lowered to it; under `--strict` both are errors instead. Returns the
+/// effective rates and a note for every adjustment.
+pub fn reconcile(
+    update_frequency: u64,
+    tick_rate: f64,
+    frame_rate: f64,
+    strict: bool,
+) -> Result<(Rates, Vec<String>)> {
+    if update_frequency == 0 {
+        return Err(eyre!("--update-frequency must be at least 1 ms"));
+    }
+    for (flag, rate) in [("--tick-rate", tick_rate), ("--frame-rate", frame_rate)] {
+        if !rate.is_finite() || rate <= 0.0 {
+            return Err(eyre!("{flag} must be a positive number, got {rate}"));
+        }
+    }
+    let sampling_rate = 1000.0 / update_frequency as f64;
+    let mut notes = vec![];
+    let mut adjust = |flag: &str, from: f64, to: f64, reason: &str| {
+        let note = format!("{flag} {from} {reason}, using {to}");
+        match strict {
+            true => Err(eyre!("{note}")),
+            false => {
+                notes.push(note);
+                Ok(to)
+            }
+        }
+    };
+    let tick_rate = if tick_rate < sampling_rate {
+        let reason = format!(
+            "is below the {sampling_rate} samples/s of --update-frequency {update_frequency}"
+        );
+        adjust("--tick-rate", tick_rate, sampling_rate, &reason)?
+    } else if tick_rate > MAX_RATE {
+        adjust("--tick-rate", tick_rate, MAX_RATE, "only wastes CPU")?
+    } else {
+        tick_rate
+    };
+    let frame_rate = if frame_rate > MAX_RATE {
+        adjust("--frame-rate", frame_rate, MAX_RATE, "only wastes CPU")?
+    } else {
+        frame_rate
+    };
+    let rates = Rates {
+        tick_rate,
+        frame_rate,
+    };
+    Ok((rates, notes))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn rates(tick_rate: f64, frame_rate: f64) -> Rates {
+        Rates {
+            tick_rate,
+            frame_rate,
+        }
+    }
+
+    #[test]
+    fn test_defaults_unchanged() {
+        let (effective, notes) = reconcile(1000, 4.0, 60.0, false).unwrap();
+        assert_eq!(effective, rates(4.0, 60.0));
+        assert!(notes.is_empty());
+        // exactly the sampling rate is enough
+        assert_eq!(reconcile(250, 4.0, 60.0, true).unwrap().0, rates(4.0, 60.0));
+    }
+
+    #[test]
+    fn test_tick_rate_raised() {
+        let (effective, notes) = reconcile(50, 4.0, 60.0, false).unwrap();
+        assert_eq!(effective, rates(20.0, 60.0));
+        assert_eq!(
+            notes,
+            ["--tick-rate 4 is below the 20 samples/s of --update-frequency 50, using 20"]
+        );
+        // a sample every millisecond needs the most ticks there are
+        assert_eq!(
+            reconcile(1, 4.0, 60.0, false).unwrap().0,
+            rates(1000.0, 60.0)
+        );
+    }
+
+    #[test]
+    fn test_huge_rates_lowered() {
+        let (effective, notes) = reconcile(1000, 1e6, 5000.0, false).unwrap();
+        assert_eq!(effective, rates(MAX_RATE, MAX_RATE));
+        assert_eq!(notes.len(), 2);
+    }
+
+    #[test]
+    fn test_strict() {
+        let error = reconcile(50, 4.0, 60.0, true).unwrap_err();
+        assert!(error.to_string().starts_with("--tick-rate 4 is below"));
+        assert!(reconcile(1000, 4.0, 1e4, true).is_err());
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(reconcile(0, 4.0, 60.0, false).is_err());
+        assert!(reconcile(1000, 0.0, 60.0, false).is_err());
+        assert!(reconcile(1000, 4.0, -1.0, false).is_err());
+        assert!(reconcile(1000, f64::NAN, 60.0, false).is_err());
+        assert!(reconcile(1000, 4.0, f64::INFINITY, false).is_err());
+    }
+}
@@ -0,0 +1,328 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{eyre::eyre, Result};
+use regex::Regex;
+
+use crate::config::{
+    builtin_regexes, get_config_file, parse_regex_entries, read_regex_store, regexes_to_toml,
+    write_regex_store, ConfigLock, CorruptConfig, RegexEntry,
+};
+
+/// What an import changed in the store.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub added: usize,
+    pub replaced: usize,
+    pub unchanged: usize,
+}
+
+/// Run `ttydash add`: store a new regex under `name`.
+pub fn add(name: &str, regex: &str, force: bool) -> Result<()> {
+    let path = get_config_file();
+    add_to(&path, name, regex, force)?;
+    eprintln!("Added {name} to {}", path.display());
+    Ok(())
+}
+
+/// Store a new regex in the config file at `path`. A file that can't be parsed is left alone,
+/// unless `force` moves it to `.bak` so a new one can be started.
+fn add_to(path: &Path, name: &str, regex: &str, force: bool) -> Result<()> {
+    let _lock = ConfigLock::acquire(path)?;
+    let entry = RegexEntry {
+        name: name.to_string(),
+        regex: regex.to_string(),
+        description: String::new(),
+    };
+    let store = match read_regex_store(path) {
+        Err(err) if err.is::<CorruptConfig>() => {
+            let backup = backup_path(path);
+            if !force {
+                return Err(eyre!(
+                    "{err}\nOr pass --force to move it to {} and start a new one",
+                    backup.display()
+                ));
+            }
+            fs::rename(path, &backup)?;
+            eprintln!(
+                "Moved the unreadable {} to {}, its other settings are only there now",
+                path.display(),
+                backup.display()
+            );
+            vec![]
+        }
+        store => store?,
+    };
+    let (store, _) = merge(store, vec![entry], false)?;
+    write_regex_store(path, &store)
+}
+
+/// `config.json5.bak` for `config.json5`.
+fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    PathBuf::from(backup)
+}
+
+/// Run `ttydash remove`: drop the regex `name` from the store.
+pub fn remove(name: &str) -> Result<()> {
+    let path = get_config_file();
+    let _lock = ConfigLock::acquire(&path)?;
+    let mut store = read_regex_store(&path)?;
+    let before = store.len();
+    store.retain(|entry| entry.name != name);
+    if store.len() == before {
+        let builtin = builtin_regexes().iter().any(|entry| entry.name == name);
+        return Err(if builtin {
+            eyre!("{name} is a built-in regex and can't be removed")
+        } else {
+            eyre!("There is no regex named {name} in {}", path.display())
+        });
+    }
+    write_regex_store(&path, &store)?;
+    eprintln!("Removed {name} from {}", path.display());
+    Ok(())
+}
+
+/// Run `ttydash import`: add the regexes of `file`, `-` for stdin, to the store.
+pub fn import(file: &Path, overwrite: bool) -> Result<()> {
+    let text = if file == Path::new("-") {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text)?;
+        text
+    } else {
+        fs::read_to_string(file).map_err(|err| eyre!("Can't read {}: {err}", file.display()))?
+    };
+    let path = get_config_file();
+    let _lock = ConfigLock::acquire(&path)?;
+    let (store, report) = merge(
+        read_regex_store(&path)?,
+        parse_regex_entries(&text)?,
+        overwrite,
+    )?;
+    write_regex_store(&path, &store)?;
+    eprintln!(
+        "Imported {} regexes into {}: {} added, {} replaced, {} unchanged",
+        report.added + report.replaced + report.unchanged,
+        path.display(),
+        report.added,
+        report.replaced,
+        report.unchanged
+    );
+    Ok(())
+}
+
+/// Run `ttydash export`: write the store to `file`, stdout when it is missing or `-`. A `.toml`
+/// file gets TOML, everything else JSON.
+pub fn export(file: Option<&Path>, include_builtin: bool) -> Result<()> {
+    let mut entries = read_regex_store(&get_config_file())?;
+    if include_builtin {
+        // stored regexes take precedence over the built-in ones of the same name
+        let builtins = builtin_regexes()
+            .into_iter()
+            .filter(|builtin| !entries.iter().any(|entry| entry.name == builtin.name))
+            .collect::<Vec<_>>();
+        entries.extend(builtins);
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+    let file = file.filter(|file| *file != Path::new("-"));
+    let toml = file.is_some_and(|file| file.extension().is_some_and(|ext| ext == "toml"));
+    let text = if toml {
+        regexes_to_toml(&entries)
+    } else {
+        serde_json::to_string_pretty(&entries)? + "\n"
+    };
+    match file {
+        Some(file) => fs::write(file, text)?,
+        None => io::stdout().write_all(text.as_bytes())?,
+    }
+    Ok(())
+}
+
+/// Add `imported` to `store`. Fails without changing anything when a regex doesn't compile, a
+/// name is given twice, or, unless `overwrite`, a name is already taken by a different regex.
+fn merge(
+    mut store: Vec<RegexEntry>,
+    imported: Vec<RegexEntry>,
+    overwrite: bool,
+) -> Result<(Vec<RegexEntry>, ImportReport)> {
+    let mut problems = vec![];
+    let mut seen = HashMap::new();
+    for entry in &imported {
+        if entry.name.trim().is_empty() {
+            problems.push(format!("a regex has no name: {}", entry.regex));
+        }
+        if let Err(err) = Regex::new(&entry.regex) {
+            problems.push(format!("{} does not compile: {err}", entry.name));
+        }
+        if seen.insert(entry.name.as_str(), ()).is_some() {
+            problems.push(format!("{} is given more than once", entry.name));
+        }
+    }
+    let builtins = builtin_regexes();
+    let conflicts = imported
+        .iter()
+        .filter_map(|entry| {
+            let existing = store
+                .iter()
+                .chain(&builtins)
+                .find(|existing| existing.name == entry.name)?;
+            (existing != entry).then_some(&entry.name)
+        })
+        .collect::<Vec<_>>();
+    if !conflicts.is_empty() && !overwrite {
+        for name in conflicts {
+            problems.push(format!(
+                "{name} already exists with a different regex, use --overwrite to replace it"
+            ));
+        }
+    }
+    if !problems.is_empty() {
+        return Err(eyre!("Nothing was imported:\n  {}", problems.join("\n  ")));
+    }
+
+    let mut report = ImportReport::default();
+    for entry in imported {
+        match store
+            .iter_mut()
+            .find(|existing| existing.name == entry.name)
+        {
+            Some(existing) if *existing == entry => report.unchanged += 1,
+            Some(existing) => {
+                *existing = entry;
+                report.replaced += 1;
+            }
+            None => {
+                // a built-in regex taken over as is needs no copy in the store
+                if builtins.contains(&entry) {
+                    report.unchanged += 1;
+                    continue;
+                }
+                if builtins.iter().any(|builtin| builtin.name == entry.name) {
+                    report.replaced += 1;
+                } else {
+                    report.added += 1;
+                }
+                store.push(entry);
+            }
+        }
+    }
+    store.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok((store, report))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn entry(name: &str, regex: &str) -> RegexEntry {
+        RegexEntry {
+            name: name.to_string(),
+            regex: regex.to_string(),
+            description: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let store = vec![entry("latency", r"(\d+)ms"), entry("load", r"load (\d+)")];
+        let imported = vec![
+            entry("load", r"load (\d+)"),
+            entry("temp", r"(\d+)C"),
+            entry("ping", r"time=(\d+\.\d+) ms"),
+        ];
+        let (merged, report) = merge(store, imported, false).unwrap();
+        let names = merged.iter().map(|e| e.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["latency", "load", "temp"]);
+        assert_eq!(
+            report,
+            ImportReport {
+                added: 1,
+                replaced: 0,
+                unchanged: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_merge_conflicts() {
+        let store = vec![entry("latency", r"(\d+)ms")];
+        let imported = vec![entry("latency", r"(\d+) ms")];
+        let err = merge(store.clone(), imported.clone(), false).unwrap_err();
+        assert!(err.to_string().contains("--overwrite"));
+
+        let (merged, report) = merge(store, imported.clone(), true).unwrap();
+        assert_eq!(merged, imported);
+        assert_eq!(report.replaced, 1);
+    }
+
+    #[test]
+    fn test_add_to_corrupted() {
+        let dir = std::env::temp_dir().join(format!("ttydash-add-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json5");
+        let broken = "{ keybindings: { Home: {";
+        fs::write(&path, broken).unwrap();
+
+        let err = add_to(&path, "temp", r"(\d+)C", false).unwrap_err();
+        assert!(err.to_string().contains("--force"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), broken);
+
+        add_to(&path, "temp", r"(\d+)C", true).unwrap();
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), broken);
+        let store = read_regex_store(&path).unwrap();
+        assert_eq!(store, [entry("temp", r"(\d+)C")]);
+
+        // empty and missing files are a fresh start without --force
+        fs::write(&path, "").unwrap();
+        add_to(&path, "temp", r"(\d+)C", false).unwrap();
+        fs::remove_file(&path).unwrap();
+        add_to(&path, "temp", r"(\d+)C", false).unwrap();
+        assert_eq!(read_regex_store(&path).unwrap(), [entry("temp", r"(\d+)C")]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_concurrent_adds() {
+        let dir = std::env::temp_dir().join(format!("ttydash-adds-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("config.json5");
+        let names = (0..8).map(|i| format!("regex{i}")).collect::<Vec<_>>();
+        std::thread::scope(|scope| {
+            for name in &names {
+                let path = &path;
+                scope.spawn(move || add_to(path, name, r"(\d+)", false).unwrap());
+            }
+        });
+        let store = read_regex_store(&path).unwrap();
+        let stored = store.iter().map(|entry| &entry.name).collect::<Vec<_>>();
+        assert_eq!(stored, names.iter().collect::<Vec<_>>());
+        // no temporary file is left behind
+        let mut files = fs::read_dir(&dir)
+            .unwrap()
+            .map(|file| file.unwrap().file_name())
+            .collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(files, ["config.json5", "config.json5.lock"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_merge_rejects_invalid() {
+        let imported = vec![
+            entry("broken", r"(\d+"),
+            entry("twice", "a"),
+            entry("twice", "a"),
+        ];
+        let err = merge(vec![], imported, true).unwrap_err().to_string();
+        assert!(err.contains("broken does not compile"));
+        assert!(err.contains("twice is given more than once"));
+    }
+}
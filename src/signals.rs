@@ -0,0 +1,133 @@
+//! SIGTERM and SIGINT, e.g. from systemd stopping the unit or tmux killing the pane, quit
+//! through the usual shutdown so the terminal is restored and the summary written. SIGHUP
+//! reloads the config file.
+
+use std::{
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use color_eyre::Result;
+use futures::{Stream, StreamExt};
+use signal_hook::consts::signal::{SIGHUP, SIGINT, SIGTERM};
+use signal_hook_tokio::{Handle, Signals};
+use tokio::{sync::mpsc::UnboundedSender, task::JoinHandle};
+use tracing::{info, warn};
+
+use crate::{action::Action, tui::TerminalOutput};
+
+/// A second signal within this long exits at once, for a shutdown that hangs.
+const FORCE_WINDOW: Duration = Duration::from_secs(2);
+
+/// Turns the signals into `Action::Quit` and `Action::ReloadConfig` while the dashboard runs.
+pub struct SignalHandler {
+    handle: Handle,
+    task: JoinHandle<()>,
+    /// The signal that asked to quit, 0 for none.
+    received: Arc<AtomicI32>,
+}
+
+impl SignalHandler {
+    pub fn spawn(action_tx: UnboundedSender<Action>) -> Result<Self> {
+        let signals = Signals::new([SIGTERM, SIGINT, SIGHUP])?;
+        let handle = signals.handle();
+        let received = Arc::new(AtomicI32::new(0));
+        let task = tokio::spawn(forward(signals, action_tx, received.clone()));
+        Ok(Self {
+            handle,
+            task,
+            received,
+        })
+    }
+
+    /// 128 plus the number of the signal that ended the session, like shells report it.
+    pub fn exit_code(&self) -> Option<i32> {
+        match self.received.load(Ordering::Relaxed) {
+            0 => None,
+            signal => Some(128 + signal),
+        }
+    }
+
+    pub fn close(&self) {
+        self.handle.close();
+        self.task.abort();
+    }
+}
+
+async fn forward(
+    mut signals: impl Stream<Item = i32> + Unpin,
+    action_tx: UnboundedSender<Action>,
+    received: Arc<AtomicI32>,
+) {
+    let mut first = None;
+    while let Some(signal) = signals.next().await {
+        if signal == SIGHUP {
+            info!("Reloading the config on SIGHUP");
+            let _ = action_tx.send(Action::ReloadConfig);
+            continue;
+        }
+        let now = Instant::now();
+        if forces_exit(first, now) {
+            warn!("Exiting at once on a second signal {signal}");
+            force_exit(signal);
+        }
+        info!("Quitting on signal {signal}");
+        received.store(signal, Ordering::Relaxed);
+        first = Some(now);
+        let _ = action_tx.send(Action::Quit);
+    }
+}
+
+/// Whether a signal arriving at `now` is the second one in a row, after one at `first`.
+fn forces_exit(first: Option<Instant>, now: Instant) -> bool {
+    first.is_some_and(|first| now.duration_since(first) < FORCE_WINDOW)
+}
+
+/// Give the terminal back as far as possible and exit without the usual shutdown.
+fn force_exit(signal: i32) -> ! {
+    let _ = crossterm::execute!(
+        TerminalOutput::open(),
+        crossterm::terminal::LeaveAlternateScreen,
+        crossterm::cursor::Show
+    );
+    let _ = crossterm::terminal::disable_raw_mode();
+    std::process::exit(128 + signal);
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    #[test]
+    fn test_second_signal_forces_exit() {
+        let now = Instant::now();
+        assert!(!forces_exit(None, now));
+        assert!(forces_exit(Some(now), now + Duration::from_millis(1500)));
+        assert!(!forces_exit(Some(now), now + Duration::from_secs(3)));
+    }
+
+    #[tokio::test]
+    async fn test_signal_quits() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let received = Arc::new(AtomicI32::new(0));
+        forward(futures::stream::iter([SIGTERM]), tx, received.clone()).await;
+        assert_eq!(rx.recv().await, Some(Action::Quit));
+        assert_eq!(received.load(Ordering::Relaxed), SIGTERM);
+    }
+
+    #[tokio::test]
+    async fn test_sighup_reloads() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let received = Arc::new(AtomicI32::new(0));
+        forward(futures::stream::iter([SIGHUP]), tx, received.clone()).await;
+        assert_eq!(rx.recv().await, Some(Action::ReloadConfig));
+        // not a reason to quit
+        assert_eq!(received.load(Ordering::Relaxed), 0);
+    }
+}
@@ -0,0 +1,329 @@
+use std::{fmt, time::Duration};
+
+/// Per-chart input settings given with `--source`, as comma separated `key=value` pairs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Source {
+    /// Advance the chart's history at most once per interval, dropping the samples in between.
+    pub every: Option<Duration>,
+    /// When the chart takes a sample, once per update interval or for every value that arrives.
+    pub cadence: Cadence,
+    /// Chart on/off values like `--format bool` does, given as `format=bool`.
+    pub on_off: bool,
+    /// Read the chart's values from a command run on another host instead of stdin.
+    pub ssh: Option<Ssh>,
+    /// Title of the chart, defaults to the host of an `ssh:` source.
+    pub title: Option<String>,
+    /// Consider at most this many lines, dropping the excess.
+    pub throttle: Option<Throttle>,
+    /// What the chart shows, for the `F1` popup and the summary.
+    pub description: Option<String>,
+}
+
+impl fmt::Display for Source {
+    /// The spec again, in the order of [`Source`]'s fields.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut pairs = vec![];
+        if let Some(every) = self.every {
+            pairs.push(format!("every={}", format_duration(every)));
+        }
+        if self.cadence == Cadence::Arrival {
+            pairs.push("cadence=arrival".to_string());
+        }
+        if self.on_off {
+            pairs.push("format=bool".to_string());
+        }
+        if let Some(ssh) = &self.ssh {
+            pairs.push(format!("ssh:{}!{}", ssh.host, ssh.command));
+        }
+        if let Some(title) = &self.title {
+            pairs.push(format!("title={title}"));
+        }
+        if let Some(throttle) = self.throttle {
+            pairs.push(format!(
+                "throttle={}/{}",
+                throttle.lines,
+                format_duration(throttle.per)
+            ));
+        }
+        if let Some(description) = &self.description {
+            pairs.push(format!("desc={description}"));
+        }
+        write!(f, "{}", pairs.join(","))
+    }
+}
+
+/// When a chart takes its samples, given as `cadence=arrival`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Cadence {
+    /// A sample per update interval, the way charts have always advanced.
+    #[default]
+    Interval,
+    /// A sample for every value, stamped with the time it arrived, for sources that only print
+    /// a line when something happens. The chart's columns are spaced by time.
+    Arrival,
+}
+
+/// A line rate limit given as `throttle=100/s`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Throttle {
+    pub lines: u32,
+    pub per: Duration,
+}
+
+impl Throttle {
+    pub fn lines_per_second(&self) -> f64 {
+        self.lines as f64 / self.per.as_secs_f64()
+    }
+}
+
+/// A command run with `ssh host command`, given as `ssh:host!command`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ssh {
+    pub host: String,
+    pub command: String,
+}
+
+impl Ssh {
+    /// The `ssh` arguments, never asking for a password the dashboard couldn't type in.
+    pub fn args(&self) -> [&str; 4] {
+        ["-o", "BatchMode=yes", &self.host, &self.command]
+    }
+}
+
+/// Parse a `--source` spec such as `every=5s` or `ssh:host!command,title=name`.
+pub fn parse_source(value: &str) -> Result<Source, String> {
+    let mut source = Source::default();
+    for pair in value
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+    {
+        if let Some(remote) = pair.strip_prefix("ssh:") {
+            let Some((host, command)) = remote
+                .split_once('!')
+                .filter(|(host, command)| !host.is_empty() && !command.trim().is_empty())
+            else {
+                return Err(format!("expected `ssh:host!command`, got `{pair}`"));
+            };
+            source.ssh = Some(Ssh {
+                host: host.to_string(),
+                command: command.trim().to_string(),
+            });
+            continue;
+        }
+        let Some((key, value)) = pair.split_once('=') else {
+            return Err(format!("expected `key=value`, got `{pair}`"));
+        };
+        match key.trim() {
+            "every" => source.every = Some(parse_duration(value.trim())?),
+            "cadence" => source.cadence = parse_cadence(value.trim())?,
+            "format" => source.on_off = parse_on_off(value.trim())?,
+            "title" => source.title = Some(value.trim().to_string()),
+            "throttle" => source.throttle = Some(parse_throttle(value.trim())?),
+            "desc" => source.description = Some(value.trim().to_string()),
+            other => {
+                return Err(format!(
+                    "unknown source setting `{other}`, expected `every`, `cadence`, `format`, \
+                     `title`, `throttle` or `desc`"
+                ))
+            }
+        }
+    }
+    Ok(source)
+}
+
+/// The first samples of every chart that `--warmup` leaves out of the stats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Warmup {
+    Samples(usize),
+    /// Samples taken within this long of the chart's first one.
+    Duration(Duration),
+}
+
+/// Parse `--warmup`, a number of samples like `20` or a duration like `30s`.
+pub fn parse_warmup(value: &str) -> Result<Warmup, String> {
+    match value.parse::<usize>() {
+        Ok(samples) => Ok(Warmup::Samples(samples)),
+        Err(_) => parse_duration(value).map(Warmup::Duration),
+    }
+}
+
+/// Parse `cadence=`, `interval` or `arrival`.
+fn parse_cadence(value: &str) -> Result<Cadence, String> {
+    match value {
+        "interval" => Ok(Cadence::Interval),
+        "arrival" => Ok(Cadence::Arrival),
+        _ => Err(format!(
+            "invalid cadence `{value}`, expected `interval` or `arrival`"
+        )),
+    }
+}
+
+/// Whether a `format=` setting charts on/off values.
+fn parse_on_off(value: &str) -> Result<bool, String> {
+    match value {
+        "number" => Ok(false),
+        "bool" => Ok(true),
+        _ => Err(format!(
+            "invalid format `{value}`, expected `number` or `bool`"
+        )),
+    }
+}
+
+/// Parse a line rate like `100/s`, `5/2m` or `1000/500ms`.
+fn parse_throttle(value: &str) -> Result<Throttle, String> {
+    let invalid = || format!("invalid throttle `{value}`, expected lines per time like `100/s`");
+    let (lines, per) = value.split_once('/').ok_or_else(invalid)?;
+    let lines = lines.trim().parse::<u32>().map_err(|_| invalid())?;
+    let per = per.trim();
+    // `/s` means per one second
+    let per = if per.starts_with(|c: char| c.is_ascii_digit()) {
+        parse_duration(per)?
+    } else {
+        parse_duration(&format!("1{per}"))?
+    };
+    if lines == 0 {
+        return Err(format!("throttle `{value}` must allow at least one line"));
+    }
+    Ok(Throttle { lines, per })
+}
+
+/// Parse a duration like `500ms`, `5s`, `2m` or `1h`, a bare number is in seconds.
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number = number
+        .parse::<f64>()
+        .map_err(|_| format!("invalid duration `{value}`"))?;
+    let seconds = match unit {
+        "ms" => number / 1000.0,
+        "" | "s" => number,
+        "m" => number * 60.0,
+        "h" => number * 3600.0,
+        _ => {
+            return Err(format!(
+                "invalid duration `{value}`, expected a unit of ms, s, m or h"
+            ))
+        }
+    };
+    if seconds <= 0.0 {
+        return Err(format!("duration `{value}` must be positive"));
+    }
+    Duration::try_from_secs_f64(seconds).map_err(|_| format!("duration `{value}` is too long"))
+}
+
+/// A duration as [`parse_duration`] reads it, in whole seconds when it is.
+fn format_duration(duration: Duration) -> String {
+    if duration.subsec_millis() == 0 {
+        format!("{}s", duration.as_secs())
+    } else {
+        format!("{}ms", duration.as_millis())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("5s"), Ok(Duration::from_secs(5)));
+        assert_eq!(parse_duration("1.5"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_duration("1h"), Ok(Duration::from_secs(3600)));
+        assert!(parse_duration("5d").is_err());
+        assert!(parse_duration("0s").is_err());
+        assert!(parse_duration("s").is_err());
+        assert_eq!(
+            parse_duration("99999999999999999999"),
+            Err("duration `99999999999999999999` is too long".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_warmup() {
+        assert_eq!(parse_warmup("20"), Ok(Warmup::Samples(20)));
+        assert_eq!(
+            parse_warmup("30s"),
+            Ok(Warmup::Duration(Duration::from_secs(30)))
+        );
+        assert!(parse_warmup("soon").is_err());
+    }
+
+    #[test]
+    fn test_parse_source() {
+        assert_eq!(
+            parse_source("every=5s"),
+            Ok(Source {
+                every: Some(Duration::from_secs(5)),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            parse_source("ssh:web-1!vmstat 1,title=web"),
+            Ok(Source {
+                ssh: Some(Ssh {
+                    host: "web-1".to_string(),
+                    command: "vmstat 1".to_string(),
+                }),
+                title: Some("web".to_string()),
+                ..Default::default()
+            })
+        );
+        assert!(parse_source("ssh:web-1").is_err());
+        assert_eq!(
+            parse_source("throttle=100/s").unwrap().throttle,
+            Some(Throttle {
+                lines: 100,
+                per: Duration::from_secs(1)
+            })
+        );
+        assert_eq!(
+            parse_source("throttle=5/2m").unwrap().throttle,
+            Some(Throttle {
+                lines: 5,
+                per: Duration::from_secs(120)
+            })
+        );
+        assert!(parse_source("throttle=100").is_err());
+        assert!(parse_source("throttle=0/s").is_err());
+        assert!(parse_source("throttle=10/d").is_err());
+        assert!(parse_source("ssh:!uptime").is_err());
+        assert_eq!(parse_source(""), Ok(Source::default()));
+        assert!(parse_source("every").is_err());
+        assert!(parse_source("rate=5s").is_err());
+        assert_eq!(
+            parse_source("cadence=arrival").unwrap().cadence,
+            Cadence::Arrival
+        );
+        assert_eq!(parse_source("cadence=interval"), Ok(Source::default()));
+        assert!(parse_source("cadence=burst").is_err());
+        assert!(parse_source("format=bool").unwrap().on_off);
+        assert_eq!(parse_source("format=number"), Ok(Source::default()));
+        assert!(parse_source("format=statsd").is_err());
+    }
+
+    #[test]
+    fn test_source_round_trip() {
+        for spec in [
+            "every=5s",
+            "every=500ms,ssh:web-1!vmstat 1,title=web",
+            "cadence=arrival,title=deploys",
+            "format=bool,title=health",
+            "throttle=5/120s,desc=p99 of the checkout API",
+        ] {
+            assert_eq!(parse_source(spec).unwrap().to_string(), spec);
+        }
+        assert_eq!(
+            parse_source("desc= requests per second ")
+                .unwrap()
+                .description,
+            Some("requests per second".to_string())
+        );
+    }
+}
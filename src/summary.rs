@@ -0,0 +1,270 @@
+use std::{
+    fs::File,
+    io::{self, stdout, BufWriter, Write},
+    path::Path,
+};
+
+use color_eyre::Result;
+use serde::Serialize;
+
+/// Version of the summary layout, bumped when fields change meaning or new ones appear.
+/// 2 added `values` and `times` with `--summary-include-samples`, 3 added `reference`, 4 added
+/// `raw_name`, 5 added `session`.
+pub const SCHEMA_VERSION: u32 = 5;
+
+/// Aggregated statistics of every chart, written by `--summary` and served by `--http`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Summary {
+    pub schema_version: u32,
+    pub charts: Vec<ChartSummary>,
+    /// Input lines that could not be parsed, e.g. unknown StatsD metric types.
+    pub skipped_lines: usize,
+    /// Notes added with `n`, oldest first.
+    pub notes: Vec<Note>,
+}
+
+/// A note typed in during the session.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Note {
+    /// Unix time in seconds.
+    pub time: f64,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChartSummary {
+    pub title: String,
+    /// The field, metric or file name the chart was named after as it came in, when it had
+    /// escape sequences, line breaks or other characters that were dropped to draw it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw_name: Option<String>,
+    /// From `--descriptions` or a source's `desc=`.
+    pub description: Option<String>,
+    pub unit: String,
+    /// From `--reference`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<f64>,
+    pub samples: usize,
+    /// Samples left out of the stats by `--warmup`.
+    pub warmup_samples: usize,
+    pub last: Option<f64>,
+    pub average: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// Whether the chart is paused now.
+    pub paused: bool,
+    /// Total time the chart was paused, including the current pause.
+    pub paused_seconds: f64,
+    /// The stats of every sample since the chart started or was cleared, however many the
+    /// history kept, when there was one after the warmup.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<SessionSummary>,
+    /// The retained samples, oldest first, with `--summary-include-samples`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub values: Option<Vec<f64>>,
+    /// Unix time in seconds of every value, when the chart got a sample.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub times: Option<Vec<f64>>,
+}
+
+/// The stats of a chart's whole session, its percentiles within 1% of the exact ones.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SessionSummary {
+    pub samples: usize,
+    pub average: f64,
+    pub min: f64,
+    pub max: f64,
+    pub p50: Option<f64>,
+    pub p90: Option<f64>,
+    pub p95: Option<f64>,
+    pub p99: Option<f64>,
+}
+
+impl Default for Summary {
+    fn default() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            charts: Vec::new(),
+            skipped_lines: 0,
+            notes: Vec::new(),
+        }
+    }
+}
+
+impl Summary {
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Write the summary to `path`, or to stdout when the path is `-`. It is streamed rather
+    /// than built in memory first, the samples of a long history can be large.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        if path == Path::new("-") {
+            self.write_to(stdout().lock())
+        } else {
+            self.write_to(File::create(path)?)
+        }
+    }
+
+    fn write_to(&self, writer: impl Write) -> Result<()> {
+        let mut writer = BufWriter::new(writer);
+        // as the `io::Error` it is, so a closed stdout can be told apart
+        serde_json::to_writer_pretty(&mut writer, self).map_err(io::Error::from)?;
+        writeln!(writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Charts exported on their own with `E`: a summary of just them, with all their samples, and
+/// when and by what it was written.
+#[derive(Debug, Clone, Serialize)]
+pub struct Export {
+    /// Unix time in seconds.
+    pub exported_at: f64,
+    /// The version of ttydash that wrote it.
+    pub ttydash: String,
+    #[serde(flatten)]
+    pub summary: Summary,
+}
+
+impl Export {
+    pub fn write_json(&self, writer: impl Write) -> Result<()> {
+        let mut writer = BufWriter::new(writer);
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writeln!(writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Write every chart as `time,value` rows, after `#` comment lines with its title, unit,
+    /// reference and stats, and the session's notes.
+    pub fn write_csv(&self, writer: impl Write) -> Result<()> {
+        let mut writer = BufWriter::new(writer);
+        writeln!(
+            writer,
+            "# exported by ttydash {} at {} (unix time)",
+            self.ttydash, self.exported_at
+        )?;
+        for note in &self.summary.notes {
+            writeln!(writer, "# note at {}: {}", note.time, note.text)?;
+        }
+        for chart in &self.summary.charts {
+            writeln!(writer, "# title: {}", chart.title)?;
+            if !chart.unit.is_empty() {
+                writeln!(writer, "# unit: {}", chart.unit)?;
+            }
+            if let Some(reference) = chart.reference {
+                writeln!(writer, "# reference: {reference}")?;
+            }
+            if let (Some(average), Some(min), Some(max)) = (chart.average, chart.min, chart.max) {
+                writeln!(writer, "# average: {average}, min: {min}, max: {max}")?;
+            }
+            writeln!(writer, "time,value")?;
+            let times = chart.times.iter().flatten();
+            for (time, value) in times.zip(chart.values.iter().flatten()) {
+                writeln!(writer, "{time},{value}")?;
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// At most `max` values, the means of consecutive buckets of `values` when there are more.
+pub fn bucket_means(values: &[f64], max: usize) -> Vec<f64> {
+    if values.len() <= max {
+        return values.to_vec();
+    }
+    values
+        .chunks(values.len().div_ceil(max))
+        .map(|bucket| bucket.iter().sum::<f64>() / bucket.len() as f64)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_bucket_means() {
+        let values = [1.0, 3.0, 5.0, 7.0, 9.0];
+        assert_eq!(bucket_means(&values, 5), values);
+        assert_eq!(bucket_means(&values, 2), [3.0, 8.0]);
+        assert_eq!(bucket_means(&values, 3), [2.0, 6.0, 9.0]);
+        assert_eq!(bucket_means(&values, 1), [5.0]);
+    }
+
+    #[test]
+    fn test_schema_version_and_samples() {
+        let summary = Summary {
+            charts: vec![
+                ChartSummary {
+                    values: Some(vec![1.0, 2.0]),
+                    times: Some(vec![10.0, 11.0]),
+                    ..Default::default()
+                },
+                ChartSummary::default(),
+            ],
+            ..Default::default()
+        };
+        let mut json = Vec::new();
+        summary.write_to(&mut json).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(json["schema_version"], SCHEMA_VERSION);
+        assert_eq!(json["charts"][0]["values"], serde_json::json!([1.0, 2.0]));
+        assert_eq!(json["charts"][0]["times"], serde_json::json!([10.0, 11.0]));
+        assert!(json["charts"][1].get("values").is_none());
+    }
+
+    #[test]
+    fn test_export() {
+        let export = Export {
+            exported_at: 100.0,
+            ttydash: "1.2.3".to_string(),
+            summary: Summary {
+                charts: vec![ChartSummary {
+                    title: "latency".to_string(),
+                    unit: "ms".to_string(),
+                    reference: Some(250.0),
+                    samples: 2,
+                    average: Some(1.5),
+                    min: Some(1.0),
+                    max: Some(2.0),
+                    values: Some(vec![1.0, 2.0]),
+                    times: Some(vec![10.0, 11.5]),
+                    ..Default::default()
+                }],
+                notes: vec![Note {
+                    time: 11.0,
+                    text: "deploy".to_string(),
+                }],
+                ..Default::default()
+            },
+        };
+        let mut csv = vec![];
+        export.write_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "# exported by ttydash 1.2.3 at 100 (unix time)\n\
+             # note at 11: deploy\n\
+             # title: latency\n\
+             # unit: ms\n\
+             # reference: 250\n\
+             # average: 1.5, min: 1, max: 2\n\
+             time,value\n\
+             10,1\n\
+             11.5,2\n"
+        );
+
+        let mut json = vec![];
+        export.write_json(&mut json).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(json["ttydash"], "1.2.3");
+        assert_eq!(json["schema_version"], SCHEMA_VERSION);
+        assert_eq!(json["charts"][0]["reference"], 250.0);
+        assert_eq!(json["charts"][0]["values"], serde_json::json!([1.0, 2.0]));
+    }
+}
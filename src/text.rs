@@ -0,0 +1,63 @@
+//! Text from the input, the sources and the user made safe to draw: a metric name, a file name or
+//! a line of stderr can hold escape sequences or line breaks that would scribble over the frame.
+
+/// `text` as a single line safe to draw: escape sequences, control characters and invisible
+/// format characters are dropped and every run of line breaks becomes one space.
+pub fn sanitize(text: &str) -> String {
+    if !text
+        .chars()
+        .any(|c| c.is_control() || is_invisible(c) || is_line_break(c))
+    {
+        return text.to_string();
+    }
+    // stripping the escape sequences drops tabs too
+    let text = strip_ansi_escapes::strip_str(text.replace('\t', " "));
+    let mut line = String::with_capacity(text.len());
+    let mut line_break = false;
+    for c in text.chars() {
+        if is_line_break(c) {
+            line_break = true;
+            continue;
+        }
+        if line_break && !line.is_empty() {
+            line.push(' ');
+        }
+        line_break = false;
+        if !c.is_control() && !is_invisible(c) {
+            line.push(c);
+        }
+    }
+    line
+}
+
+fn is_line_break(c: char) -> bool {
+    matches!(c, '\n' | '\r' | '\u{85}' | '\u{2028}' | '\u{2029}')
+}
+
+/// Zero-width and bidirectional formatting characters, which take no cell of their own but can
+/// reorder or join the ones around them.
+fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{200B}'..='\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2060}'..='\u{2069}' | '\u{FEFF}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(sanitize("web-1.cpu"), "web-1.cpu");
+        assert_eq!(sanitize("\x1b[2Jcpu\x1b[31m idle\x1b[0m"), "cpu idle");
+        assert_eq!(sanitize("disk\r\n\r\nio\n"), "disk io");
+        assert_eq!(sanitize("a\tb\x07c\u{9b}d"), "a bcd");
+        assert_eq!(sanitize("zero\u{200b}width\u{feff}"), "zerowidth");
+        assert_eq!(sanitize("\u{202e}lmth.exe"), "lmth.exe");
+        assert_eq!(sanitize("para\u{2029}graph"), "para graph");
+        assert_eq!(sanitize("latência µs 温度"), "latência µs 温度");
+    }
+}
@@ -1,7 +1,8 @@
 #![allow(dead_code)] // Remove this once you start using the code
 
 use std::{
-    io::{stdout, Stdout},
+    fs::{File, OpenOptions},
+    io::{self, stdout, Stdout, Write},
     ops::{Deref, DerefMut},
     time::Duration,
 };
@@ -16,7 +17,10 @@ use crossterm::{
     terminal::{EnterAlternateScreen, LeaveAlternateScreen},
 };
 use futures::{FutureExt, StreamExt};
-use ratatui::backend::CrosstermBackend as Backend;
+use ratatui::{
+    backend::{Backend, CrosstermBackend, TestBackend},
+    buffer::Buffer,
+};
 use serde::{Deserialize, Serialize};
 use tokio::{
     sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
@@ -42,8 +46,71 @@ pub enum Event {
     Resize(u16, u16),
 }
 
-pub struct Tui {
-    pub terminal: ratatui::Terminal<Backend<Stdout>>,
+/// The controlling terminal, so the dashboard is drawn even when stdout is redirected.
+#[cfg(unix)]
+const TTY_PATH: &str = "/dev/tty";
+#[cfg(windows)]
+const TTY_PATH: &str = "CONOUT$";
+
+/// Where the TUI is drawn: the terminal device if it can be opened, stdout otherwise.
+pub enum TerminalOutput {
+    Tty(File),
+    Stdout(Stdout),
+}
+
+impl TerminalOutput {
+    pub fn open() -> Self {
+        Self::select(|| OpenOptions::new().write(true).open(TTY_PATH))
+    }
+
+    fn select(open_tty: impl FnOnce() -> io::Result<File>) -> Self {
+        match open_tty() {
+            Ok(tty) => TerminalOutput::Tty(tty),
+            Err(err) => {
+                tracing::debug!("Drawing on stdout, {TTY_PATH} is not available: {err}");
+                TerminalOutput::Stdout(stdout())
+            }
+        }
+    }
+}
+
+impl Write for TerminalOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            TerminalOutput::Tty(tty) => tty.write(buf),
+            TerminalOutput::Stdout(stdout) => stdout.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            TerminalOutput::Tty(tty) => tty.flush(),
+            TerminalOutput::Stdout(stdout) => stdout.flush(),
+        }
+    }
+}
+
+/// What a [`Tui`] draws on: the terminal, or a [`TestBackend`] to drive the app in tests.
+pub trait TuiBackend: Backend + 'static {
+    /// Where the escape sequences besides the frames go, like entering the alternate screen,
+    /// `None` when there is no terminal to send them to.
+    fn output(&mut self) -> Option<&mut dyn Write>;
+}
+
+impl TuiBackend for CrosstermBackend<TerminalOutput> {
+    fn output(&mut self) -> Option<&mut dyn Write> {
+        Some(self)
+    }
+}
+
+impl TuiBackend for TestBackend {
+    fn output(&mut self) -> Option<&mut dyn Write> {
+        None
+    }
+}
+
+pub struct Tui<B: TuiBackend = CrosstermBackend<TerminalOutput>> {
+    pub terminal: ratatui::Terminal<B>,
     pub task: JoinHandle<()>,
     pub cancellation_token: CancellationToken,
     pub event_rx: UnboundedReceiver<Event>,
@@ -56,9 +123,15 @@ pub struct Tui {
 
 impl Tui {
     pub fn new() -> Result<Self> {
+        Self::with_backend(CrosstermBackend::new(TerminalOutput::open()))
+    }
+}
+
+impl<B: TuiBackend> Tui<B> {
+    pub fn with_backend(backend: B) -> Result<Self> {
         let (event_tx, event_rx) = mpsc::unbounded_channel();
         Ok(Self {
-            terminal: ratatui::Terminal::new(Backend::new(stdout()))?,
+            terminal: ratatui::Terminal::new(backend)?,
             task: tokio::spawn(async {}),
             cancellation_token: CancellationToken::new(),
             event_rx,
@@ -165,13 +238,18 @@ impl Tui {
     }
 
     pub fn enter(&mut self) -> Result<()> {
-        crossterm::terminal::enable_raw_mode()?;
-        crossterm::execute!(stdout(), EnterAlternateScreen, cursor::Hide)?;
-        if self.mouse {
-            crossterm::execute!(stdout(), EnableMouseCapture)?;
-        }
-        if self.paste {
-            crossterm::execute!(stdout(), EnableBracketedPaste)?;
+        let (mouse, paste) = (self.mouse, self.paste);
+        if let Some(mut output) = self.terminal.backend_mut().output() {
+            // raw mode also turns off IXON flow control until `exit`, so Ctrl-S reaches the
+            // keybindings instead of freezing the output
+            crossterm::terminal::enable_raw_mode()?;
+            crossterm::execute!(&mut output, EnterAlternateScreen, cursor::Hide)?;
+            if mouse {
+                crossterm::execute!(&mut output, EnableMouseCapture)?;
+            }
+            if paste {
+                crossterm::execute!(&mut output, EnableBracketedPaste)?;
+            }
         }
         self.start();
         Ok(())
@@ -179,20 +257,33 @@ impl Tui {
 
     pub fn exit(&mut self) -> Result<()> {
         self.stop()?;
-        if crossterm::terminal::is_raw_mode_enabled()? {
-            self.flush()?;
-            if self.paste {
-                crossterm::execute!(stdout(), DisableBracketedPaste)?;
-            }
-            if self.mouse {
-                crossterm::execute!(stdout(), DisableMouseCapture)?;
-            }
-            crossterm::execute!(stdout(), LeaveAlternateScreen, cursor::Show)?;
+        // a test backend never took the terminal over
+        let terminal = self.terminal.backend_mut().output().is_some();
+        if terminal && crossterm::terminal::is_raw_mode_enabled()? {
+            // raw mode is left even when the output can't be written to anymore
+            let restored = self.restore_output();
             crossterm::terminal::disable_raw_mode()?;
+            restored?;
         }
         Ok(())
     }
 
+    /// Leave the alternate screen and undo what `enter` turned on.
+    fn restore_output(&mut self) -> io::Result<()> {
+        self.flush()?;
+        let (mouse, paste) = (self.mouse, self.paste);
+        let Some(mut output) = self.terminal.backend_mut().output() else {
+            return Ok(());
+        };
+        if paste {
+            crossterm::execute!(&mut output, DisableBracketedPaste)?;
+        }
+        if mouse {
+            crossterm::execute!(&mut output, DisableMouseCapture)?;
+        }
+        crossterm::execute!(&mut output, LeaveAlternateScreen, cursor::Show)
+    }
+
     pub fn cancel(&self) {
         self.cancellation_token.cancel();
     }
@@ -214,22 +305,68 @@ impl Tui {
     }
 }
 
-impl Deref for Tui {
-    type Target = ratatui::Terminal<Backend<Stdout>>;
+/// The cells `current` changes over `previous`, what drawing it sends to the terminal. All of
+/// them when there is no previous frame of the same size, which is redrawn whole.
+pub fn changed_cells(previous: Option<&Buffer>, current: &Buffer) -> usize {
+    match previous {
+        Some(previous) if previous.area == current.area => previous.diff(current).len(),
+        _ => current.content.len(),
+    }
+}
+
+impl<B: TuiBackend> Deref for Tui<B> {
+    type Target = ratatui::Terminal<B>;
 
     fn deref(&self) -> &Self::Target {
         &self.terminal
     }
 }
 
-impl DerefMut for Tui {
+impl<B: TuiBackend> DerefMut for Tui<B> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.terminal
     }
 }
 
-impl Drop for Tui {
+impl<B: TuiBackend> Drop for Tui<B> {
     fn drop(&mut self) {
-        self.exit().unwrap();
+        if let Err(err) = self.exit() {
+            error!("Unable to exit Terminal: {err:?}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_changed_cells() {
+        let previous = Buffer::with_lines(["12 ms ", "█▄    "]);
+        let current = Buffer::with_lines(["13 ms ", "█▄▂   "]);
+        assert_eq!(changed_cells(Some(&previous), &current), 2);
+        assert_eq!(changed_cells(Some(&current), &current), 0);
+        assert_eq!(changed_cells(None, &current), 12);
+        let resized = Buffer::with_lines(["13 ms", "█▄▂  "]);
+        assert_eq!(changed_cells(Some(&current), &resized), 10);
+    }
+
+    #[test]
+    fn test_falls_back_to_stdout() {
+        let output = TerminalOutput::select(|| Err(io::ErrorKind::NotFound.into()));
+        assert!(matches!(output, TerminalOutput::Stdout(_)));
+    }
+
+    #[test]
+    fn test_prefers_tty() {
+        let path = std::env::temp_dir().join(format!("ttydash-tty-{}", std::process::id()));
+        let mut output = TerminalOutput::select(|| File::create(&path));
+        assert!(matches!(output, TerminalOutput::Tty(_)));
+        write!(output, "frame").unwrap();
+        output.flush().unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "frame");
+        std::fs::remove_file(path).unwrap();
     }
 }
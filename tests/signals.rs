@@ -0,0 +1,104 @@
+//! The binary on a terminal of its own, ended by a signal the way systemd or tmux end it.
+#![cfg(unix)]
+
+use std::{
+    fs::{self, File},
+    io::{Read, Write},
+    os::{
+        fd::{FromRawFd, OwnedFd},
+        unix::process::{CommandExt, ExitStatusExt},
+    },
+    process::{Command, Stdio},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+
+/// A pseudo-terminal, the controller and the device end.
+fn pty() -> (File, File) {
+    let (mut controller, mut device) = (0, 0);
+    let size = libc::winsize {
+        ws_row: 20,
+        ws_col: 80,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    let opened = unsafe {
+        libc::openpty(
+            &mut controller,
+            &mut device,
+            std::ptr::null_mut(),
+            std::ptr::null(),
+            &size,
+        )
+    };
+    assert_eq!(opened, 0, "{}", std::io::Error::last_os_error());
+    unsafe {
+        (
+            File::from(OwnedFd::from_raw_fd(controller)),
+            File::from(OwnedFd::from_raw_fd(device)),
+        )
+    }
+}
+
+#[test]
+fn test_sigterm_writes_summary() {
+    let dir = std::env::temp_dir().join(format!("ttydash-signals-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let summary = dir.join("summary.json");
+    let (mut controller, device) = pty();
+    let mut command = Command::new(env!("CARGO_BIN_EXE_ttydash"));
+    command
+        .args(["-t", "latency", "--summary"])
+        .arg(&summary)
+        .env("TTYDASH_CONFIG", &dir)
+        .env("TTYDASH_DATA", &dir)
+        .stdin(Stdio::piped())
+        .stdout(device.try_clone().unwrap())
+        .stderr(device);
+    // the pty becomes the controlling terminal, the dashboard is drawn on its /dev/tty
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setsid() == -1 || libc::ioctl(1, libc::TIOCSCTTY as _, 0) == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    let mut child = command.spawn().unwrap();
+    drop(command);
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buf = [0; 4096];
+        while let Ok(n @ 1..) = controller.read(&mut buf) {
+            if tx.send(buf[..n].to_vec()).is_err() {
+                break;
+            }
+        }
+    });
+    child.stdin.as_mut().unwrap().write_all(b"42\n").unwrap();
+    // frames are only drawn once the signals are handled
+    let mut drawn = Vec::new();
+    while !strip_ansi_escapes::strip_str(String::from_utf8_lossy(&drawn)).contains("42") {
+        let output = rx
+            .recv_timeout(Duration::from_secs(10))
+            .expect("nothing drawn");
+        drawn.extend(output);
+    }
+    unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGTERM) };
+    let status = (0..1000)
+        .find_map(|_| {
+            thread::sleep(Duration::from_millis(10));
+            child.try_wait().unwrap()
+        })
+        .expect("still running");
+    assert_eq!(status.code(), Some(143), "{status}");
+    assert_eq!(status.signal(), None);
+    let summary: Value = serde_json::from_str(&fs::read_to_string(&summary).unwrap()).unwrap();
+    assert_eq!(summary["charts"][0]["title"], "latency");
+    assert_eq!(summary["charts"][0]["last"], 42.0);
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,197 @@
+//! End-to-end runs of the binary through the hidden `ttydash simulate`, from the flags to the
+//! drawn frame and the `--summary` numbers, with the fixtures under `fixtures/`.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+
+/// A directory of its own for every test, used as the config, data and output directory.
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ttydash-simulate-{}-{name}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Run `ttydash <args> simulate` on `fixture`, returns the last frame and the summary.
+fn simulate(name: &str, fixture: &str, args: &[&str]) -> (String, Value) {
+    let dir = scratch(name);
+    let (frame, summary) = (dir.join("frame.txt"), dir.join("summary.json"));
+    let input = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(fixture);
+    let status = Command::new(env!("CARGO_BIN_EXE_ttydash"))
+        .args(args)
+        .arg("--summary")
+        .arg(&summary)
+        .args(["simulate", "--width", "60", "--height", "10", "--render-to"])
+        .arg(&frame)
+        .env("TTYDASH_CONFIG", &dir)
+        .env("TTYDASH_DATA", &dir)
+        .stdin(Stdio::from(fs::File::open(input).unwrap()))
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let frame = fs::read_to_string(frame).unwrap();
+    let summary = serde_json::from_str(&fs::read_to_string(summary).unwrap()).unwrap();
+    fs::remove_dir_all(dir).unwrap();
+    (frame, summary)
+}
+
+#[test]
+fn test_units() {
+    let (frame, summary) = simulate(
+        "units",
+        "latency.log",
+        &[
+            "-u",
+            "ms",
+            "-t",
+            "latency",
+            "--descriptions",
+            "Response time of the API",
+        ],
+    );
+    let chart = &summary["charts"][0];
+    assert_eq!(chart["title"], "latency");
+    assert_eq!(chart["description"], "Response time of the API");
+    assert_eq!(chart["samples"], 5);
+    assert_eq!(chart["last"], 20.0);
+    assert_eq!(chart["max"], 40.0);
+    assert_eq!(frame.lines().count(), 10);
+    assert!(frame.contains("latency"));
+    assert!(frame.contains("Max: 40.00 ms"));
+}
+
+#[test]
+fn test_graphite() {
+    let (frame, summary) = simulate("graphite", "graphite.txt", &["--format", "graphite"]);
+    let titles = summary["charts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|chart| chart["title"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(titles, ["web.cpu", "web.mem"]);
+    assert_eq!(summary["charts"][0]["average"], 20.0);
+    assert!(frame.contains("web.mem"));
+}
+
+#[test]
+fn test_frames_are_reproducible() {
+    let args = ["-u", "ms", "--time-axis", "clock"];
+    let (first, _) = simulate("first", "latency.log", &args);
+    let (second, _) = simulate("second", "latency.log", &args);
+    assert_eq!(first, second);
+}
+
+#[test]
+fn test_summary_samples() {
+    let args = ["-u", "ms", "--summary-include-samples"];
+    let (_, summary) = simulate("samples", "latency.log", &args);
+    assert_eq!(summary["schema_version"], 5);
+    let chart = &summary["charts"][0];
+    assert_eq!(chart["session"]["samples"], 5);
+    assert_eq!(chart["session"]["max"], 40.0);
+    assert_eq!(
+        chart["values"],
+        serde_json::json!([12.0, 15.0, 40.0, 13.0, 20.0])
+    );
+    assert_eq!(chart["times"].as_array().unwrap().len(), 5);
+
+    let args = [&args[..], &["--summary-max-samples", "2"]].concat();
+    let (_, summary) = simulate("max-samples", "latency.log", &args);
+    let chart = &summary["charts"][0];
+    assert_eq!(
+        chart["values"],
+        serde_json::json!([22.333333333333332, 16.5])
+    );
+    let times = chart["times"].as_array().unwrap();
+    assert!(times[0].as_f64().unwrap() < times[1].as_f64().unwrap());
+}
+
+#[test]
+fn test_count_by_status_class() {
+    let args = [
+        "--count-by",
+        r#"" ([1-5])\d\d "#,
+        "--max-groups",
+        "3",
+        "--summary-include-samples",
+    ];
+    let (_, summary) = simulate("count-by", "access.log", &args);
+    let counts = summary["charts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|chart| {
+            let values = chart["values"].as_array().unwrap();
+            let total = values
+                .iter()
+                .map(|value| value.as_f64().unwrap())
+                .sum::<f64>();
+            (chart["title"].as_str().unwrap().to_string(), total)
+        })
+        .collect::<Vec<_>>();
+    let expected = [("2", 5.0), ("4", 2.0), ("5", 2.0), ("other", 1.0)];
+    let expected = expected.map(|(title, total)| (title.to_string(), total));
+    assert_eq!(counts, expected);
+}
+
+#[test]
+fn test_once() {
+    let dir = scratch("once");
+    let input = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/latency.log");
+    let output = Command::new(env!("CARGO_BIN_EXE_ttydash"))
+        .args([
+            "--once", "--width", "50", "--height", "6", "-u", "ms", "-t", "latency",
+        ])
+        .env("TTYDASH_CONFIG", &dir)
+        .env("TTYDASH_DATA", &dir)
+        .stdin(Stdio::from(fs::File::open(input).unwrap()))
+        .output()
+        .unwrap();
+    fs::remove_dir_all(dir).unwrap();
+    assert!(output.status.success());
+    let text = String::from_utf8(output.stdout).unwrap();
+    let lines = text.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 7);
+    assert!(lines[..6].iter().all(|line| line.chars().count() == 50));
+    assert!(lines[6].starts_with("latency: last "));
+}
+
+#[test]
+fn test_closed_stdout() {
+    let dir = scratch("closed-stdout");
+    let input = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/latency.log");
+    for args in [
+        &["--summary", "-", "simulate", "--render-to", "-"][..],
+        &["--once"],
+    ] {
+        // the reading end is gone before anything is written, like `| head` that quit
+        let (reader, writer) = std::io::pipe().unwrap();
+        drop(reader);
+        let output = Command::new(env!("CARGO_BIN_EXE_ttydash"))
+            .args(args)
+            .env("TTYDASH_CONFIG", &dir)
+            .env("TTYDASH_DATA", &dir)
+            .stdin(Stdio::from(fs::File::open(&input).unwrap()))
+            .stdout(writer)
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(output.status.success(), "{args:?}: {stderr}");
+        assert!(
+            stderr.contains("Warning: stdout was closed, the frame wasn't written"),
+            "{stderr}"
+        );
+        if args.contains(&"--summary") {
+            assert!(stderr.contains("the summary wasn't written"), "{stderr}");
+        }
+    }
+    fs::remove_dir_all(dir).unwrap();
+}